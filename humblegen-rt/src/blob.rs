@@ -0,0 +1,216 @@
+//! `GEN`, `HANDLER` - raw binary bodies (`blob["media/type"]`).
+//!
+//! A `Blob` is a byte stream plus its content type. It is passed through verbatim,
+//! i.e., neither JSON nor any other wire format applies.
+//! The media type annotated in the humblespec is the expected `Content-Type` of a request body
+//! (`type/*` and `*/*` match any subtype or type) and the default `Content-Type` of a response body.
+
+use crate::handler::HandlerResponse;
+use crate::server::BodyReadLimits;
+use crate::service_protocol::{self, ErrorResponse, RuntimeError, ToErrorResponse};
+use crate::wire_format;
+
+use futures::stream::Stream;
+use hyper::header::{self, HeaderValue};
+use hyper::{Body, Request, Response};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::Poll;
+use tokio::time::Instant;
+
+/// Media type of a response whose `blob` annotation is a wildcard and whose handler did not set one.
+pub const FALLBACK_MEDIA_TYPE: &str = "application/octet-stream";
+
+/// A raw binary request or response body.
+pub struct Blob {
+    content_type: Option<String>,
+    body: Body,
+}
+
+impl Blob {
+    /// A blob with the given contents, e.g. a `Vec<u8>` or a `String`.
+    ///
+    /// Responses default to the media type annotated in the humblespec.
+    pub fn new(body: impl Into<Body>) -> Self {
+        Blob {
+            content_type: None,
+            body: body.into(),
+        }
+    }
+
+    /// A blob whose contents are produced by `stream` while the response is sent.
+    pub fn wrap_stream<S, O, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<O, E>> + Send + 'static,
+        O: Into<hyper::body::Bytes> + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    {
+        Self::new(Body::wrap_stream(stream))
+    }
+
+    /// Overrides the media type annotated in the humblespec.
+    pub fn with_content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    /// The content type of the blob, if set by the client or the handler.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// The contents of the blob, as a stream of chunks.
+    pub fn into_body(self) -> Body {
+        self.body
+    }
+
+    /// Reads the whole blob into memory.
+    pub async fn bytes(self) -> Result<Vec<u8>, hyper::Error> {
+        hyper::body::to_bytes(self.body).await.map(|b| b.to_vec())
+    }
+}
+
+impl std::fmt::Debug for Blob {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Blob")
+            .field("content_type", &self.content_type)
+            .finish()
+    }
+}
+
+/// Whether `content_type` matches the annotated `media_type`, which may be a wildcard.
+fn media_type_matches(media_type: &str, content_type: &str) -> bool {
+    let content_type = wire_format::essence(content_type);
+    match media_type.strip_suffix("/*") {
+        Some("*") => true,
+        Some(ty) => content_type
+            .strip_prefix(&ty.to_ascii_lowercase())
+            .is_some_and(|rest| rest.starts_with('/')),
+        None => content_type.eq_ignore_ascii_case(media_type),
+    }
+}
+
+/// Helper function used by generated code to take the body of a request as a `Blob`.
+///
+/// Enforces the `BodyReadLimits` found in the request's extensions
+/// (or their defaults if there are none) while the handler reads the blob:
+/// reading fails with an I/O error if the body exceeds the size limit
+/// or is not received within the body read timeout.
+/// Compressed bodies are rejected.
+pub fn deser_blob(req: &mut Request<Body>, media_type: &str) -> Result<Blob, ErrorResponse> {
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned);
+    match &content_type {
+        Some(ct) if media_type_matches(media_type, ct) => {}
+        _ if media_type == "*/*" => {}
+        other => {
+            return Err(RuntimeError::UnsupportedMediaType(format!(
+                "expected `{}`, got `{}`",
+                media_type,
+                other.as_deref().unwrap_or("")
+            ))
+            .to_error_response())
+        }
+    }
+    if let Some(content_encoding) = req.headers().get(header::CONTENT_ENCODING) {
+        if content_encoding != "identity" {
+            return Err(RuntimeError::UnsupportedContentEncoding(
+                String::from_utf8_lossy(content_encoding.as_bytes()).into_owned(),
+            )
+            .to_error_response());
+        }
+    }
+
+    let limits = req
+        .extensions()
+        .get::<BodyReadLimits>()
+        .copied()
+        .unwrap_or_default();
+    let max_body_size = limits.max_body_size;
+    let content_length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if matches!(content_length, Some(l) if l > max_body_size) {
+        return Err(RuntimeError::PayloadTooLarge { max_body_size }.to_error_response());
+    }
+
+    let mut body = std::mem::replace(req.body_mut(), Body::empty());
+    let mut deadline = limits
+        .timeout
+        .map(|timeout| tokio::time::delay_until(Instant::now() + timeout));
+    let mut read = 0u64;
+    let limited = futures::stream::poll_fn(move |cx| {
+        if let Some(deadline) = &mut deadline {
+            if Pin::new(deadline).poll(cx).is_ready() {
+                return Poll::Ready(Some(Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "body read timeout exceeded",
+                ))));
+            }
+        }
+        let chunk = match futures::ready!(Pin::new(&mut body).poll_next(cx)) {
+            None => return Poll::Ready(None),
+            Some(chunk) => chunk.map_err(io::Error::other),
+        };
+        Poll::Ready(Some(chunk.and_then(|chunk| {
+            read += chunk.len() as u64;
+            if read > max_body_size {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("body exceeds {} bytes", max_body_size),
+                ))
+            } else {
+                Ok(chunk)
+            }
+        })))
+    });
+    Ok(Blob {
+        content_type,
+        body: Body::wrap_stream(limited),
+    })
+}
+
+/// Conversion of a `HandlerResponse` of a `blob` endpoint to a hyper response.
+/// Invoked from generated code within a `DispatcherClosure`.
+pub fn handler_response_to_hyper_response(
+    media_type: &str,
+    handler_response: HandlerResponse<Blob>,
+) -> Response<Body> {
+    match handler_response {
+        Ok(blob) => {
+            let content_type = match blob.content_type {
+                Some(ref content_type) => HeaderValue::from_str(content_type),
+                None if media_type.ends_with("/*") => {
+                    Ok(HeaderValue::from_static(FALLBACK_MEDIA_TYPE))
+                }
+                None => HeaderValue::from_str(media_type),
+            };
+            let content_type = match content_type {
+                Ok(content_type) => content_type,
+                Err(e) => {
+                    tracing::error!(error = ?e, "handler returned invalid blob content type");
+                    return RuntimeError::SerializeHandlerResponse(e.to_string())
+                        .to_error_response()
+                        .to_hyper_response();
+                }
+            };
+            let mut response = Response::new(blob.body);
+            response
+                .headers_mut()
+                .insert(header::CONTENT_TYPE, content_type);
+            response
+        }
+        Err(e) => {
+            tracing::error!(error = ?e, "handler returned error");
+            service_protocol::ServiceError::from(e)
+                .to_error_response()
+                .to_hyper_response()
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! `SERVER` - built-in routes for operations: liveness, readiness and introspection.
+//!
+//! Built-in routes are mounted at absolute URL paths (outside of any service root)
+//! and only answer `GET` requests.
+//!
+//! - liveness: always answers `{"status": "alive"}`
+//! - readiness: runs a user-supplied check and answers `{"status": "ready"}` if it succeeds,
+//!   or `RuntimeError::NotReady` (status 503) if it fails or exceeds `server::Config::request_timeout`
+//! - introspection: answers the mounted services and their routes (`Introspection`)
+
+use crate::server::Server;
+use crate::service_protocol::{RuntimeError, ToErrorResponse};
+
+use derivative::Derivative;
+use futures::future::BoxFuture;
+use hyper::{Body, Method, Response};
+use serde::{Deserialize, Serialize};
+
+use std::future::Future;
+use std::sync::Arc;
+
+/// An async readiness check, see `readiness_check`.
+/// Returns `Err(reason)` if the server is not ready to handle requests.
+pub type ReadinessCheck = Arc<dyn Fn() -> BoxFuture<'static, Result<(), String>> + Send + Sync>;
+
+/// Wraps an async closure into a `ReadinessCheck`.
+pub fn readiness_check<F, Fut>(check: F) -> ReadinessCheck
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), String>> + Send + 'static,
+{
+    Arc::new(move || Box::pin(check()))
+}
+
+/// Configuration of the built-in routes, see `server::Config::builtin_routes`.
+///
+/// A route whose endpoint is `None` is not mounted.
+#[derive(Derivative, Clone, Default)]
+#[derivative(Debug)]
+pub struct BuiltinRoutesConfig {
+    /// URL path of the liveness route, e.g. `/health`.
+    pub liveness: Option<String>,
+    /// URL path of the readiness route, e.g. `/ready`, and the check it runs.
+    #[derivative(Debug(format_with = "fmt_readiness"))]
+    pub readiness: Option<(String, ReadinessCheck)>,
+    /// URL path of the introspection route, e.g. `/introspection`.
+    pub introspection: Option<String>,
+}
+
+fn fmt_readiness(
+    readiness: &Option<(String, ReadinessCheck)>,
+    formatter: &mut std::fmt::Formatter<'_>,
+) -> std::fmt::Result {
+    write!(formatter, "{:?}", readiness.as_ref().map(|(path, _)| path))
+}
+
+/// Response of the introspection route.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Introspection {
+    pub services: Vec<ServiceInfo>,
+}
+
+/// A service mounted by a server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceInfo {
+    /// The name of the humblespec service.
+    pub name: String,
+    /// The URL path prefix at which the service is mounted.
+    pub root: String,
+    pub routes: Vec<RouteInfo>,
+}
+
+/// A route of a mounted service.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteInfo {
+    pub method: String,
+    /// The route's URL path relative to the service root, with `{param}` placeholders.
+    pub template: String,
+    /// The name of the handler trait fn.
+    pub handler: String,
+}
+
+impl Introspection {
+    pub fn of(server: &Server) -> Self {
+        Self {
+            services: server
+                .services
+                .entries()
+                .map(|service| ServiceInfo {
+                    name: service.name.clone(),
+                    root: service.root.clone(),
+                    routes: service
+                        .routes
+                        .entries()
+                        .map(|route| RouteInfo {
+                            method: route.method.to_string(),
+                            template: route.template.clone(),
+                            handler: route.name.clone(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn json_response<T: Serialize>(value: &T) -> Response<Body> {
+    Response::new(Body::from(
+        serde_json::to_string(value).expect("built-in responses must be JSON-serializable"),
+    ))
+}
+
+/// Answers a request to one of the configured built-in routes, or returns `None`
+/// if `method` and `path` do not address one.
+pub(crate) async fn response(
+    server: &Server,
+    method: &Method,
+    path: &str,
+) -> Option<Response<Body>> {
+    // hyper omits the body of responses to HEAD requests
+    if method != Method::GET && method != Method::HEAD {
+        return None;
+    }
+    let config = &server.config.builtin_routes;
+    let is = |endpoint: &Option<String>| endpoint.as_deref() == Some(path);
+
+    if is(&config.liveness) {
+        return Some(json_response(&serde_json::json!({ "status": "alive" })));
+    }
+    if let Some((_, check)) = config.readiness.as_ref().filter(|(e, _)| e == path) {
+        let result = match server.config.request_timeout {
+            None => check().await,
+            Some(timeout) => tokio::time::timeout(timeout, check())
+                .await
+                .unwrap_or_else(|_| Err(format!("readiness check timed out after {:?}", timeout))),
+        };
+        return Some(match result {
+            Ok(()) => json_response(&serde_json::json!({ "status": "ready" })),
+            Err(reason) => {
+                tracing::warn!(%reason, "readiness check failed");
+                RuntimeError::NotReady(reason)
+                    .to_error_response()
+                    .to_hyper_response()
+            }
+        });
+    }
+    if is(&config.introspection) {
+        return Some(json_response(&Introspection::of(server)));
+    }
+    None
+}
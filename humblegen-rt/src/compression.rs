@@ -0,0 +1,214 @@
+//! `SERVER` - HTTP content codings (compression) for response and request bodies.
+//!
+//! - Responses are compressed with the best coding the client accepts (`Accept-Encoding`)
+//!   if compression is enabled and the body is at least as large as the configured threshold.
+//! - Request bodies with a `Content-Encoding` are decompressed before deserialization.
+
+use crate::service_protocol::{ErrorResponse, RuntimeError, ToErrorResponse};
+
+use hyper::body::HttpBody;
+use hyper::header::{self, HeaderValue};
+use hyper::{Body, Response};
+
+use std::io::{Read, Write};
+
+/// A content coding supported by the runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentCoding {
+    Brotli,
+    Gzip,
+    Deflate,
+}
+
+impl ContentCoding {
+    /// Supported codings, in order of preference.
+    const ALL: [ContentCoding; 3] = [
+        ContentCoding::Brotli,
+        ContentCoding::Gzip,
+        ContentCoding::Deflate,
+    ];
+
+    /// The coding's name as used in `Accept-Encoding` and `Content-Encoding` headers.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContentCoding::Brotli => "br",
+            ContentCoding::Gzip => "gzip",
+            ContentCoding::Deflate => "deflate",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|c| {
+            c.as_str().eq_ignore_ascii_case(s)
+                || (*c == ContentCoding::Gzip && s.eq_ignore_ascii_case("x-gzip"))
+        })
+    }
+
+    fn encode(self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            ContentCoding::Brotli => {
+                let mut out = Vec::new();
+                {
+                    let mut w = brotli::CompressorWriter::new(&mut out, 4096, 5, 22);
+                    w.write_all(data)?;
+                }
+                Ok(out)
+            }
+            ContentCoding::Gzip => {
+                let mut w =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                w.write_all(data)?;
+                w.finish()
+            }
+            ContentCoding::Deflate => {
+                // HTTP's `deflate` is the zlib format (RFC 7230 4.2.2)
+                let mut w =
+                    flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                w.write_all(data)?;
+                w.finish()
+            }
+        }
+    }
+
+    fn decoder<'a>(self, data: &'a [u8]) -> Box<dyn Read + 'a> {
+        match self {
+            ContentCoding::Brotli => Box::new(brotli::Decompressor::new(data, 4096)),
+            ContentCoding::Gzip => Box::new(flate2::read::GzDecoder::new(data)),
+            ContentCoding::Deflate => Box::new(flate2::read::ZlibDecoder::new(data)),
+        }
+    }
+}
+
+/// Selects the preferred supported coding from an `Accept-Encoding` header value.
+///
+/// Returns `None` if the client accepts none of the supported codings
+/// (in which case the response is sent uncompressed).
+pub fn negotiate(accept_encoding: &HeaderValue) -> Option<ContentCoding> {
+    let accept_encoding = accept_encoding.to_str().ok()?;
+    let mut wildcard_q = None;
+    let mut qs: Vec<(ContentCoding, f32)> = Vec::new();
+    for item in accept_encoding.split(',') {
+        let mut parts = item.split(';').map(str::trim);
+        let name = parts.next().unwrap_or("");
+        let q = parts
+            .find_map(|p| p.strip_prefix("q="))
+            .map(|q| q.parse::<f32>().unwrap_or(0.0))
+            .unwrap_or(1.0);
+        if name == "*" {
+            wildcard_q = Some(q);
+        } else if let Some(coding) = ContentCoding::from_str(name) {
+            qs.push((coding, q));
+        }
+    }
+
+    let q_of = |coding: ContentCoding| {
+        qs.iter()
+            .find(|(c, _)| *c == coding)
+            .map(|(_, q)| *q)
+            .or(wildcard_q)
+            .unwrap_or(0.0)
+    };
+    // `max_by` returns the last maximum => iterate in reverse order of preference
+    ContentCoding::ALL
+        .iter()
+        .rev()
+        .copied()
+        .map(|c| (c, q_of(c)))
+        .filter(|(_, q)| *q > 0.0)
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(c, _)| c)
+}
+
+/// Compresses `response` with `coding` if its body is in memory and at least `threshold` bytes large.
+///
+/// Responses that already carry a `Content-Encoding` are left untouched.
+/// Compression runs on a blocking thread, not on the executor.
+pub async fn compress_response(
+    response: Response<Body>,
+    coding: Option<ContentCoding>,
+    threshold: usize,
+) -> Response<Body> {
+    let (mut parts, body) = response.into_parts();
+    // only compress bodies that are already in memory, not streams
+    let size = body.size_hint().exact();
+    if parts.headers.contains_key(header::CONTENT_ENCODING)
+        || !matches!(size, Some(size) if size as usize >= threshold)
+    {
+        return Response::from_parts(parts, body);
+    }
+    // the response is compressed for clients that accept a supported coding
+    parts
+        .headers
+        .append(header::VARY, HeaderValue::from_static("Accept-Encoding"));
+    let coding = match coding {
+        Some(coding) => coding,
+        None => return Response::from_parts(parts, body),
+    };
+
+    let uncompressed = match hyper::body::to_bytes(body).await {
+        Ok(uncompressed) => uncompressed,
+        Err(e) => {
+            // unreachable in practice: in-memory bodies cannot fail
+            tracing::error!(err = ?e, "cannot read response body for compression");
+            return RuntimeError::SerializeHandlerResponse(e.to_string())
+                .to_error_response()
+                .to_hyper_response();
+        }
+    };
+    let compressed = tokio::task::spawn_blocking({
+        let uncompressed = uncompressed.clone();
+        move || coding.encode(&uncompressed)
+    })
+    .await;
+    match compressed {
+        Ok(Ok(compressed)) => {
+            parts.headers.insert(
+                header::CONTENT_ENCODING,
+                HeaderValue::from_static(coding.as_str()),
+            );
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(compressed))
+        }
+        Ok(Err(e)) => {
+            tracing::error!(err = ?e, "cannot compress response body, sending it uncompressed");
+            Response::from_parts(parts, Body::from(uncompressed))
+        }
+        Err(e) => {
+            tracing::error!(err = ?e, "compression task failed, sending the response body uncompressed");
+            Response::from_parts(parts, Body::from(uncompressed))
+        }
+    }
+}
+
+/// Decodes a request `body` that was sent with the given `Content-Encoding` header value.
+///
+/// Fails with `RuntimeError::PayloadTooLarge` if the decoded body exceeds `max_body_size` bytes.
+pub fn decode_request_body(
+    content_encoding: &HeaderValue,
+    body: Vec<u8>,
+    max_body_size: u64,
+) -> Result<Vec<u8>, ErrorResponse> {
+    let unsupported = || {
+        RuntimeError::UnsupportedContentEncoding(
+            String::from_utf8_lossy(content_encoding.as_bytes()).into_owned(),
+        )
+        .to_error_response()
+    };
+    let content_encoding = content_encoding.to_str().map_err(|_| unsupported())?.trim();
+    if content_encoding.eq_ignore_ascii_case("identity") {
+        return Ok(body);
+    }
+    let coding = ContentCoding::from_str(content_encoding).ok_or_else(unsupported)?;
+
+    // read at most one byte more than allowed to detect oversized bodies (zip bombs)
+    let mut decoded = Vec::new();
+    coding
+        .decoder(&body)
+        .take(max_body_size + 1)
+        .read_to_end(&mut decoded)
+        .map_err(|e| RuntimeError::PostBodyReadError(format!("{}", e)).to_error_response())?;
+    if decoded.len() as u64 > max_body_size {
+        return Err(RuntimeError::PayloadTooLarge { max_body_size }.to_error_response());
+    }
+    Ok(decoded)
+}
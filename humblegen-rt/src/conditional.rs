@@ -0,0 +1,138 @@
+//! `SERVER`, `HANDLER` - entity tags and conditional requests.
+//!
+//! - If enabled (`Config::etags`), successful `GET` responses carry a weak `ETag`,
+//!   computed from the JSON serialization of the handler's response.
+//!   A `GET` (or `HEAD`) request whose `If-None-Match` header matches it is answered with `304 Not Modified`.
+//! - `PUT` and `PATCH` handlers receive the request's `If-Match` header as an `IfMatch`
+//!   and may reject the request with `ServiceError::PreconditionFailed` (`412 Precondition Failed`).
+//!
+//! Since all generated entity tags are weak, they are always compared weakly (RFC 7232 2.3.2).
+
+use crate::handler::ServiceError;
+
+use hyper::header::{self, HeaderValue};
+use hyper::{Body, Request, Response, StatusCode};
+use sha1::Digest;
+
+/// The entity tag of a handler response, recorded in the response's extensions
+/// by `server::handler_response_to_hyper_response_with_format`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ETag(pub String);
+
+impl ETag {
+    /// The weak entity tag of `json`, the JSON serialization of a handler response.
+    pub fn of_json(json: &[u8]) -> Self {
+        ETag(format!("W/\"{:x}\"", sha1::Sha1::digest(json)))
+    }
+
+    /// The weak entity tag of `value`, as sent along with `value` in a JSON response.
+    ///
+    /// Handlers use it to evaluate an `IfMatch` precondition against the current state of a resource.
+    pub fn of<T: serde::Serialize>(value: &T) -> Result<Self, serde_json::Error> {
+        serde_json::to_vec(value).map(|json| Self::of_json(&json))
+    }
+}
+
+impl std::fmt::Display for ETag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The `If-Match` precondition of a `PUT` or `PATCH` request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IfMatch {
+    /// `If-Match: *`, i.e., the resource must exist.
+    Any,
+    /// The request applies only if the resource's current entity tag is one of these.
+    Tags(Vec<String>),
+}
+
+impl IfMatch {
+    /// Helper function used by generated code to pass the `If-Match` header to the handler.
+    /// `None` if the request has no (readable) `If-Match` header.
+    pub fn from_request(req: &Request<Body>) -> Option<Self> {
+        let value = req.headers().get(header::IF_MATCH)?.to_str().ok()?;
+        if value.trim() == "*" {
+            return Some(IfMatch::Any);
+        }
+        Some(IfMatch::Tags(parse_entity_tags(value)))
+    }
+
+    /// Whether the precondition holds for a resource whose current entity tag is `current`
+    /// (`None` if the resource does not exist).
+    pub fn matches(&self, current: Option<&ETag>) -> bool {
+        match (self, current) {
+            (_, None) => false,
+            (IfMatch::Any, Some(_)) => true,
+            (IfMatch::Tags(tags), Some(current)) => tags.iter().any(|t| weak_eq(t, &current.0)),
+        }
+    }
+
+    /// Evaluates the precondition against the current state of a resource
+    /// (`None` if the resource does not exist).
+    ///
+    /// Fails with `ServiceError::PreconditionFailed` if it does not hold,
+    /// or with `ServiceError::Internal` if `current` cannot be serialized.
+    pub fn check<T: serde::Serialize>(&self, current: Option<&T>) -> Result<(), ServiceError> {
+        let current = current
+            .map(ETag::of)
+            .transpose()
+            .map_err(|e| ServiceError::Internal(Box::new(e)))?;
+        if self.matches(current.as_ref()) {
+            Ok(())
+        } else {
+            Err(ServiceError::PreconditionFailed)
+        }
+    }
+}
+
+/// Adds the `ETag` recorded by the handler to a successful response to `GET` request and
+/// answers it with `304 Not Modified` if it matches the request's `If-None-Match` header.
+pub(crate) fn apply_etag(
+    if_none_match: Option<&HeaderValue>,
+    mut response: Response<Body>,
+) -> Response<Body> {
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+    let etag = match response.extensions().get::<ETag>() {
+        Some(etag) => etag.clone(),
+        None => return response,
+    };
+    let etag_value = match HeaderValue::from_str(&etag.0) {
+        Ok(v) => v,
+        Err(_) => return response,
+    };
+    let not_modified = if_none_match
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| {
+            v.trim() == "*" || parse_entity_tags(v).iter().any(|t| weak_eq(t, &etag.0))
+        });
+    if not_modified {
+        let mut not_modified = Response::new(Body::empty());
+        *not_modified.status_mut() = StatusCode::NOT_MODIFIED;
+        *not_modified.extensions_mut() = std::mem::take(response.extensions_mut());
+        response = not_modified;
+    }
+    response.headers_mut().insert(header::ETAG, etag_value);
+    response
+}
+
+/// The entity tags of an `If-Match` or `If-None-Match` header.
+fn parse_entity_tags(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Weak comparison of two entity tags, ignoring their `W/` prefixes.
+fn weak_eq(a: &str, b: &str) -> bool {
+    fn opaque(tag: &str) -> &str {
+        tag.strip_prefix("W/").unwrap_or(tag)
+    }
+    opaque(a) == opaque(b)
+}
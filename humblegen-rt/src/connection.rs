@@ -0,0 +1,81 @@
+//! `SERVER`, `HANDLER` - information about the connection a request was received on.
+//!
+//! - `listen_and_run_forever` inserts a `ConnectionInfo` into the extensions of each request.
+//!   Servers with their own accept loop (e.g. to terminate TLS) insert it themselves
+//!   before passing the request to `server::handle_request`.
+//! - `handle_request` resolves the `ClientIp` of a request with a `ConnectionInfo`:
+//!   the peer's IP address, unless the peer is a trusted proxy (`Config::trusted_proxies`),
+//!   in which case it is taken from the `X-Forwarded-For` header.
+//!
+//! Both are available to the handler trait's `intercept_handler_pre`, e.g. for audit logs or IP allowlists.
+
+use hyper::header::HeaderMap;
+use hyper::{Body, Request};
+
+use std::net::{IpAddr, SocketAddr};
+
+/// Name of the header in which proxies pass on the IP addresses of their clients.
+pub const X_FORWARDED_FOR: &str = "X-Forwarded-For";
+
+/// The connection a request was received on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    /// The address of the connection's peer, i.e., of the client or of a proxy in front of the server.
+    pub remote_addr: SocketAddr,
+    /// The address the server accepted the connection on.
+    pub local_addr: SocketAddr,
+    /// The DER-encoded certificate chain presented by the peer during a TLS handshake, if any.
+    /// Always `None` for connections accepted by `listen_and_run_forever`, which does not terminate TLS.
+    pub tls_peer_certificates: Option<Vec<Vec<u8>>>,
+}
+
+impl ConnectionInfo {
+    /// The connection info in the extensions of `req`, if any.
+    pub fn of(req: &Request<Body>) -> Option<&Self> {
+        req.extensions().get()
+    }
+}
+
+/// The IP address of the client that sent a request, resolved by `handle_request`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl ClientIp {
+    /// The client IP address in the extensions of `req`, if any.
+    pub fn of(req: &Request<Body>) -> Option<IpAddr> {
+        req.extensions().get::<Self>().map(|ip| ip.0)
+    }
+}
+
+/// The IP address of the client of a connection from `peer`.
+///
+/// If `peer` is one of the `trusted_proxies`, the `X-Forwarded-For` header is searched from right to left
+/// for the first address that is not a trusted proxy (or, failing that, its leftmost address).
+/// The search stops at the first malformed address.
+pub(crate) fn client_ip(peer: IpAddr, headers: &HeaderMap, trusted_proxies: &[IpAddr]) -> IpAddr {
+    let forwarded_for = headers
+        .get_all(X_FORWARDED_FOR)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .collect::<Vec<_>>();
+
+    let mut client = peer;
+    for hop in forwarded_for.iter().rev() {
+        if !trusted_proxies.contains(&client) {
+            break;
+        }
+        match parse_hop(hop.trim()) {
+            Some(ip) => client = ip,
+            None => break,
+        }
+    }
+    client
+}
+
+/// Parses an `X-Forwarded-For` entry, which some proxies write with a port.
+fn parse_hop(hop: &str) -> Option<IpAddr> {
+    hop.parse::<IpAddr>()
+        .ok()
+        .or_else(|| hop.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+}
@@ -0,0 +1,147 @@
+//! `SERVER` - correlation of requests across services: request IDs and W3C trace context.
+//!
+//! Both are made available to interceptors and handlers through the request's extensions
+//! (`RequestId`, `TraceContext`), so that they can be forwarded to downstream services.
+
+use hyper::header::{HeaderMap, HeaderValue};
+use rand::Rng;
+
+use std::sync::Arc;
+
+pub const TRACEPARENT_HEADER_NAME: &str = "traceparent";
+pub const TRACESTATE_HEADER_NAME: &str = "tracestate";
+
+/// Maximum length of a request ID accepted from a client.
+pub const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Generates request IDs for requests that do not carry a (valid) `Request-ID` header.
+pub type RequestIdGenerator = Arc<dyn Fn() -> String + Send + Sync>;
+
+/// The default `RequestIdGenerator`: 30 random alphanumeric characters.
+pub fn random_request_id() -> String {
+    rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(30)
+        .collect()
+}
+
+/// Whether a request ID sent by a client is accepted.
+///
+/// Accepted are non-empty IDs of at most `MAX_REQUEST_ID_LEN` characters
+/// consisting of ASCII alphanumerics, `-`, `_` and `.`.
+pub fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_' || b == b'.')
+}
+
+/// The ID of the request currently being handled.
+///
+/// Inserted into the request's extensions by the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// A [W3C trace context](https://www.w3.org/TR/trace-context/).
+///
+/// Inserted into the request's extensions by the server.
+/// The server continues the trace of a valid incoming `traceparent` header,
+/// and starts a new trace otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    /// 32 lowercase hex digits.
+    pub trace_id: String,
+    /// The span ID of the caller (16 lowercase hex digits), if the trace was continued.
+    pub parent_id: Option<String>,
+    /// The span ID of the request handled by this server (16 lowercase hex digits).
+    pub span_id: String,
+    pub flags: u8,
+    /// The opaque `tracestate` header value sent along with the incoming `traceparent`.
+    pub tracestate: Option<String>,
+}
+
+fn random_hex(digits: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..digits)
+        .map(|_| std::char::from_digit(rng.gen_range(0, 16), 16).unwrap())
+        .collect()
+}
+
+fn is_hex_id(s: &str, digits: usize) -> bool {
+    s.len() == digits
+        && s.bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+        && s.bytes().any(|b| b != b'0')
+}
+
+/// Parses a `traceparent` header value into `(trace_id, parent_id, flags)`.
+fn parse_traceparent(traceparent: &str) -> Option<(&str, &str, u8)> {
+    let mut parts = traceparent.trim().split('-');
+    let version = parts.next()?;
+    let trace_id = parts.next()?;
+    let parent_id = parts.next()?;
+    let flags = parts.next()?;
+    let version_ok = version.len() == 2
+        && u8::from_str_radix(version, 16).is_ok()
+        && version != "ff"
+        // version 00 has exactly four fields, later versions may append more
+        && (version != "00" || parts.next().is_none());
+    if !version_ok || !is_hex_id(trace_id, 32) || !is_hex_id(parent_id, 16) || flags.len() != 2 {
+        return None;
+    }
+    let flags = u8::from_str_radix(flags, 16).ok()?;
+    Some((trace_id, parent_id, flags))
+}
+
+impl TraceContext {
+    /// Continues the trace context of the `traceparent` and `tracestate` headers in `headers`,
+    /// or starts a new one if there is no valid `traceparent` header.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let traceparent = headers
+            .get(TRACEPARENT_HEADER_NAME)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_traceparent);
+        match traceparent {
+            Some((trace_id, parent_id, flags)) => Self {
+                trace_id: trace_id.to_owned(),
+                parent_id: Some(parent_id.to_owned()),
+                span_id: random_hex(16),
+                flags,
+                tracestate: headers
+                    .get(TRACESTATE_HEADER_NAME)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_owned),
+            },
+            None => Self {
+                trace_id: random_hex(32),
+                parent_id: None,
+                span_id: random_hex(16),
+                flags: 0,
+                tracestate: None,
+            },
+        }
+    }
+
+    /// The `traceparent` header value identifying this server's span, for responses
+    /// and requests to downstream services.
+    pub fn traceparent(&self) -> String {
+        format!("00-{}-{}-{:02x}", self.trace_id, self.span_id, self.flags)
+    }
+
+    /// Inserts the `traceparent` and (if any) `tracestate` headers into `headers`.
+    pub fn insert_headers(&self, headers: &mut HeaderMap) {
+        headers.insert(
+            TRACEPARENT_HEADER_NAME,
+            HeaderValue::from_str(&self.traceparent())
+                .expect("traceparent is a valid header value"),
+        );
+        if let Some(tracestate) = self
+            .tracestate
+            .as_ref()
+            .and_then(|s| HeaderValue::from_str(s).ok())
+        {
+            headers.insert(TRACESTATE_HEADER_NAME, tracestate);
+        }
+    }
+}
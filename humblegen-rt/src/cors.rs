@@ -0,0 +1,308 @@
+//! `SERVER` - Cross-Origin Resource Sharing (CORS), see <https://fetch.spec.whatwg.org/#http-cors-protocol>.
+//!
+//! If the server is configured with a `CorsConfig`, the runtime
+//!
+//! - answers CORS preflight requests (`OPTIONS` with an `Access-Control-Request-Method` header)
+//!   for every route it serves, without invoking the route's handler and
+//! - adds the CORS response headers to every response to a request from an allowed origin.
+
+use crate::service_protocol::{RuntimeError, ToErrorResponse};
+
+use anyhow::Context;
+use hyper::header::{self, HeaderMap, HeaderName, HeaderValue, InvalidHeaderValue};
+use hyper::{Body, Method, Request, Response};
+
+use std::time::Duration;
+
+/// CORS configuration of a server.
+///
+/// The default configuration allows cross-origin requests from any origin, with every method
+/// that the requested route is mounted for and any request header. Other configurations are built
+/// by `CorsConfig::builder`, which validates the configured origins and header names.
+///
+/// Configured through the generated `Builder`.
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    allowed_origins: Option<Vec<HeaderValue>>,
+    allowed_methods: Option<Vec<Method>>,
+    allowed_headers: Option<Vec<HeaderName>>,
+    /// The `Access-Control-Expose-Headers` header, including `Request-ID`.
+    exposed_headers: HeaderValue,
+    allow_credentials: bool,
+    max_age: Option<Duration>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: None,
+            allowed_methods: None,
+            allowed_headers: None,
+            exposed_headers: HeaderValue::from_static(crate::server::REQUEST_ID_HEADER_NAME),
+            allow_credentials: false,
+            max_age: None,
+        }
+    }
+}
+
+/// Builds a `CorsConfig`, see `CorsConfig::builder`.
+#[derive(Debug, Clone, Default)]
+pub struct CorsConfigBuilder {
+    allowed_origins: Option<Vec<String>>,
+    allowed_methods: Option<Vec<Method>>,
+    allowed_headers: Option<Vec<String>>,
+    exposed_headers: Vec<String>,
+    allow_credentials: bool,
+    max_age: Option<Duration>,
+}
+
+impl CorsConfig {
+    pub fn builder() -> CorsConfigBuilder {
+        CorsConfigBuilder::default()
+    }
+}
+
+impl CorsConfigBuilder {
+    /// Only allows cross-origin requests from `origins` (e.g. `https://example.com`).
+    /// By default, any origin is allowed, which cannot be combined with `allow_credentials`.
+    pub fn allowed_origins<S: Into<String>>(
+        mut self,
+        origins: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.allowed_origins = Some(origins.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Only allows `methods` in cross-origin requests.
+    /// By default, every method that the requested route is mounted for is allowed.
+    pub fn allowed_methods(mut self, methods: impl IntoIterator<Item = Method>) -> Self {
+        self.allowed_methods = Some(methods.into_iter().collect());
+        self
+    }
+
+    /// Only allows the request headers `headers` (case-insensitive) in cross-origin requests.
+    /// By default, any request header is allowed.
+    pub fn allowed_headers<S: Into<String>>(
+        mut self,
+        headers: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.allowed_headers = Some(headers.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Allows cross-origin callers to read the response headers `headers`,
+    /// in addition to the `Request-ID` header, which is always exposed.
+    pub fn exposed_headers<S: Into<String>>(
+        mut self,
+        headers: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.exposed_headers = headers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Allows cross-origin requests to include credentials (cookies, `Authorization` headers).
+    ///
+    /// Requires `allowed_origins`: otherwise *every* website could make authenticated requests
+    /// on behalf of the user.
+    pub fn allow_credentials(mut self, allow_credentials: bool) -> Self {
+        self.allow_credentials = allow_credentials;
+        self
+    }
+
+    /// Sets how long browsers may cache the response to a preflight request.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Fails if an origin is not a valid header value, a header name is invalid
+    /// or credentials are allowed without restricting the allowed origins.
+    pub fn build(self) -> anyhow::Result<CorsConfig> {
+        if self.allow_credentials && self.allowed_origins.is_none() {
+            anyhow::bail!("CORS credentials can only be allowed for explicitly allowed origins");
+        }
+        let allowed_origins = self
+            .allowed_origins
+            .map(|origins| {
+                origins
+                    .iter()
+                    .map(|origin| {
+                        HeaderValue::from_str(origin)
+                            .with_context(|| format!("invalid CORS origin {:?}", origin))
+                    })
+                    .collect::<anyhow::Result<_>>()
+            })
+            .transpose()?;
+        let allowed_headers = self
+            .allowed_headers
+            .map(|headers| {
+                headers
+                    .iter()
+                    .map(|h| header_name(h))
+                    .collect::<anyhow::Result<_>>()
+            })
+            .transpose()?;
+        let exposed_headers = std::iter::once(crate::server::REQUEST_ID_HEADER_NAME)
+            .chain(self.exposed_headers.iter().map(String::as_str))
+            .map(header_name)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(CorsConfig {
+            allowed_origins,
+            allowed_methods: self.allowed_methods,
+            allowed_headers,
+            exposed_headers: join_header_names(exposed_headers.iter())?,
+            allow_credentials: self.allow_credentials,
+            max_age: self.max_age,
+        })
+    }
+}
+
+fn header_name(name: &str) -> anyhow::Result<HeaderName> {
+    HeaderName::from_bytes(name.as_bytes())
+        .with_context(|| format!("invalid CORS header name {:?}", name))
+}
+
+/// Whether `req` is a CORS preflight request.
+pub fn is_preflight(req: &Request<Body>) -> bool {
+    req.method() == Method::OPTIONS
+        && req.headers().contains_key(header::ORIGIN)
+        && req
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+}
+
+/// Answers the CORS preflight request `req` for a route mounted for `route_methods`.
+pub fn preflight_response(
+    config: &CorsConfig,
+    req: &Request<Body>,
+    route_methods: &[Method],
+) -> Response<Body> {
+    let reject = |reason: &str| {
+        tracing::debug!(reason, "rejected CORS preflight request");
+        RuntimeError::CorsRequestRejected(reason.to_owned())
+            .to_error_response()
+            .to_hyper_response()
+    };
+
+    let origin = match req.headers().get(header::ORIGIN) {
+        Some(origin) if config.allows_origin(origin) => origin,
+        _ => return reject("origin not allowed"),
+    };
+
+    let requested_method = req
+        .headers()
+        .get(header::ACCESS_CONTROL_REQUEST_METHOD)
+        .and_then(|m| Method::from_bytes(m.as_bytes()).ok());
+    let allowed_methods: Vec<&Method> = route_methods
+        .iter()
+        .filter(|m| config.allows_method(m))
+        .collect();
+    match requested_method {
+        Some(m) if allowed_methods.contains(&&m) => {}
+        _ => return reject("method not allowed"),
+    }
+
+    let requested_headers = req
+        .headers()
+        .get(header::ACCESS_CONTROL_REQUEST_HEADERS)
+        .cloned();
+    if let (Some(allowed_headers), Some(requested_headers)) =
+        (&config.allowed_headers, &requested_headers)
+    {
+        let all_allowed = requested_headers
+            .to_str()
+            .unwrap_or("")
+            .split(',')
+            .map(str::trim)
+            .filter(|h| !h.is_empty())
+            .all(|h| {
+                allowed_headers
+                    .iter()
+                    .any(|a| a.as_str().eq_ignore_ascii_case(h))
+            });
+        if !all_allowed {
+            return reject("header not allowed");
+        }
+    }
+
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = hyper::StatusCode::NO_CONTENT;
+    let headers = response.headers_mut();
+    config.insert_origin_headers(origin, headers);
+    // methods are tokens, which are valid header values
+    if let Ok(allowed_methods) = join_header_names(allowed_methods.into_iter()) {
+        headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, allowed_methods);
+    }
+    if let Some(requested_headers) = requested_headers {
+        // the requested headers have been checked against `allowed_headers` above
+        headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, requested_headers);
+    }
+    if let Some(max_age) = config.max_age {
+        headers.insert(
+            header::ACCESS_CONTROL_MAX_AGE,
+            HeaderValue::from(max_age.as_secs()),
+        );
+    }
+    response
+}
+
+/// Adds the CORS headers to the `response` of a (non-preflight) request with the given `origin` header.
+pub fn add_response_headers(
+    config: &CorsConfig,
+    origin: Option<&HeaderValue>,
+    response: &mut Response<Body>,
+) {
+    let origin = match origin {
+        Some(origin) if config.allows_origin(origin) => origin,
+        _ => return,
+    };
+    let headers = response.headers_mut();
+    config.insert_origin_headers(origin, headers);
+    headers.insert(
+        header::ACCESS_CONTROL_EXPOSE_HEADERS,
+        config.exposed_headers.clone(),
+    );
+}
+
+impl CorsConfig {
+    fn allows_origin(&self, origin: &HeaderValue) -> bool {
+        match &self.allowed_origins {
+            None => true,
+            Some(allowed) => allowed.contains(origin),
+        }
+    }
+
+    fn allows_method(&self, method: &Method) -> bool {
+        match &self.allowed_methods {
+            None => true,
+            Some(allowed) => allowed.contains(method),
+        }
+    }
+
+    fn insert_origin_headers(&self, origin: &HeaderValue, headers: &mut HeaderMap) {
+        // `build` ensures that credentials are only allowed for explicitly allowed origins,
+        // as `*` is not permitted in combination with credentials
+        if self.allowed_origins.is_none() {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_ORIGIN,
+                HeaderValue::from_static("*"),
+            );
+        } else {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+            headers.append(header::VARY, HeaderValue::from_static("Origin"));
+        }
+        if self.allow_credentials {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+    }
+}
+
+/// Joins methods or header names into a header value.
+fn join_header_names<'a, T: AsRef<str> + 'a>(
+    names: impl Iterator<Item = &'a T>,
+) -> Result<HeaderValue, InvalidHeaderValue> {
+    HeaderValue::from_str(&names.map(AsRef::as_ref).collect::<Vec<_>>().join(", "))
+}
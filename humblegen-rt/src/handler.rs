@@ -15,6 +15,8 @@ pub type HandlerResponse<T> = Result<T, ServiceError>;
 pub enum ServiceError {
     Authentication,
     Authorization,
+    /// A precondition of the request (e.g. its `If-Match` header) does not hold.
+    PreconditionFailed,
     Internal(Box<dyn std::error::Error + Send + Sync>),
 }
 
@@ -23,6 +25,7 @@ impl Display for ServiceError {
         match self {
             ServiceError::Authentication => write!(f, "authentication error"),
             ServiceError::Authorization => write!(f, "not authorized"),
+            ServiceError::PreconditionFailed => write!(f, "precondition failed"),
             ServiceError::Internal(e) => write!(f, "internal server error: {:?}", e),
         }
     }
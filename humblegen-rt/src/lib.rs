@@ -8,15 +8,32 @@
 
 pub mod serialization_helpers;
 pub use serialization_helpers as deser_helpers; // compat
+pub mod blob;
+pub mod builtin_routes;
+pub mod compression;
+pub mod conditional;
+pub mod connection;
+pub mod correlation;
+pub mod cors;
 pub mod handler;
+pub mod metrics;
+pub mod mock;
+pub mod multipart;
+pub mod rate_limit;
 pub mod regexset_map;
 pub mod server;
 pub mod service_protocol;
+pub mod stream;
+pub mod test_client;
+pub mod websocket;
+pub mod wire_format;
 
 pub extern crate anyhow;
 pub extern crate chrono;
 pub extern crate downcast_rs;
 pub extern crate hyper;
+#[cfg(feature = "metrics")]
+pub extern crate prometheus;
 pub extern crate regex;
 pub extern crate tokio;
 pub extern crate tracing;
@@ -0,0 +1,108 @@
+//! `SERVER` - [Prometheus](https://prometheus.io) metrics for requests handled by a server.
+//!
+//! Requires the `metrics` cargo feature. Metrics are registered with the default
+//! `prometheus` registry, so application-defined metrics are exposed alongside them:
+//!
+//! - `humblegen_requests_total`: counter of handled requests
+//! - `humblegen_request_duration_seconds`: histogram of request handling latencies
+//!
+//! Both are labelled by `service` (humblespec service name), `route` (handler trait fn name),
+//! `method` and `outcome` (see `server::Outcome::label`).
+//! Only requests that are routed to a handler are recorded.
+
+use crate::server::{Config, Outcome};
+
+use hyper::{Body, Method, Request, Response};
+
+use std::time::Duration;
+
+/// Metrics configuration, see `server::Config::metrics`.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsConfig {
+    /// URL path at which the metrics are exposed in the Prometheus text format
+    /// (e.g. `/metrics`), or `None` to not expose them.
+    pub endpoint: Option<String>,
+}
+
+/// Whether the runtime was built with the `metrics` cargo feature.
+pub const fn is_supported() -> bool {
+    cfg!(feature = "metrics")
+}
+
+#[cfg(feature = "metrics")]
+mod prom {
+    use lazy_static::lazy_static;
+    use prometheus::{register_histogram_vec, register_int_counter_vec};
+    use prometheus::{HistogramVec, IntCounterVec};
+
+    const LABELS: &[&str] = &["service", "route", "method", "outcome"];
+
+    lazy_static! {
+        pub static ref REQUESTS_TOTAL: IntCounterVec = register_int_counter_vec!(
+            "humblegen_requests_total",
+            "Number of requests handled, by route and outcome.",
+            LABELS
+        )
+        .expect("metric can be registered");
+        pub static ref REQUEST_DURATION_SECONDS: HistogramVec = register_histogram_vec!(
+            "humblegen_request_duration_seconds",
+            "Request handling latencies in seconds, by route and outcome.",
+            LABELS
+        )
+        .expect("metric can be registered");
+    }
+}
+
+/// Records a request that was routed to the handler trait fn `route` of `service`.
+#[cfg(feature = "metrics")]
+pub(crate) fn observe(
+    service: &str,
+    route: &str,
+    method: &Method,
+    outcome: Outcome,
+    elapsed: Duration,
+) {
+    let labels = [service, route, method.as_str(), outcome.label()];
+    prom::REQUESTS_TOTAL.with_label_values(&labels).inc();
+    prom::REQUEST_DURATION_SECONDS
+        .with_label_values(&labels)
+        .observe(elapsed.as_secs_f64());
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) fn observe(_: &str, _: &str, _: &Method, _: Outcome, _: Duration) {}
+
+/// Returns the metrics exposition if `req` is a `GET` request for the configured endpoint.
+#[cfg(feature = "metrics")]
+pub(crate) fn exposition_response(config: &Config, req: &Request<Body>) -> Option<Response<Body>> {
+    use prometheus::Encoder;
+
+    let endpoint = config.metrics.as_ref()?.endpoint.as_ref()?;
+    if !(req.method() == Method::GET || req.method() == Method::HEAD)
+        || req.uri().path() != endpoint
+    {
+        return None;
+    }
+    let encoder = prometheus::TextEncoder::new();
+    let mut buf = Vec::new();
+    if let Err(e) = encoder.encode(&prometheus::gather(), &mut buf) {
+        tracing::error!(err = ?e, "cannot encode metrics");
+        return Some(
+            Response::builder()
+                .status(500)
+                .body(Body::empty())
+                .expect("response must be buildable"),
+        );
+    }
+    Some(
+        Response::builder()
+            .header(hyper::header::CONTENT_TYPE, encoder.format_type())
+            .body(Body::from(buf))
+            .expect("response must be buildable"),
+    )
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) fn exposition_response(_: &Config, _: &Request<Body>) -> Option<Response<Body>> {
+    None
+}
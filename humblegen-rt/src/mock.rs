@@ -0,0 +1,220 @@
+//! `GEN`, `HANDLER` - fake data for mock handler implementations.
+//!
+//! If the Rust backend is asked to generate mocks, it implements `Fake` for every type of the humblespec
+//! and generates a `Mock$ServiceName` handler for every service. A mock answers each request with
+//! a canned response, if one is configured for the endpoint, or with a value produced by `Fake::fake`.
+//!
+//! Fake data is pseudo-random, but deterministic: mocks created with the same seed
+//! answer the same sequence of requests with the same responses.
+
+use crate::blob::Blob;
+use crate::stream::ResponseStream;
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Nesting depth beyond which lists, maps and options are empty,
+/// which keeps fake values of recursive types finite.
+const MAX_DEPTH: usize = 4;
+
+/// Maximum number of items in a fake list, map or stream.
+const MAX_ITEMS: usize = 3;
+
+const WORDS: &[&str] = &[
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliett",
+    "kilo", "lima", "mike", "november", "oscar", "papa", "quebec", "romeo", "sierra", "tango",
+];
+
+/// The source of randomness of fake data.
+#[derive(Debug, Clone)]
+pub struct FakeRng {
+    rng: StdRng,
+    depth: usize,
+}
+
+impl FakeRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            depth: 0,
+        }
+    }
+
+    /// Helper function used by generated code to pick one of `n` enum variants.
+    pub fn variant(&mut self, n: usize) -> usize {
+        self.rng.gen_range(0, n)
+    }
+
+    /// The number of items of a fake list, map or stream, `0` beyond the maximum nesting depth.
+    pub fn collection_len(&mut self) -> usize {
+        if self.depth >= MAX_DEPTH {
+            0
+        } else {
+            self.rng.gen_range(0, MAX_ITEMS + 1)
+        }
+    }
+
+    /// Whether a fake option is `Some`, always `false` beyond the maximum nesting depth.
+    pub fn option_is_some(&mut self) -> bool {
+        self.depth < MAX_DEPTH && self.rng.gen()
+    }
+
+    /// Fakes a value nested in a collection or option using `fake`.
+    pub fn nested<T>(&mut self, fake: impl FnOnce(&mut Self) -> T) -> T {
+        self.depth += 1;
+        let value = fake(self);
+        self.depth -= 1;
+        value
+    }
+}
+
+/// A type whose values can be faked.
+pub trait Fake: Sized {
+    fn fake(rng: &mut FakeRng) -> Self;
+}
+
+impl Fake for () {
+    fn fake(_rng: &mut FakeRng) -> Self {}
+}
+
+impl Fake for bool {
+    fn fake(rng: &mut FakeRng) -> Self {
+        rng.rng.gen()
+    }
+}
+
+impl Fake for u8 {
+    fn fake(rng: &mut FakeRng) -> Self {
+        rng.rng.gen()
+    }
+}
+
+impl Fake for i32 {
+    fn fake(rng: &mut FakeRng) -> Self {
+        rng.rng.gen_range(-1000, 1000)
+    }
+}
+
+impl Fake for u32 {
+    fn fake(rng: &mut FakeRng) -> Self {
+        rng.rng.gen_range(0, 1000)
+    }
+}
+
+impl Fake for f64 {
+    fn fake(rng: &mut FakeRng) -> Self {
+        // two decimal places survive a JSON round trip unchanged
+        f64::from(rng.rng.gen_range(-100_000, 100_000)) / 100.0
+    }
+}
+
+impl Fake for String {
+    fn fake(rng: &mut FakeRng) -> Self {
+        let words = rng.rng.gen_range(1, 4);
+        (0..words)
+            .map(|_| WORDS[rng.rng.gen_range(0, WORDS.len())])
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl Fake for DateTime<Utc> {
+    /// A point in time between 2000 and 2030, with a resolution of seconds.
+    fn fake(rng: &mut FakeRng) -> Self {
+        Utc.timestamp_opt(rng.rng.gen_range(946_684_800, 1_893_456_000), 0)
+            .unwrap()
+    }
+}
+
+impl Fake for NaiveDate {
+    /// A date between 2000 and 2030.
+    fn fake(rng: &mut FakeRng) -> Self {
+        NaiveDate::from_num_days_from_ce_opt(rng.rng.gen_range(730_120, 741_077))
+            .expect("dates between 2000 and 2030 are valid")
+    }
+}
+
+impl Fake for uuid::Uuid {
+    /// A version 4 (random) UUID.
+    fn fake(rng: &mut FakeRng) -> Self {
+        uuid::Builder::from_bytes(rng.rng.gen())
+            .set_variant(uuid::Variant::RFC4122)
+            .set_version(uuid::Version::Random)
+            .build()
+    }
+}
+
+impl<T: Fake> Fake for Option<T> {
+    fn fake(rng: &mut FakeRng) -> Self {
+        if rng.option_is_some() {
+            Some(rng.nested(T::fake))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: Fake> Fake for Vec<T> {
+    fn fake(rng: &mut FakeRng) -> Self {
+        let len = rng.collection_len();
+        (0..len).map(|_| rng.nested(T::fake)).collect()
+    }
+}
+
+impl<K: Fake + Eq + Hash, V: Fake> Fake for HashMap<K, V> {
+    fn fake(rng: &mut FakeRng) -> Self {
+        let len = rng.collection_len();
+        (0..len)
+            .map(|_| (rng.nested(K::fake), rng.nested(V::fake)))
+            .collect()
+    }
+}
+
+impl<T: Fake, E: Fake> Fake for Result<T, E> {
+    /// Always `Ok`, domain errors are returned by canned responses.
+    fn fake(rng: &mut FakeRng) -> Self {
+        Ok(T::fake(rng))
+    }
+}
+
+impl Fake for Blob {
+    /// Random bytes without a content type, i.e., of the endpoint's media type.
+    fn fake(rng: &mut FakeRng) -> Self {
+        let len = rng.rng.gen_range(0, 64);
+        Blob::new((0..len).map(|_| rng.rng.gen::<u8>()).collect::<Vec<_>>())
+    }
+}
+
+impl<T: Fake + Send + 'static> Fake for ResponseStream<T> {
+    fn fake(rng: &mut FakeRng) -> Self {
+        let items: Vec<T> = Fake::fake(rng);
+        ResponseStream::new(futures::stream::iter(items))
+    }
+}
+
+macro_rules! fake_tuple {
+    ($($t:ident),+) => {
+        impl<$($t: Fake),+> Fake for ($($t,)+) {
+            fn fake(rng: &mut FakeRng) -> Self {
+                ($($t::fake(rng),)+)
+            }
+        }
+    };
+}
+
+fake_tuple!(A);
+fake_tuple!(A, B);
+fake_tuple!(A, B, C);
+fake_tuple!(A, B, C, D);
+fake_tuple!(A, B, C, D, E);
+fake_tuple!(A, B, C, D, E, F);
+fake_tuple!(A, B, C, D, E, F, G);
+fake_tuple!(A, B, C, D, E, F, G, H);
+fake_tuple!(A, B, C, D, E, F, G, H, I);
+fake_tuple!(A, B, C, D, E, F, G, H, I, J);
+fake_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+fake_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
@@ -0,0 +1,290 @@
+//! `GEN`, `HANDLER` - multipart request bodies (`POST /path -> multipart { meta: Meta, file: file }`).
+//!
+//! The dispatcher of an endpoint with a multipart body parses the `multipart/form-data` request
+//! part by part (see `Multipart`). Parts other than files are JSON-encoded and deserialized into
+//! their declared type. Files are passed to the handler as a `FilePart`, which implements `AsyncRead`.
+//!
+//! Parts are collected by name, in any order. If the last declared part is a file, it must also be
+//! sent last: it is streamed to the handler as it arrives. All other parts are buffered in memory,
+//! which counts against the body size limit.
+
+use crate::server::BodyReadLimits;
+use crate::service_protocol::{ErrorResponse, RuntimeError, ToErrorResponse};
+
+use futures::stream::{Stream, StreamExt};
+use multer::bytes::Bytes;
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::time::{Delay, Instant};
+
+/// Media type of multipart request bodies.
+pub const MULTIPART_MEDIA_TYPE: &str = "multipart/form-data";
+
+/// A file part of a multipart request body.
+///
+/// Reading a streamed file fails with an I/O error if the body exceeds the size limit
+/// or is not received within the body read timeout.
+pub struct FilePart {
+    /// The file name sent by the client, if any.
+    pub file_name: Option<String>,
+    /// The content type sent by the client, if any.
+    pub content_type: Option<String>,
+    data: FileData,
+}
+
+enum FileData {
+    Buffered(io::Cursor<Bytes>),
+    Streaming(Box<StreamingFile>),
+}
+
+struct StreamingFile {
+    field: multer::Field<'static>,
+    leftover: Bytes,
+    /// Elapses when the body read timeout is exceeded.
+    deadline: Option<Delay>,
+}
+
+impl FilePart {
+    /// Reads the whole file into memory.
+    pub async fn bytes(mut self) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        tokio::io::AsyncReadExt::read_to_end(&mut self, &mut bytes).await?;
+        Ok(bytes)
+    }
+}
+
+impl tokio::io::AsyncRead for FilePart {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        match &mut self.data {
+            FileData::Buffered(cursor) => Poll::Ready(io::Read::read(cursor, buf)),
+            FileData::Streaming(file) => {
+                let StreamingFile {
+                    field,
+                    leftover,
+                    deadline,
+                } = &mut **file;
+                while leftover.is_empty() {
+                    if let Some(deadline) = deadline {
+                        if Pin::new(deadline).poll(cx).is_ready() {
+                            return Poll::Ready(Err(io::Error::new(
+                                io::ErrorKind::TimedOut,
+                                "body read timeout exceeded",
+                            )));
+                        }
+                    }
+                    match futures::ready!(Pin::new(&mut *field).poll_next(cx)) {
+                        None => return Poll::Ready(Ok(0)),
+                        Some(Err(e)) => return Poll::Ready(Err(io::Error::other(e))),
+                        Some(Ok(chunk)) => *leftover = chunk,
+                    }
+                }
+                let n = leftover.len().min(buf.len());
+                buf[..n].copy_from_slice(&leftover.split_to(n));
+                Poll::Ready(Ok(n))
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for FilePart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FilePart")
+            .field("file_name", &self.file_name)
+            .field("content_type", &self.content_type)
+            .finish()
+    }
+}
+
+/// A multipart request body, whose parts are taken by name by generated code.
+pub struct Multipart {
+    /// The buffered parts by name.
+    parts: HashMap<String, BufferedPart>,
+    /// The part to be streamed, if it was received.
+    streamed: Option<multer::Field<'static>>,
+    deadline: Option<Instant>,
+}
+
+struct BufferedPart {
+    file_name: Option<String>,
+    content_type: Option<String>,
+    bytes: Bytes,
+}
+
+impl Multipart {
+    /// Helper function used by generated code to read a multipart request body.
+    ///
+    /// Buffers all parts up to the part named `streamed`, which is left to be streamed
+    /// (see `streaming_file`). Parts that are not taken by generated code are ignored.
+    ///
+    /// Enforces the `BodyReadLimits` found in the request's extensions
+    /// (or their defaults if there are none); both limits apply to the whole body.
+    /// Compressed bodies are rejected.
+    pub async fn from_request(
+        req: &mut hyper::Request<hyper::Body>,
+        streamed: Option<&str>,
+    ) -> Result<Self, ErrorResponse> {
+        let content_type = req
+            .headers()
+            .get(hyper::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("");
+        let boundary = multer::parse_boundary(content_type).map_err(|_| {
+            RuntimeError::UnsupportedMediaType(format!(
+                "expected `{}` with a boundary, got `{}`",
+                MULTIPART_MEDIA_TYPE, content_type
+            ))
+            .to_error_response()
+        })?;
+        if let Some(content_encoding) = req.headers().get(hyper::header::CONTENT_ENCODING) {
+            if content_encoding != "identity" {
+                return Err(RuntimeError::UnsupportedContentEncoding(
+                    String::from_utf8_lossy(content_encoding.as_bytes()).into_owned(),
+                )
+                .to_error_response());
+            }
+        }
+        let limits = req
+            .extensions()
+            .get::<BodyReadLimits>()
+            .copied()
+            .unwrap_or_default();
+
+        let body = std::mem::replace(req.body_mut(), hyper::Body::empty())
+            .map(|chunk| chunk.map(|chunk| chunk.to_vec()));
+        let constraints = multer::Constraints::new()
+            .size_limit(multer::SizeLimit::new().whole_stream(limits.max_body_size));
+        let mut inner = multer::Multipart::with_constraints(body, boundary, constraints);
+        let deadline = limits.timeout.map(|timeout| Instant::now() + timeout);
+        let mut multipart = Multipart {
+            parts: HashMap::new(),
+            streamed: None,
+            deadline,
+        };
+
+        while let Some(field) = with_deadline(limits, deadline, inner.next_field()).await? {
+            let name = match field.name() {
+                Some(name) if Some(name) == streamed => {
+                    multipart.streamed = Some(field);
+                    break;
+                }
+                Some(name) => name.to_owned(),
+                None => continue,
+            };
+            if multipart.parts.contains_key(&name) {
+                return Err(
+                    RuntimeError::PostBodyInvalid(format!("duplicate part `{}`", name))
+                        .to_error_response(),
+                );
+            }
+            let (file_name, content_type) = file_metadata(&field);
+            let bytes = with_deadline(limits, deadline, field.bytes()).await?;
+            multipart.parts.insert(
+                name,
+                BufferedPart {
+                    file_name,
+                    content_type,
+                    bytes,
+                },
+            );
+        }
+        Ok(multipart)
+    }
+
+    /// Takes the part `name` and deserializes it from JSON.
+    ///
+    /// A missing part is deserialized from `null`, i.e., it is only allowed for `option[T]` parts.
+    pub fn field<T: serde::de::DeserializeOwned>(
+        &mut self,
+        name: &str,
+    ) -> Result<T, ErrorResponse> {
+        let json = match self.parts.remove(name) {
+            Some(part) => part.bytes,
+            None => Bytes::from_static(b"null"),
+        };
+        serde_json::from_slice(&json).map_err(|e| {
+            RuntimeError::PostBodyInvalid(format!("invalid part `{}`: {}", name, e))
+                .to_error_response()
+        })
+    }
+
+    /// Takes the buffered file part `name`.
+    pub fn buffered_file(&mut self, name: &str) -> Result<FilePart, ErrorResponse> {
+        let part = self.parts.remove(name).ok_or_else(|| missing_file(name))?;
+        Ok(FilePart {
+            file_name: part.file_name,
+            content_type: part.content_type,
+            data: FileData::Buffered(io::Cursor::new(part.bytes)),
+        })
+    }
+
+    /// Takes the file part `name`, passed as `streamed` to `from_request`, whose contents are
+    /// read from the connection as the handler reads the `FilePart`.
+    pub fn streaming_file(&mut self, name: &str) -> Result<FilePart, ErrorResponse> {
+        let field = self.streamed.take().ok_or_else(|| missing_file(name))?;
+        let (file_name, content_type) = file_metadata(&field);
+        Ok(FilePart {
+            file_name,
+            content_type,
+            data: FileData::Streaming(Box::new(StreamingFile {
+                field,
+                leftover: Bytes::new(),
+                deadline: self.deadline.map(tokio::time::delay_until),
+            })),
+        })
+    }
+}
+
+fn missing_file(name: &str) -> ErrorResponse {
+    RuntimeError::PostBodyInvalid(format!("missing file part `{}`", name)).to_error_response()
+}
+
+fn file_metadata(field: &multer::Field<'static>) -> (Option<String>, Option<String>) {
+    (
+        field.file_name().map(str::to_owned),
+        field.content_type().map(|m| m.to_string()),
+    )
+}
+
+/// Awaits `read`, enforcing the body read timeout, which elapses at `deadline`.
+async fn with_deadline<T>(
+    limits: BodyReadLimits,
+    deadline: Option<Instant>,
+    read: impl Future<Output = Result<T, multer::Error>>,
+) -> Result<T, ErrorResponse> {
+    let result = match deadline {
+        None => read.await,
+        Some(deadline) => tokio::time::timeout_at(deadline, read)
+            .await
+            .map_err(|_| RuntimeError::PostBodyReadTimeout.to_error_response())?,
+    };
+    result.map_err(|e| {
+        // errors while reading a part are wrapped in `StreamReadFailed`
+        let size_exceeded =
+            |e: &multer::Error| matches!(e, multer::Error::StreamSizeExceeded { .. });
+        match e {
+            multer::Error::StreamReadFailed(ref inner)
+                if matches!(inner.downcast_ref(), Some(e) if size_exceeded(e)) =>
+            {
+                RuntimeError::PayloadTooLarge {
+                    max_body_size: limits.max_body_size,
+                }
+                .to_error_response()
+            }
+            ref e if size_exceeded(e) => RuntimeError::PayloadTooLarge {
+                max_body_size: limits.max_body_size,
+            }
+            .to_error_response(),
+            multer::Error::StreamReadFailed(e) => {
+                RuntimeError::PostBodyReadError(e.to_string()).to_error_response()
+            }
+            e => RuntimeError::PostBodyInvalid(e.to_string()).to_error_response(),
+        }
+    })
+}
@@ -0,0 +1,201 @@
+//! `SERVER`, `HANDLER` - token bucket rate limiting per client.
+//!
+//! A service can be configured with a `RateLimit`, and individual endpoints can override it
+//! (see `ServiceConfig`). Each client, identified by a `RateLimitKey`, has a bucket of `burst` tokens
+//! that is refilled at a constant rate. Every request takes a token; requests that find their bucket
+//! empty are rejected with `RuntimeError::RateLimited` and a `Retry-After` header.
+//!
+//! Requests are rate limited before their handler's interceptor is invoked,
+//! except with `RateLimitKey::Context`, where the key is derived from the interceptor's result.
+
+use crate::connection::ClientIp;
+use crate::service_protocol::{ErrorResponse, RuntimeError, ToErrorResponse};
+
+use hyper::header::HeaderName;
+use hyper::{Body, Request};
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Derives the rate limit key of a request, see `RateLimitKey::Custom`.
+pub type RateLimitKeyFn = Arc<dyn Fn(&Request<Body>) -> Option<String> + Send + Sync>;
+
+/// Identifies the client whose bucket a request takes a token from.
+///
+/// Requests without a key are not rate limited.
+#[derive(Clone)]
+pub enum RateLimitKey {
+    /// The IP address of the client: the peer's address or, for requests from a trusted proxy,
+    /// the address named by `X-Forwarded-For` (see `connection::ClientIp`).
+    ClientIp,
+    /// The value of a request header, e.g. an API key.
+    Header(HeaderName),
+    /// The key returned by the handler trait's `rate_limit_key` for the interceptor's context,
+    /// e.g. the ID of the authenticated user.
+    Context,
+    /// A function of the request.
+    Custom(RateLimitKeyFn),
+}
+
+impl std::fmt::Debug for RateLimitKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RateLimitKey::ClientIp => write!(f, "ClientIp"),
+            RateLimitKey::Header(name) => f.debug_tuple("Header").field(name).finish(),
+            RateLimitKey::Context => write!(f, "Context"),
+            RateLimitKey::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// A token bucket rate limit.
+///
+/// Clones share their buckets, i.e., a `RateLimit` used for several services or endpoints
+/// limits the requests to all of them together.
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+    burst: u32,
+    refill_interval: Duration,
+    key: RateLimitKey,
+    buckets: Arc<Mutex<Buckets>>,
+}
+
+/// The buckets of the clients that made a request since the last sweep.
+///
+/// A bucket that has not been touched for `burst * refill_interval` is full,
+/// which is the state a new bucket starts in, so it is dropped by the next sweep.
+/// Sweeps happen at most once per `burst * refill_interval`.
+#[derive(Debug)]
+struct Buckets {
+    by_key: HashMap<String, Bucket>,
+    swept_at: Instant,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimit {
+    /// Allows `requests` requests per `period` and client, keyed by `RateLimitKey::ClientIp`.
+    /// Up to `requests` requests may be made in a burst.
+    pub fn new(requests: u32, period: Duration) -> Self {
+        assert!(requests > 0, "a rate limit must allow at least one request");
+        Self {
+            burst: requests,
+            refill_interval: period / requests,
+            key: RateLimitKey::ClientIp,
+            buckets: Arc::new(Mutex::new(Buckets {
+                by_key: HashMap::new(),
+                swept_at: Instant::now(),
+            })),
+        }
+    }
+
+    /// Allows `requests` requests per second and client.
+    pub fn per_second(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(1))
+    }
+
+    /// Allows `requests` requests per minute and client.
+    pub fn per_minute(requests: u32) -> Self {
+        Self::new(requests, Duration::from_secs(60))
+    }
+
+    /// Sets the number of requests a client may make in a burst (at least 1).
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// Sets how clients are identified.
+    pub fn key(mut self, key: RateLimitKey) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// Takes a token from the bucket of `key`.
+    /// Fails with the time until the next token is available if the bucket is empty.
+    fn take(&self, key: String) -> Result<(), Duration> {
+        let now = Instant::now();
+        let burst = f64::from(self.burst);
+        let refill = |bucket: &mut Bucket| {
+            let refilled = now.duration_since(bucket.refilled_at).as_secs_f64()
+                / self.refill_interval.as_secs_f64();
+            bucket.tokens = (bucket.tokens + refilled).min(burst);
+            bucket.refilled_at = now;
+        };
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let full_after = self.refill_interval * self.burst;
+        if now.duration_since(buckets.swept_at) >= full_after {
+            buckets
+                .by_key
+                .retain(|_, bucket| now.duration_since(bucket.refilled_at) < full_after);
+            buckets.swept_at = now;
+        }
+        let bucket = buckets.by_key.entry(key).or_insert(Bucket {
+            tokens: burst,
+            refilled_at: now,
+        });
+        refill(bucket);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(self.refill_interval.mul_f64(1.0 - bucket.tokens))
+        }
+    }
+
+    fn check(&self, key: Option<String>) -> Result<(), ErrorResponse> {
+        let key = match key {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+        self.take(key).map_err(|wait| {
+            tracing::debug!(?wait, "request rate limited");
+            // `Retry-After` has a resolution of seconds, round up
+            let retry_after = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+            RuntimeError::RateLimited { retry_after }.to_error_response()
+        })
+    }
+}
+
+/// The rate limit of a route whose key depends on the interceptor's context,
+/// inserted into the request's extensions by `admit`.
+#[derive(Debug, Clone)]
+struct DeferredRateLimit(RateLimit);
+
+/// Admits `req` under `limit`, or defers the decision to `admit_context`
+/// if requests are keyed by `RateLimitKey::Context`.
+pub(crate) fn admit(limit: &RateLimit, req: &mut Request<Body>) -> Result<(), ErrorResponse> {
+    let key = match &limit.key {
+        RateLimitKey::ClientIp => ClientIp::of(req).map(|ip| ip.to_string()),
+        RateLimitKey::Header(name) => req
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned),
+        RateLimitKey::Custom(key) => key(req),
+        RateLimitKey::Context => {
+            req.extensions_mut()
+                .insert(DeferredRateLimit(limit.clone()));
+            return Ok(());
+        }
+    };
+    limit.check(key)
+}
+
+/// Helper function used by generated code to rate limit a request by the key the handler
+/// derives from the interceptor's context, if its route is limited with `RateLimitKey::Context`.
+pub fn admit_context(
+    req: &Request<Body>,
+    key: impl FnOnce() -> Option<String>,
+) -> Result<(), ErrorResponse> {
+    match req.extensions().get::<DeferredRateLimit>() {
+        Some(DeferredRateLimit(limit)) => limit.check(key()),
+        None => Ok(()),
+    }
+}
@@ -87,4 +87,17 @@ impl<I, T: Entry<I>> RegexSetMap<I, T> {
 
         GetResult::One(&self.entries[matching_idx])
     }
+
+    /// All entries, in insertion order.
+    pub fn entries(&self) -> impl Iterator<Item = &T> {
+        self.entries.iter()
+    }
+
+    /// All entries whose `.regex()` matches `s`, regardless of `.matches_input()`.
+    pub fn get_all_ignoring_input<'a>(&'a self, s: &str) -> impl Iterator<Item = &'a T> + 'a {
+        self.set
+            .matches(s)
+            .into_iter()
+            .map(move |matching_idx| &self.entries[matching_idx])
+    }
 }
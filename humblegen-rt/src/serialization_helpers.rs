@@ -1,38 +1,193 @@
 //! `GEN` - deserialization helpers used by dispatcher.
 
+use crate::compression;
+use crate::server::BodyReadLimits;
 use crate::service_protocol::ErrorResponse;
 use crate::service_protocol::RuntimeError;
 use crate::service_protocol::ToErrorResponse;
+use crate::wire_format::WireFormat;
 
+use hyper::body::HttpBody;
 use serde::{Deserializer, Serializer};
 
-pub fn deser_param<T, E>(name: &str, value: &str) -> Result<T, ErrorResponse>
-where
-    E: std::fmt::Display,
-    T: std::str::FromStr<Err = E>,
-{
-    // TODO: Use std::primitive::str here, once Rust 1.43.0 has been out longer.
-    str::parse(value).map_err(|e| {
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Helper function used by generated code to deserialize a route param.
+///
+/// The param's `value` is percent-decoded before parsing.
+pub fn deser_param<T: RouteParam>(name: &str, value: &str) -> Result<T, ErrorResponse> {
+    let invalid = |parse_error: String| {
         RuntimeError::RouteParamInvalid {
             param_name: name.to_owned(),
-            parse_error: format!("{}", e),
+            parse_error,
         }
         .to_error_response()
-    })
+    };
+    let value = percent_encoding::percent_decode_str(value)
+        .decode_utf8()
+        .map_err(|e| invalid(format!("percent-decoded value is not valid UTF-8: {}", e)))?;
+    T::parse_route_param(&value).map_err(invalid)
+}
+
+/// A type that can be parsed from a (percent-decoded) route param.
+///
+/// Implemented for the humblespec built-in types that can be used as route params.
+pub trait RouteParam: Sized {
+    /// Parses `value`, returning a human-readable error message on failure.
+    fn parse_route_param(value: &str) -> Result<Self, String>;
+
+    /// Formats `self` such that `parse_route_param` parses it back.
+    fn format_route_param(&self) -> String;
+}
+
+/// Implements `RouteParam` via `FromStr`, prefixing errors with a description of the expected format.
+macro_rules! impl_route_param_from_str {
+    ($($ty:ty => $expected:expr),* $(,)?) => {
+        $(
+            impl RouteParam for $ty {
+                fn parse_route_param(value: &str) -> Result<Self, String> {
+                    str::parse(value)
+                        .map_err(|e| format!("expected {}, got {:?}: {}", $expected, value, e))
+                }
+
+                fn format_route_param(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    };
+}
+
+impl_route_param_from_str! {
+    i32 => "a signed 32-bit integer",
+    u32 => "an unsigned 32-bit integer",
+    u8 => "an unsigned 8-bit integer",
+    f64 => "a floating-point number",
+    uuid::Uuid => "a UUID",
+    chrono::NaiveDate => "a date in the format YYYY-MM-DD",
+}
+
+impl RouteParam for chrono::DateTime<chrono::Utc> {
+    fn parse_route_param(value: &str) -> Result<Self, String> {
+        str::parse(value)
+            .map_err(|e| format!("expected an RFC 3339 date and time, got {:?}: {}", value, e))
+    }
+
+    fn format_route_param(&self) -> String {
+        self.to_rfc3339()
+    }
+}
+
+impl RouteParam for String {
+    fn parse_route_param(value: &str) -> Result<Self, String> {
+        Ok(value.to_owned())
+    }
+
+    fn format_route_param(&self) -> String {
+        self.clone()
+    }
+}
+
+impl RouteParam for Vec<u8> {
+    fn parse_route_param(value: &str) -> Result<Self, String> {
+        base64::decode(value)
+            .map_err(|e| format!("expected base64-encoded bytes, got {:?}: {}", value, e))
+    }
+
+    fn format_route_param(&self) -> String {
+        base64::encode(self)
+    }
+}
+
+impl RouteParam for bool {
+    fn parse_route_param(value: &str) -> Result<Self, String> {
+        match value {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(format!("expected `true` or `false`, got {:?}", value)),
+        }
+    }
+
+    fn format_route_param(&self) -> String {
+        self.to_string()
+    }
 }
 
 /// Helper function used by generated code to deserialize POST body data.
+///
+/// Enforces the `BodyReadLimits` found in the request's extensions
+/// (or their defaults if there are none).
+/// Bodies with a `Content-Encoding` are decompressed first; the size limit
+/// applies to both the compressed and the decompressed body.
+/// The wire format of the body is determined by its `Content-Type` (see `WireFormat::from_content_type`).
 pub async fn deser_post_data<T: serde::de::DeserializeOwned>(
-    req_body: &mut hyper::Body,
+    req: &mut hyper::Request<hyper::Body>,
 ) -> Result<T, ErrorResponse> {
-    let bytes = hyper::body::to_bytes(req_body)
+    let format = WireFormat::from_content_type(req.headers().get(hyper::header::CONTENT_TYPE))?;
+    let limits = req
+        .extensions()
+        .get::<BodyReadLimits>()
+        .copied()
+        .unwrap_or_default();
+    let body = std::mem::take(req.body_mut());
+    let bytes = read_body(req.headers(), body, limits)
         .await
-        .map_err(|e| RuntimeError::PostBodyReadError(format!("{}", e)).to_error_response())?
-        .to_vec();
-    match serde_json::from_slice::<T>(&bytes[..]) {
-        Ok(b) => Ok(b),
-        Err(e) => Err(RuntimeError::PostBodyReadError(format!("{}", e)).to_error_response()),
+        .map_err(RuntimeError::to_error_response)?;
+    let bytes = match req.headers().get(hyper::header::CONTENT_ENCODING) {
+        None => bytes,
+        Some(content_encoding) => {
+            compression::decode_request_body(content_encoding, bytes, limits.max_body_size)?
+        }
+    };
+    format
+        .deserialize::<T>(&bytes[..])
+        .map_err(|e| RuntimeError::PostBodyReadError(e).to_error_response())
+}
+
+/// Reads the body of a request (or a response) with the given `headers` within `limits`,
+/// without decoding it.
+///
+/// Fails with `PayloadTooLarge` as soon as the body, or its announced `Content-Length`,
+/// exceeds the size limit, and with `PostBodyReadTimeout` if reading it takes too long.
+pub async fn read_body(
+    headers: &hyper::HeaderMap,
+    body: hyper::Body,
+    limits: BodyReadLimits,
+) -> Result<Vec<u8>, RuntimeError> {
+    let max_body_size = limits.max_body_size;
+    // reject early if the sender announces a body that is too large
+    let content_length = headers
+        .get(hyper::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if matches!(content_length, Some(l) if l > max_body_size) {
+        return Err(RuntimeError::PayloadTooLarge { max_body_size });
+    }
+
+    let read = read_body_limited(body, max_body_size);
+    match limits.timeout {
+        None => read.await,
+        Some(timeout) => tokio::time::timeout(timeout, read)
+            .await
+            .map_err(|_| RuntimeError::PostBodyReadTimeout)?,
+    }
+}
+
+/// Reads `body` chunk by chunk and fails as soon as it exceeds `max_body_size` bytes.
+async fn read_body_limited(
+    mut body: hyper::Body,
+    max_body_size: u64,
+) -> Result<Vec<u8>, RuntimeError> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| RuntimeError::PostBodyReadError(format!("{}", e)))?;
+        if (bytes.len() + chunk.len()) as u64 > max_body_size {
+            return Err(RuntimeError::PayloadTooLarge { max_body_size });
+        }
+        bytes.extend_from_slice(&chunk);
     }
+    Ok(bytes)
 }
 
 /// Helper function used by generated code to deserialize the URL query from application/x-www-form-urlencoded into a type T.
@@ -45,14 +200,127 @@ pub fn deser_query_serde_urlencoded<'a, T: serde::de::Deserialize<'a>>(
     }
 }
 
-/// Helper function used by generated code to deserialize the URL query into a primitive type.
-pub fn deser_query_primitive<E: std::fmt::Display, T: std::str::FromStr<Err = E>>(
+/// Helper function used by generated code to deserialize the URL query into a type T.
+///
+/// Lists of built-in types are repeated keys (`tags=a&tags=b`), other nested values use
+/// bracket notation (`filter[name]=x`, `items[0][id]=1`), see `service_protocol.md`.
+/// Brackets may be percent-encoded.
+pub fn deser_query_serde_qs<T: serde::de::DeserializeOwned>(
     query: &str,
 ) -> Result<T, ErrorResponse> {
-    str::parse(query).map_err(|e| RuntimeError::QueryInvalid(format!("{}", e)).to_error_response())
+    serde_qs::Config::new(QUERY_MAX_DEPTH, false)
+        .deserialize_str(&group_repeated_keys(query))
+        .map_err(|e| RuntimeError::QueryInvalid(format!("{}", e)).to_error_response())
+}
+
+/// Maximum nesting depth of bracket notation keys accepted by `deser_query_serde_qs`.
+const QUERY_MAX_DEPTH: usize = 5;
+
+/// Rewrites keys that occur more than once in `query` to bracket notation (`tags=a&tags=b`
+/// becomes `tags[]=a&tags[]=b`), which serde_qs decodes as a sequence.
+fn group_repeated_keys(query: &str) -> Cow<'_, str> {
+    fn key(pair: &str) -> &str {
+        pair.split('=').next().unwrap_or("")
+    }
+    let is_list_key = |key: &str| {
+        key.ends_with("[]")
+            || key
+                .get(key.len().saturating_sub(6)..)
+                .is_some_and(|suffix| suffix.eq_ignore_ascii_case("%5B%5D"))
+    };
+
+    let mut counts = HashMap::new();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        *counts.entry(key(pair)).or_insert(0) += 1;
+    }
+    let is_repeated = |key: &str| counts[key] > 1 && !is_list_key(key);
+    if !counts.keys().any(|key| is_repeated(key)) {
+        return Cow::Borrowed(query);
+    }
+
+    let pairs: Vec<_> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let key = key(pair);
+            if is_repeated(key) {
+                Cow::Owned(format!("{}[]{}", key, &pair[key.len()..]))
+            } else {
+                Cow::Borrowed(pair)
+            }
+        })
+        .collect();
+    Cow::Owned(pairs.join("&"))
+}
+
+/// Deserializes a list of built-in types from a URL query field, see `deser_query_serde_qs`.
+///
+/// Used by generated code with `#[serde(deserialize_with)]`, since serde_qs decodes a key
+/// that occurs only once as a single value rather than a list of one element.
+pub fn deser_query_list_field<'de, D, T>(input: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: RouteParam,
+{
+    struct ListVisitor<T>(std::marker::PhantomData<T>);
+
+    impl<'de, T: RouteParam> serde::de::Visitor<'de> for ListVisitor<T> {
+        type Value = Vec<T>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a value or a list of values")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            T::parse_route_param(v)
+                .map(|value| vec![value])
+                .map_err(E::custom)
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::SeqAccess<'de>,
+        {
+            let mut values = Vec::new();
+            while let Some(value) = seq.next_element::<String>()? {
+                values.push(T::parse_route_param(&value).map_err(serde::de::Error::custom)?);
+            }
+            Ok(values)
+        }
+    }
+
+    input.deserialize_any(ListVisitor(std::marker::PhantomData))
+}
+
+/// Helper function used by generated code to deserialize the URL query into a primitive type.
+///
+/// The query is percent-decoded before parsing.
+pub fn deser_query_primitive<T: RouteParam>(query: &str) -> Result<T, ErrorResponse> {
+    let invalid = |e: String| RuntimeError::QueryInvalid(e).to_error_response();
+    let value = percent_encoding::percent_decode_str(query)
+        .decode_utf8()
+        .map_err(|e| invalid(format!("percent-decoded query is not valid UTF-8: {}", e)))?;
+    T::parse_route_param(&value).map_err(invalid)
+}
+
+/// Helper function used by generated code to deserialize the URL query into a list of primitive types.
+///
+/// The query is a `&`-separated list of percent-encoded values.
+pub fn deser_query_list<T: RouteParam>(query: &str) -> Result<Vec<T>, ErrorResponse> {
+    query
+        .split('&')
+        .filter(|item| !item.is_empty())
+        .map(deser_query_primitive)
+        .collect()
 }
 
 /// Helper function used by generate code to deserialize a humblegen `bytes` field.
+///
+/// Human-readable formats (JSON) carry bytes as base64-encoded strings,
+/// binary formats (CBOR, MessagePack) as native byte strings.
 pub fn deser_bytes<'de, D>(input: D) -> Result<Vec<u8>, D::Error>
 where
     D: Deserializer<'de>,
@@ -65,7 +333,7 @@ where
             &self,
             formatter: &mut std::fmt::Formatter<'_>,
         ) -> std::result::Result<(), std::fmt::Error> {
-            write!(formatter, "a base64-encoded byte array")
+            write!(formatter, "a base64-encoded byte array or a byte string")
         }
 
         fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
@@ -74,15 +342,136 @@ where
         {
             base64::decode(v).map_err(E::custom)
         }
+
+        fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(v.to_vec())
+        }
+
+        fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(v)
+        }
     }
 
-    input.deserialize_str(BytesSerdeVisitor)
+    if input.is_human_readable() {
+        input.deserialize_str(BytesSerdeVisitor)
+    } else {
+        input.deserialize_byte_buf(BytesSerdeVisitor)
+    }
 }
 
 /// Helper function used by generate code to serialize a humblegen `bytes` field.
+///
+/// See `deser_bytes` for the representation.
 pub fn ser_bytes<S>(v: &[u8], serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
-    serializer.serialize_str(&base64::encode(v))
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&base64::encode(v))
+    } else {
+        serializer.serialize_bytes(v)
+    }
+}
+
+/// Helper module used by generated code to (de)serialize a field with humblegen `bytes` nested in
+/// options, lists and maps, e.g. `option[list[bytes]]`, via `#[serde(with)]`.
+///
+/// Each `bytes` value is represented as described in `deser_bytes`.
+pub mod nested_bytes {
+    use super::{deser_bytes, ser_bytes};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use std::collections::HashMap;
+    use std::hash::Hash;
+
+    /// A type that is `bytes` or contains `bytes` nested in options, lists and maps.
+    pub trait NestedBytes: Sized {
+        fn serialize_nested<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+        fn deserialize_nested<'de, D: Deserializer<'de>>(input: D) -> Result<Self, D::Error>;
+    }
+
+    /// Serializes a nested value via `NestedBytes`.
+    struct Ser<'a, T>(&'a T);
+
+    impl<'a, T: NestedBytes> Serialize for Ser<'a, T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.0.serialize_nested(serializer)
+        }
+    }
+
+    /// Deserializes a nested value via `NestedBytes`.
+    struct De<T>(T);
+
+    impl<'de, T: NestedBytes> Deserialize<'de> for De<T> {
+        fn deserialize<D: Deserializer<'de>>(input: D) -> Result<Self, D::Error> {
+            T::deserialize_nested(input).map(De)
+        }
+    }
+
+    impl NestedBytes for Vec<u8> {
+        fn serialize_nested<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            ser_bytes(self, serializer)
+        }
+
+        fn deserialize_nested<'de, D: Deserializer<'de>>(input: D) -> Result<Self, D::Error> {
+            deser_bytes(input)
+        }
+    }
+
+    impl<T: NestedBytes> NestedBytes for Option<T> {
+        fn serialize_nested<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.as_ref().map(Ser).serialize(serializer)
+        }
+
+        fn deserialize_nested<'de, D: Deserializer<'de>>(input: D) -> Result<Self, D::Error> {
+            Ok(Option::<De<T>>::deserialize(input)?.map(|De(value)| value))
+        }
+    }
+
+    impl<T: NestedBytes> NestedBytes for Vec<T> {
+        fn serialize_nested<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.iter().map(Ser))
+        }
+
+        fn deserialize_nested<'de, D: Deserializer<'de>>(input: D) -> Result<Self, D::Error> {
+            let values = Vec::<De<T>>::deserialize(input)?;
+            Ok(values.into_iter().map(|De(value)| value).collect())
+        }
+    }
+
+    impl<K, T> NestedBytes for HashMap<K, T>
+    where
+        K: Serialize + for<'de> Deserialize<'de> + Eq + Hash,
+        T: NestedBytes,
+    {
+        fn serialize_nested<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_map(self.iter().map(|(key, value)| (key, Ser(value))))
+        }
+
+        fn deserialize_nested<'de, D: Deserializer<'de>>(input: D) -> Result<Self, D::Error> {
+            let entries = HashMap::<K, De<T>>::deserialize(input)?;
+            Ok(entries
+                .into_iter()
+                .map(|(key, De(value))| (key, value))
+                .collect())
+        }
+    }
+
+    pub fn serialize<T: NestedBytes, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.serialize_nested(serializer)
+    }
+
+    pub fn deserialize<'de, T: NestedBytes, D: Deserializer<'de>>(input: D) -> Result<T, D::Error> {
+        T::deserialize_nested(input)
+    }
 }
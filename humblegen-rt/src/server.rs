@@ -1,9 +1,19 @@
 //! `GEN` Generic parts of the humblegen HTTP service server implementation, based on [`hyper`](https://hyper.rs).
 
+use crate::builtin_routes;
+use crate::builtin_routes::BuiltinRoutesConfig;
+use crate::compression;
+use crate::conditional;
+use crate::connection::{self, ClientIp, ConnectionInfo};
+use crate::correlation::{self, RequestId, RequestIdGenerator, TraceContext};
+use crate::cors::{self, CorsConfig};
 use crate::handler::HandlerResponse;
+use crate::metrics::{self, MetricsConfig};
+use crate::rate_limit::{self, RateLimit};
 use crate::regexset_map;
 use crate::regexset_map::RegexSetMap;
 use crate::service_protocol::{self, RuntimeError, ToErrorResponse};
+use crate::wire_format::{self, WireFormat};
 use derivative::Derivative;
 use tracing_futures::Instrument;
 
@@ -12,31 +22,161 @@ use hyper::Body;
 use hyper::Request;
 use hyper::Response;
 
+use std::collections::HashMap;
 use std::convert::Infallible;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use rand::Rng;
+/// Default for `Config::max_body_size`: 2 MiB.
+pub const DEFAULT_MAX_BODY_SIZE: u64 = 2 * 1024 * 1024;
 
-/// Serve `services` via HTTP, binding to the given `addr`.
+/// Default for `Config::body_read_timeout`.
+pub const DEFAULT_BODY_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Server-wide configuration.
+///
+/// Populated by the generated `Builder`.
+#[derive(Derivative, Clone)]
+#[derivative(Debug)]
+pub struct Config {
+    /// Maximum size of a request body in bytes.
+    /// Larger bodies are rejected with `RuntimeError::PayloadTooLarge`.
+    pub max_body_size: u64,
+    /// Maximum time spent reading a request body.
+    /// Slower bodies are rejected with `RuntimeError::PostBodyReadTimeout`.
+    /// `None` disables the timeout.
+    pub body_read_timeout: Option<Duration>,
+    /// Maximum time spent handling a request, including the interceptor and reading the body.
+    /// When it elapses, the handler future is dropped and the request is answered with
+    /// `RuntimeError::Timeout`.
+    /// `None` (the default) disables the timeout.
+    pub request_timeout: Option<Duration>,
+    /// Cross-Origin Resource Sharing configuration.
+    /// `None` (the default) disables CORS support.
+    pub cors: Option<CorsConfig>,
+    /// Minimum size in bytes of a response body to be compressed,
+    /// if the client accepts a supported content coding (`br`, `gzip` or `deflate`).
+    /// `None` (the default) disables response compression.
+    pub compression_threshold: Option<usize>,
+    /// Whether successful `GET` responses carry a weak `ETag` and conditional `GET` requests
+    /// (`If-None-Match`) are answered with `304 Not Modified`, see `conditional`.
+    /// Disabled by default.
+    pub etags: bool,
+    /// Generates the IDs of requests that do not carry a valid `Request-ID` header.
+    #[derivative(Debug = "ignore")]
+    pub request_id_generator: RequestIdGenerator,
+    /// Prometheus metrics configuration (requires the `metrics` cargo feature).
+    /// `None` (the default) disables metrics.
+    pub metrics: Option<MetricsConfig>,
+    /// Built-in liveness, readiness and introspection routes (none are mounted by default).
+    pub builtin_routes: BuiltinRoutesConfig,
+    /// Proxies whose `X-Forwarded-For` header is trusted to name the client of a request
+    /// (none by default), see `connection::ClientIp`.
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            body_read_timeout: Some(DEFAULT_BODY_READ_TIMEOUT),
+            request_timeout: None,
+            cors: None,
+            compression_threshold: None,
+            etags: false,
+            request_id_generator: Arc::new(correlation::random_request_id),
+            metrics: None,
+            builtin_routes: BuiltinRoutesConfig::default(),
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+/// Per-service overrides of the server-wide `Config`.
+///
+/// A field set to `None` falls back to the corresponding `Config` value.
+#[derive(Debug, Clone, Default)]
+pub struct ServiceConfig {
+    /// Overrides `Config::max_body_size`.
+    pub max_body_size: Option<u64>,
+    /// Overrides `Config::body_read_timeout`.
+    pub body_read_timeout: Option<Duration>,
+    /// Overrides `Config::request_timeout`.
+    pub request_timeout: Option<Duration>,
+    /// Rate limit of the service's endpoints (default: none).
+    pub rate_limit: Option<RateLimit>,
+    /// Rate limits of individual endpoints, keyed by handler trait fn name (e.g. `get_monsters`).
+    /// Overrides `rate_limit`. Building a server fails if a key names no endpoint of the service.
+    pub endpoint_rate_limits: HashMap<String, RateLimit>,
+}
+
+/// The limits that apply to reading the body of a request.
+///
+/// `handle_request_impl` inserts it into the request's extensions before invoking
+/// the dispatcher, where it is picked up by `serialization_helpers::deser_post_data`.
+#[derive(Debug, Clone, Copy)]
+pub struct BodyReadLimits {
+    pub max_body_size: u64,
+    pub timeout: Option<Duration>,
+}
+
+impl BodyReadLimits {
+    fn new(config: &Config, service_config: &ServiceConfig) -> Self {
+        Self {
+            max_body_size: service_config.max_body_size.unwrap_or(config.max_body_size),
+            timeout: service_config
+                .body_read_timeout
+                .or(config.body_read_timeout),
+        }
+    }
+}
+
+impl Default for BodyReadLimits {
+    fn default() -> Self {
+        Self::new(&Config::default(), &ServiceConfig::default())
+    }
+}
+
+/// The services exposed by a server, together with the server's configuration.
+///
+/// Instantiated by generated code.
+#[derive(Debug)]
+pub struct Server {
+    pub services: RegexSetMap<Request<Body>, Service>,
+    pub config: Config,
+}
+
+/// Serve `server` via HTTP, binding to the given `addr`.
 /// Invokes `handle_request`.
 ///
 /// Invoked by generated code.
-pub async fn listen_and_run_forever(
-    services: RegexSetMap<Request<Body>, Service>,
-    addr: &SocketAddr,
-) -> anyhow::Result<()> {
+pub async fn listen_and_run_forever(server: Server, addr: &SocketAddr) -> anyhow::Result<()> {
+    if server.config.metrics.is_some() && !metrics::is_supported() {
+        tracing::warn!(
+            "metrics are configured, but humblegen-rt was built without the `metrics` feature"
+        );
+    }
+
     // Note: this is the standard (noisy) dance for handling hyper requests.
-    let services = Arc::new(services);
-    let server = hyper::Server::bind(addr).serve(hyper::service::make_service_fn(
-        move |_sock: &hyper::server::conn::AddrStream| {
-            let services = Arc::clone(&services);
+    let server = Arc::new(server);
+    let incoming = hyper::server::conn::AddrIncoming::bind(addr).context("cannot bind")?;
+    let local_addr = incoming.local_addr();
+    let hyper_server = hyper::Server::builder(incoming).serve(hyper::service::make_service_fn(
+        move |sock: &hyper::server::conn::AddrStream| {
+            let server = Arc::clone(&server);
+            let connection_info = ConnectionInfo {
+                remote_addr: sock.remote_addr(),
+                local_addr,
+                tls_peer_certificates: None,
+            };
             async move {
                 Ok::<_, Infallible>(hyper::service::service_fn(
-                    move |req: hyper::Request<hyper::Body>| {
-                        let services = Arc::clone(&services);
+                    move |mut req: hyper::Request<hyper::Body>| {
+                        let server = Arc::clone(&server);
+                        req.extensions_mut().insert(connection_info.clone());
                         async move {
-                            let resp = handle_request(services, req).await;
+                            let resp = handle_request(server, req).await;
                             Ok::<Response<hyper::Body>, Infallible>(resp)
                         }
                     },
@@ -45,74 +185,168 @@ pub async fn listen_and_run_forever(
         },
     ));
 
-    server.await.context("server error")?;
+    hyper_server.await.context("server error")?;
     Ok(())
 }
 
-const REQUEST_ID_HEADER_NAME: &'static str = "Request-ID";
+pub(crate) const REQUEST_ID_HEADER_NAME: &str = "Request-ID";
 
-/// The routine that maps an incoming hyper request to a service in `services`,
+/// The routine that maps an incoming hyper request to a service in `server.services`,
 /// and invokes the service's dispatcher.
-pub async fn handle_request(
-    services: Arc<RegexSetMap<Request<Body>, Service>>,
-    req: Request<Body>,
-) -> Response<Body> {
-    let request_id: String = rand::thread_rng()
-        .sample_iter(&rand::distributions::Alphanumeric)
-        .take(30)
-        .collect();
-    let span = tracing::error_span!("handle_request", request_id = ?request_id);
-    handle_request_impl(services, req, request_id)
+///
+/// Accepts the request ID of a valid incoming `Request-ID` header and continues the trace
+/// of an incoming `traceparent` header (see `correlation`).
+/// Resolves the `ClientIp` of a request that carries a `ConnectionInfo` (see `connection`).
+pub async fn handle_request(server: Arc<Server>, mut req: Request<Body>) -> Response<Body> {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER_NAME)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| correlation::is_valid_request_id(id))
+        .map(str::to_owned)
+        .unwrap_or_else(|| (server.config.request_id_generator)());
+    let trace_context = TraceContext::from_headers(req.headers());
+    let client_ip = ConnectionInfo::of(&req).map(|conn| {
+        connection::client_ip(
+            conn.remote_addr.ip(),
+            req.headers(),
+            &server.config.trusted_proxies,
+        )
+    });
+    let span = tracing::error_span!(
+        "handle_request",
+        request_id = ?request_id,
+        client_ip = ?client_ip,
+        trace_id = %trace_context.trace_id,
+        parent_span_id = ?trace_context.parent_id,
+        span_id = %trace_context.span_id,
+    );
+    req.extensions_mut().insert(RequestId(request_id.clone()));
+    if let Some(client_ip) = client_ip {
+        req.extensions_mut().insert(ClientIp(client_ip));
+    }
+    req.extensions_mut().insert(trace_context);
+    handle_request_impl(server, req, request_id)
         .instrument(span)
         .await
 }
 
 pub async fn handle_request_impl(
-    services: Arc<RegexSetMap<Request<Body>, Service>>,
-    req: Request<Body>,
+    server: Arc<Server>,
+    mut req: Request<Body>,
     request_id: String,
 ) -> Response<Body> {
     let path = req.uri().path().to_string(); // necessary because we need to move req into dispatcher, but also need to move captures into dispatcher
+    let origin = req.headers().get(hyper::header::ORIGIN).cloned();
+    let trace_context = req.extensions().get::<TraceContext>().cloned();
+    let accept_encoding = req
+        .headers()
+        .get(hyper::header::ACCEPT_ENCODING)
+        .and_then(compression::negotiate);
+    let if_none_match = req.headers().get(hyper::header::IF_NONE_MATCH).cloned();
 
-    let mut response = match services.get(&path, &req) {
-        regexset_map::GetResult::None => RuntimeError::NoServiceMounted
-            .to_error_response()
-            .to_hyper_response(),
-        regexset_map::GetResult::Ambiguous => RuntimeError::ServiceMountsAmbiguous
-            .to_error_response()
-            .to_hyper_response(),
-        regexset_map::GetResult::One(service) => {
-            tracing::debug!(service_regex = (service.0).0.as_str(), "service matched");
-            let tuple = &service.0;
-            let service_regex_captures = tuple.0.captures(&path).unwrap();
-            let service = service_regex_captures["root"].to_string();
-            let suffix = &service_regex_captures["suffix"];
-            match tuple.1.get(&suffix, &req) {
-                regexset_map::GetResult::None => RuntimeError::NoRouteMountedInService { service }
-                    .to_error_response()
-                    .to_hyper_response(),
-                regexset_map::GetResult::Ambiguous => {
-                    RuntimeError::RouteMountsAmbiguous { service }
-                        .to_error_response()
-                        .to_hyper_response()
-                }
-                regexset_map::GetResult::One(route) => {
-                    tracing::debug!(route_regex = route.regex.as_str(), "route matched");
-                    let captures = route.regex.captures(suffix).unwrap();
-                    let dispatcher = &route.dispatcher;
-
-                    let dispatcher_result = {
-                        let dispatcher_span = tracing::error_span!("invoke_dispatcher");
-                        dispatcher(req, captures).instrument(dispatcher_span).await
-                    };
-                    match dispatcher_result {
-                        Ok(r) => {
-                            tracing::debug!("handler returned Ok");
-                            r
+    let method = req.method().clone();
+
+    let mut response = if let Some(response) = metrics::exposition_response(&server.config, &req) {
+        response
+    } else if let Some(response) = builtin_routes::response(&server, &method, &path).await {
+        response
+    } else {
+        match server.services.get(&path, &req) {
+            regexset_map::GetResult::None => RuntimeError::NoServiceMounted
+                .to_error_response()
+                .to_hyper_response(),
+            regexset_map::GetResult::Ambiguous => RuntimeError::ServiceMountsAmbiguous
+                .to_error_response()
+                .to_hyper_response(),
+            regexset_map::GetResult::One(service) => {
+                tracing::debug!(service_regex = service.regex.as_str(), "service matched");
+                req.extensions_mut()
+                    .insert(BodyReadLimits::new(&server.config, &service.config));
+                let service_regex_captures = service.regex.captures(&path).unwrap();
+                let suffix = &service_regex_captures["suffix"];
+                let routes = &service.routes;
+                let service_config = &service.config;
+                let request_timeout = service
+                    .config
+                    .request_timeout
+                    .or(server.config.request_timeout);
+                let service_name = &service.name;
+                let service = service_regex_captures["root"].to_string();
+                let preflight_cors = server
+                    .config
+                    .cors
+                    .as_ref()
+                    .filter(|_| cors::is_preflight(&req));
+                if let Some(cors_config) = preflight_cors {
+                    let route_methods = allowed_methods(routes, suffix);
+                    if route_methods.is_empty() {
+                        RuntimeError::NoRouteMountedInService { service }
+                            .to_error_response()
+                            .to_hyper_response()
+                    } else {
+                        cors::preflight_response(cors_config, &req, &route_methods)
+                    }
+                } else {
+                    // HEAD requests are dispatched to GET routes, hyper omits the response body
+                    if req.method() == hyper::Method::HEAD {
+                        *req.method_mut() = hyper::Method::GET;
+                    }
+                    match routes.get(suffix, &req) {
+                        regexset_map::GetResult::None => {
+                            let allowed = allowed_methods(routes, suffix);
+                            if allowed.is_empty() {
+                                RuntimeError::NoRouteMountedInService { service }
+                                    .to_error_response()
+                                    .to_hyper_response()
+                            } else if method == hyper::Method::OPTIONS {
+                                let mut response = Response::new(Body::empty());
+                                *response.status_mut() = hyper::StatusCode::NO_CONTENT;
+                                insert_allow_header(&mut response, &allowed);
+                                response
+                            } else {
+                                let mut response = RuntimeError::MethodNotAllowed {
+                                    service,
+                                    allowed: allowed.iter().map(|m| m.to_string()).collect(),
+                                }
+                                .to_error_response()
+                                .to_hyper_response();
+                                insert_allow_header(&mut response, &allowed);
+                                response
+                            }
+                        }
+                        regexset_map::GetResult::Ambiguous => {
+                            RuntimeError::RouteMountsAmbiguous { service }
+                                .to_error_response()
+                                .to_hyper_response()
                         }
-                        Err(e) => {
-                            tracing::error!(err = ?e, "handler returned error");
-                            e.to_hyper_response()
+                        regexset_map::GetResult::One(route) => {
+                            let start = Instant::now();
+                            let rate_limit = service_config
+                                .endpoint_rate_limits
+                                .get(&route.name)
+                                .or(service_config.rate_limit.as_ref());
+                            let admitted = match rate_limit {
+                                Some(limit) => rate_limit::admit(limit, &mut req),
+                                None => Ok(()),
+                            };
+                            let response = match admitted {
+                                Ok(()) => {
+                                    invoke_route(route, req, suffix, request_timeout, &request_id)
+                                        .await
+                                }
+                                Err(e) => e.to_hyper_response(),
+                            };
+                            if server.config.metrics.is_some() {
+                                metrics::observe(
+                                    service_name,
+                                    &route.name,
+                                    &route.method,
+                                    Outcome::of(&response),
+                                    start.elapsed(),
+                                );
+                            }
+                            response
                         }
                     }
                 }
@@ -120,27 +354,152 @@ pub async fn handle_request_impl(
         }
     };
 
-    response.headers_mut().insert(
-        REQUEST_ID_HEADER_NAME,
-        hyper::header::HeaderValue::from_str(&request_id)
-            .expect("request ID is expected to be valid header value"),
-    );
+    if server.config.etags && (method == hyper::Method::GET || method == hyper::Method::HEAD) {
+        response = conditional::apply_etag(if_none_match.as_ref(), response);
+    }
 
-    response.headers_mut().insert(
-        hyper::header::CONTENT_TYPE,
-        hyper::header::HeaderValue::from_static("application/json"),
-    );
+    // accepted request IDs are valid header values, but generated ones need not be
+    match hyper::header::HeaderValue::from_str(&request_id) {
+        Ok(v) => {
+            response.headers_mut().insert(REQUEST_ID_HEADER_NAME, v);
+        }
+        Err(_) => tracing::error!("request ID is not a valid header value"),
+    }
+    if let Some(trace_context) = trace_context {
+        trace_context.insert_headers(response.headers_mut());
+    }
+
+    if let Some(cors_config) = &server.config.cors {
+        cors::add_response_headers(cors_config, origin.as_ref(), &mut response);
+    }
+
+    // a protocol switch (WebSocket upgrade), an answer to OPTIONS or a 304 has no body
+    if !matches!(
+        response.status(),
+        hyper::StatusCode::SWITCHING_PROTOCOLS
+            | hyper::StatusCode::NO_CONTENT
+            | hyper::StatusCode::NOT_MODIFIED
+    ) {
+        // handler responses carry the content type of the negotiated wire format, everything else is JSON
+        response
+            .headers_mut()
+            .entry(hyper::header::CONTENT_TYPE)
+            .or_insert_with(|| {
+                hyper::header::HeaderValue::from_static(wire_format::JSON_MEDIA_TYPE)
+            });
+
+        if let Some(threshold) = server.config.compression_threshold {
+            response = compression::compress_response(response, accept_encoding, threshold).await;
+        }
+    }
 
     tracing::debug!(http_status = ?response.status(), "finished request");
 
     response
 }
 
+/// The methods of the routes matching `suffix`, plus `HEAD` if there is a `GET` route and `OPTIONS`.
+/// Empty if no route matches.
+fn allowed_methods(routes: &RegexSetMap<Request<Body>, Route>, suffix: &str) -> Vec<hyper::Method> {
+    let mut methods: Vec<hyper::Method> = Vec::new();
+    for route in routes.get_all_ignoring_input(suffix) {
+        if !methods.contains(&route.method) {
+            methods.push(route.method.clone());
+        }
+    }
+    if methods.is_empty() {
+        return methods;
+    }
+    if methods.contains(&hyper::Method::GET) && !methods.contains(&hyper::Method::HEAD) {
+        methods.push(hyper::Method::HEAD);
+    }
+    methods.push(hyper::Method::OPTIONS);
+    methods
+}
+
+fn insert_allow_header(response: &mut Response<Body>, methods: &[hyper::Method]) {
+    let allow = methods
+        .iter()
+        .map(hyper::Method::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+    response.headers_mut().insert(
+        hyper::header::ALLOW,
+        hyper::header::HeaderValue::from_str(&allow).expect("methods are valid header values"),
+    );
+}
+
+/// Invokes `route`'s dispatcher for `req`, the part of whose path after the service root is `suffix`.
+async fn invoke_route(
+    route: &Route,
+    req: Request<Body>,
+    suffix: &str,
+    request_timeout: Option<Duration>,
+    request_id: &str,
+) -> Response<Body> {
+    tracing::debug!(route_regex = route.regex.as_str(), "route matched");
+    let captures = route.regex.captures(suffix).unwrap();
+    let dispatcher = &route.dispatcher;
+
+    let dispatcher_result = {
+        let dispatcher_span = tracing::error_span!("invoke_dispatcher");
+        let dispatch = dispatcher(req, captures).instrument(dispatcher_span);
+        match request_timeout {
+            None => dispatch.await,
+            // on timeout, `dispatch` (and with it the handler future) is dropped
+            Some(timeout) => tokio::time::timeout(timeout, dispatch)
+                .await
+                .unwrap_or_else(|_| {
+                    tracing::error!(request_id = %request_id, ?timeout, "request timed out");
+                    Err(RuntimeError::Timeout.to_error_response())
+                }),
+        }
+    };
+    match dispatcher_result {
+        Ok(r) => {
+            tracing::debug!("handler returned Ok");
+            r
+        }
+        Err(e) => {
+            tracing::error!(err = ?e, "handler returned error");
+            e.to_hyper_response()
+        }
+    }
+}
+
 /// A service is a collection of Routes that share a common `prefix`.
 ///
 /// Instantiated by generated code.
 #[derive(Debug)]
-pub struct Service(pub (regex::Regex, RegexSetMap<Request<Body>, Route>));
+pub struct Service {
+    /// The name of the humblespec service.
+    pub name: String,
+    /// The URL path prefix at which the service is mounted.
+    pub root: String,
+    /// Matches the service's URL path prefix as capture group `root`
+    /// and the rest of the path as capture group `suffix`.
+    pub regex: regex::Regex,
+    pub routes: RegexSetMap<Request<Body>, Route>,
+    pub config: ServiceConfig,
+}
+
+impl Service {
+    /// Checks that `config.endpoint_rate_limits` only names endpoints of the service.
+    ///
+    /// Invoked by generated code when the server is built.
+    pub fn check_config(&self) -> anyhow::Result<()> {
+        for name in self.config.endpoint_rate_limits.keys() {
+            if !self.routes.entries().any(|route| &route.name == name) {
+                anyhow::bail!(
+                    "rate limit configured for unknown endpoint `{}` of service `{}`",
+                    name,
+                    self.name
+                );
+            }
+        }
+        Ok(())
+    }
+}
 
 // helper type that avoids bloating the type signature of `DispatcherClosure`.
 type BoxSyncFuture<Output> =
@@ -162,7 +521,11 @@ type DispatcherClosure = dyn Fn(
 #[derive(Derivative)]
 #[derivative(Debug)]
 pub struct Route {
+    /// The name of the handler trait fn invoked by the dispatcher.
+    pub name: String,
     pub method: hyper::Method,
+    /// The URL path relative to the service root, with `{param}` placeholders for route params.
+    pub template: String,
     pub regex: regex::Regex,
     #[derivative(Debug = "ignore")]
     pub dispatcher: Box<DispatcherClosure>,
@@ -179,26 +542,101 @@ impl<'a> regexset_map::Entry<Request<Body>> for Route {
 
 impl<'a> regexset_map::Entry<Request<Body>> for Service {
     fn regex(&self) -> &regex::Regex {
-        let pair = &self.0;
-        &pair.0
+        &self.regex
     }
     fn matches_input(&self, _req: &Request<Body>) -> bool {
         true
     }
 }
 
-/// Conversion of a `HandlerResponse` to a hyper response.
-/// Invoked from generated code within a `DispatcherClosure`.
+/// The outcome of handling a request, derived from the response by `Outcome::of`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The handler returned a regular response.
+    Ok,
+    /// The handler returned the error variant of a humblespec result type.
+    DomainError,
+    /// A `ServiceError` or `RuntimeError`, identified by its variant name.
+    Error(&'static str),
+}
+
+impl Outcome {
+    /// The outcome of a handled request: `DomainError` if recorded in `response`'s extensions,
+    /// `Error` if the response was built from an `ErrorResponse`, `Ok` otherwise.
+    pub fn of(response: &Response<Body>) -> Self {
+        let extensions = response.extensions();
+        if let Some(outcome) = extensions.get::<Outcome>() {
+            *outcome
+        } else if let Some(service_protocol::ErrorName(name)) = extensions.get() {
+            Outcome::Error(name)
+        } else {
+            Outcome::Ok
+        }
+    }
+
+    /// `ok`, `domain_error`, or the variant name of the `ServiceError` / `RuntimeError`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Outcome::Ok => "ok",
+            Outcome::DomainError => "domain_error",
+            Outcome::Error(kind) => kind,
+        }
+    }
+}
+
+/// Conversion of a `HandlerResponse` to a hyper response, serializing a successful response as JSON.
 pub fn handler_response_to_hyper_response<T>(handler_response: HandlerResponse<T>) -> Response<Body>
+where
+    T: serde::Serialize,
+{
+    handler_response_to_hyper_response_with_format(WireFormat::Json, handler_response)
+}
+
+/// Like `handler_response_to_hyper_response`, but serializes a successful response in `format`.
+/// Invoked from generated code within a `DispatcherClosure`.
+pub fn handler_response_to_hyper_response_with_format<T>(
+    format: WireFormat,
+    handler_response: HandlerResponse<T>,
+) -> Response<Body>
+where
+    T: serde::Serialize,
+{
+    serialize_handler_response(format, handler_response, |json| Some(json))
+}
+
+/// Serializes a handler response in `format`.
+///
+/// Successful JSON responses are tagged with the `conditional::ETag` of the part of the body
+/// selected by `etag_source` (none if it returns `None`).
+fn serialize_handler_response<T>(
+    format: WireFormat,
+    handler_response: HandlerResponse<T>,
+    etag_source: fn(&[u8]) -> Option<&[u8]>,
+) -> Response<Body>
 where
     T: serde::Serialize,
 {
     match handler_response {
-        Ok(x) => serde_json::to_string(&x)
-            .map(|s| Response::new(Body::from(s)))
+        Ok(x) => format
+            .serialize(&x)
+            .map(|body| {
+                let etag = match format {
+                    WireFormat::Json => etag_source(&body).map(conditional::ETag::of_json),
+                    WireFormat::Cbor | WireFormat::MessagePack => None,
+                };
+                let mut response = Response::new(Body::from(body));
+                response.headers_mut().insert(
+                    hyper::header::CONTENT_TYPE,
+                    hyper::header::HeaderValue::from_static(format.media_type()),
+                );
+                if let Some(etag) = etag {
+                    response.extensions_mut().insert(etag);
+                }
+                response
+            })
             .unwrap_or_else(|e| {
                 tracing::error!(error = ?e, "cannot serialize handler response");
-                RuntimeError::SerializeHandlerResponse(e.to_string())
+                RuntimeError::SerializeHandlerResponse(e)
                     .to_error_response()
                     .to_hyper_response()
             }),
@@ -210,3 +648,25 @@ where
         }
     }
 }
+
+/// Like `handler_response_to_hyper_response_with_format`, for handlers returning a humblespec
+/// result type. Records `Outcome::DomainError` for the result type's error variant.
+/// Invoked from generated code within a `DispatcherClosure`.
+pub fn result_handler_response_to_hyper_response_with_format<T, E>(
+    format: WireFormat,
+    handler_response: HandlerResponse<Result<T, E>>,
+) -> Response<Body>
+where
+    T: serde::Serialize,
+    E: serde::Serialize,
+{
+    let domain_error = matches!(handler_response, Ok(Err(_)));
+    // the entity tag of `{"Ok":x}` is that of `x`, domain errors are not tagged
+    let mut response = serialize_handler_response(format, handler_response, |json| {
+        json.strip_prefix(b"{\"Ok\":")?.strip_suffix(b"}")
+    });
+    if domain_error && response.status().is_success() {
+        response.extensions_mut().insert(Outcome::DomainError);
+    }
+    response
+}
@@ -36,6 +36,10 @@ pub enum ServiceError {
     /// The request cannot be fulfilled due to an authorization problem.
     /// Maps to HTTP status code 403.
     Authorization,
+    /// A precondition of the request (e.g. its `If-Match` header) does not hold
+    /// for the current state of the resource.
+    /// Maps to HTTP status code 412.
+    PreconditionFailed,
     /// The request cannot be fulfilled due to an error internal to the service
     /// that is _not_ domain-specific. (Domain-specific errors should be represented
     /// as humblespec result types in the service definition.)
@@ -56,27 +60,72 @@ pub enum RuntimeError {
     RouteMountsAmbiguous {
         service: String,
     },
+    MethodNotAllowed {
+        service: String,
+        allowed: Vec<String>,
+    },
     RouteParamInvalid {
         param_name: String,
         parse_error: String,
     },
     QueryInvalid(String),
     PostBodyReadError(String),
+    PostBodyReadTimeout,
     PostBodyInvalid(String),
+    PayloadTooLarge {
+        max_body_size: u64,
+    },
+    Timeout,
+    RateLimited {
+        /// Seconds until the client may retry, repeated in the `Retry-After` header.
+        retry_after: u64,
+    },
+    CorsRequestRejected(String),
+    UnsupportedContentEncoding(String),
+    UnsupportedMediaType(String),
+    NotReady(String),
+    WebSocketUpgradeRequired(String),
     SerializeHandlerResponse(String),
     SerializeErrorResponse(String),
+    /// A validating proxy (`humblegen proxy`) could not reach its upstream,
+    /// or rejected a response of the upstream that does not match the humblespec.
+    BadGateway(String),
 }
 
 impl ErrorResponse {
     pub fn to_hyper_response(&self) -> Response<Body> {
-        hyper::Response::builder()
+        let mut response = hyper::Response::builder()
             .status(self.code)
             .body(
                 serde_json::to_string_pretty(self)
                     .expect("runtime responses must be JSON-serializable")
                     .into(),
             )
-            .expect("runtime responses must always be buildable")
+            .expect("runtime responses must always be buildable");
+        response
+            .extensions_mut()
+            .insert(ErrorName(self.kind.name()));
+        if let ErrorResponseKind::Runtime(RuntimeError::RateLimited { retry_after }) = self.kind {
+            response
+                .headers_mut()
+                .insert(hyper::header::RETRY_AFTER, retry_after.into());
+        }
+        response
+    }
+}
+
+/// The variant name of the `ServiceError` / `RuntimeError` a response was built from,
+/// recorded in the response's extensions by `ErrorResponse::to_hyper_response`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorName(pub &'static str);
+
+impl ErrorResponseKind {
+    /// The variant name of the service or runtime error.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorResponseKind::Service(e) => e.name(),
+            ErrorResponseKind::Runtime(e) => e.name(),
+        }
     }
 }
 
@@ -99,27 +148,76 @@ impl ToErrorResponse for RuntimeError {
 }
 
 impl RuntimeError {
+    /// The variant name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuntimeError::NoServiceMounted => "NoServiceMounted",
+            RuntimeError::ServiceMountsAmbiguous => "ServiceMountsAmbiguous",
+            RuntimeError::NoRouteMountedInService { .. } => "NoRouteMountedInService",
+            RuntimeError::RouteMountsAmbiguous { .. } => "RouteMountsAmbiguous",
+            RuntimeError::MethodNotAllowed { .. } => "MethodNotAllowed",
+            RuntimeError::RouteParamInvalid { .. } => "RouteParamInvalid",
+            RuntimeError::QueryInvalid(_) => "QueryInvalid",
+            RuntimeError::PostBodyReadError(_) => "PostBodyReadError",
+            RuntimeError::PostBodyReadTimeout => "PostBodyReadTimeout",
+            RuntimeError::PostBodyInvalid(_) => "PostBodyInvalid",
+            RuntimeError::PayloadTooLarge { .. } => "PayloadTooLarge",
+            RuntimeError::Timeout => "Timeout",
+            RuntimeError::RateLimited { .. } => "RateLimited",
+            RuntimeError::CorsRequestRejected(_) => "CorsRequestRejected",
+            RuntimeError::UnsupportedContentEncoding(_) => "UnsupportedContentEncoding",
+            RuntimeError::UnsupportedMediaType(_) => "UnsupportedMediaType",
+            RuntimeError::NotReady(_) => "NotReady",
+            RuntimeError::WebSocketUpgradeRequired(_) => "WebSocketUpgradeRequired",
+            RuntimeError::SerializeHandlerResponse(_) => "SerializeHandlerResponse",
+            RuntimeError::SerializeErrorResponse(_) => "SerializeErrorResponse",
+            RuntimeError::BadGateway(_) => "BadGateway",
+        }
+    }
+
     fn status_code(&self) -> u16 {
         match self {
             RuntimeError::NoServiceMounted => 404,
             RuntimeError::NoRouteMountedInService { .. } => 404,
             RuntimeError::RouteMountsAmbiguous { .. } => 500,
+            RuntimeError::MethodNotAllowed { .. } => 405,
             RuntimeError::ServiceMountsAmbiguous => 500,
             RuntimeError::RouteParamInvalid { .. } => 400,
             RuntimeError::QueryInvalid(_) => 400,
             RuntimeError::PostBodyReadError(_) => 400,
+            RuntimeError::PostBodyReadTimeout => 408,
             RuntimeError::PostBodyInvalid(_) => 400,
+            RuntimeError::PayloadTooLarge { .. } => 413,
+            RuntimeError::Timeout => 504,
+            RuntimeError::RateLimited { .. } => 429,
+            RuntimeError::CorsRequestRejected(_) => 403,
+            RuntimeError::UnsupportedContentEncoding(_) => 415,
+            RuntimeError::UnsupportedMediaType(_) => 415,
+            RuntimeError::NotReady(_) => 503,
+            RuntimeError::WebSocketUpgradeRequired(_) => 426,
             RuntimeError::SerializeHandlerResponse(_) => 500,
             RuntimeError::SerializeErrorResponse(_) => 500,
+            RuntimeError::BadGateway(_) => 502,
         }
     }
 }
 
 impl ServiceError {
+    /// The variant name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServiceError::Authentication => "Authentication",
+            ServiceError::Authorization => "Authorization",
+            ServiceError::PreconditionFailed => "PreconditionFailed",
+            ServiceError::Internal(_) => "Internal",
+        }
+    }
+
     pub fn status_code(&self) -> u16 {
         match self {
             ServiceError::Authentication => 401,
             ServiceError::Authorization => 403,
+            ServiceError::PreconditionFailed => 412,
             ServiceError::Internal(_) => 500,
         }
     }
@@ -130,6 +228,7 @@ impl From<super::handler::ServiceError> for ServiceError {
         match e {
             super::handler::ServiceError::Authentication => ServiceError::Authentication,
             super::handler::ServiceError::Authorization => ServiceError::Authorization,
+            super::handler::ServiceError::PreconditionFailed => ServiceError::PreconditionFailed,
             super::handler::ServiceError::Internal(e) => ServiceError::Internal(format!("{}", e)),
         }
     }
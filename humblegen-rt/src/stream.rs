@@ -0,0 +1,186 @@
+//! `GEN`, `HANDLER` - streaming responses of endpoints returning `stream[T]`.
+//!
+//! A handler returns a `ResponseStream<T>`, which the runtime writes item by item as
+//! [NDJSON](http://ndjson.org) or as [Server-Sent Events](https://html.spec.whatwg.org/multipage/server-sent-events.html).
+//! Items are only pulled from the handler's stream when the connection can take more data.
+
+use crate::handler::HandlerResponse;
+use crate::service_protocol::{self, ToErrorResponse};
+
+use futures::stream::{BoxStream, Stream, StreamExt};
+use hyper::header::{self, HeaderValue};
+use hyper::{Body, Request, Response};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pub const NDJSON_MEDIA_TYPE: &str = "application/x-ndjson";
+pub const SSE_MEDIA_TYPE: &str = "text/event-stream";
+
+/// The response type of a handler for an endpoint returning `stream[T]`.
+pub struct ResponseStream<T>(BoxStream<'static, T>);
+
+impl<T> ResponseStream<T> {
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: Stream<Item = T> + Send + 'static,
+    {
+        ResponseStream(stream.boxed())
+    }
+}
+
+impl<T> Stream for ResponseStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.0.poll_next_unpin(cx)
+    }
+}
+
+impl<T> std::fmt::Debug for ResponseStream<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseStream").finish()
+    }
+}
+
+/// The framing of a streaming response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFormat {
+    /// One JSON document per line (`application/x-ndjson`).
+    Ndjson,
+    /// One `data:` event per item (`text/event-stream`).
+    ServerSentEvents,
+}
+
+impl StreamFormat {
+    pub fn media_type(self) -> &'static str {
+        match self {
+            StreamFormat::Ndjson => NDJSON_MEDIA_TYPE,
+            StreamFormat::ServerSentEvents => SSE_MEDIA_TYPE,
+        }
+    }
+
+    /// Server-Sent Events if the `Accept` header explicitly accepts `text/event-stream`, NDJSON otherwise.
+    pub fn from_accept(accept: Option<&HeaderValue>) -> Self {
+        let accepts_sse = accept
+            .and_then(|v| v.to_str().ok())
+            .map(|accept| {
+                accept.split(',').any(|item| {
+                    let mut params = item.split(';');
+                    let media_type = params.next().unwrap_or("").trim();
+                    let q = params
+                        .find_map(|p| p.trim().strip_prefix("q="))
+                        .map(|q| q.trim().parse::<f32>().unwrap_or(0.0))
+                        .unwrap_or(1.0);
+                    media_type.eq_ignore_ascii_case(SSE_MEDIA_TYPE) && q > 0.0
+                })
+            })
+            .unwrap_or(false);
+        if accepts_sse {
+            StreamFormat::ServerSentEvents
+        } else {
+            StreamFormat::Ndjson
+        }
+    }
+
+    /// Serializes a single item as JSON, framed in this format.
+    pub fn encode_item<T: serde::Serialize>(self, item: &T) -> Result<Vec<u8>, String> {
+        let json = serde_json::to_vec(item).map_err(|e| e.to_string())?;
+        Ok(match self {
+            StreamFormat::Ndjson => {
+                let mut line = json;
+                line.push(b'\n');
+                line
+            }
+            StreamFormat::ServerSentEvents => {
+                let mut event = b"data: ".to_vec();
+                event.extend_from_slice(&json);
+                event.extend_from_slice(b"\n\n");
+                event
+            }
+        })
+    }
+}
+
+/// Helper function used by generated code to negotiate the format of a streaming response.
+pub fn response_format(req: &Request<Body>) -> StreamFormat {
+    StreamFormat::from_accept(req.headers().get(header::ACCEPT))
+}
+
+/// Conversion of a `HandlerResponse` of a `stream[T]` endpoint to a hyper response.
+/// Invoked from generated code within a `DispatcherClosure`.
+///
+/// If an item cannot be serialized, the error is logged and the response is aborted,
+/// i.e., the client sees an incomplete body.
+pub fn handler_response_to_hyper_response<T>(
+    format: StreamFormat,
+    handler_response: HandlerResponse<ResponseStream<T>>,
+) -> Response<Body>
+where
+    T: serde::Serialize + Send + 'static,
+{
+    match handler_response {
+        Ok(items) => {
+            let chunks = items.map(move |item| {
+                format.encode_item(&item).map_err(|e| {
+                    tracing::error!(error = ?e, "cannot serialize handler response stream item");
+                    e
+                })
+            });
+            let mut response = Response::new(Body::wrap_stream(chunks));
+            let headers = response.headers_mut();
+            headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(format.media_type()),
+            );
+            headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+            response
+        }
+        Err(e) => {
+            tracing::error!(error = ?e, "handler returned error");
+            service_protocol::ServiceError::from(e)
+                .to_error_response()
+                .to_hyper_response()
+        }
+    }
+}
+
+/// Consumes an NDJSON response body, yielding the deserialized items as they arrive.
+///
+/// Useful for Rust clients of `stream[T]` endpoints.
+pub fn decode_ndjson<T>(body: Body) -> impl Stream<Item = Result<T, String>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    futures::stream::unfold(
+        (body, Vec::new(), false),
+        |(mut body, mut buf, mut done)| async move {
+            loop {
+                if let Some(pos) = buf.iter().position(|b| *b == b'\n') {
+                    let line: Vec<u8> = buf.drain(..=pos).collect();
+                    if line.iter().all(u8::is_ascii_whitespace) {
+                        continue;
+                    }
+                    let item = serde_json::from_slice(&line).map_err(|e| e.to_string());
+                    return Some((item, (body, buf, done)));
+                }
+                if done {
+                    if buf.iter().all(u8::is_ascii_whitespace) {
+                        return None;
+                    }
+                    let item = serde_json::from_slice(&buf).map_err(|e| e.to_string());
+                    buf.clear();
+                    return Some((item, (body, buf, done)));
+                }
+                match body.next().await {
+                    Some(Ok(chunk)) => buf.extend_from_slice(&chunk),
+                    Some(Err(e)) => {
+                        done = true;
+                        buf.clear();
+                        return Some((Err(e.to_string()), (body, buf, done)));
+                    }
+                    None => done = true,
+                }
+            }
+        },
+    )
+}
@@ -0,0 +1,301 @@
+//! `GEN`, `HANDLER` - in-process client for testing handler implementations.
+//!
+//! A `TestClient` passes requests directly to `server::handle_request`, without binding a socket,
+//! and reads the complete response into memory. It is created by the generated `Builder::into_test_client`.
+//!
+//! - `TestClient::request` (and `get`, `post`, ...) builds arbitrary requests and returns a `TestResponse`
+//!   with status, headers and body.
+//! - The generated `$ServiceNameTestClient` has one typed method per endpoint, which returns the decoded
+//!   response body or a `TestError` (e.g. the `ErrorResponse` of a rejected request).
+
+use crate::blob::{self, Blob};
+use crate::connection::ConnectionInfo;
+use crate::serialization_helpers::RouteParam;
+use crate::server::{self, Server};
+use crate::service_protocol::ErrorResponse;
+use crate::wire_format;
+
+use hyper::body::Bytes;
+use hyper::header::{self, HeaderMap, HeaderName, HeaderValue};
+use hyper::{Body, Method, Request, StatusCode};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use std::sync::Arc;
+
+/// Characters that are percent-encoded in route params (RFC 3986 `unreserved` characters are not).
+const ROUTE_PARAM_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Like `ROUTE_PARAM_ENCODE_SET`, but keeps the `/` separators of rest params (`{path: str..}`).
+const REST_PARAM_ENCODE_SET: &AsciiSet = &ROUTE_PARAM_ENCODE_SET.remove(b'/');
+
+/// An in-process client of a `Server`.
+#[derive(Debug, Clone)]
+pub struct TestClient {
+    server: Arc<Server>,
+    default_headers: HeaderMap,
+    connection_info: Option<ConnectionInfo>,
+}
+
+impl TestClient {
+    pub fn new(server: Server) -> Self {
+        Self {
+            server: Arc::new(server),
+            default_headers: HeaderMap::new(),
+            connection_info: None,
+        }
+    }
+
+    /// Sends header `name` with every request, e.g. an `Authorization` header.
+    ///
+    /// Panics if `value` is not a valid header value.
+    pub fn with_header(mut self, name: HeaderName, value: &str) -> Self {
+        let value = HeaderValue::from_str(value).expect("invalid header value");
+        self.default_headers.insert(name, value);
+        self
+    }
+
+    /// Attaches `connection_info` to every request, as if it was received on that connection.
+    /// Requests have no `ConnectionInfo` (and thus no `ClientIp`) by default.
+    pub fn with_connection_info(mut self, connection_info: ConnectionInfo) -> Self {
+        self.connection_info = Some(connection_info);
+        self
+    }
+
+    /// Starts a request to `path_and_query` (e.g. `/api/monsters?limit=3`).
+    pub fn request(&self, method: Method, path_and_query: &str) -> TestRequest<'_> {
+        TestRequest {
+            client: self,
+            method,
+            uri: path_and_query.to_owned(),
+            headers: self.default_headers.clone(),
+            body: Body::empty(),
+        }
+    }
+
+    pub fn get(&self, path_and_query: &str) -> TestRequest<'_> {
+        self.request(Method::GET, path_and_query)
+    }
+
+    pub fn post(&self, path_and_query: &str) -> TestRequest<'_> {
+        self.request(Method::POST, path_and_query)
+    }
+
+    pub fn put(&self, path_and_query: &str) -> TestRequest<'_> {
+        self.request(Method::PUT, path_and_query)
+    }
+
+    pub fn patch(&self, path_and_query: &str) -> TestRequest<'_> {
+        self.request(Method::PATCH, path_and_query)
+    }
+
+    pub fn delete(&self, path_and_query: &str) -> TestRequest<'_> {
+        self.request(Method::DELETE, path_and_query)
+    }
+}
+
+/// A request under construction, see `TestClient::request`.
+#[derive(Debug)]
+pub struct TestRequest<'a> {
+    client: &'a TestClient,
+    method: Method,
+    uri: String,
+    headers: HeaderMap,
+    body: Body,
+}
+
+impl<'a> TestRequest<'a> {
+    /// Sets header `name`, replacing a default header of the client.
+    ///
+    /// Panics if `value` is not a valid header value.
+    pub fn header(mut self, name: HeaderName, value: &str) -> Self {
+        let value = HeaderValue::from_str(value).expect("invalid header value");
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Appends `query` (already encoded) to the URL.
+    pub fn raw_query(mut self, query: &str) -> Self {
+        if !query.is_empty() {
+            self.uri
+                .push(if self.uri.contains('?') { '&' } else { '?' });
+            self.uri.push_str(query);
+        }
+        self
+    }
+
+    /// Appends the query of an endpoint whose query is a struct.
+    ///
+    /// Panics if `query` cannot be serialized.
+    pub fn query<Q: serde::Serialize>(self, query: &Q) -> Self {
+        let query = serde_qs::to_string(query).expect("cannot serialize query");
+        self.raw_query(&query)
+    }
+
+    /// Appends the query of an endpoint whose query is a built-in type.
+    pub fn query_primitive<Q: RouteParam>(self, query: &Q) -> Self {
+        let query = route_param(query);
+        self.raw_query(&query)
+    }
+
+    /// Appends the query of an endpoint whose query is a list of built-in types.
+    pub fn query_list<Q: RouteParam>(self, query: &[Q]) -> Self {
+        let query = query.iter().map(route_param).collect::<Vec<_>>().join("&");
+        self.raw_query(&query)
+    }
+
+    /// Sets the body, along with its `Content-Type`.
+    pub fn body(mut self, body: impl Into<Body>, content_type: &str) -> Self {
+        self.body = body.into();
+        self.header(header::CONTENT_TYPE, content_type)
+    }
+
+    /// Sets a JSON body.
+    ///
+    /// Panics if `body` cannot be serialized.
+    pub fn json<T: serde::Serialize>(self, body: &T) -> Self {
+        let body = serde_json::to_vec(body).expect("cannot serialize body");
+        self.body(body, wire_format::JSON_MEDIA_TYPE)
+    }
+
+    /// Sets the body of an endpoint whose body is a `blob[media_type]`.
+    /// The `Content-Type` is the blob's, or else `media_type` (unless it is a wildcard).
+    pub fn blob(mut self, blob: Blob, media_type: &str) -> Self {
+        let content_type = blob
+            .content_type()
+            .map(str::to_owned)
+            .or_else(|| Some(media_type.to_owned()).filter(|m| !m.ends_with("/*")));
+        self.body = blob.into_body();
+        match content_type {
+            Some(content_type) => self.header(header::CONTENT_TYPE, &content_type),
+            None => self,
+        }
+    }
+
+    /// Sends the request and reads the complete response.
+    ///
+    /// Panics if the request is malformed or if the response body cannot be read.
+    pub async fn send(self) -> TestResponse {
+        let mut req = Request::builder()
+            .method(self.method)
+            .uri(self.uri)
+            .body(self.body)
+            .expect("invalid request");
+        *req.headers_mut() = self.headers;
+        if let Some(connection_info) = &self.client.connection_info {
+            req.extensions_mut().insert(connection_info.clone());
+        }
+        let response = server::handle_request(Arc::clone(&self.client.server), req).await;
+        let (parts, body) = response.into_parts();
+        let body = hyper::body::to_bytes(body)
+            .await
+            .expect("cannot read response body");
+        TestResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body,
+        }
+    }
+}
+
+/// A complete response to a `TestRequest`.
+#[derive(Debug, Clone)]
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+/// The failure of a typed request of a generated test client.
+#[derive(Debug)]
+pub enum TestError {
+    /// The server answered with an error response (status code 4XX or 5XX).
+    ErrorResponse(ErrorResponse),
+    /// The response could not be decoded.
+    InvalidResponse { status: StatusCode, error: String },
+}
+
+impl std::fmt::Display for TestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TestError::ErrorResponse(e) => write!(f, "error response: {:?}", e),
+            TestError::InvalidResponse { status, error } => {
+                write!(f, "invalid response with status {}: {}", status, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TestError {}
+
+impl TestResponse {
+    /// The body, deserialized from JSON.
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
+
+    /// The body of an unsuccessful response, deserialized as an `ErrorResponse`.
+    pub fn error_response(&self) -> Option<ErrorResponse> {
+        if self.status.is_success() {
+            return None;
+        }
+        self.json().ok()
+    }
+
+    /// Decodes the body of a data response (JSON).
+    pub fn decode<T: serde::de::DeserializeOwned>(&self) -> Result<T, TestError> {
+        self.check_status()?;
+        self.json().map_err(|e| self.invalid(e.to_string()))
+    }
+
+    /// Decodes the items of a `stream[T]` response (NDJSON).
+    pub fn decode_ndjson<T: serde::de::DeserializeOwned>(&self) -> Result<Vec<T>, TestError> {
+        self.check_status()?;
+        self.body
+            .split(|b| *b == b'\n')
+            .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+            .map(|line| serde_json::from_slice(line).map_err(|e| self.invalid(e.to_string())))
+            .collect()
+    }
+
+    /// Decodes the body of a `blob` response.
+    pub fn decode_blob(&self) -> Result<Blob, TestError> {
+        self.check_status()?;
+        let content_type = self
+            .headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or(blob::FALLBACK_MEDIA_TYPE);
+        Ok(Blob::new(self.body.clone()).with_content_type(content_type))
+    }
+
+    fn check_status(&self) -> Result<(), TestError> {
+        if self.status.is_success() {
+            return Ok(());
+        }
+        match self.json() {
+            Ok(e) => Err(TestError::ErrorResponse(e)),
+            Err(e) => Err(self.invalid(format!("not an error response: {}", e))),
+        }
+    }
+
+    fn invalid(&self, error: String) -> TestError {
+        TestError::InvalidResponse {
+            status: self.status,
+            error,
+        }
+    }
+}
+
+/// Helper function used by generated code to percent-encode a route param.
+pub fn route_param<T: RouteParam>(value: &T) -> String {
+    utf8_percent_encode(&value.format_route_param(), ROUTE_PARAM_ENCODE_SET).to_string()
+}
+
+/// Helper function used by generated code to percent-encode a rest param (`{path: str..}`).
+pub fn rest_param<T: RouteParam>(value: &T) -> String {
+    utf8_percent_encode(&value.format_route_param(), REST_PARAM_ENCODE_SET).to_string()
+}
@@ -0,0 +1,208 @@
+//! `GEN`, `HANDLER` - WebSocket endpoints (`WS /path -> ClientMsg -> ServerMsg`).
+//!
+//! The dispatcher of a WebSocket endpoint answers the upgrade request with `101 Switching Protocols`
+//! (see `upgrade_response`) and then runs the handler on the upgraded connection (see `spawn_handler`).
+//! The handler receives a `MessageStream` of client messages and a `MessageSink` for server messages.
+//! Messages are JSON-encoded text frames.
+
+use crate::handler::HandlerResponse;
+use crate::service_protocol::{ErrorResponse, RuntimeError, ToErrorResponse};
+
+use futures::sink::{Sink, SinkExt};
+use futures::stream::{SplitSink, SplitStream, Stream, StreamExt};
+use hyper::header::{self, HeaderValue};
+use hyper::upgrade::Upgraded;
+use hyper::{Body, Request, Response, StatusCode};
+use sha1::{Digest, Sha1};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use tracing_futures::Instrument;
+
+/// GUID appended to the client's key to compute `Sec-WebSocket-Accept` (RFC 6455, section 1.3).
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+type Connection = WebSocketStream<Upgraded>;
+
+/// An error on a WebSocket connection.
+#[derive(Debug)]
+pub enum WebSocketError {
+    /// The connection failed or was closed unexpectedly.
+    Connection(String),
+    /// A message of the client could not be deserialized.
+    Deserialize(String),
+    /// A message of the server could not be serialized.
+    Serialize(String),
+}
+
+impl std::fmt::Display for WebSocketError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebSocketError::Connection(e) => write!(f, "websocket connection error: {}", e),
+            WebSocketError::Deserialize(e) => write!(f, "cannot deserialize message: {}", e),
+            WebSocketError::Serialize(e) => write!(f, "cannot serialize message: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for WebSocketError {}
+
+/// The messages sent by the client, deserialized as `T`.
+///
+/// Ends when the client closes the connection.
+/// A message that cannot be deserialized yields an error, but does not end the stream.
+pub struct MessageStream<T> {
+    inner: SplitStream<Connection>,
+    _message: PhantomData<fn() -> T>,
+}
+
+impl<T: serde::de::DeserializeOwned> Stream for MessageStream<T> {
+    type Item = Result<T, WebSocketError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let message = match futures::ready!(self.inner.poll_next_unpin(cx)) {
+                None => return Poll::Ready(None),
+                Some(Err(e)) => {
+                    return Poll::Ready(Some(Err(WebSocketError::Connection(e.to_string()))))
+                }
+                Some(Ok(message)) => message,
+            };
+            let item = match message {
+                Message::Text(text) => serde_json::from_str(&text),
+                Message::Binary(bytes) => serde_json::from_slice(&bytes),
+                // pings are answered by the connection itself
+                Message::Ping(_) | Message::Pong(_) => continue,
+                Message::Close(_) => return Poll::Ready(None),
+            };
+            return Poll::Ready(Some(
+                item.map_err(|e| WebSocketError::Deserialize(e.to_string())),
+            ));
+        }
+    }
+}
+
+/// The sink for messages sent to the client, serialized from `T`.
+pub struct MessageSink<T> {
+    inner: SplitSink<Connection, Message>,
+    _message: PhantomData<fn(T)>,
+}
+
+impl<T: serde::Serialize> Sink<T> for MessageSink<T> {
+    type Error = WebSocketError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready_unpin(cx).map_err(connection_error)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        let text =
+            serde_json::to_string(&item).map_err(|e| WebSocketError::Serialize(e.to_string()))?;
+        self.inner
+            .start_send_unpin(Message::Text(text))
+            .map_err(connection_error)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_flush_unpin(cx).map_err(connection_error)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_close_unpin(cx).map_err(connection_error)
+    }
+}
+
+fn connection_error(e: tokio_tungstenite::tungstenite::Error) -> WebSocketError {
+    WebSocketError::Connection(e.to_string())
+}
+
+/// Helper function used by generated code to validate a WebSocket upgrade request
+/// and build the `101 Switching Protocols` response.
+pub fn upgrade_response(req: &Request<Body>) -> Result<Response<Body>, ErrorResponse> {
+    let rejected = |reason: &str| {
+        RuntimeError::WebSocketUpgradeRequired(reason.to_owned()).to_error_response()
+    };
+    let headers = req.headers();
+    let header_contains = |name: header::HeaderName, token: &str| {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .any(|v| v.trim().eq_ignore_ascii_case(token))
+    };
+    if !header_contains(header::CONNECTION, "upgrade") {
+        return Err(rejected("`Connection` header must contain `upgrade`"));
+    }
+    if !header_contains(header::UPGRADE, "websocket") {
+        return Err(rejected("`Upgrade` header must be `websocket`"));
+    }
+    if headers
+        .get(header::SEC_WEBSOCKET_VERSION)
+        .map(|v| v.as_bytes())
+        != Some(b"13")
+    {
+        return Err(rejected("`Sec-WebSocket-Version` header must be `13`"));
+    }
+    let key = headers
+        .get(header::SEC_WEBSOCKET_KEY)
+        .ok_or_else(|| rejected("`Sec-WebSocket-Key` header is missing"))?;
+
+    let mut sha1 = Sha1::new();
+    sha1.update(key.as_bytes());
+    sha1.update(WEBSOCKET_GUID.as_bytes());
+    let accept = base64::encode(sha1.finalize());
+
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+    let headers = response.headers_mut();
+    headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+    headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+    headers.insert(
+        header::SEC_WEBSOCKET_ACCEPT,
+        HeaderValue::from_str(&accept).expect("base64 is a valid header value"),
+    );
+    Ok(response)
+}
+
+/// Helper function used by generated code to run `handler` on the connection once `req` is upgraded.
+///
+/// The handler runs in a separate task, within the current tracing span.
+/// The connection is closed when the handler returns.
+pub fn spawn_handler<In, Out, F, Fut>(req: Request<Body>, handler: F)
+where
+    In: serde::de::DeserializeOwned + 'static,
+    Out: serde::Serialize + 'static,
+    F: FnOnce(MessageStream<In>, MessageSink<Out>) -> Fut + Send + 'static,
+    Fut: Future<Output = HandlerResponse<()>> + Send + 'static,
+{
+    let task = async move {
+        let upgraded = match req.into_body().on_upgrade().await {
+            Ok(upgraded) => upgraded,
+            Err(e) => {
+                tracing::error!(error = ?e, "websocket upgrade failed");
+                return;
+            }
+        };
+        let connection = WebSocketStream::from_raw_socket(upgraded, Role::Server, None).await;
+        let (sink, stream) = connection.split();
+        let incoming = MessageStream {
+            inner: stream,
+            _message: PhantomData,
+        };
+        let outgoing = MessageSink {
+            inner: sink,
+            _message: PhantomData,
+        };
+        tracing::debug!("websocket connection established");
+        match handler(incoming, outgoing).await {
+            Ok(()) => tracing::debug!("websocket handler finished"),
+            Err(e) => tracing::error!(error = ?e, "websocket handler returned error"),
+        }
+    };
+    tokio::spawn(task.instrument(tracing::Span::current()));
+}
@@ -0,0 +1,158 @@
+//! `GEN` - wire formats for request and response bodies.
+//!
+//! JSON is always supported and the default.
+//! CBOR (`application/cbor`) and MessagePack (`application/msgpack`) are supported
+//! if the `cbor` and `msgpack` cargo features are enabled, respectively.
+//!
+//! - The format of a request body is determined by the request's `Content-Type` header.
+//! - The format of a response body is negotiated using the request's `Accept` header.
+
+use crate::service_protocol::{ErrorResponse, RuntimeError, ToErrorResponse};
+
+use hyper::header::{self, HeaderValue};
+use hyper::Request;
+
+pub const JSON_MEDIA_TYPE: &str = "application/json";
+pub const CBOR_MEDIA_TYPE: &str = "application/cbor";
+pub const MSGPACK_MEDIA_TYPE: &str = "application/msgpack";
+
+/// A wire format, which this build of the runtime may not support (see `WireFormat::is_supported`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WireFormat {
+    #[default]
+    Json,
+    Cbor,
+    MessagePack,
+}
+
+/// The media type of a `Content-Type` or `Accept` item, without parameters, lowercased.
+pub(crate) fn essence(media_type: &str) -> String {
+    media_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase()
+}
+
+impl WireFormat {
+    /// The media type used in the `Content-Type` header of bodies in this format.
+    pub fn media_type(self) -> &'static str {
+        match self {
+            WireFormat::Json => JSON_MEDIA_TYPE,
+            WireFormat::Cbor => CBOR_MEDIA_TYPE,
+            WireFormat::MessagePack => MSGPACK_MEDIA_TYPE,
+        }
+    }
+
+    /// Whether the cargo feature of the format is enabled.
+    pub fn is_supported(self) -> bool {
+        match self {
+            WireFormat::Json => true,
+            WireFormat::Cbor => cfg!(feature = "cbor"),
+            WireFormat::MessagePack => cfg!(feature = "msgpack"),
+        }
+    }
+
+    /// Looks up the format for a media type (without parameters).
+    fn from_media_type(media_type: &str) -> Option<Self> {
+        match media_type {
+            JSON_MEDIA_TYPE => Some(WireFormat::Json),
+            CBOR_MEDIA_TYPE => Some(WireFormat::Cbor),
+            MSGPACK_MEDIA_TYPE | "application/x-msgpack" => Some(WireFormat::MessagePack),
+            _ => None,
+        }
+    }
+
+    #[cfg(not(all(feature = "cbor", feature = "msgpack")))]
+    fn unsupported(self) -> String {
+        format!(
+            "{} is not supported by this build of the runtime",
+            self.media_type()
+        )
+    }
+
+    /// Determines the format of a request body from its `Content-Type` header.
+    ///
+    /// For compatibility with clients that do not set a (correct) `Content-Type`,
+    /// bodies of all media types other than the binary formats are treated as JSON.
+    /// Binary formats whose cargo feature is disabled are rejected with
+    /// `RuntimeError::UnsupportedMediaType`.
+    pub fn from_content_type(content_type: Option<&HeaderValue>) -> Result<Self, ErrorResponse> {
+        let media_type = match content_type.and_then(|v| v.to_str().ok()) {
+            None => return Ok(WireFormat::Json),
+            Some(v) => essence(v),
+        };
+        match Self::from_media_type(&media_type) {
+            Some(format) if !format.is_supported() => {
+                Err(RuntimeError::UnsupportedMediaType(media_type).to_error_response())
+            }
+            Some(format) => Ok(format),
+            None => Ok(WireFormat::Json),
+        }
+    }
+
+    /// Negotiates the response format from an `Accept` header.
+    ///
+    /// Picks the supported format with the highest quality value, preferring
+    /// the one listed first on ties. Falls back to JSON if no supported format is acceptable.
+    pub fn from_accept(accept: Option<&HeaderValue>) -> Self {
+        let accept = match accept.and_then(|v| v.to_str().ok()) {
+            None => return WireFormat::Json,
+            Some(v) => v,
+        };
+        let mut best: Option<(WireFormat, f32)> = None;
+        for item in accept.split(',') {
+            let q = item
+                .split(';')
+                .skip(1)
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .map(|q| q.trim().parse::<f32>().unwrap_or(0.0))
+                .unwrap_or(1.0);
+            let media_type = essence(item);
+            let format = match media_type.as_str() {
+                "*/*" | "application/*" => Some(WireFormat::Json),
+                other => Self::from_media_type(other).filter(|format| format.is_supported()),
+            };
+            match (format, best) {
+                (Some(format), None) if q > 0.0 => best = Some((format, q)),
+                (Some(format), Some((_, best_q))) if q > best_q => best = Some((format, q)),
+                _ => (),
+            }
+        }
+        best.map(|(format, _)| format).unwrap_or_default()
+    }
+
+    /// Serializes `value` in this format.
+    /// Fails if the format is not supported.
+    pub fn serialize<T: serde::Serialize>(self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            WireFormat::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            #[cfg(feature = "cbor")]
+            WireFormat::Cbor => serde_cbor::to_vec(value).map_err(|e| e.to_string()),
+            #[cfg(feature = "msgpack")]
+            WireFormat::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+            #[cfg(not(all(feature = "cbor", feature = "msgpack")))]
+            _ => Err(self.unsupported()),
+        }
+    }
+
+    /// Deserializes a `T` from `bytes` in this format.
+    /// Fails if the format is not supported.
+    pub fn deserialize<T: serde::de::DeserializeOwned>(self, bytes: &[u8]) -> Result<T, String> {
+        match self {
+            WireFormat::Json => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+            #[cfg(feature = "cbor")]
+            WireFormat::Cbor => serde_cbor::from_slice(bytes).map_err(|e| e.to_string()),
+            #[cfg(feature = "msgpack")]
+            WireFormat::MessagePack => rmp_serde::from_slice(bytes).map_err(|e| e.to_string()),
+            #[cfg(not(all(feature = "cbor", feature = "msgpack")))]
+            _ => Err(self.unsupported()),
+        }
+    }
+}
+
+/// Helper function used by generated code to negotiate the format of a handler response.
+pub fn response_format(req: &Request<hyper::Body>) -> WireFormat {
+    WireFormat::from_accept(req.headers().get(header::ACCEPT))
+}
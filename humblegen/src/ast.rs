@@ -187,7 +187,7 @@ pub enum ServiceRoute {
         /// The query type, if specified. (example: `GetMonstersQuery`)
         query: Option<TypeIdent>,
         /// The route return type.
-        ret: TypeIdent,
+        ret: ReturnType,
     },
     /// A POST endpoint.
     Post {
@@ -196,9 +196,9 @@ pub enum ServiceRoute {
         /// The query type, if specified. (example: `GetMonstersQuery`)
         query: Option<TypeIdent>,
         /// The POST body type. (example: `MonsterData`)
-        body: TypeIdent,
+        body: BodyType,
         /// The route return type.
-        ret: TypeIdent,
+        ret: ReturnType,
     },
     /// A DELETE endpoint
     Delete {
@@ -207,7 +207,7 @@ pub enum ServiceRoute {
         /// The query type, if specified. (example: `GetMonstersQuery`)
         query: Option<TypeIdent>,
         /// The route return type.
-        ret: TypeIdent,
+        ret: ReturnType,
     },
     /// A PUT endpoint.
     Put {
@@ -216,9 +216,9 @@ pub enum ServiceRoute {
         /// The query type, if specified. (example: `GetMonstersQuery`)
         query: Option<TypeIdent>,
         /// The POST body type. (example: `MonsterData`)
-        body: TypeIdent,
+        body: BodyType,
         /// The route return type.
-        ret: TypeIdent,
+        ret: ReturnType,
     },
     /// A PATCH endpoint.
     Patch {
@@ -227,9 +227,20 @@ pub enum ServiceRoute {
         /// The query type, if specified. (example: `GetMonstersQuery`)
         query: Option<TypeIdent>,
         /// The POST body type. (example: `MonsterData`)
-        body: TypeIdent,
+        body: BodyType,
         /// The route return type.
-        ret: TypeIdent,
+        ret: ReturnType,
+    },
+    /// A WebSocket endpoint.
+    Ws {
+        /// The route components. See struct `ServiceRouteComponent`.
+        components: Vec<ServiceRouteComponent>,
+        /// The query type, if specified. (example: `GetMonstersQuery`)
+        query: Option<TypeIdent>,
+        /// The type of messages sent by the client. (example: `ClientMsg`)
+        client_msg: TypeIdent,
+        /// The type of messages sent by the server. (example: `ServerMsg`)
+        server_msg: TypeIdent,
     },
 }
 
@@ -242,6 +253,7 @@ impl ServiceRoute {
             ServiceRoute::Post { components, .. } => components,
             ServiceRoute::Put { components, .. } => components,
             ServiceRoute::Patch { components, .. } => components,
+            ServiceRoute::Ws { components, .. } => components,
         }
     }
 
@@ -253,27 +265,42 @@ impl ServiceRoute {
             ServiceRoute::Post { query, .. } => query,
             ServiceRoute::Put { query, .. } => query,
             ServiceRoute::Patch { query, .. } => query,
+            ServiceRoute::Ws { query, .. } => query,
         }
     }
 
-    /// The return type.
-    pub fn return_type(&self) -> &TypeIdent {
+    /// The return type, `None` for WebSocket endpoints (see `websocket_messages`).
+    pub fn return_type(&self) -> Option<&ReturnType> {
         match self {
-            ServiceRoute::Get { ret, .. } => ret,
-            ServiceRoute::Delete { ret, .. } => ret,
-            ServiceRoute::Post { ret, .. } => ret,
-            ServiceRoute::Put { ret, .. } => ret,
-            ServiceRoute::Patch { ret, .. } => ret,
+            ServiceRoute::Get { ret, .. } => Some(ret),
+            ServiceRoute::Delete { ret, .. } => Some(ret),
+            ServiceRoute::Post { ret, .. } => Some(ret),
+            ServiceRoute::Put { ret, .. } => Some(ret),
+            ServiceRoute::Patch { ret, .. } => Some(ret),
+            ServiceRoute::Ws { .. } => None,
         }
     }
 
-    pub fn request_body(&self) -> Option<&TypeIdent> {
+    pub fn request_body(&self) -> Option<&BodyType> {
         match self {
             ServiceRoute::Get { .. } => None,
             ServiceRoute::Delete { .. } => None,
             ServiceRoute::Post { body, .. } => Some(body),
             ServiceRoute::Put { body, .. } => Some(body),
             ServiceRoute::Patch { body, .. } => Some(body),
+            ServiceRoute::Ws { .. } => None,
+        }
+    }
+
+    /// The types of messages sent by the client and by the server, if this is a WebSocket endpoint.
+    pub fn websocket_messages(&self) -> Option<(&TypeIdent, &TypeIdent)> {
+        match self {
+            ServiceRoute::Ws {
+                client_msg,
+                server_msg,
+                ..
+            } => Some((client_msg, server_msg)),
+            _ => None,
         }
     }
 
@@ -284,6 +311,7 @@ impl ServiceRoute {
             ServiceRoute::Post { .. } => "POST",
             ServiceRoute::Put { .. } => "PUT",
             ServiceRoute::Patch { .. } => "PATCH",
+            ServiceRoute::Ws { .. } => "WS",
         }
     }
 }
@@ -297,10 +325,13 @@ impl ServiceRoute {
 /// - `Literal("monsters")
 /// - `Variable(FieldDefPair{ name: "id", type_ident: TypeIdent::BuiltIn(AtomType::Str) })`
 ///
+/// A rest parameter `{path: str..}` can only be the last component and captures the rest
+/// of the route, including slashes. It is always of type `str`.
 #[derive(Debug, Clone)]
 pub enum ServiceRouteComponent {
     Literal(String),
     Variable(FieldDefPair),
+    Rest(FieldDefPair),
 }
 
 /// A field node (field definition inside struct).
@@ -349,6 +380,46 @@ pub enum TypeIdent {
     UserDefined(String),
 }
 
+/// The return type of an endpoint.
+#[derive(Debug, Clone)]
+pub enum ReturnType {
+    /// A value of the given type.
+    Data(TypeIdent),
+    /// `stream[T]`, a stream of values of the given type.
+    Stream(TypeIdent),
+    /// `blob["media/type"]`, a raw binary body of the given media type.
+    Blob(String),
+}
+
+/// The request body type of an endpoint.
+#[derive(Debug, Clone)]
+pub enum BodyType {
+    /// A value of the given type.
+    Data(TypeIdent),
+    /// `multipart { .. }`, a `multipart/form-data` body.
+    Multipart(Vec<MultipartPart>),
+    /// `blob["media/type"]`, a raw binary body of the given media type.
+    Blob(String),
+}
+
+/// A part of a `multipart { .. }` request body.
+#[derive(Debug, Clone)]
+pub enum MultipartPart {
+    /// A JSON-encoded part of the given type.
+    Field(FieldDefPair),
+    /// A file part (`name: file`), passed to the handler as a byte stream.
+    File(String),
+}
+
+impl MultipartPart {
+    pub fn name(&self) -> &str {
+        match self {
+            MultipartPart::Field(pair) => &pair.name,
+            MultipartPart::File(name) => name,
+        }
+    }
+}
+
 impl TypeIdent {
     pub fn user_defined(&self) -> Option<&String> {
         match self {
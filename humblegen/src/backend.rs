@@ -1,3 +1,5 @@
 pub mod docs;
 pub mod elm;
+#[cfg(feature = "openapi")]
+pub mod openapi;
 pub mod rust;
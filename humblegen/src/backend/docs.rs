@@ -271,7 +271,7 @@ impl Context {
                         ),
                         &basic_options()
                     ),
-                    endpointReturn = Self::type_ident_to_html(endpoint.route.return_type()),
+                    endpointReturn = Self::return_type_to_html(&endpoint.route),
                     endpointRouteQuery = endpoint
                         .route
                         .query()
@@ -335,6 +335,42 @@ impl Context {
         }
     }
 
+    /// The return type of an endpoint, the type of messages sent by the server for WebSocket endpoints.
+    fn return_type_to_html(route: &ast::ServiceRoute) -> String {
+        match route.return_type() {
+            Some(ast::ReturnType::Data(ty)) => Self::type_ident_to_html(ty),
+            Some(ast::ReturnType::Stream(ty)) => {
+                format!("stream[{}]", Self::type_ident_to_html(ty))
+            }
+            Some(ast::ReturnType::Blob(media_type)) => format!("blob[\"{}\"]", media_type),
+            None => route
+                .websocket_messages()
+                .map(|(_, server_msg)| Self::type_ident_to_html(server_msg))
+                .unwrap_or_default(),
+        }
+    }
+
+    fn body_type_to_html(body: &ast::BodyType) -> String {
+        match body {
+            ast::BodyType::Data(ty) => Self::type_ident_to_html(ty),
+            ast::BodyType::Blob(media_type) => format!("blob[\"{}\"]", media_type),
+            ast::BodyType::Multipart(parts) => format!(
+                "multipart {{ {} }}",
+                parts
+                    .iter()
+                    .map(|part| match part {
+                        ast::MultipartPart::Field(pair) => format!(
+                            "{}: {}",
+                            pair.name,
+                            Self::type_ident_to_html(&pair.type_ident)
+                        ),
+                        ast::MultipartPart::File(name) => format!("{}: file", name),
+                    })
+                    .join(", ")
+            ),
+        }
+    }
+
     pub fn link_to_user_defined_type(name: &str) -> String {
         format!("type-{}", name)
     }
@@ -353,6 +389,13 @@ impl Context {
                         Escape(&Self::type_ident_to_html(&type_ident))
                     )
                 }
+                ast::ServiceRouteComponent::Rest(ast::FieldDefPair { name, type_ident }) => {
+                    format!(
+                        "/<var><span class=\"var-bracket\">{{</span><span class=\"var-name\">{}</span><span class=\"var-ty-name-sep\">:</span><span class=\"var-ty\">{}..</span><span class=\"var-bracket\">}}</span></var>",
+                        Escape(name),
+                        Escape(&Self::type_ident_to_html(type_ident))
+                    )
+                }
             })
             .join("")
     }
@@ -363,7 +406,8 @@ impl Context {
             .iter()
             .map(|c| match c {
                 ast::ServiceRouteComponent::Literal(lit) => format!("/{}", Escape(&lit)),
-                ast::ServiceRouteComponent::Variable(ast::FieldDefPair { name, type_ident }) => {
+                ast::ServiceRouteComponent::Variable(ast::FieldDefPair { name, type_ident })
+                | ast::ServiceRouteComponent::Rest(ast::FieldDefPair { name, type_ident }) => {
                     format!(
                         "/{}:{}",
                         Escape(&name),
@@ -377,10 +421,18 @@ impl Context {
     }
 
     pub fn properties_to_html(route: &ast::ServiceRoute) -> String {
-        match route.request_body() {
-            Some(type_ident) => format!(
+        // list the messages sent by the client of a WebSocket endpoint like a body
+        let client_msg = route
+            .websocket_messages()
+            .map(|(client_msg, _)| Self::type_ident_to_html(client_msg));
+        match route
+            .request_body()
+            .map(Self::body_type_to_html)
+            .or(client_msg)
+        {
+            Some(body) => format!(
                 include_str!("docs/endpoint-properties.html"),
-                endpointBody = Self::type_ident_to_html(type_ident),
+                endpointBody = body,
             ),
             None => "".to_owned(),
         }
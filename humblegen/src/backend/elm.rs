@@ -273,6 +273,12 @@ impl Generator {
                     )?;
                     write!(file.start_line()?, "import Url.Builder")?;
                     write!(file.start_line()?, "{}", "import Http")?;
+                    // elm/file is only required by services with multipart endpoints
+                    if service.endpoints.iter().any(|e| {
+                        matches!(e.route.request_body(), Some(ast::BodyType::Multipart(_)))
+                    }) {
+                        write!(file.start_line()?, "{}", "import File")?;
+                    }
 
                     write!(
                         file.start_line()?,
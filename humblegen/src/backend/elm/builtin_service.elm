@@ -1,3 +1,6 @@
+import Bytes exposing (Bytes)
+import Bytes.Decode
+import Dict
 import Http
 import Json.Decode as D
 import Json.Encode as E
@@ -24,6 +27,14 @@ type ResponseBody
     = StringResponse String
 
 
+{-| A raw binary body (`blob["media/type"]`) along with its content type.
+-}
+type alias Blob =
+    { contentType : String
+    , body : Bytes
+    }
+
+
 type Error
     = Bug String
     | HttpBug Http.Metadata ResponseBody
@@ -31,6 +42,7 @@ type Error
     | TransportError String
     | AuthorizationError -- humble service protocol level authorization error (e.g. the server-side request handler indicates that the client is unauthorized to access the resource. The client's access token is valid, though.
     | AuthenticationError -- humble service protocol level authentication error (e.g. the server-side request handler indicates that the client did not provide a valid access token)
+    | PreconditionFailed -- humble service protocol level precondition failure (e.g. the `If-Match` header of a PUT or PATCH request does not match the current entity tag of the resource)
     | ServerError
 
 
@@ -53,39 +65,94 @@ noQueryEncoder : QueryEncoder Never
 noQueryEncoder _ = []
 
 jsonResolver : D.Decoder t -> Http.Resolver Error t
-jsonResolver =
-    let
-        resolve decoder response =
-            case response of
-                Http.BadUrl_ badUrl ->
-                    Err <| Bug <| "bad url: " ++ badUrl
+jsonResolver decoder =
+    bodyResolver (D.decodeString decoder)
+
 
-                Http.Timeout_ ->
-                    Err <| TransportError "Http.Timeout_"
+{-| Resolves the NDJSON body of a `stream[T]` endpoint once it is complete, decoding one item per line.
+-}
+ndjsonResolver : D.Decoder t -> Http.Resolver Error (List t)
+ndjsonResolver decoder =
+    let
+        decodeLines body =
+            body
+                |> String.lines
+                |> List.filter (not << String.isEmpty << String.trim)
+                |> List.foldr (\line acc -> Result.map2 (::) (D.decodeString decoder line) acc) (Ok [])
+    in
+    bodyResolver decodeLines
 
-                Http.NetworkError_ ->
-                    Err <| TransportError "Http.NetworkError_"
 
-                Http.BadStatus_ metadata body ->
-                    Err <|
-                        case metadata.statusCode of
-                            401 ->
-                                AuthorizationError
+bodyResolver : (String -> Result D.Error t) -> Http.Resolver Error t
+bodyResolver =
+    let
+        resolve decode response =
+            case response of
+                Http.GoodStatus_ metadata body ->
+                    decode body
+                        |> Result.mapError (InvalidResponse metadata (StringResponse body))
 
-                            403 ->
-                                AuthenticationError
+                _ ->
+                    Err <| responseError identity response
+    in
+    Http.stringResolver << resolve
 
-                            500 ->
-                                ServerError
 
-                            _ ->
-                                HttpBug metadata (StringResponse body)
+{-| Resolves the body of a `blob` endpoint, taking its content type from the response headers.
+-}
+blobResolver : Http.Resolver Error Blob
+blobResolver =
+    let
+        -- error responses are JSON
+        bytesToString bytes =
+            Bytes.Decode.decode (Bytes.Decode.string (Bytes.width bytes)) bytes
+                |> Maybe.withDefault ""
 
+        resolve response =
+            case response of
                 Http.GoodStatus_ metadata body ->
-                    D.decodeString decoder body
-                        |> Result.mapError (InvalidResponse metadata (StringResponse body))
+                    Ok
+                        { contentType = Dict.get "content-type" metadata.headers |> Maybe.withDefault "application/octet-stream"
+                        , body = body
+                        }
+
+                _ ->
+                    Err <| responseError bytesToString response
     in
-    Http.stringResolver << resolve
+    Http.bytesResolver resolve
+
+
+responseError : (body -> String) -> Http.Response body -> Error
+responseError bodyToString response =
+    case response of
+        Http.BadUrl_ badUrl ->
+            Bug <| "bad url: " ++ badUrl
+
+        Http.Timeout_ ->
+            TransportError "Http.Timeout_"
+
+        Http.NetworkError_ ->
+            TransportError "Http.NetworkError_"
+
+        Http.BadStatus_ metadata body ->
+            case metadata.statusCode of
+                401 ->
+                    AuthorizationError
+
+                403 ->
+                    AuthenticationError
+
+                412 ->
+                    PreconditionFailed
+
+                500 ->
+                    ServerError
+
+                _ ->
+                    HttpBug metadata (StringResponse (bodyToString body))
+
+        Http.GoodStatus_ metadata body ->
+            Bug "unexpected good status"
 
 
 withBase : String -> Request q t -> Request q t
@@ -118,11 +185,16 @@ withJsonBody encoder value req =
     { req | body = Http.stringBody "application/json" <| E.encode 2 (encoder value) }
 
 
+withBlobBody : Blob -> Request q t -> Request q t
+withBlobBody blob req =
+    { req | body = Http.bytesBody blob.contentType blob.body }
+
+
 makeUrl : Request q t -> String
 makeUrl req =
     Url.Builder.crossOrigin
          req.base
-            req.urlComponents
+            (List.map Url.percentEncode req.urlComponents)
             (Maybe.withDefault [] <| Maybe.map req.queryEncoder req.query)
 
 
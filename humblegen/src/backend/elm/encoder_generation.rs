@@ -13,7 +13,11 @@ pub fn generate_struct_and_enum_encoders(spec: &ast::Spec) -> String {
                 let query_encoder = generate_struct_query_encoder(sdef);
                 Some(format!("{}\n\n\n{}", json_encoder, query_encoder))
             }
-            ast::SpecItem::EnumDef(edef) => Some(generate_enum_encoder(edef)),
+            ast::SpecItem::EnumDef(edef) => {
+                let json_encoder = generate_enum_encoder(edef);
+                let query_encoder = generate_enum_query_encoder(edef);
+                Some(format!("{}\n\n\n{}", json_encoder, query_encoder))
+            }
             ast::SpecItem::ServiceDef(_) => None,
         })
         .join("\n\n\n")
@@ -29,13 +33,37 @@ fn generate_struct_json_encoder(sdef: &ast::StructDef) -> String {
     )
 }
 
+/// Generate elm code for the query encoders of a struct.
+///
+/// `buildQuery{Name}` encodes the struct as a whole URL query, `queryEncode{Name}` encodes it
+/// below a key prefix using bracket notation (see `service_protocol.md`).
 fn generate_struct_query_encoder(sdef: &ast::StructDef) -> String {
     let ns = "";
     format!(
-        "{encoder_name} : {type_name} -> List Url.Builder.QueryParameter\n{encoder_name} obj =\n    [ {fields}\n    ]",
-        encoder_name = query_struct_encoder_name(&sdef.name, ns),
+        "{build_name} : {type_name} -> List Url.Builder.QueryParameter\n{build_name} =\n    {encoder_name} \"\"\n\n\n\
+         {encoder_name} : String -> {type_name} -> List Url.Builder.QueryParameter\n{encoder_name} prefix obj =\n    List.concat\n        [ {fields}\n        ]",
+        build_name = query_struct_encoder_name(&sdef.name, ns),
+        encoder_name = query_type_encoder_name(&sdef.name, ns),
         type_name = sdef.name,
-        fields = sdef.fields.iter().map(|f| generate_field_query_encoder(f, ns)).join("\n    , "),
+        fields = sdef
+            .fields
+            .iter()
+            .map(|f| generate_field_query_encoder(f, "prefix", ns))
+            .join("\n        , "),
+    )
+}
+
+fn generate_enum_query_encoder(edef: &ast::EnumDef) -> String {
+    let ns = "";
+    format!(
+        "{encoder_name} : String -> {type_name} -> List Url.Builder.QueryParameter\n{encoder_name} prefix v =\n    case v of\n        {variants}",
+        encoder_name = query_type_encoder_name(&edef.name, ns),
+        type_name = edef.name,
+        variants = edef
+            .variants
+            .iter()
+            .map(|v| generate_variant_query_encoder_branch(v, ns))
+            .join("\n        "),
     )
 }
 
@@ -63,40 +91,56 @@ fn generate_field_json_encoder(field: &ast::FieldNode, ns: &str) -> String {
     )
 }
 
-fn generate_field_query_encoder(field: &ast::FieldNode, ns: &str) -> String {
-    // TODO: escape strings (but we could fix this in the whole codebase)
-    match field.pair.type_ident {
-        ast::TypeIdent::BuiltIn(ast::AtomType::Str) => format!(
-            "Url.Builder.string \"{name}\" obj.{field_name}",
-            name = field.pair.name,
-            field_name = field_name(&field.pair.name)
+fn generate_field_query_encoder(field: &ast::FieldNode, prefix: &str, ns: &str) -> String {
+    format!(
+        "{value_encoder} (builtinQueryKey {prefix} \"{name}\") obj.{field_name}",
+        value_encoder = generate_type_query_encoder(&field.pair.type_ident, ns),
+        prefix = prefix,
+        name = field.pair.name,
+        field_name = field_name(&field.pair.name),
+    )
+}
+
+fn generate_variant_query_encoder_branch(variant: &ast::VariantDef, ns: &str) -> String {
+    let key = format!("(builtinQueryKey prefix \"{}\")", variant.name);
+    match variant.variant_type {
+        ast::VariantType::Simple => format!(
+            "{name} -> [ Url.Builder.string prefix \"{name}\" ]",
+            name = variant.name
         ),
-        ast::TypeIdent::BuiltIn(ast::AtomType::Uuid) => format!(
-            "Url.Builder.string \"{name}\" (BuiltinUuid.encodeQuery obj.{field_name})",
-            name = field.pair.name,
-            field_name = field_name(&field.pair.name)
+        ast::VariantType::Tuple(ref tdef) => format!(
+            "{name} {field_names} -> List.concat [ {field_encoders} ]",
+            name = variant.name,
+            field_names = (0..tdef.elements().len())
+                .map(|i| format!("x{}", i))
+                .join(" "),
+            field_encoders = tdef
+                .elements()
+                .iter()
+                .enumerate()
+                .map(|(idx, component)| format!(
+                    "{} (builtinQueryKey {} \"{}\") x{}",
+                    generate_type_query_encoder(component, ns),
+                    key,
+                    idx,
+                    idx
+                ))
+                .join(", "),
         ),
-        ast::TypeIdent::BuiltIn(ast::AtomType::Bytes) => format!(
-            "Url.Builder.string \"{name}\" (BuiltinBytes.encodeQuery obj.{field_name})",
-            name = field.pair.name,
-            field_name = field_name(&field.pair.name)
+        ast::VariantType::Struct(ref fields) => format!(
+            "{name} obj -> List.concat [ {fields} ]",
+            name = variant.name,
+            fields = fields
+                .iter()
+                .map(|f| generate_field_query_encoder(f, &key, ns))
+                .join(", "),
         ),
-        ast::TypeIdent::BuiltIn(ast::AtomType::I32)
-        | ast::TypeIdent::BuiltIn(ast::AtomType::U32)
-        | ast::TypeIdent::BuiltIn(ast::AtomType::U8) => format!(
-            "Url.Builder.int \"{name}\" obj.{field_name}",
-            name = field.pair.name,
-            field_name = field_name(&field.pair.name),
+        ast::VariantType::Newtype(ref ty) => format!(
+            "{name} obj -> {enc} {key} obj",
+            name = variant.name,
+            enc = generate_type_query_encoder(ty, ns),
+            key = key,
         ),
-        _ => {
-            // encode other types as json encoded strings
-            format!(
-                "obj.{field_name} |> {value_encoder} |> E.encode 4 |> Url.Builder.string \"{name}\"",
-                name = field.pair.name,
-                field_name = field_name(&field.pair.name),
-                value_encoder = generate_complex_type_query_encoder(&field.pair.type_ident, ns)
-            )
-        }
     }
 }
 
@@ -148,11 +192,13 @@ fn generate_type_encoder(
             format!("E.list {}", to_atom(generate_type_json_encoder(inner, ns)))
         }
         ast::TypeIdent::Option(inner) => format!(
-            "builtinEncodeMaybe {}",
+            "{}builtinEncodeMaybe {}",
+            ns,
             to_atom(generate_type_json_encoder(inner, ns))
         ),
         ast::TypeIdent::Result(ok, err) => format!(
-            "builtinEncodeResult {} {}",
+            "{}builtinEncodeResult {} {}",
+            ns,
             to_atom(generate_type_json_encoder(err, ns)),
             to_atom(generate_type_json_encoder(ok, ns))
         ),
@@ -176,8 +222,64 @@ pub(crate) fn generate_type_json_encoder(type_ident: &ast::TypeIdent, ns: &str)
     generate_type_encoder(&generate_atom_json_encoder, type_ident, ns)
 }
 
-fn generate_complex_type_query_encoder(type_ident: &ast::TypeIdent, ns: &str) -> String {
-    generate_type_encoder(&generate_atom_query_encoder, type_ident, ns)
+/// Generate elm code for a query encoder, i.e. a function `String -> t -> List Url.Builder.QueryParameter`
+/// that encodes a value below the given key.
+fn generate_type_query_encoder(type_ident: &ast::TypeIdent, ns: &str) -> String {
+    match type_ident {
+        ast::TypeIdent::BuiltIn(atom) => generate_atom_query_encoder(atom, ns),
+        ast::TypeIdent::List(inner) => {
+            // lists of atoms use repeated keys, others need indices to group nested keys
+            let list_encoder = match **inner {
+                ast::TypeIdent::BuiltIn(_) => "builtinQueryList",
+                _ => "builtinQueryIndexedList",
+            };
+            format!(
+                "{}{} {}",
+                ns,
+                list_encoder,
+                to_atom(generate_type_query_encoder(inner, ns))
+            )
+        }
+        ast::TypeIdent::Option(inner) => format!(
+            "{}builtinQueryMaybe {}",
+            ns,
+            to_atom(generate_type_query_encoder(inner, ns))
+        ),
+        ast::TypeIdent::Result(ok, err) => format!(
+            "{}builtinQueryResult {} {}",
+            ns,
+            to_atom(generate_type_query_encoder(err, ns)),
+            to_atom(generate_type_query_encoder(ok, ns))
+        ),
+        ast::TypeIdent::Map(key, value) => {
+            assert!(
+                matches!(**key, ast::TypeIdent::BuiltIn(ast::AtomType::Str)),
+                "can only encode string keys in maps"
+            );
+            format!(
+                "{}builtinQueryDict {}",
+                ns,
+                to_atom(generate_type_query_encoder(value, ns))
+            )
+        }
+        ast::TypeIdent::Tuple(tdef) => {
+            let elements = tdef.elements();
+            assert!(
+                (1..=3).contains(&elements.len()),
+                "elm tuples have one to three elements"
+            );
+            format!(
+                "{}builtinQueryTuple{} {}",
+                ns,
+                elements.len(),
+                elements
+                    .iter()
+                    .map(|e| to_atom(generate_type_query_encoder(e, ns)))
+                    .join(" ")
+            )
+        }
+        ast::TypeIdent::UserDefined(ident) => query_type_encoder_name(ident, ns),
+    }
 }
 
 pub(crate) fn generate_type_urlcomponent_encoder(type_ident: &ast::TypeIdent, ns: &str) -> String {
@@ -200,18 +302,20 @@ fn generate_atom_json_encoder(atom: &ast::AtomType, ns: &str) -> String {
     }
 }
 
+/// Generate elm code for the query encoder of an atom, `()` is encoded as no query parameters.
 fn generate_atom_query_encoder(atom: &ast::AtomType, ns: &str) -> String {
-    match atom {
-        ast::AtomType::Empty => "E.null".to_owned(),
-        ast::AtomType::Str => "Url.Builder.string".to_owned(),
-        ast::AtomType::Uuid => "Url.Builder.uuid".to_owned(),
-        ast::AtomType::Bytes => "Url.Builder.bytes".to_owned(),
-        ast::AtomType::I32 | ast::AtomType::U32 | ast::AtomType::U8 => "Url.Builder.int".to_owned(),
-        ast::AtomType::F64 => "E.float".to_owned(),
-        ast::AtomType::Bool => "E.bool".to_owned(),
-        ast::AtomType::DateTime => format!("{}builtinEncodeIso8601", ns),
-        ast::AtomType::Date => format!("{}builtinEncodeDate", ns),
-    }
+    let to_string = match atom {
+        ast::AtomType::Empty => return "(\\_ _ -> [])".to_owned(),
+        ast::AtomType::Str => "identity".to_owned(),
+        ast::AtomType::I32 | ast::AtomType::U32 | ast::AtomType::U8 => "String.fromInt".to_owned(),
+        ast::AtomType::F64 => "String.fromFloat".to_owned(),
+        ast::AtomType::Bool => format!("{}builtinEncodeBoolUrlcomponent", ns),
+        ast::AtomType::DateTime => format!("{}builtinEncodeIso8601Urlcomponent", ns),
+        ast::AtomType::Date => format!("{}builtinEncodeDateUrlcomponent", ns),
+        ast::AtomType::Uuid => "BuiltinUuid.encodeQuery".to_owned(),
+        ast::AtomType::Bytes => "BuiltinBytes.encodeQuery".to_owned(),
+    };
+    format!("{}builtinQueryAtom {}", ns, to_string)
 }
 
 fn generate_atom_urlcomponent_encoder(atom: &ast::AtomType, ns: &str) -> String {
@@ -220,9 +324,9 @@ fn generate_atom_urlcomponent_encoder(atom: &ast::AtomType, ns: &str) -> String
         ast::AtomType::Str => "identity".to_owned(),
         ast::AtomType::I32 | ast::AtomType::U32 | ast::AtomType::U8 => "String.fromInt".to_owned(),
         ast::AtomType::F64 => "String.fromFloat".to_owned(),
-        ast::AtomType::Bool => "String.fromBool".to_owned(),
-        ast::AtomType::DateTime => format!("{}builtinEncodeIso8601", ns),
-        ast::AtomType::Date => format!("{}builtinEncodeDate", ns),
+        ast::AtomType::Bool => format!("{}builtinEncodeBoolUrlcomponent", ns),
+        ast::AtomType::DateTime => format!("{}builtinEncodeIso8601Urlcomponent", ns),
+        ast::AtomType::Date => format!("{}builtinEncodeDateUrlcomponent", ns),
         ast::AtomType::Uuid => "BuiltinUuid.encodeUrlcomponent".to_owned(),
         ast::AtomType::Bytes => "BuiltinBytes.encodeUrlcomponent".to_owned(),
     }
@@ -252,15 +356,20 @@ pub(crate) fn struct_or_enum_encoder_name(ident: &str, ns: &str) -> String {
     format!("{}encode{}", ns, ident.to_pascal_case())
 }
 
-pub(crate) fn query_encoder(ident: &ast::TypeIdent, ns: &str) -> String {
-    // TODO: should narrow type of query parameter. According to spec query has to be a user defined struct
-    if let ast::TypeIdent::UserDefined(query_ty_name) = ident {
-        query_struct_encoder_name(query_ty_name, ns)
-    } else {
-        panic!("query MUST be a user defined struct");
-    }
+/// Construct the name of the encoder of the query type `ident`, which must be a user defined struct.
+///
+/// Returns `None` for other query types, which only the Rust backend supports.
+pub(crate) fn query_encoder(ident: &ast::TypeIdent, ns: &str) -> Option<String> {
+    ident
+        .user_defined()
+        .map(|query_ty_name| query_struct_encoder_name(query_ty_name, ns))
 }
 
 pub(crate) fn query_struct_encoder_name(ident: &str, ns: &str) -> String {
     format!("{}buildQuery{}", ns, ident.to_pascal_case())
 }
+
+/// Construct name of the query encoder function (encoding below a key prefix) for specific `ident`.
+fn query_type_encoder_name(ident: &str, ns: &str) -> String {
+    format!("{}queryEncode{}", ns, ident.to_pascal_case())
+}
@@ -2,8 +2,8 @@
 #![allow(clippy::write_literal)]
 
 use super::{
-    decoder_generation, encoder_generation, generate_doc_comment, to_atom, type_generation,
-    IndentWriter,
+    decoder_generation, encoder_generation, field_name, generate_doc_comment, to_atom,
+    type_generation, IndentWriter,
 };
 use crate::{ast, LibError};
 use inflector::Inflector;
@@ -21,6 +21,30 @@ pub(crate) fn generate(service: &ast::ServiceDef, file: &mut IndentWriter) -> Re
     file.empty_lines(2)?;
 
     for endpoint in &service.endpoints {
+        // elm/http cannot open WebSockets, these need to be connected through ports
+        let ret = match endpoint.route.return_type() {
+            Some(ret) => ret,
+            None => continue,
+        };
+        let query_encoder = endpoint
+            .route
+            .query()
+            .as_ref()
+            .map(|query| {
+                encoder_generation::query_encoder(query, "AE.").ok_or_else(|| {
+                    LibError::UnsupportedQuery {
+                        backend: super::BACKEND_NAME,
+                        endpoint: format!(
+                            "{}.{}",
+                            service.name,
+                            synthesize_endpoint_name(&endpoint.route)
+                        ),
+                        reason: "the query must be a user defined struct",
+                    }
+                })
+            })
+            .transpose()?;
+
         // Note: we currently generate a single flat function for each endpoint. This is what
         // OpenApi does. A worthfile, alternative api would generate an enum of endpoints
         // enum Endpoints = GetPet | PostMonster | etc first that is consumed by a generic
@@ -40,7 +64,9 @@ pub(crate) fn generate(service: &ast::ServiceDef, file: &mut IndentWriter) -> Re
             write!(line_arguments, "{}", endpoint_name)?;
 
             for (idx, component) in endpoint.route.components().iter().enumerate() {
-                if let ast::ServiceRouteComponent::Variable(arg) = component {
+                if let ast::ServiceRouteComponent::Variable(arg)
+                | ast::ServiceRouteComponent::Rest(arg) = component
+                {
                     write!(
                         line_type_signature,
                         "{} -> ",
@@ -59,7 +85,7 @@ pub(crate) fn generate(service: &ast::ServiceDef, file: &mut IndentWriter) -> Re
                 write!(
                     line_type_signature,
                     "{} -> ",
-                    to_atom(type_generation::generate_type_ident(&body, "Ty."))
+                    to_atom(type_generation::generate_body_type(body, "Ty."))
                 )?;
                 write!(line_arguments, " body")?;
             }
@@ -74,10 +100,7 @@ pub(crate) fn generate(service: &ast::ServiceDef, file: &mut IndentWriter) -> Re
                     .as_ref()
                     .map(|q| type_generation::generate_type_ident(q, "Ty."))
                     .unwrap_or_else(|| "NoQuery".to_owned()),
-                to_atom(type_generation::generate_type_ident(
-                    endpoint.route.return_type(),
-                    "Ty."
-                ))
+                to_atom(type_generation::generate_return_type(ret, "Ty."))
             )?;
 
             file.start_line()?.write_all(&line_type_signature)?;
@@ -115,7 +138,8 @@ pub(crate) fn generate(service: &ast::ServiceDef, file: &mut IndentWriter) -> Re
                         )?;
                     }
 
-                    ast::ServiceRouteComponent::Variable(arg) => {
+                    ast::ServiceRouteComponent::Variable(arg)
+                    | ast::ServiceRouteComponent::Rest(arg) => {
                         write!(
                             file.start_line()?,
                             "{delimiter} component{idx}_{name} |> {encoder}",
@@ -137,34 +161,63 @@ pub(crate) fn generate(service: &ast::ServiceDef, file: &mut IndentWriter) -> Re
 
         // queryEncoder
         {
-            if let Some(ident) = endpoint.route.query() {
-                write!(
-                    file.start_line()?,
-                    "{}",
-                    to_atom(encoder_generation::query_encoder(ident, "AE."))
-                )?;
+            if let Some(query_encoder) = query_encoder {
+                write!(file.start_line()?, "{}", to_atom(query_encoder))?;
             } else {
                 write!(file.start_line()?, "noQueryEncoder")?;
             }
         }
 
         // resolver
-        write!(
-            file.start_line()?,
-            "(jsonResolver ({}))",
-            to_atom(decoder_generation::generate_type_decoder(
-                &endpoint.route.return_type(),
-                "AD."
-            ))
-        )?;
+        match ret {
+            ast::ReturnType::Stream(item) => write!(
+                file.start_line()?,
+                "(ndjsonResolver ({}))",
+                to_atom(decoder_generation::generate_type_decoder(item, "AD."))
+            )?,
+            ast::ReturnType::Blob(_) => write!(file.start_line()?, "blobResolver")?,
+            ast::ReturnType::Data(ret) => write!(
+                file.start_line()?,
+                "(jsonResolver ({}))",
+                to_atom(decoder_generation::generate_type_decoder(ret, "AD."))
+            )?,
+        }
 
         // |> withBody if we send a body
-        if let Some(body) = endpoint.route.request_body() {
-            write!(
+        match endpoint.route.request_body() {
+            Some(ast::BodyType::Multipart(parts)) => {
+                let parts = parts
+                    .iter()
+                    .map(|part| match part {
+                        ast::MultipartPart::Field(pair) => format!(
+                            "Http.stringPart \"{name}\" (E.encode 0 ({encoder} body.{field}))",
+                            name = pair.name,
+                            encoder = encoder_generation::generate_type_json_encoder(
+                                &pair.type_ident,
+                                "AE."
+                            ),
+                            field = field_name(&pair.name)
+                        ),
+                        ast::MultipartPart::File(name) => format!(
+                            "Http.filePart \"{name}\" body.{field}",
+                            name = name,
+                            field = field_name(name)
+                        ),
+                    })
+                    .collect::<Vec<_>>();
+                write!(
+                    file.start_line()?,
+                    "|> withBody (Http.multipartBody [ {} ])",
+                    parts.join(", ")
+                )?;
+            }
+            Some(ast::BodyType::Blob(_)) => write!(file.start_line()?, "|> withBlobBody body")?,
+            Some(ast::BodyType::Data(body)) => write!(
                 file.start_line()?,
                 "|> withJsonBody {} body",
                 to_atom(encoder_generation::generate_type_json_encoder(body, "AE."))
-            )?;
+            )?,
+            None => {}
         }
 
         file.decrease_indent();
@@ -189,7 +242,7 @@ fn synthesize_endpoint_name(route: &ast::ServiceRoute) -> String {
             ast::ServiceRouteComponent::Literal(lit) => {
                 out.push(lit.clone().to_pascal_case());
             }
-            ast::ServiceRouteComponent::Variable(var) => {
+            ast::ServiceRouteComponent::Variable(var) | ast::ServiceRouteComponent::Rest(var) => {
                 out.push(format!("By{}Of", var.name.clone().to_pascal_case()));
             }
         }
@@ -203,6 +256,7 @@ fn synthesize_endpoint_name(route: &ast::ServiceRoute) -> String {
         ast::ServiceRoute::Delete { .. } => "delete",
         ast::ServiceRoute::Put { .. } => "replace",
         ast::ServiceRoute::Patch { .. } => "modify",
+        ast::ServiceRoute::Ws { .. } => "connect",
     };
 
     format!("{}{}", verb, action)
@@ -18,6 +18,18 @@ builtinEncodeIso8601 : Time.Posix -> E.Value
 builtinEncodeIso8601 =
     Iso8601.encode
 
+builtinEncodeDateUrlcomponent : Date.Date -> String
+builtinEncodeDateUrlcomponent =
+    Date.toIsoString
+
+builtinEncodeIso8601Urlcomponent : Time.Posix -> String
+builtinEncodeIso8601Urlcomponent =
+    Iso8601.fromTime
+
+builtinEncodeBoolUrlcomponent : Bool -> String
+builtinEncodeBoolUrlcomponent b =
+    if b then "true" else "false"
+
 
 builtinEncodeMaybe : (t -> E.Value) -> Maybe t -> E.Value
 builtinEncodeMaybe encoder =
@@ -28,4 +40,56 @@ builtinEncodeResult : (err -> E.Value) -> (ok -> E.Value) -> Result err ok -> E.
 builtinEncodeResult errEncoder okEncoder res =
     case res of
         Err err -> E.object [("Err", errEncoder err)] 
-        Ok ok -> E.object [("Ok", okEncoder ok)]
\ No newline at end of file
+        Ok ok -> E.object [("Ok", okEncoder ok)]
+
+
+builtinQueryKey : String -> String -> String
+builtinQueryKey prefix name =
+    if String.isEmpty prefix then name else prefix ++ "[" ++ name ++ "]"
+
+
+builtinQueryAtom : (t -> String) -> String -> t -> List Url.Builder.QueryParameter
+builtinQueryAtom toString key v =
+    [ Url.Builder.string key (toString v) ]
+
+
+builtinQueryMaybe : (String -> t -> List Url.Builder.QueryParameter) -> String -> Maybe t -> List Url.Builder.QueryParameter
+builtinQueryMaybe encoder key =
+    Maybe.map (encoder key) >> Maybe.withDefault []
+
+
+builtinQueryList : (String -> t -> List Url.Builder.QueryParameter) -> String -> List t -> List Url.Builder.QueryParameter
+builtinQueryList encoder key =
+    List.concatMap (encoder key)
+
+
+builtinQueryIndexedList : (String -> t -> List Url.Builder.QueryParameter) -> String -> List t -> List Url.Builder.QueryParameter
+builtinQueryIndexedList encoder key =
+    List.indexedMap (\idx -> encoder (builtinQueryKey key (String.fromInt idx))) >> List.concat
+
+
+builtinQueryDict : (String -> t -> List Url.Builder.QueryParameter) -> String -> Dict String t -> List Url.Builder.QueryParameter
+builtinQueryDict encoder key =
+    Dict.toList >> List.concatMap (\( k, v ) -> encoder (builtinQueryKey key k) v)
+
+
+builtinQueryResult : (String -> err -> List Url.Builder.QueryParameter) -> (String -> ok -> List Url.Builder.QueryParameter) -> String -> Result err ok -> List Url.Builder.QueryParameter
+builtinQueryResult errEncoder okEncoder key res =
+    case res of
+        Err err -> errEncoder (builtinQueryKey key "Err") err
+        Ok ok -> okEncoder (builtinQueryKey key "Ok") ok
+
+
+builtinQueryTuple1 : (String -> a -> List Url.Builder.QueryParameter) -> String -> a -> List Url.Builder.QueryParameter
+builtinQueryTuple1 encoder0 key x0 =
+    encoder0 (builtinQueryKey key "0") x0
+
+
+builtinQueryTuple2 : (String -> a -> List Url.Builder.QueryParameter) -> (String -> b -> List Url.Builder.QueryParameter) -> String -> ( a, b ) -> List Url.Builder.QueryParameter
+builtinQueryTuple2 encoder0 encoder1 key ( x0, x1 ) =
+    encoder0 (builtinQueryKey key "0") x0 ++ encoder1 (builtinQueryKey key "1") x1
+
+
+builtinQueryTuple3 : (String -> a -> List Url.Builder.QueryParameter) -> (String -> b -> List Url.Builder.QueryParameter) -> (String -> c -> List Url.Builder.QueryParameter) -> String -> ( a, b, c ) -> List Url.Builder.QueryParameter
+builtinQueryTuple3 encoder0 encoder1 encoder2 key ( x0, x1, x2 ) =
+    encoder0 (builtinQueryKey key "0") x0 ++ encoder1 (builtinQueryKey key "1") x1 ++ encoder2 (builtinQueryKey key "2") x2
\ No newline at end of file
@@ -194,6 +194,39 @@ pub(crate) fn generate_type_ident(type_ident: &ast::TypeIdent, ns: &str) -> Stri
     }
 }
 
+/// Generate elm code for the return type of an endpoint.
+pub(crate) fn generate_return_type(ret: &ast::ReturnType, ns: &str) -> String {
+    match ret {
+        ast::ReturnType::Data(type_ident) => generate_type_ident(type_ident, ns),
+        // the elm client receives a stream as a whole
+        ast::ReturnType::Stream(item) => format!("List {}", to_atom(generate_type_ident(item, ns))),
+        // `ServiceBuiltIn.Blob`, carrying the content type along with the bytes
+        ast::ReturnType::Blob(_) => "Blob".to_owned(),
+    }
+}
+
+/// Generate elm code for the request body type of an endpoint.
+pub(crate) fn generate_body_type(body: &ast::BodyType, ns: &str) -> String {
+    match body {
+        ast::BodyType::Data(type_ident) => generate_type_ident(type_ident, ns),
+        ast::BodyType::Multipart(parts) => format!(
+            "{{ {} }}",
+            parts
+                .iter()
+                .map(|part| match part {
+                    ast::MultipartPart::Field(pair) => format!(
+                        "{} : {}",
+                        field_name(&pair.name),
+                        generate_type_ident(&pair.type_ident, ns)
+                    ),
+                    ast::MultipartPart::File(name) => format!("{} : File.File", field_name(name)),
+                })
+                .join(", ")
+        ),
+        ast::BodyType::Blob(_) => "Blob".to_owned(),
+    }
+}
+
 pub(crate) fn generate_local_type_ident(type_ident: &ast::TypeIdent) -> String {
     generate_type_ident(type_ident, "")
 }
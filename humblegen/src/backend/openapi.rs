@@ -0,0 +1,564 @@
+//! OpenAPI 3 generator.
+//!
+//! Describes the services of a spec as an OpenAPI 3.0 document, in YAML or, if the output file
+//! ends in `.json`, in JSON. Schemas follow the JSON encoding of humblespec types
+//! (see `docs/humblespec/service_protocol.md`):
+//!
+//! - enums are externally tagged: simple variants are strings, all others objects with a single entry,
+//! - `result[T][E]` is `{"Ok": T}` or `{"Err": E}`,
+//! - `bytes` are base64 strings, and `()` is `null`,
+//! - `option`, `list` and `map` fields of structs may be missing.
+//!
+//! Things OpenAPI 3.0 cannot express are described by extensions: `x-humblegen-query` is the schema
+//! of a query string that is a single value or a list of values (e.g. `?{i32}`), `x-humblegen-keys`
+//! the schema of the keys of a map whose keys are not strings, `x-humblegen-rest` the name and schema
+//! of a rest parameter (e.g. `/files/{path: str..}`), which takes the rest of the path including its `/`s
+//! and is therefore left out of the path, and `x-humblegen-websocket` the schemas of the messages
+//! of a `WS` endpoint.
+
+use crate::{ast, LibError, Spec};
+
+use serde_json::{json, Map, Value};
+use std::{fs::File, io::Write, path::Path};
+
+/// The OpenAPI version of the generated documents.
+const OPENAPI_VERSION: &str = "3.0.3";
+
+pub struct Generator {
+    title: String,
+    version: String,
+    root: String,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self {
+            title: "API".to_owned(),
+            version: "0.0.0".to_owned(),
+            root: "/api".to_owned(),
+        }
+    }
+}
+
+impl Generator {
+    /// Sets the title of the API (`info.title`, default: `API`).
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Sets the version of the API (`info.version`, default: `0.0.0`).
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// Sets the URL path prefix at which all services are mounted (`servers[0].url`, default: `/api`).
+    pub fn with_root(mut self, root: impl Into<String>) -> Self {
+        self.root = root.into();
+        self
+    }
+
+    /// The OpenAPI document describing the services and types of `spec`.
+    pub fn document(&self, spec: &Spec) -> Value {
+        let mut tags = Vec::new();
+        let mut paths = Map::new();
+        let mut schemas = Map::new();
+        for spec_item in spec.iter() {
+            match spec_item {
+                ast::SpecItem::StructDef(sdef) => {
+                    let schema = struct_schema(&sdef.fields);
+                    schemas.insert(sdef.name.clone(), with_doc(schema, &sdef.doc_comment));
+                }
+                ast::SpecItem::EnumDef(edef) => {
+                    let schema = enum_schema(edef);
+                    schemas.insert(edef.name.clone(), with_doc(schema, &edef.doc_comment));
+                }
+                ast::SpecItem::ServiceDef(service) => {
+                    tags.push(with_doc(
+                        json!({ "name": service.name }),
+                        &service.doc_comment,
+                    ));
+                    for endpoint in &service.endpoints {
+                        let path = paths
+                            .entry(path_template(&endpoint.route))
+                            .or_insert_with(|| json!({}));
+                        path[method(&endpoint.route)] = operation(spec, service, endpoint);
+                    }
+                }
+            }
+        }
+        schemas.insert("ErrorResponse".to_owned(), error_response_schema());
+
+        json!({
+            "openapi": OPENAPI_VERSION,
+            "info": { "title": self.title, "version": self.version },
+            "servers": [{ "url": self.root }],
+            "tags": tags,
+            "paths": paths,
+            "components": { "schemas": schemas },
+        })
+    }
+}
+
+impl crate::CodeGenerator for Generator {
+    fn generate(&self, spec: &Spec, output: &Path) -> Result<(), LibError> {
+        let document = self.document(spec);
+        let serialized = if output.extension() == Some("json".as_ref()) {
+            serde_json::to_string_pretty(&document).expect("JSON values serialize")
+        } else {
+            serde_yaml::to_string(&document).expect("JSON values serialize to YAML")
+        };
+
+        // TODO: support folder as output path
+        let mut outfile = File::create(output).map_err(LibError::IoError)?;
+        outfile
+            .write_all(serialized.as_bytes())
+            .map_err(LibError::IoError)?;
+        Ok(())
+    }
+}
+
+/// The OpenAPI path of a route, e.g. `/monsters/{id}`, without its rest parameter, if any.
+fn path_template(route: &ast::ServiceRoute) -> String {
+    let path: String = route
+        .components()
+        .iter()
+        .filter_map(|component| match component {
+            ast::ServiceRouteComponent::Literal(literal) => Some(format!("/{}", literal)),
+            ast::ServiceRouteComponent::Variable(pair) => Some(format!("/{{{}}}", pair.name)),
+            ast::ServiceRouteComponent::Rest(_) => None,
+        })
+        .collect();
+    if path.is_empty() {
+        "/".to_owned()
+    } else {
+        path
+    }
+}
+
+fn method(route: &ast::ServiceRoute) -> &'static str {
+    match route {
+        ast::ServiceRoute::Get { .. } | ast::ServiceRoute::Ws { .. } => "get",
+        ast::ServiceRoute::Delete { .. } => "delete",
+        ast::ServiceRoute::Post { .. } => "post",
+        ast::ServiceRoute::Put { .. } => "put",
+        ast::ServiceRoute::Patch { .. } => "patch",
+    }
+}
+
+fn operation(spec: &Spec, service: &ast::ServiceDef, endpoint: &ast::ServiceEndpoint) -> Value {
+    let route = &endpoint.route;
+    let mut operation = with_doc(
+        json!({
+            "tags": [service.name],
+            "operationId": crate::backend::rust::route_name(route),
+        }),
+        &endpoint.doc_comment,
+    );
+
+    let mut parameters = Vec::new();
+    for component in route.components() {
+        match component {
+            ast::ServiceRouteComponent::Literal(_) => {}
+            ast::ServiceRouteComponent::Variable(pair) => {
+                parameters.push(json!({
+                    "name": pair.name,
+                    "in": "path",
+                    "required": true,
+                    "schema": schema(&pair.type_ident),
+                }));
+            }
+            // a path parameter cannot contain `/`
+            ast::ServiceRouteComponent::Rest(pair) => {
+                operation["x-humblegen-rest"] = json!({
+                    "name": pair.name,
+                    "description": "The rest of the path, which may contain `/`.",
+                    "schema": schema(&pair.type_ident),
+                });
+            }
+        }
+    }
+    if let Some(query) = route.query() {
+        match query
+            .user_defined()
+            .and_then(|name| find_struct(spec, name))
+        {
+            Some(sdef) => parameters.extend(sdef.fields.iter().map(query_parameter)),
+            // a single value or a list of `&`-separated values, which has no name
+            None => operation["x-humblegen-query"] = schema(query),
+        }
+    }
+    if let ast::ServiceRoute::Put { .. } | ast::ServiceRoute::Patch { .. } = route {
+        parameters.push(json!({
+            "name": "If-Match",
+            "in": "header",
+            "description": "Entity tag of the expected state of the resource.",
+            "schema": { "type": "string" },
+        }));
+    }
+    if !parameters.is_empty() {
+        operation["parameters"] = Value::Array(parameters);
+    }
+
+    if let Some(body) = route.request_body() {
+        operation["requestBody"] = json!({
+            "required": true,
+            "content": request_content(body),
+        });
+    }
+
+    let success = match route.websocket_messages() {
+        Some((client_msg, server_msg)) => {
+            operation["x-humblegen-websocket"] = json!({
+                "client": schema(client_msg),
+                "server": schema(server_msg),
+            });
+            json!({
+                "101": {
+                    "description": "Upgrade to a WebSocket connection, on which messages are JSON values in text frames.",
+                },
+            })
+        }
+        None => json!({ "200": route.return_type().map(response) }),
+    };
+    let mut responses = success;
+    responses["default"] = json!({
+        "description": "The request was rejected or failed.",
+        "content": { "application/json": { "schema": reference("ErrorResponse") } },
+    });
+    operation["responses"] = responses;
+    operation
+}
+
+fn find_struct<'a>(spec: &'a Spec, name: &str) -> Option<&'a ast::StructDef> {
+    spec.iter().find_map(|spec_item| match spec_item {
+        ast::SpecItem::StructDef(sdef) if sdef.name == name => Some(sdef),
+        _ => None,
+    })
+}
+
+/// A field of a query struct, which is decoded using bracket notation (e.g. `filter[name]=godzilla`).
+fn query_parameter(field: &ast::FieldNode) -> Value {
+    let ast::FieldDefPair { name, type_ident } = &field.pair;
+    let mut parameter = with_doc(
+        json!({
+            "name": name,
+            "in": "query",
+            "required": !is_defaulted(type_ident),
+            "schema": schema(type_ident),
+        }),
+        &field.doc_comment,
+    );
+    let scalar = match type_ident {
+        ast::TypeIdent::Option(inner) => matches!(**inner, ast::TypeIdent::BuiltIn(_)),
+        _ => matches!(type_ident, ast::TypeIdent::BuiltIn(_)),
+    };
+    if !scalar {
+        parameter["style"] = json!("deepObject");
+        parameter["explode"] = json!(true);
+    }
+    parameter
+}
+
+fn request_content(body: &ast::BodyType) -> Value {
+    match body {
+        ast::BodyType::Blob(media_type) => json!({ media_type.as_str(): { "schema": binary() } }),
+        ast::BodyType::Multipart(parts) => {
+            let mut properties = Map::new();
+            let mut required = Vec::new();
+            let mut encoding = Map::new();
+            for part in parts {
+                match part {
+                    ast::MultipartPart::Field(ast::FieldDefPair { name, type_ident }) => {
+                        properties.insert(name.clone(), schema(type_ident));
+                        encoding.insert(name.clone(), json!({ "contentType": "application/json" }));
+                        if !matches!(
+                            type_ident,
+                            ast::TypeIdent::Option(_)
+                                | ast::TypeIdent::BuiltIn(ast::AtomType::Empty)
+                        ) {
+                            required.push(name.clone());
+                        }
+                    }
+                    ast::MultipartPart::File(name) => {
+                        properties.insert(name.clone(), binary());
+                        required.push(name.clone());
+                    }
+                }
+            }
+            let mut schema = json!({
+                "type": "object",
+                "properties": properties,
+            });
+            // a trailing file part is streamed, so it must come after all other parts
+            if let Some(ast::MultipartPart::File(name)) = parts.last() {
+                schema["description"] = json!(format!(
+                    "The `{}` part must be sent last, the order of the other parts does not matter.",
+                    name
+                ));
+            }
+            if !required.is_empty() {
+                schema["required"] = json!(required);
+            }
+            json!({
+                "multipart/form-data": { "schema": schema, "encoding": encoding },
+            })
+        }
+        ast::BodyType::Data(body) => json!({ "application/json": { "schema": schema(body) } }),
+    }
+}
+
+/// The successful response of an endpoint returning `ret`.
+fn response(ret: &ast::ReturnType) -> Value {
+    match ret {
+        ast::ReturnType::Blob(media_type) => json!({
+            "description": "Binary data.",
+            "content": { media_type.as_str(): { "schema": binary() } },
+        }),
+        ast::ReturnType::Stream(item) => json!({
+            "description": "A stream of JSON values, one per line, or one per event if Server-Sent Events are accepted.",
+            "content": {
+                "application/x-ndjson": { "schema": schema(item) },
+                "text/event-stream": { "schema": schema(item) },
+            },
+        }),
+        ast::ReturnType::Data(ret) => json!({
+            "description": "Success.",
+            "content": { "application/json": { "schema": schema(ret) } },
+        }),
+    }
+}
+
+/// The schema of the JSON encoding of values of `type_ident`.
+fn schema(type_ident: &ast::TypeIdent) -> Value {
+    match type_ident {
+        ast::TypeIdent::BuiltIn(atom) => atom_schema(*atom),
+        ast::TypeIdent::List(inner) => json!({ "type": "array", "items": schema(inner) }),
+        ast::TypeIdent::Option(inner) => with(schema(inner), "nullable", json!(true)),
+        ast::TypeIdent::Result(ok, err) => json!({
+            "oneOf": [tagged("Ok", schema(ok)), tagged("Err", schema(err))],
+        }),
+        ast::TypeIdent::Map(key, value) => {
+            let mut schema_ = json!({ "type": "object", "additionalProperties": schema(value) });
+            if !matches!(**key, ast::TypeIdent::BuiltIn(ast::AtomType::Str)) {
+                schema_["x-humblegen-keys"] = schema(key);
+            }
+            schema_
+        }
+        ast::TypeIdent::Tuple(tdef) => tuple_schema(tdef),
+        ast::TypeIdent::UserDefined(name) => reference(name),
+    }
+}
+
+fn atom_schema(atom: ast::AtomType) -> Value {
+    match atom {
+        ast::AtomType::Empty => json!({ "nullable": true, "enum": [null] }),
+        ast::AtomType::Str => json!({ "type": "string" }),
+        ast::AtomType::I32 => json!({ "type": "integer", "format": "int32" }),
+        ast::AtomType::U32 => json!({
+            "type": "integer",
+            "format": "int64",
+            "minimum": 0,
+            "maximum": u32::MAX,
+        }),
+        ast::AtomType::U8 => json!({ "type": "integer", "minimum": 0, "maximum": u8::MAX }),
+        ast::AtomType::F64 => json!({ "type": "number", "format": "double" }),
+        ast::AtomType::Bool => json!({ "type": "boolean" }),
+        ast::AtomType::DateTime => json!({ "type": "string", "format": "date-time" }),
+        ast::AtomType::Date => json!({ "type": "string", "format": "date" }),
+        ast::AtomType::Uuid => json!({ "type": "string", "format": "uuid" }),
+        ast::AtomType::Bytes => json!({ "type": "string", "format": "byte" }),
+    }
+}
+
+/// Tuples are arrays of fixed length. OpenAPI 3.0 cannot describe the type of each element,
+/// so all elements are described by the union of the element types.
+fn tuple_schema(tdef: &ast::TupleDef) -> Value {
+    let mut element_schemas: Vec<Value> = Vec::new();
+    for element in tdef.elements() {
+        let element_schema = schema(element);
+        if !element_schemas.contains(&element_schema) {
+            element_schemas.push(element_schema);
+        }
+    }
+    let items = if element_schemas.len() == 1 {
+        element_schemas.remove(0)
+    } else {
+        json!({ "anyOf": element_schemas })
+    };
+    let len = tdef.elements().len();
+    json!({ "type": "array", "items": items, "minItems": len, "maxItems": len })
+}
+
+fn struct_schema(fields: &ast::StructFields) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for field in fields.iter() {
+        let ast::FieldDefPair { name, type_ident } = &field.pair;
+        properties.insert(
+            name.clone(),
+            with_doc(schema(type_ident), &field.doc_comment),
+        );
+        if !is_defaulted(type_ident) {
+            required.push(name.clone());
+        }
+    }
+    let mut schema = json!({ "type": "object", "properties": properties });
+    // OpenAPI 3.0 does not allow empty `required` lists
+    if !required.is_empty() {
+        schema["required"] = json!(required);
+    }
+    schema
+}
+
+fn enum_schema(edef: &ast::EnumDef) -> Value {
+    let simple_variants: Vec<_> = edef.simple_variants().map(|v| v.name.clone()).collect();
+    let mut variant_schemas = Vec::new();
+    if !simple_variants.is_empty() {
+        variant_schemas.push(json!({ "type": "string", "enum": simple_variants }));
+    }
+    for variant in edef.complex_variants() {
+        let content = match &variant.variant_type {
+            ast::VariantType::Simple => unreachable!("complex variants are not simple"),
+            ast::VariantType::Newtype(type_ident) => schema(type_ident),
+            ast::VariantType::Tuple(tdef) => tuple_schema(tdef),
+            ast::VariantType::Struct(fields) => struct_schema(fields),
+        };
+        variant_schemas.push(with_doc(
+            tagged(&variant.name, content),
+            &variant.doc_comment,
+        ));
+    }
+    match variant_schemas.len() {
+        // an enum without variants has no values
+        0 => json!({ "not": {} }),
+        1 => variant_schemas.remove(0),
+        _ => json!({ "oneOf": variant_schemas }),
+    }
+}
+
+/// The schema of `humblegen_rt::service_protocol::ErrorResponse`, the body of all non-2xx responses.
+fn error_response_schema() -> Value {
+    let string = || json!({ "type": "string" });
+    let count = || json!({ "type": "integer", "format": "int64", "minimum": 0 });
+
+    let service_error = json!({
+        "description": "An error returned by a handler.",
+        "oneOf": [
+            {
+                "type": "string",
+                "enum": ["Authentication", "Authorization", "PreconditionFailed"],
+            },
+            tagged("Internal", string()),
+        ],
+    });
+
+    let mut runtime_errors = vec![json!({
+        "type": "string",
+        "enum": ["NoServiceMounted", "ServiceMountsAmbiguous", "PostBodyReadTimeout", "Timeout"],
+    })];
+    for (variant, fields) in [
+        ("NoRouteMountedInService", vec![("service", string())]),
+        ("RouteMountsAmbiguous", vec![("service", string())]),
+        (
+            "MethodNotAllowed",
+            vec![
+                ("service", string()),
+                ("allowed", json!({ "type": "array", "items": string() })),
+            ],
+        ),
+        (
+            "RouteParamInvalid",
+            vec![("param_name", string()), ("parse_error", string())],
+        ),
+        ("PayloadTooLarge", vec![("max_body_size", count())]),
+        ("RateLimited", vec![("retry_after", count())]),
+    ] {
+        let required: Vec<_> = fields.iter().map(|(name, _)| *name).collect();
+        let properties: Map<String, Value> = fields
+            .into_iter()
+            .map(|(name, schema)| (name.to_owned(), schema))
+            .collect();
+        runtime_errors.push(tagged(
+            variant,
+            json!({ "type": "object", "required": required, "properties": properties }),
+        ));
+    }
+    for variant in &[
+        "QueryInvalid",
+        "PostBodyReadError",
+        "PostBodyInvalid",
+        "CorsRequestRejected",
+        "UnsupportedContentEncoding",
+        "UnsupportedMediaType",
+        "NotReady",
+        "WebSocketUpgradeRequired",
+        "SerializeHandlerResponse",
+        "SerializeErrorResponse",
+        "BadGateway",
+    ] {
+        runtime_errors.push(tagged(variant, string()));
+    }
+    let runtime_error = json!({
+        "description": "An error of the server runtime, e.g. an invalid request.",
+        "oneOf": runtime_errors,
+    });
+
+    json!({
+        "type": "object",
+        "description": "The body of all responses with a status code other than 2xx.",
+        "required": ["code", "kind"],
+        "properties": {
+            "code": { "type": "integer", "description": "The HTTP status code." },
+            "kind": {
+                "oneOf": [tagged("Service", service_error), tagged("Runtime", runtime_error)],
+            },
+        },
+    })
+}
+
+/// Fields that serde fills in when they are missing.
+fn is_defaulted(type_ident: &ast::TypeIdent) -> bool {
+    matches!(
+        type_ident,
+        ast::TypeIdent::Option(_) | ast::TypeIdent::List(_) | ast::TypeIdent::Map(_, _)
+    )
+}
+
+/// An externally tagged value, i.e., an object with the single entry `tag`.
+fn tagged(tag: &str, content: Value) -> Value {
+    json!({
+        "type": "object",
+        "required": [tag],
+        "properties": { tag: content },
+        "additionalProperties": false,
+    })
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+fn binary() -> Value {
+    json!({ "type": "string", "format": "binary" })
+}
+
+/// Adds the doc comment as description.
+fn with_doc(schema: Value, doc_comment: &Option<String>) -> Value {
+    match doc_comment {
+        Some(doc_comment) => with(schema, "description", json!(doc_comment.trim())),
+        None => schema,
+    }
+}
+
+/// Adds `key` to `schema`. Siblings of `$ref` are ignored in OpenAPI 3.0, so references are wrapped.
+fn with(schema: Value, key: &str, value: Value) -> Value {
+    let mut schema = if schema.get("$ref").is_some() {
+        json!({ "allOf": [schema] })
+    } else {
+        schema
+    };
+    schema[key] = value;
+    schema
+}
@@ -3,10 +3,14 @@
 pub(crate) mod rustfmt;
 mod service_server;
 
+#[cfg(any(feature = "mock", feature = "proxy", feature = "openapi"))]
+pub(crate) use service_server::route_name;
+
 use crate::{ast, Artifact, LibError, Spec};
 use anyhow::Result;
 use proc_macro2::TokenStream;
 use quote::quote;
+use std::collections::HashSet;
 use std::path::Path;
 use std::{fs::File, io::Write};
 
@@ -25,10 +29,17 @@ fn fmt_opt_string(s: &Option<String>) -> &str {
 }
 
 /// Generate rust code for a struct definition.
-pub(crate) fn generate_struct_def(sdef: &ast::StructDef) -> TokenStream {
+///
+/// Lists and maps of structs used in URL queries default to empty, as they are omitted from
+/// queries when empty.
+pub(crate) fn generate_struct_def(sdef: &ast::StructDef, in_query: bool) -> TokenStream {
     let ident = fmt_ident(&sdef.name);
     let doc_comment = fmt_opt_string(&sdef.doc_comment);
-    let fields: Vec<_> = sdef.fields.iter().map(generate_pub_field_node).collect();
+    let fields: Vec<_> = sdef
+        .fields
+        .iter()
+        .map(|field| generate_pub_field_node(field, in_query))
+        .collect();
 
     quote!(
         #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
@@ -54,6 +65,96 @@ pub(crate) fn generate_enum_def(edef: &ast::EnumDef) -> TokenStream {
     })
 }
 
+/// Generate the `Fake` implementation of a struct, used by mocks.
+fn generate_struct_fake(sdef: &ast::StructDef) -> TokenStream {
+    let ident = fmt_ident(&sdef.name);
+    let fields = generate_fields_fake(&sdef.fields);
+    let rng = if sdef.fields.iter().next().is_some() {
+        quote!(rng)
+    } else {
+        quote!(_rng)
+    };
+
+    quote!(
+        impl ::humblegen_rt::mock::Fake for #ident {
+            fn fake(#rng: &mut ::humblegen_rt::mock::FakeRng) -> Self {
+                #ident { #fields }
+            }
+        }
+    )
+}
+
+/// Generate the `Fake` implementation of an enum, which picks a variant at random.
+fn generate_enum_fake(edef: &ast::EnumDef) -> TokenStream {
+    let ident = fmt_ident(&edef.name);
+    if edef.variants.is_empty() {
+        let msg = format!(
+            "cannot fake a value of `{}`, which has no variants",
+            edef.name
+        );
+        return quote!(
+            impl ::humblegen_rt::mock::Fake for #ident {
+                fn fake(_rng: &mut ::humblegen_rt::mock::FakeRng) -> Self {
+                    panic!(#msg)
+                }
+            }
+        );
+    }
+
+    let num_variants = proc_macro2::Literal::usize_unsuffixed(edef.variants.len());
+    let arms: Vec<_> = edef
+        .variants
+        .iter()
+        .enumerate()
+        .map(|(idx, variant)| {
+            let variant_ident = fmt_ident(&variant.name);
+            let value = match variant.variant_type {
+                ast::VariantType::Simple => quote!(#ident::#variant_ident),
+                ast::VariantType::Tuple(ref inner) => {
+                    let components = inner
+                        .elements()
+                        .iter()
+                        .map(|_| quote!(::humblegen_rt::mock::Fake::fake(rng)));
+                    quote!(#ident::#variant_ident(#(#components),*))
+                }
+                ast::VariantType::Struct(ref fields) => {
+                    let fields = generate_fields_fake(fields);
+                    quote!(#ident::#variant_ident { #fields })
+                }
+                ast::VariantType::Newtype(_) => {
+                    quote!(#ident::#variant_ident(::humblegen_rt::mock::Fake::fake(rng)))
+                }
+            };
+            // the last variant is the catch-all arm
+            if idx + 1 == edef.variants.len() {
+                quote!(_ => #value)
+            } else {
+                let idx = proc_macro2::Literal::usize_unsuffixed(idx);
+                quote!(#idx => #value)
+            }
+        })
+        .collect();
+
+    quote!(
+        impl ::humblegen_rt::mock::Fake for #ident {
+            fn fake(rng: &mut ::humblegen_rt::mock::FakeRng) -> Self {
+                match rng.variant(#num_variants) {
+                    #(#arms),*
+                }
+            }
+        }
+    )
+}
+
+/// Generate the field initializers of a faked struct or struct variant.
+fn generate_fields_fake(fields: &ast::StructFields) -> TokenStream {
+    let fields = fields.iter().map(|field| {
+        let ident = fmt_ident(&field.pair.name);
+        quote!(#ident: ::humblegen_rt::mock::Fake::fake(rng))
+    });
+    quote!(#(#fields),*)
+}
+
 /// Generate rust code for a field node.
 fn generate_field_def_pair(pair: &ast::FieldDefPair) -> TokenStream {
     let ident = fmt_ident(&pair.name);
@@ -65,9 +166,9 @@ fn generate_field_def_pair(pair: &ast::FieldDefPair) -> TokenStream {
 ///
 /// Even though all fields are pub in generated code, fields in a `pub enum` cannot carry an
 /// additional `pub` qualifier.
-fn generate_pub_field_node(field: &ast::FieldNode) -> TokenStream {
+fn generate_pub_field_node(field: &ast::FieldNode, in_query: bool) -> TokenStream {
     let doc_comment = fmt_opt_string(&field.doc_comment);
-    let attributes = generate_field_attributes(&field.pair.type_ident);
+    let attributes = generate_field_attributes(&field.pair.type_ident, in_query);
     let field = generate_field_def_pair(&field.pair);
     quote! {
         #[doc = #doc_comment]
@@ -92,8 +193,9 @@ fn generate_variant(variant: &ast::VariantDef) -> TokenStream {
                 .iter()
                 .map(|field| {
                     let doc_comment = fmt_opt_string(&field.doc_comment);
+                    let attributes = generate_field_attributes(&field.pair.type_ident, false);
                     let fld = generate_field_def_pair(&field.pair);
-                    quote!(#[doc = #doc_comment] #fld)
+                    quote!(#[doc = #doc_comment] #(#[#attributes])* #fld)
                 })
                 .collect();
 
@@ -137,35 +239,75 @@ fn generate_type_ident(type_ident: &ast::TypeIdent) -> TokenStream {
     }
 }
 
+/// Generate rust code for the return type of an endpoint.
+fn generate_return_type(ret: &ast::ReturnType) -> TokenStream {
+    match ret {
+        ast::ReturnType::Data(type_ident) => generate_type_ident(type_ident),
+        ast::ReturnType::Stream(item) => {
+            let item_ty = generate_type_ident(item);
+            quote!(::humblegen_rt::stream::ResponseStream<#item_ty>)
+        }
+        ast::ReturnType::Blob(_) => quote!(::humblegen_rt::blob::Blob),
+    }
+}
+
 /// The list of attributes that are tacked onto the struct / enum field definition.
 /// Without the surrounding `#[` and `]`
 type FieldAttributes = Vec<TokenStream>;
 
 /// Render the list of field attributes for the given type_ident
-fn generate_field_attributes(type_ident: &ast::TypeIdent) -> FieldAttributes {
+fn generate_field_attributes(type_ident: &ast::TypeIdent, in_query: bool) -> FieldAttributes {
+    let mut attributes = match type_ident {
+        // lists of built-in types are repeated keys, which serde_qs decodes as a single value if
+        // the key occurs only once
+        ast::TypeIdent::List(inner) if in_query && is_query_list_element(inner) => {
+            let mut attributes = vec![quote! {
+                serde(deserialize_with = "::humblegen_rt::serialization_helpers::deser_query_list_field")
+            }];
+            if contains_bytes(type_ident) {
+                attributes.push(quote! {
+                    serde(serialize_with = "::humblegen_rt::serialization_helpers::nested_bytes::serialize")
+                });
+            }
+            attributes
+        }
+        ast::TypeIdent::BuiltIn(ast::AtomType::Bytes) => vec![
+            quote! { serde(deserialize_with = "::humblegen_rt::serialization_helpers::deser_bytes") },
+            quote! { serde(serialize_with = "::humblegen_rt::serialization_helpers::ser_bytes") },
+        ],
+        _ if contains_bytes(type_ident) => {
+            vec![quote! { serde(with = "::humblegen_rt::serialization_helpers::nested_bytes") }]
+        }
+        _ => vec![],
+    };
+    if in_query
+        && matches!(
+            type_ident,
+            ast::TypeIdent::List(_) | ast::TypeIdent::Map(_, _)
+        )
+    {
+        attributes.push(quote! { serde(default) });
+    }
+    attributes
+}
+
+/// Whether a list of `element` in a URL query is decoded by `deser_query_list_field`.
+fn is_query_list_element(element: &ast::TypeIdent) -> bool {
+    matches!(
+        element,
+        ast::TypeIdent::BuiltIn(atom) if !matches!(atom, ast::AtomType::Empty)
+    )
+}
+
+/// Whether `type_ident` is `bytes` nested in options, lists and maps, which is serialized by
+/// `nested_bytes`.
+fn contains_bytes(type_ident: &ast::TypeIdent) -> bool {
     match type_ident {
-        ast::TypeIdent::BuiltIn(atom) => match atom {
-            ast::AtomType::Empty => vec![],
-            ast::AtomType::Str => vec![],
-            ast::AtomType::I32 => vec![],
-            ast::AtomType::U32 => vec![],
-            ast::AtomType::U8 => vec![],
-            ast::AtomType::F64 => vec![],
-            ast::AtomType::Bool => vec![],
-            ast::AtomType::DateTime => vec![],
-            ast::AtomType::Date => vec![],
-            ast::AtomType::Uuid => vec![],
-            ast::AtomType::Bytes => vec![
-                quote! { serde(deserialize_with = "::humblegen_rt::serialization_helpers::deser_bytes") },
-                quote! { serde(serialize_with = "::humblegen_rt::serialization_helpers::ser_bytes") },
-            ],
-        },
-        ast::TypeIdent::List(_) => vec![],
-        ast::TypeIdent::Option(_) => vec![],
-        ast::TypeIdent::Result(_, _) => vec![],
-        ast::TypeIdent::Map(_, _) => vec![],
-        ast::TypeIdent::Tuple(_) => vec![],
-        ast::TypeIdent::UserDefined(_) => vec![],
+        ast::TypeIdent::BuiltIn(ast::AtomType::Bytes) => true,
+        ast::TypeIdent::Option(inner)
+        | ast::TypeIdent::List(inner)
+        | ast::TypeIdent::Map(_, inner) => contains_bytes(inner),
+        _ => false,
     }
 }
 
@@ -202,24 +344,95 @@ fn generate_atom(atom: &ast::AtomType) -> TokenStream {
 }
 
 /// Generate rust code for a spec definition.
-pub fn render_spec(spec: &ast::Spec) -> TokenStream {
+pub fn render_spec(spec: &ast::Spec) -> Result<TokenStream, LibError> {
     let mut out = TokenStream::new();
 
+    let query_types = query_types(spec);
     out.extend(spec.iter().flat_map(|spec_item| match spec_item {
-        ast::SpecItem::StructDef(sdef) => generate_struct_def(sdef),
+        ast::SpecItem::StructDef(sdef) => {
+            generate_struct_def(sdef, query_types.contains(sdef.name.as_str()))
+        }
         ast::SpecItem::EnumDef(edef) => generate_enum_def(edef),
         ast::SpecItem::ServiceDef(_) => quote! {}, // done below
     }));
 
     out.extend(service_server::generate_services(
         spec.iter().filter_map(|si| si.service_def()),
-    ));
+    )?);
+
+    Ok(out)
+}
 
-    out
+/// The names of the user defined types used in URL queries, directly or nested.
+fn query_types(spec: &ast::Spec) -> HashSet<&str> {
+    let mut names = HashSet::new();
+    let mut pending: Vec<&ast::TypeIdent> = spec
+        .iter()
+        .filter_map(|si| si.service_def())
+        .flat_map(|sdef| sdef.endpoints.iter())
+        .filter_map(|endpoint| endpoint.route.query().as_ref())
+        .collect();
+    while let Some(type_ident) = pending.pop() {
+        match type_ident {
+            ast::TypeIdent::UserDefined(name) => {
+                if !names.insert(name.as_str()) {
+                    continue;
+                }
+                for spec_item in spec.iter() {
+                    match spec_item {
+                        ast::SpecItem::StructDef(sdef) if &sdef.name == name => {
+                            pending.extend(sdef.fields.iter().map(|field| &field.pair.type_ident))
+                        }
+                        ast::SpecItem::EnumDef(edef) if &edef.name == name => {
+                            for variant in &edef.variants {
+                                match variant.variant_type {
+                                    ast::VariantType::Simple => {}
+                                    ast::VariantType::Tuple(ref tdef) => {
+                                        pending.extend(tdef.elements())
+                                    }
+                                    ast::VariantType::Struct(ref fields) => pending
+                                        .extend(fields.iter().map(|field| &field.pair.type_ident)),
+                                    ast::VariantType::Newtype(ref ty) => pending.push(ty),
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            ast::TypeIdent::List(inner) | ast::TypeIdent::Option(inner) => pending.push(inner),
+            ast::TypeIdent::Result(first, second) | ast::TypeIdent::Map(first, second) => {
+                pending.push(first);
+                pending.push(second);
+            }
+            ast::TypeIdent::Tuple(tdef) => pending.extend(tdef.elements()),
+            _ => {}
+        }
+    }
+    names
+}
+
+/// Generate the mocks of a spec: `Fake` implementations of its types and a `Mock$ServiceName`
+/// handler per service (see `humblegen_rt::mock`).
+pub fn render_mocks(spec: &ast::Spec) -> Result<TokenStream, LibError> {
+    let mut out = TokenStream::new();
+
+    out.extend(spec.iter().flat_map(|spec_item| match spec_item {
+        ast::SpecItem::StructDef(sdef) => generate_struct_fake(sdef),
+        ast::SpecItem::EnumDef(edef) => generate_enum_fake(edef),
+        ast::SpecItem::ServiceDef(_) => quote! {}, // done below
+    }));
+
+    out.extend(service_server::generate_mocks(
+        spec.iter().filter_map(|si| si.service_def()),
+    )?);
+
+    Ok(out)
 }
 
 pub struct Generator {
     _artifact: Artifact,
+    mocks: bool,
 }
 
 impl Generator {
@@ -227,6 +440,7 @@ impl Generator {
         match artifact {
             Artifact::TypesOnly | Artifact::ServerEndpoints => Ok(Self {
                 _artifact: artifact,
+                mocks: false,
             }),
             Artifact::ClientEndpoints => Err(LibError::UnsupportedArtifact {
                 artifact,
@@ -234,12 +448,22 @@ impl Generator {
             }),
         }
     }
+
+    /// Additionally generates mock handler implementations (see `render_mocks`).
+    pub fn with_mocks(mut self) -> Self {
+        self.mocks = true;
+        self
+    }
 }
 
 impl crate::CodeGenerator for Generator {
     fn generate(&self, spec: &Spec, output: &Path) -> Result<(), LibError> {
         // TODO: honor artifact field
-        let generated_code_unformatted = render_spec(spec).to_string();
+        let mut generated_code = render_spec(spec)?;
+        if self.mocks {
+            generated_code.extend(render_mocks(spec)?);
+        }
+        let generated_code_unformatted = generated_code.to_string();
         let generated_code = rustfmt::rustfmt_2018_generated_string(&generated_code_unformatted)
             .map(std::borrow::Cow::into_owned)
             .unwrap_or(generated_code_unformatted);
@@ -27,12 +27,12 @@
 //! - Then, we generate code using those intermediate representations.
 //!
 
-use crate::ast;
+use crate::{ast, LibError};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 
 use super::fmt_opt_string;
-use super::generate_type_ident;
+use super::{generate_return_type, generate_type_ident};
 
 /// Lowered representation of an `ast::ServiceDef`.
 struct Service {
@@ -50,8 +50,49 @@ struct ServiceRoute {
     components: Vec<ServiceRouteComponent>,
     query_type: Option<TokenStream>,
     query_deser_fn: TokenStream,
+    /// `TestRequest` method that appends the query.
+    query_test_fn: TokenStream,
     post_body_type: Option<TokenStream>,
+    /// How `post_body` is read from the request.
+    post_body: Option<PostBody>,
+    /// Whether the handler receives the request's `If-Match` precondition (`PUT` and `PATCH`).
+    if_match: bool,
+    /// Types of the messages sent by client and server, for WebSocket endpoints.
+    websocket_messages: Option<(TokenStream, TokenStream)>,
     ret_type: TokenStream,
+    /// Expression that negotiates the response format from `req`.
+    response_format: TokenStream,
+    /// Helper that converts the handler's response into a hyper response.
+    response_fn: TokenStream,
+    /// Return type of the test client method, decoded by `TestResponse` method `test_decode_fn`.
+    test_ret_type: TokenStream,
+    test_decode_fn: TokenStream,
+}
+
+/// Lowered representation of the request body of an `ast::ServiceRoute`.
+enum PostBody {
+    /// deserialized in the request's wire format
+    Data,
+    /// `multipart { .. }`, read into a struct generated per endpoint
+    Multipart(MultipartBody),
+    /// `blob["media/type"]`, passed through verbatim
+    Blob { media_type: String },
+}
+
+/// Lowered representation of an `ast::BodyType::Multipart` request body.
+struct MultipartBody {
+    struct_ident: proc_macro2::Ident,
+    parts: Vec<MultipartPart>,
+}
+
+/// Lowered representation of an `ast::MultipartPart`.
+struct MultipartPart {
+    spec_name: String,
+    rust_field_ident: proc_macro2::Ident,
+    /// `None` for file parts
+    rust_field_type: Option<TokenStream>,
+    /// whether the part is streamed to the handler, only the case for a last file part
+    is_streaming: bool,
 }
 
 /// Lowered representation of an `ast::ServiceRouteComponent`.
@@ -64,17 +105,19 @@ enum ServiceRouteComponent {
         rust_var_ident: proc_macro2::Ident,
         rust_var_type: TokenStream,
         url_regex_str: String,
+        /// `{name: str..}`, matching the rest of the path
+        is_rest: bool,
     },
 }
 
 /// Entrypoint for generate *all* services of a humblespec.
 pub fn generate_services<'a, I: Iterator<Item = &'a ast::ServiceDef>>(
     all_services: I,
-) -> TokenStream {
-    let all_services = lower_all_services(all_services);
+) -> Result<TokenStream, LibError> {
+    let all_services = lower_all_services(all_services)?;
 
     if all_services.is_empty() {
-        return quote! {};
+        return Ok(quote! {});
     }
 
     let mut out = TokenStream::new();
@@ -83,7 +126,7 @@ pub fn generate_services<'a, I: Iterator<Item = &'a ast::ServiceDef>>(
     out.extend(quote! {
         #[allow(unused_imports)]
         use ::humblegen_rt::deser_helpers::{
-            deser_post_data, deser_query_primitive, deser_query_serde_urlencoded, deser_param,
+            deser_post_data, deser_query_list, deser_query_primitive, deser_query_serde_qs, deser_param,
         };
         #[allow(unused_imports)]
         use ::humblegen_rt::service_protocol::ErrorResponse;
@@ -92,7 +135,7 @@ pub fn generate_services<'a, I: Iterator<Item = &'a ast::ServiceDef>>(
         #[allow(unused_imports)]
         use ::humblegen_rt::regexset_map::RegexSetMap;
         #[allow(unused_imports)]
-        use ::humblegen_rt::server::{self, handler_response_to_hyper_response, Route, Service};
+        use ::humblegen_rt::server::{self, Route, Service};
         #[allow(unused_imports)]
         use ::std::sync::Arc;
         use std::net::SocketAddr;
@@ -104,11 +147,114 @@ pub fn generate_services<'a, I: Iterator<Item = &'a ast::ServiceDef>>(
         #[derive(Debug)]
         pub struct Builder {
             services: Vec<Service>,
+            config: server::Config,
         }
 
         impl Builder {
             pub fn new() -> Self {
-                Self { services: vec![] }
+                Self { services: vec![], config: server::Config::default() }
+            }
+
+            /// Sets the maximum size of a request body in bytes.
+            /// Requests with larger bodies are rejected with HTTP status 413.
+            /// Can be overridden per service using `add_with_config`.
+            pub fn max_body_size(mut self, max_body_size: u64) -> Self {
+                self.config.max_body_size = max_body_size;
+                self
+            }
+
+            /// Sets the maximum time spent reading a request body.
+            /// Requests whose body takes longer are rejected with HTTP status 408.
+            /// Can be overridden per service using `add_with_config`.
+            pub fn body_read_timeout(mut self, timeout: ::std::time::Duration) -> Self {
+                self.config.body_read_timeout = Some(timeout);
+                self
+            }
+
+            /// Sets the maximum time spent handling a request.
+            /// When it elapses, the handler is cancelled and the request is answered with HTTP status 504.
+            /// Can be overridden per service using `add_with_config`.
+            pub fn request_timeout(mut self, timeout: ::std::time::Duration) -> Self {
+                self.config.request_timeout = Some(timeout);
+                self
+            }
+
+            /// Enables Cross-Origin Resource Sharing.
+            /// CORS preflight requests are answered for every mounted route,
+            /// and CORS headers are added to every response to an allowed origin.
+            pub fn cors(mut self, cors: ::humblegen_rt::cors::CorsConfig) -> Self {
+                self.config.cors = Some(cors);
+                self
+            }
+
+            /// Enables response compression for response bodies of at least `threshold` bytes.
+            /// The content coding (`br`, `gzip` or `deflate`) is negotiated using the request's
+            /// `Accept-Encoding` header.
+            pub fn compression(mut self, threshold: usize) -> Self {
+                self.config.compression_threshold = Some(threshold);
+                self
+            }
+
+            /// Enables weak `ETag`s on successful JSON responses to `GET` requests.
+            /// Requests whose `If-None-Match` header matches the `ETag` are answered with HTTP status 304.
+            pub fn etags(mut self) -> Self {
+                self.config.etags = true;
+                self
+            }
+
+            /// Sets the generator of request IDs for requests without a valid `Request-ID` header
+            /// (default: 30 random alphanumeric characters).
+            pub fn request_id_generator(
+                mut self,
+                generator: impl Fn() -> String + Send + Sync + 'static,
+            ) -> Self {
+                self.config.request_id_generator = ::std::sync::Arc::new(generator);
+                self
+            }
+
+            /// Enables Prometheus metrics (requires the `metrics` feature of `humblegen-rt`).
+            /// Requests are recorded per route and outcome; set `MetricsConfig::endpoint`
+            /// to expose the metrics.
+            pub fn metrics(mut self, metrics: ::humblegen_rt::metrics::MetricsConfig) -> Self {
+                self.config.metrics = Some(metrics);
+                self
+            }
+
+            /// Mounts a liveness route at URL path `path` (e.g. `/health`)
+            /// that answers `GET` requests with HTTP status 200.
+            pub fn liveness_endpoint(mut self, path: &str) -> Self {
+                self.config.builtin_routes.liveness = Some(path.to_owned());
+                self
+            }
+
+            /// Mounts a readiness route at URL path `path` (e.g. `/ready`).
+            /// It answers `GET` requests with HTTP status 200 if `check` returns `Ok`,
+            /// and with HTTP status 503 otherwise.
+            pub fn readiness_endpoint<F, Fut>(mut self, path: &str, check: F) -> Self
+            where
+                F: Fn() -> Fut + Send + Sync + 'static,
+                Fut: ::std::future::Future<Output = Result<(), String>> + Send + 'static,
+            {
+                self.config.builtin_routes.readiness =
+                    Some((path.to_owned(), ::humblegen_rt::builtin_routes::readiness_check(check)));
+                self
+            }
+
+            /// Mounts an introspection route at URL path `path` (e.g. `/introspection`)
+            /// that answers `GET` requests with the mounted services and their routes.
+            pub fn introspection_endpoint(mut self, path: &str) -> Self {
+                self.config.builtin_routes.introspection = Some(path.to_owned());
+                self
+            }
+
+            /// Trusts the `X-Forwarded-For` header of requests from the given proxies
+            /// to name the client (see `humblegen_rt::connection::ClientIp`).
+            pub fn trusted_proxies(
+                mut self,
+                proxies: impl IntoIterator<Item = ::std::net::IpAddr>,
+            ) -> Self {
+                self.config.trusted_proxies = proxies.into_iter().collect();
+                self
             }
 
             /// Mounts `handler` at URL path prefix `root`.
@@ -122,29 +268,55 @@ pub fn generate_services<'a, I: Iterator<Item = &'a ast::ServiceDef>>(
             /// and `root="/api"` will expose
             /// * handler method `fn bar() -> i32` at `/api/bar` and
             /// * handler method `fn baz() -> String` at `/api/baz`
-            pub fn add<Context: Default + Sized + Send + Sync>(mut self, root: &str, handler: Handler<Context>) -> Self {
+            pub fn add<Context: Default + Sized + Send + Sync>(self, root: &str, handler: Handler<Context>) -> Self {
+                self.add_with_config(root, handler, server::ServiceConfig::default())
+            }
+
+            /// Like `add`, but overrides the server-wide configuration for this service
+            /// with the values set in `config`.
+            pub fn add_with_config<Context: Default + Sized + Send + Sync>(mut self, root: &str, handler: Handler<Context>, config: server::ServiceConfig) -> Self {
                 if !root.starts_with('/') {
                     panic!("root must start with \"/\"")
                 } else  if root.ends_with('/') {
                     panic!("root must not end with \"/\"")
                 }
 
+                let name = handler.service_name().to_owned();
                 let routes: Vec<Route> = handler.into_routes();
                 let routes = RegexSetMap::new(routes).unwrap();
-                self.services.push(Service((
-                    humblegen_rt::regex::Regex::new(&format!(r"^(?P<root>{})(?P<suffix>/.*)", root))
+                self.services.push(Service {
+                    name,
+                    root: root.to_owned(),
+                    regex: humblegen_rt::regex::Regex::new(&format!(r"^(?P<root>{})(?P<suffix>/.*)", root))
                         .unwrap(),
                     routes,
-                )));
+                    config,
+                });
                 self
             }
 
             /// Starts an HTTP server bound to address `addr` and serves incoming requests using
             /// the previously `add`ed handlers.
             pub async fn listen_and_run_forever(self, addr: &SocketAddr) -> humblegen_rt::anyhow::Result<()> {
+                server::listen_and_run_forever(self.into_server()?, addr).await
+            }
+
+            /// Builds the server without starting it, e.g. to serve connections accepted by a custom
+            /// accept loop (that terminates TLS) using `humblegen_rt::server::handle_request`.
+            pub fn into_server(self) -> humblegen_rt::anyhow::Result<server::Server> {
                 use humblegen_rt::anyhow::Context;
+                for service in &self.services {
+                    service.check_config()?;
+                }
                 let services = RegexSetMap::new(self.services).context("invalid service configuration")?;
-                server::listen_and_run_forever(services, addr).await
+                Ok(server::Server { services, config: self.config })
+            }
+
+            /// Builds an in-process client of the server for testing, which passes requests
+            /// to the handlers without binding a socket.
+            /// Use `$ServiceNameTestClient` for typed requests to a mounted service.
+            pub fn into_test_client(self) -> humblegen_rt::anyhow::Result<::humblegen_rt::test_client::TestClient> {
+                Ok(::humblegen_rt::test_client::TestClient::new(self.into_server()?))
             }
         }
 
@@ -174,6 +346,17 @@ pub fn generate_services<'a, I: Iterator<Item = &'a ast::ServiceDef>>(
         })
         .collect();
 
+    let handler_service_name_arms: Vec<_> = all_services
+        .iter()
+        .map(|s| {
+            let Service { trait_name, .. } = s;
+            let trait_name_str = format!("{}", trait_name);
+            quote! {
+                Handler::#trait_name(_) => #trait_name_str
+            }
+        })
+        .collect();
+
     let handler_debug_arms: Vec<_> = all_services
         .iter()
         .map(|s| {
@@ -199,6 +382,12 @@ pub fn generate_services<'a, I: Iterator<Item = &'a ast::ServiceDef>>(
                     #(#handler_into_routes_match_arms,)*
                 }
             }
+
+            fn service_name(&self) -> &'static str {
+                match self {
+                    #(#handler_service_name_arms,)*
+                }
+            }
         }
 
         impl<Context: Default + Sized + Send + Sync + 'static> std::fmt::Debug for Handler<Context> {
@@ -215,7 +404,7 @@ pub fn generate_services<'a, I: Iterator<Item = &'a ast::ServiceDef>>(
     // generate code for the service definitions
     out.extend(all_services.iter().map(generate_service).flatten());
 
-    out
+    Ok(out)
 }
 
 /// generates rust code for a single a single service, which includes:
@@ -231,27 +420,11 @@ fn generate_service(service: &Service) -> TokenStream {
         .map(|r| {
             let ServiceRoute {
                 traitfn_ident,
-                post_body_type,
-                query_type,
-                components,
                 ret_type,
                 doc_comment,
                 ..
             } = r;
-            let mut param_list = vec![];
-            param_list.push(quote! {&self});
-            param_list.push(quote! {ctx: Self::Context});
-            param_list.extend(post_body_type.iter().map(|t| quote! { post_body: #t }));
-            param_list.extend(query_type.iter().map(|t| quote! { query: Option<#t> }));
-            param_list.extend(components.iter().filter_map(|c| match c {
-                ServiceRouteComponent::Literal { .. } => None,
-                ServiceRouteComponent::Param {
-                    rust_var_ident,
-                    rust_var_type,
-                    ..
-                } => Some(quote! { #rust_var_ident : #rust_var_type }),
-            }));
-            let param_list = quote! { #(#param_list),* };
+            let param_list = generate_traitfn_params(r);
 
             let decl_without_comment = quote! {
                 async fn #traitfn_ident (#param_list) -> Response<#ret_type>
@@ -276,6 +449,9 @@ fn generate_service(service: &Service) -> TokenStream {
         ) -> Result<Self::Context, ServiceError> {
             Ok(Self::Context::default())
         }
+        fn rate_limit_key(&self, _ctx: &Self::Context) -> Option<String> {
+            None
+        }
     };
     let trait_def_as_doc_comment = {
         let d = quote! {
@@ -301,8 +477,11 @@ fn generate_service(service: &Service) -> TokenStream {
         let ServiceRoute {
             traitfn_ident,
             hyper_method,
+            response_format,
+            response_fn,
             ..
         } = r;
+        let traitfn_name = traitfn_ident.to_string();
 
         let regex_str = r
             .components
@@ -318,14 +497,62 @@ fn generate_service(service: &Service) -> TokenStream {
             .collect::<Vec<_>>()
             .join("");
         let regex_str = format!("^{}$", regex_str);
+        let template = r
+            .components
+            .iter()
+            .map(|c| match c {
+                ServiceRouteComponent::Literal { spec } => format!("/{}", spec),
+                ServiceRouteComponent::Param {
+                    spec_arg_name,
+                    is_rest,
+                    ..
+                } => format!("/{{{}{}}}", spec_arg_name, if *is_rest { ".." } else { "" }),
+            })
+            .collect::<String>();
+        // the route at the service root has no components
+        let template = if template.is_empty() {
+            "/".to_owned()
+        } else {
+            template
+        };
 
         // post body
         let post_body_var = r.post_body_type.iter().map(|_| {
                 quote! { post_body }
         }).collect::<Vec<_>>();
-        let post_body_def = r.post_body_type.as_ref().map(|pbt| quote!{
-            let post_body: #pbt =
-            deser_post_data(req.body_mut()).await?;
+        let post_body_def = r.post_body.as_ref().map(|post_body| match post_body {
+            PostBody::Data => {
+                let pbt = &r.post_body_type;
+                quote!{
+                    let post_body: #pbt =
+                    deser_post_data(&mut req).await?;
+                }
+            }
+            PostBody::Multipart(MultipartBody { struct_ident, parts }) => {
+                let part_inits = parts.iter().map(|p| {
+                    let MultipartPart { spec_name, rust_field_ident, rust_field_type, is_streaming } = p;
+                    match rust_field_type {
+                        Some(ty) => quote! { #rust_field_ident: multipart.field::<#ty>(#spec_name)? },
+                        None if *is_streaming => quote! { #rust_field_ident: multipart.streaming_file(#spec_name)? },
+                        None => quote! { #rust_field_ident: multipart.buffered_file(#spec_name)? },
+                    }
+                });
+                let streamed = match parts.iter().find(|p| p.is_streaming) {
+                    Some(part) => {
+                        let spec_name = &part.spec_name;
+                        quote! { Some(#spec_name) }
+                    }
+                    None => quote! { None },
+                };
+                quote! {
+                    let mut multipart =
+                        ::humblegen_rt::multipart::Multipart::from_request(&mut req, #streamed).await?;
+                    let post_body = #struct_ident { #(#part_inits),* };
+                }
+            }
+            PostBody::Blob { media_type } => quote! {
+                let post_body = ::humblegen_rt::blob::deser_blob(&mut req, #media_type)?;
+            },
         });
 
         // query
@@ -354,21 +581,52 @@ fn generate_service(service: &Service) -> TokenStream {
             )),
         }).unzip();
 
+        // If-Match precondition
+        let if_match_var = r.if_match.then(|| quote! { if_match });
+        let if_match_def = r.if_match.then(|| quote! {
+            let if_match = ::humblegen_rt::conditional::IfMatch::from_request(&req);
+        });
+
         let mut arg_list = Vec::new();
         arg_list.extend(&post_body_var);
         arg_list.extend(&query_var);
         arg_list.extend(&route_param_vars);
+        arg_list.extend(&if_match_var);
+
+
+        // invoke handler, with the connection once upgraded for WebSocket endpoints
+        let invoke_handler = if r.websocket_messages.is_some() {
+            quote! {
+                let response = ::humblegen_rt::websocket::upgrade_response(&req)?;
+                ::humblegen_rt::websocket::spawn_handler(req, move |incoming, outgoing| async move {
+                    let span = tracing::error_span!("handler");
+                    handler.#traitfn_ident( ctx, #(#arg_list,)* incoming, outgoing ).instrument(span).await
+                });
+                Ok(response)
+            }
+        } else {
+            quote! {
+                let response_format = #response_format;
+                drop(req); // free some memory
 
+                // Invoke handler if interceptor doesn't return a ServiceError
+                {
+                    let span = tracing::error_span!("handler");
+                    Ok(#response_fn(response_format, handler.#traitfn_ident( ctx, #(#arg_list),* ).instrument(span).await))
+                }
+            }
+        };
 
         let route_param_parse_stmts = route_param_parse_stmts.into_iter();
         let route_param_vars2 = route_param_vars.iter();
         let route_param_vars = route_param_vars.iter();
-        let arg_list = arg_list.into_iter();
         quote! {
             {
                 let handler = Arc::clone(&handler);
                 Route{
+                    name: #traitfn_name.to_owned(),
                     method: #hyper_method,
+                    template: #template.to_owned(),
                     regex: ::humblegen_rt::regex::Regex::new(#regex_str).unwrap(),
                     dispatcher: Box::new(
                         move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
@@ -391,20 +649,16 @@ fn generate_service(service: &Service) -> TokenStream {
                                         })
                                         .map_err(|e| e.to_error_response())?
                                 };
+                                ::humblegen_rt::rate_limit::admit_context(&req, || handler.rate_limit_key(&ctx))?;
 
                                 // deserialize only after we have invoked the interceptor
                                 // => interceptor can implement some DoS protection
                                 #(let #route_param_vars = #route_param_vars2?;)*
                                 #query_def
+                                #if_match_def
                                 #post_body_def
 
-                                drop(req); // free some memory
-
-                                // Invoke handler if interceptor doesn't return a ServiceError
-                                {
-                                    let span = tracing::error_span!("handler");
-                                    Ok(handler_response_to_hyper_response(handler.#traitfn_ident( ctx, #(#arg_list),* ).instrument(span).await))
-                                }
+                                #invoke_handler
                             })
                         }
                     ),
@@ -413,8 +667,40 @@ fn generate_service(service: &Service) -> TokenStream {
         }
     });
 
+    let multipart_body_defs = service_routes.iter().filter_map(|r| {
+        let (struct_ident, parts) = match r.post_body.as_ref()? {
+            PostBody::Multipart(MultipartBody {
+                struct_ident,
+                parts,
+            }) => (struct_ident, parts),
+            _ => return None,
+        };
+        let doc_comment = format!(
+            "Multipart body of `{}::{}`.",
+            service.trait_name, r.traitfn_ident
+        );
+        let fields = parts.iter().map(|p| {
+            let ident = &p.rust_field_ident;
+            match &p.rust_field_type {
+                Some(ty) => quote! { pub #ident: #ty },
+                None => quote! { pub #ident: ::humblegen_rt::multipart::FilePart },
+            }
+        });
+        Some(quote! {
+            #[derive(Debug)]
+            #[doc = #doc_comment]
+            pub struct #struct_ident {
+                #(#fields),*
+            }
+        })
+    });
+
+    let test_client_def = generate_test_client(service);
+
     let routes_factory_name = &service.routes_factory_name;
     quote! {
+        #(#multipart_body_defs)*
+
         #trait_def
 
         #[allow(unused_variables)]
@@ -426,29 +712,311 @@ fn generate_service(service: &Service) -> TokenStream {
             vec![#(#routes),*]
         }
 
+        #test_client_def
+    }
+}
+
+/// generates the parameter list of the handler trait fn of a route
+fn generate_traitfn_params(r: &ServiceRoute) -> TokenStream {
+    let ServiceRoute {
+        post_body_type,
+        query_type,
+        components,
+        if_match,
+        websocket_messages,
+        ..
+    } = r;
+    let mut param_list = vec![];
+    param_list.push(quote! {&self});
+    param_list.push(quote! {ctx: Self::Context});
+    param_list.extend(post_body_type.iter().map(|t| quote! { post_body: #t }));
+    param_list.extend(query_type.iter().map(|t| quote! { query: Option<#t> }));
+    param_list.extend(components.iter().filter_map(|c| match c {
+        ServiceRouteComponent::Literal { .. } => None,
+        ServiceRouteComponent::Param {
+            rust_var_ident,
+            rust_var_type,
+            ..
+        } => Some(quote! { #rust_var_ident : #rust_var_type }),
+    }));
+    if *if_match {
+        param_list.push(quote! { if_match: Option<::humblegen_rt::conditional::IfMatch> });
+    }
+    if let Some((client_msg, server_msg)) = websocket_messages {
+        param_list.push(quote! { incoming: ::humblegen_rt::websocket::MessageStream<#client_msg> });
+        param_list.push(quote! { outgoing: ::humblegen_rt::websocket::MessageSink<#server_msg> });
+    }
+    quote! { #(#param_list),* }
+}
+
+/// generates the typed test client of a service, with one method per endpoint
+/// (except for WebSocket endpoints and endpoints with a multipart body)
+fn generate_test_client(service: &Service) -> TokenStream {
+    let trait_name = &service.trait_name;
+    let struct_ident = format_ident!("{}TestClient", trait_name);
+    let struct_comment = format!(
+        "Typed in-process client of a mounted `{}` service, for testing (see `Builder::into_test_client`).",
+        trait_name
+    );
+
+    let methods = service
+        .service_routes
+        .iter()
+        .filter(|r| {
+            r.websocket_messages.is_none()
+                && !matches!(r.post_body, Some(PostBody::Multipart(_)))
+        })
+        .map(|r| {
+            let ServiceRoute {
+                traitfn_ident,
+                hyper_method,
+                components,
+                query_type,
+                query_test_fn,
+                post_body_type,
+                post_body,
+                test_ret_type,
+                test_decode_fn,
+                ..
+            } = r;
+            let doc_comment = format!("Invokes `{}::{}`.", trait_name, traitfn_ident);
+
+            let mut param_list = vec![quote! {&self}];
+            param_list.extend(post_body_type.iter().map(|t| quote! { post_body: #t }));
+            param_list.extend(query_type.iter().map(|t| quote! { query: Option<#t> }));
+
+            let mut path_format = "{}".to_owned();
+            let mut path_args = vec![quote! { self.root }];
+            for c in components {
+                match c {
+                    ServiceRouteComponent::Literal { spec } => {
+                        path_format.push('/');
+                        path_format.push_str(&spec.replace('{', "{{").replace('}', "}}"));
+                    }
+                    ServiceRouteComponent::Param {
+                        rust_var_ident,
+                        rust_var_type,
+                        is_rest,
+                        ..
+                    } => {
+                        param_list.push(quote! { #rust_var_ident: #rust_var_type });
+                        path_format.push_str("/{}");
+                        let encode_fn = if *is_rest {
+                            quote! { rest_param }
+                        } else {
+                            quote! { route_param }
+                        };
+                        path_args.push(quote! { ::humblegen_rt::test_client::#encode_fn(&#rust_var_ident) });
+                    }
+                }
+            }
+
+            let set_query = query_type.as_ref().map(|_| {
+                quote! {
+                    let request = match &query {
+                        Some(query) => request.#query_test_fn(query),
+                        None => request,
+                    };
+                }
+            });
+            let set_body = post_body.as_ref().map(|post_body| match post_body {
+                PostBody::Data => quote! { let request = request.json(&post_body); },
+                PostBody::Blob { media_type } => {
+                    quote! { let request = request.blob(post_body, #media_type); }
+                }
+                PostBody::Multipart(_) => unreachable!("multipart endpoints are filtered out"),
+            });
+
+            quote! {
+                #[doc = #doc_comment]
+                pub async fn #traitfn_ident(#(#param_list),*) -> Result<#test_ret_type, ::humblegen_rt::test_client::TestError> {
+                    let path = format!(#path_format, #(#path_args),*);
+                    let request = self.client.request(#hyper_method, &path);
+                    #set_query
+                    #set_body
+                    request.send().await.#test_decode_fn()
+                }
+            }
+        });
+
+    quote! {
+        #[doc = #struct_comment]
+        #[derive(Debug, Clone)]
+        pub struct #struct_ident<'a> {
+            client: &'a ::humblegen_rt::test_client::TestClient,
+            root: String,
+        }
+
+        impl<'a> #struct_ident<'a> {
+            /// A client of the service mounted at URL path prefix `root`.
+            pub fn new(client: &'a ::humblegen_rt::test_client::TestClient, root: &str) -> Self {
+                Self { client, root: root.to_owned() }
+            }
+
+            #(#methods)*
+        }
+    }
+}
+
+/// Entrypoint for generating the `Mock$ServiceName` handlers of *all* services of a humblespec.
+pub fn generate_mocks<'a, I: Iterator<Item = &'a ast::ServiceDef>>(
+    all_services: I,
+) -> Result<TokenStream, LibError> {
+    Ok(lower_all_services(all_services)?
+        .iter()
+        .map(generate_mock)
+        .collect())
+}
+
+/// generates the mock handler of a service, which answers every request with a canned response
+/// (configured per endpoint) or with fake data
+fn generate_mock(service: &Service) -> TokenStream {
+    let trait_name = &service.trait_name;
+    let struct_ident = format_ident!("Mock{}", trait_name);
+    let struct_name = struct_ident.to_string();
+    let struct_comment = format!(
+        "Mock implementation of `{}` that answers requests with fake data (see `humblegen_rt::mock`), \
+         unless a canned response is set for the endpoint. WebSocket connections are closed right away.",
+        trait_name
+    );
+
+    // WebSocket endpoints have no canned responses
+    let canned_routes: Vec<_> = service
+        .service_routes
+        .iter()
+        .filter(|r| r.websocket_messages.is_none())
+        .collect();
+
+    let fields = canned_routes.iter().map(|r| {
+        let ServiceRoute {
+            traitfn_ident,
+            ret_type,
+            ..
+        } = r;
+        quote! {
+            #traitfn_ident: Option<Box<dyn Fn() -> Response<#ret_type> + Send + Sync>>
+        }
+    });
+    let field_inits = canned_routes.iter().map(|r| {
+        let traitfn_ident = &r.traitfn_ident;
+        quote! { #traitfn_ident: None }
+    });
+    let setters = canned_routes.iter().map(|r| {
+        let ServiceRoute {
+            traitfn_ident,
+            ret_type,
+            ..
+        } = r;
+        let setter_ident = format_ident!("on_{}", traitfn_ident);
+        let doc_comment = format!(
+            "Answers `{}::{}` with the result of `response` instead of fake data.",
+            trait_name, traitfn_ident
+        );
+        quote! {
+            #[doc = #doc_comment]
+            pub fn #setter_ident(mut self, response: impl Fn() -> Response<#ret_type> + Send + Sync + 'static) -> Self {
+                self.#traitfn_ident = Some(Box::new(response));
+                self
+            }
+        }
+    });
+
+    let trait_fns = service.service_routes.iter().map(|r| {
+        let ServiceRoute {
+            traitfn_ident,
+            websocket_messages,
+            ret_type,
+            ..
+        } = r;
+        let param_list = generate_traitfn_params(r);
+        let body = if websocket_messages.is_some() {
+            quote! { Ok(()) }
+        } else {
+            quote! {
+                match &self.#traitfn_ident {
+                    Some(response) => response(),
+                    None => Ok(self.fake()),
+                }
+            }
+        };
+        quote! {
+            async fn #traitfn_ident (#param_list) -> Response<#ret_type> {
+                #body
+            }
+        }
+    });
+
+    quote! {
+        #[doc = #struct_comment]
+        pub struct #struct_ident {
+            rng: ::std::sync::Mutex<::humblegen_rt::mock::FakeRng>,
+            #(#fields,)*
+        }
+
+        impl #struct_ident {
+            /// A mock whose fake data is generated from `seed`.
+            pub fn with_seed(seed: u64) -> Self {
+                Self {
+                    rng: ::std::sync::Mutex::new(::humblegen_rt::mock::FakeRng::seeded(seed)),
+                    #(#field_inits,)*
+                }
+            }
+
+            #(#setters)*
+
+            #[allow(dead_code)]
+            fn fake<T: ::humblegen_rt::mock::Fake>(&self) -> T {
+                T::fake(&mut self.rng.lock().unwrap())
+            }
+        }
+
+        impl Default for #struct_ident {
+            fn default() -> Self {
+                Self::with_seed(0)
+            }
+        }
+
+        impl std::fmt::Debug for #struct_ident {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct(#struct_name).finish()
+            }
+        }
+
+        #[allow(unused_variables)]
+        #[humblegen_rt::async_trait(Sync)]
+        impl #trait_name for #struct_ident {
+            type Context = ();
+
+            #(#trait_fns)*
+        }
     }
 }
 
 /// lower the `ast::ServiceDefs` into `struct Service`
 fn lower_all_services<'a, I: Iterator<Item = &'a ast::ServiceDef>>(
     all_services: I,
-) -> Vec<Service> {
+) -> Result<Vec<Service>, LibError> {
     all_services
-        .map(|sdef| Service {
-            trait_name: format_ident!("{}", sdef.name),
-            trait_comment: fmt_opt_string(&sdef.doc_comment).to_string(),
-            routes_factory_name: format_ident!("routes_{}", sdef.name),
-            service_routes: sdef
-                .endpoints
-                .iter()
-                .map(|e| lower_service_route(&e))
-                .collect(),
+        .map(|sdef| {
+            Ok(Service {
+                trait_name: format_ident!("{}", sdef.name),
+                trait_comment: fmt_opt_string(&sdef.doc_comment).to_string(),
+                routes_factory_name: format_ident!("routes_{}", sdef.name),
+                service_routes: sdef
+                    .endpoints
+                    .iter()
+                    .map(|e| lower_service_route(&sdef.name, e))
+                    .collect::<Result<_, _>>()?,
+            })
         })
         .collect()
 }
 
 /// Helper function for lowering an `ast::ServiceEndpoint` into a `ServiceRoute`.
-fn lower_service_route(endpoint: &ast::ServiceEndpoint) -> ServiceRoute {
+fn lower_service_route(
+    service_name: &str,
+    endpoint: &ast::ServiceEndpoint,
+) -> Result<ServiceRoute, LibError> {
     let components = endpoint
         .route
         .components()
@@ -457,84 +1025,213 @@ fn lower_service_route(endpoint: &ast::ServiceEndpoint) -> ServiceRoute {
             ast::ServiceRouteComponent::Literal(spec) => {
                 ServiceRouteComponent::Literal { spec: spec.clone() }
             }
-            ast::ServiceRouteComponent::Variable(ast::FieldDefPair { name, type_ident }) => {
+            ast::ServiceRouteComponent::Variable(ast::FieldDefPair { name, type_ident })
+            | ast::ServiceRouteComponent::Rest(ast::FieldDefPair { name, type_ident }) => {
+                let is_rest = matches!(c, ast::ServiceRouteComponent::Rest(_));
                 let rust_var_ident = format_ident!("{}", name);
                 let rust_var_type = generate_type_ident(type_ident);
-                let url_regex_str = r"[^/]+".to_owned();
+                // matching is done on the percent-encoded path, so encoded slashes
+                // (`%2F`) of a regular param do not end its path segment
+                let url_regex_str = if is_rest { r".*" } else { r"[^/]+" }.to_owned();
                 ServiceRouteComponent::Param {
                     spec_arg_name: name.clone(),
                     url_regex_str,
                     rust_var_ident,
                     rust_var_type,
+                    is_rest,
                 }
             }
         })
         .collect();
 
-    let post_body_type = match &endpoint.route {
-        ast::ServiceRoute::Get { .. } => None,
-        ast::ServiceRoute::Delete { .. } => None,
-        ast::ServiceRoute::Post { body, .. } => Some(generate_type_ident(body)),
-        ast::ServiceRoute::Put { body, .. } => Some(generate_type_ident(body)),
-        ast::ServiceRoute::Patch { body, .. } => Some(generate_type_ident(body)),
-    };
+    let websocket_messages = endpoint
+        .route
+        .websocket_messages()
+        .map(|(client_msg, server_msg)| {
+            (
+                generate_type_ident(client_msg),
+                generate_type_ident(server_msg),
+            )
+        });
 
-    let ret_type = generate_type_ident(endpoint.route.return_type());
+    // the handler of a WebSocket endpoint returns once the connection is done
+    let ret_type = match endpoint.route.return_type() {
+        Some(ret) => generate_return_type(ret),
+        None => quote! { () },
+    };
+    let (response_format, response_fn) = match endpoint.route.return_type() {
+        Some(ast::ReturnType::Data(ast::TypeIdent::Result(..))) => (
+            quote! { ::humblegen_rt::wire_format::response_format(&req) },
+            quote! { server::result_handler_response_to_hyper_response_with_format },
+        ),
+        Some(ast::ReturnType::Stream(_)) => (
+            quote! { ::humblegen_rt::stream::response_format(&req) },
+            quote! { ::humblegen_rt::stream::handler_response_to_hyper_response },
+        ),
+        // blobs are not negotiated, their media type is the default content type
+        Some(ast::ReturnType::Blob(media_type)) => (
+            quote! { #media_type },
+            quote! { ::humblegen_rt::blob::handler_response_to_hyper_response },
+        ),
+        _ => (
+            quote! { ::humblegen_rt::wire_format::response_format(&req) },
+            quote! { server::handler_response_to_hyper_response_with_format },
+        ),
+    };
+    // the test client reads streams completely
+    let (test_ret_type, test_decode_fn) = match endpoint.route.return_type() {
+        Some(ast::ReturnType::Stream(item)) => {
+            let item = generate_type_ident(item);
+            (quote! { Vec<#item> }, quote! { decode_ndjson })
+        }
+        Some(ast::ReturnType::Blob(_)) => (ret_type.clone(), quote! { decode_blob }),
+        _ => (ret_type.clone(), quote! { decode }),
+    };
 
-    let (query_type, query_deser_fn) = endpoint
-        .route
-        .query()
-        .as_ref()
-        .map(|qt| {
-            let deser_fn = match qt {
-                ast::TypeIdent::UserDefined(_) => quote! { deser_query_serde_urlencoded },
-                _ => quote! { deser_query_primitive },
+    let (query_type, query_deser_fn, query_test_fn) = match endpoint.route.query() {
+        Some(qt) => {
+            let unsupported = |reason| LibError::UnsupportedQuery {
+                backend: super::BACKEND_NAME,
+                endpoint: format!("{}.{}", service_name, route_name(&endpoint.route)),
+                reason,
             };
-            (Some(generate_type_ident(qt)), deser_fn)
-        })
-        .unwrap_or((None, quote! {}));
+            let (deser_fn, test_fn) = match qt {
+                ast::TypeIdent::UserDefined(_) => {
+                    (quote! { deser_query_serde_qs }, quote! { query })
+                }
+                ast::TypeIdent::BuiltIn(ast::AtomType::Empty) => {
+                    return Err(unsupported("`()` cannot be decoded from a query"))
+                }
+                ast::TypeIdent::BuiltIn(_) => {
+                    (quote! { deser_query_primitive }, quote! { query_primitive })
+                }
+                ast::TypeIdent::List(inner) => match **inner {
+                    ast::TypeIdent::BuiltIn(ast::AtomType::Empty) => {
+                        return Err(unsupported("`()` cannot be decoded from a query"))
+                    }
+                    ast::TypeIdent::BuiltIn(_) => {
+                        (quote! { deser_query_list }, quote! { query_list })
+                    }
+                    _ => return Err(unsupported(QUERY_TYPES)),
+                },
+                _ => return Err(unsupported(QUERY_TYPES)),
+            };
+            (Some(generate_type_ident(qt)), deser_fn, test_fn)
+        }
+        None => (None, quote! {}, quote! {}),
+    };
 
-    let traitfn_name_stem = &endpoint
-        .route
-        .components()
-        .iter()
-        .map(|c| match c {
-            ast::ServiceRouteComponent::Literal(l) => l.clone(),
-            ast::ServiceRouteComponent::Variable(ast::FieldDefPair { name, .. }) => name.clone(),
-        })
-        .collect::<Vec<_>>()
-        .join("_");
+    let hyper_method = match &endpoint.route {
+        ast::ServiceRoute::Get { .. } | ast::ServiceRoute::Ws { .. } => {
+            quote!(::humblegen_rt::hyper::Method::GET)
+        }
+        ast::ServiceRoute::Delete { .. } => quote!(::humblegen_rt::hyper::Method::DELETE),
+        ast::ServiceRoute::Post { .. } => quote!(::humblegen_rt::hyper::Method::POST),
+        ast::ServiceRoute::Put { .. } => quote!(::humblegen_rt::hyper::Method::PUT),
+        ast::ServiceRoute::Patch { .. } => quote!(::humblegen_rt::hyper::Method::PATCH),
+    };
+    let traitfn_ident = format_ident!("{}", route_name(&endpoint.route));
 
-    let (traitfn_name_prefix, hyper_method) = match &endpoint.route {
-        ast::ServiceRoute::Get { .. } => ("get", quote!(::humblegen_rt::hyper::Method::GET)),
-        ast::ServiceRoute::Delete { .. } => {
-            ("delete", quote!(::humblegen_rt::hyper::Method::DELETE))
+    let (post_body, post_body_type) = match endpoint.route.request_body() {
+        Some(ast::BodyType::Data(type_ident)) => {
+            (Some(PostBody::Data), Some(generate_type_ident(type_ident)))
         }
-        ast::ServiceRoute::Post { .. } => ("post", quote!(::humblegen_rt::hyper::Method::POST)),
-        ast::ServiceRoute::Put { .. } => ("put", quote!(::humblegen_rt::hyper::Method::PUT)),
-        ast::ServiceRoute::Patch { .. } => ("patch", quote!(::humblegen_rt::hyper::Method::PATCH)),
+        Some(ast::BodyType::Multipart(parts)) => {
+            let struct_ident = format_ident!(
+                "{}{}Multipart",
+                service_name,
+                inflector::cases::pascalcase::to_pascal_case(&traitfn_ident.to_string())
+            );
+            let post_body_type = quote! { #struct_ident };
+            let parts = parts
+                .iter()
+                .enumerate()
+                .map(|(idx, part)| MultipartPart {
+                    spec_name: part.name().to_owned(),
+                    rust_field_ident: format_ident!("{}", part.name()),
+                    rust_field_type: match part {
+                        ast::MultipartPart::Field(pair) => {
+                            Some(generate_type_ident(&pair.type_ident))
+                        }
+                        ast::MultipartPart::File(_) => None,
+                    },
+                    is_streaming: idx + 1 == parts.len()
+                        && matches!(part, ast::MultipartPart::File(_)),
+                })
+                .collect();
+            (
+                Some(PostBody::Multipart(MultipartBody {
+                    struct_ident,
+                    parts,
+                })),
+                Some(post_body_type),
+            )
+        }
+        Some(ast::BodyType::Blob(media_type)) => (
+            Some(PostBody::Blob {
+                media_type: media_type.clone(),
+            }),
+            Some(quote!(::humblegen_rt::blob::Blob)),
+        ),
+        None => (None, None),
     };
-    let traitfn_ident = format_ident!(
-        "{}_{}",
-        traitfn_name_prefix,
-        inflector::cases::snakecase::to_snake_case(&traitfn_name_stem)
-    );
 
     let doc_comment = {
         let doc_comment = fmt_opt_string(&endpoint.doc_comment);
         quote! { #[doc = #doc_comment] }
     };
 
-    ServiceRoute {
+    Ok(ServiceRoute {
         doc_comment,
         traitfn_ident,
         hyper_method,
         components,
         query_type,
         query_deser_fn,
+        query_test_fn,
         post_body_type,
+        post_body,
+        if_match: matches!(
+            endpoint.route,
+            ast::ServiceRoute::Put { .. } | ast::ServiceRoute::Patch { .. }
+        ),
+        websocket_messages,
         ret_type,
-    }
+        response_format,
+        response_fn,
+        test_ret_type,
+        test_decode_fn,
+    })
+}
+
+const QUERY_TYPES: &str =
+    "the query must be a user defined type, a built-in type or a list of built-in types";
+
+/// The name of the handler trait fn of a route, e.g. `get_monsters_id` for `GET /monsters/{id: i32}`.
+pub(crate) fn route_name(route: &ast::ServiceRoute) -> String {
+    let stem = route
+        .components()
+        .iter()
+        .map(|c| match c {
+            ast::ServiceRouteComponent::Literal(l) => l.clone(),
+            ast::ServiceRouteComponent::Variable(ast::FieldDefPair { name, .. })
+            | ast::ServiceRouteComponent::Rest(ast::FieldDefPair { name, .. }) => name.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join("_");
+    let prefix = match route {
+        ast::ServiceRoute::Get { .. } => "get",
+        ast::ServiceRoute::Delete { .. } => "delete",
+        ast::ServiceRoute::Post { .. } => "post",
+        ast::ServiceRoute::Put { .. } => "put",
+        ast::ServiceRoute::Patch { .. } => "patch",
+        ast::ServiceRoute::Ws { .. } => "ws",
+    };
+    format!(
+        "{}_{}",
+        prefix,
+        inflector::cases::snakecase::to_snake_case(&stem)
+    )
 }
 
 fn generate_as_rustdoc_comment_try_rustfmt(s: &TokenStream) -> String {
@@ -1,6 +1,9 @@
 use anyhow::{self, Result};
 use std::{ops::Deref, path, str};
-use structopt::StructOpt;
+use structopt::{
+    clap::{AppSettings, ArgSettings},
+    StructOpt,
+};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -18,6 +21,8 @@ pub enum Backend {
     Rust,
     Elm,
     Docs,
+    #[cfg(feature = "openapi")]
+    OpenApi,
 }
 
 impl str::FromStr for Backend {
@@ -28,6 +33,8 @@ impl str::FromStr for Backend {
             "RUST" => Ok(Backend::Rust),
             "ELM" => Ok(Backend::Elm),
             "DOCS" | "DOC" | "DOCUMENTATION" => Ok(Backend::Docs),
+            #[cfg(feature = "openapi")]
+            "OPENAPI" | "SWAGGER" => Ok(Backend::OpenApi),
             _ => Err(CliError::UnknownBackend(s.to_string())),
         }
     }
@@ -72,22 +79,43 @@ impl Deref for Artifact {
 /// Command-line arguments
 // TODO: turn into enum separating language backends from docs backend, docs backend does not need a gen_server and gen_client field
 #[derive(StructOpt)]
-#[structopt(about = "generate code from humble protocol spec")]
+#[structopt(
+    about = "generate code from humble protocol spec",
+    setting = AppSettings::SubcommandsNegateReqs
+)]
 pub(crate) struct CliArgs {
+    #[structopt(subcommand)]
+    pub(crate) command: Option<Command>,
     /// language to generate code for
-    #[structopt(short = "l", long = "language")]
-    pub(crate) backend: Backend,
+    #[structopt(short = "l", long = "language", set = ArgSettings::Required)]
+    pub(crate) backend: Option<Backend>,
     /// generate REST endpoints for a server
     #[structopt(short = "a", long = "artifacts", default_value)]
     pub(crate) artifacts: Artifact,
     /// input path to humble file
-    pub(crate) input: path::PathBuf,
+    #[structopt(set = ArgSettings::Required)]
+    pub(crate) input: Option<path::PathBuf>,
     /// input path to humble file
-    #[structopt(short = "o", long = "output")]
-    pub(crate) output: path::PathBuf,
+    #[structopt(short = "o", long = "output", set = ArgSettings::Required)]
+    pub(crate) output: Option<path::PathBuf>,
     /// prefix to be used in elm module declarations
     #[structopt(long, default_value = "\"Api\"")]
     pub(crate) elm_module_root: String,
+    /// additionally generate mock handler implementations (rust only)
+    #[structopt(long)]
+    pub(crate) mocks: bool,
+    /// title of the API (openapi only)
+    #[cfg(feature = "openapi")]
+    #[structopt(long, default_value = "API")]
+    pub(crate) api_title: String,
+    /// version of the API (openapi only)
+    #[cfg(feature = "openapi")]
+    #[structopt(long, default_value = "0.0.0")]
+    pub(crate) api_version: String,
+    /// URL path prefix at which services are mounted (openapi only)
+    #[cfg(feature = "openapi")]
+    #[structopt(long, default_value = "/api")]
+    pub(crate) root: String,
 }
 
 impl CliArgs {
@@ -98,11 +126,16 @@ impl CliArgs {
     /// requesting server endpoints for elm -- a client-side programming language --
     /// will result in an error.
     pub fn code_generator(&self) -> Result<Box<dyn humblegen::CodeGenerator>, CliError> {
-        match self.backend {
-            Backend::Rust => Ok(Box::new(
-                humblegen::backend::rust::Generator::new(*self.artifacts)
-                    .map_err(CliError::LibraryError)?,
-            )),
+        match self.backend.expect("required unless a subcommand is given") {
+            Backend::Rust => {
+                let generator = humblegen::backend::rust::Generator::new(*self.artifacts)
+                    .map_err(CliError::LibraryError)?;
+                if self.mocks {
+                    Ok(Box::new(generator.with_mocks()))
+                } else {
+                    Ok(Box::new(generator))
+                }
+            }
             Backend::Elm => Ok(Box::new(
                 humblegen::backend::elm::Generator::new(
                     *self.artifacts,
@@ -111,6 +144,168 @@ impl CliArgs {
                 .map_err(CliError::LibraryError)?,
             )),
             Backend::Docs => Ok(Box::new(humblegen::backend::docs::Generator::default())),
+            #[cfg(feature = "openapi")]
+            Backend::OpenApi => Ok(Box::new(
+                humblegen::backend::openapi::Generator::default()
+                    .with_title(self.api_title.clone())
+                    .with_version(self.api_version.clone())
+                    .with_root(self.root.clone()),
+            )),
         }
     }
 }
+
+// commands other than code generation
+#[derive(StructOpt)]
+pub(crate) enum Command {
+    /// serve a humble spec with fake data, validating requests against it
+    #[cfg(feature = "mock")]
+    Mock(MockArgs),
+    /// forward requests to a backend, validating requests and responses against a humble spec
+    #[cfg(feature = "proxy")]
+    Proxy(ProxyArgs),
+    /// convert an OpenAPI 3 document (YAML or JSON) into a humble spec
+    #[cfg(feature = "openapi")]
+    ImportOpenapi(ImportOpenApiArgs),
+}
+
+#[cfg(feature = "mock")]
+#[derive(StructOpt)]
+pub(crate) struct MockArgs {
+    /// input path to humble file
+    pub(crate) input: path::PathBuf,
+    /// address to listen on
+    #[structopt(long, default_value = "127.0.0.1:8080")]
+    pub(crate) addr: std::net::SocketAddr,
+    /// seed of the fake data
+    #[structopt(long, default_value = "0")]
+    pub(crate) seed: u64,
+    /// URL path prefix at which services are mounted
+    #[structopt(long, default_value = "/api")]
+    pub(crate) root: String,
+    /// URL path prefix of an individual service, e.g. `--mount Monsters=/monsters`
+    #[structopt(long = "mount", parse(try_from_str = parse_mount), number_of_values = 1)]
+    pub(crate) mounts: Vec<(String, String)>,
+    /// allow cross-origin requests from any origin
+    #[structopt(long)]
+    pub(crate) cors: bool,
+}
+
+#[cfg(feature = "proxy")]
+#[derive(StructOpt)]
+pub(crate) struct ProxyArgs {
+    /// input path to humble file
+    pub(crate) input: path::PathBuf,
+    /// URL of the backend, e.g. `http://127.0.0.1:3000`
+    #[structopt(long)]
+    pub(crate) upstream: humblegen_rt::hyper::Uri,
+    /// address to listen on
+    #[structopt(long, default_value = "127.0.0.1:8080")]
+    pub(crate) addr: std::net::SocketAddr,
+    /// URL path prefix at which the backend serves services
+    #[structopt(long, default_value = "/api")]
+    pub(crate) root: String,
+    /// URL path prefix of an individual service, e.g. `--mount Monsters=/monsters`
+    #[structopt(long = "mount", parse(try_from_str = parse_mount), number_of_values = 1)]
+    pub(crate) mounts: Vec<(String, String)>,
+    /// answer requests and responses that do not match the spec with an error instead of only logging them
+    #[structopt(long)]
+    pub(crate) reject: bool,
+}
+
+#[cfg(feature = "openapi")]
+#[derive(StructOpt)]
+pub(crate) struct ImportOpenApiArgs {
+    /// input path to OpenAPI document
+    pub(crate) input: path::PathBuf,
+    /// output path of the humble file, printed to stdout if omitted
+    #[structopt(short = "o", long = "output")]
+    pub(crate) output: Option<path::PathBuf>,
+}
+
+#[cfg(any(feature = "mock", feature = "proxy"))]
+fn parse_mount(s: &str) -> Result<(String, String), String> {
+    match s.find('=') {
+        Some(i) => Ok((s[..i].to_owned(), s[i + 1..].to_owned())),
+        None => Err(format!("expected `ServiceName=/path`, found `{}`", s)),
+    }
+}
+
+#[cfg(feature = "mock")]
+impl MockArgs {
+    /// Serves the spec until the process is killed.
+    pub fn run(self, spec: humblegen::Spec) -> Result<()> {
+        use humblegen_rt::cors::CorsConfig;
+
+        init_tracing();
+
+        let mut config = humblegen::mock::MockConfig {
+            seed: self.seed,
+            root: self.root,
+            mounts: self.mounts.into_iter().collect(),
+            ..Default::default()
+        };
+        if self.cors {
+            config.server.cors = Some(CorsConfig::default());
+        }
+        let mut runtime = humblegen_rt::tokio::runtime::Runtime::new()?;
+        runtime.block_on(humblegen::mock::listen_and_run_forever(
+            spec, config, &self.addr,
+        ))
+    }
+}
+
+#[cfg(feature = "proxy")]
+impl ProxyArgs {
+    /// Forwards requests until the process is killed.
+    pub fn run(self, spec: humblegen::Spec) -> Result<()> {
+        use humblegen::proxy::{Mismatches, Proxy, ProxyConfig};
+
+        init_tracing();
+
+        let mut config = ProxyConfig::new(self.upstream);
+        config.root = self.root;
+        config.mounts = self.mounts.into_iter().collect();
+        if self.reject {
+            config.mismatches = Mismatches::Reject;
+        }
+        let proxy = Proxy::new(spec, config)?;
+        let mut runtime = humblegen_rt::tokio::runtime::Runtime::new()?;
+        runtime.block_on(humblegen::proxy::listen_and_run_forever(proxy, &self.addr))
+    }
+}
+
+#[cfg(feature = "openapi")]
+impl ImportOpenApiArgs {
+    /// Writes the converted spec, printing what could not be converted to stderr.
+    pub fn run(self) -> Result<()> {
+        use anyhow::Context;
+
+        let input = std::fs::read_to_string(&self.input)
+            .context(format!("unable to open OpenAPI document {:?}", self.input))?;
+        // YAML is a superset of JSON
+        let document: serde_json::Value = serde_yaml::from_str(&input)
+            .context(format!("failed to parse OpenAPI document {:?}", self.input))?;
+        let (spec, warnings) = humblegen::import::openapi::import(&document)?;
+        for warning in &warnings {
+            eprintln!("warning: {}", warning);
+        }
+        match self.output {
+            Some(output) => std::fs::write(&output, spec.to_string())
+                .context(format!("unable to write humble file {:?}", output))?,
+            None => print!("{}", spec),
+        }
+        Ok(())
+    }
+}
+
+/// Logs to stderr, at the level given by `RUST_LOG` (default: `info`).
+#[cfg(any(feature = "mock", feature = "proxy"))]
+fn init_tracing() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+}
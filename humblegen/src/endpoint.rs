@@ -0,0 +1,250 @@
+//! Endpoints of a humblespec, routed and validated at runtime by servers without generated code
+//! (`humblegen mock` and `humblegen proxy`).
+//!
+//! Routes and request validation mirror the generated Rust server: requests that a generated server
+//! would reject are rejected with the same `ErrorResponse`.
+
+use crate::ast;
+use crate::validate::{self, Encoding, ValueError};
+
+use humblegen_rt::blob;
+use humblegen_rt::hyper::{self, Body, Method, Request};
+use humblegen_rt::multipart::Multipart;
+use humblegen_rt::regex::{self, Regex};
+use humblegen_rt::serialization_helpers as deser;
+use humblegen_rt::service_protocol::{ErrorResponse, RuntimeError, ToErrorResponse};
+use serde_json::Value;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// An endpoint of a service, with everything needed to route and validate its requests and responses.
+pub(crate) struct Endpoint {
+    pub(crate) spec: Arc<ast::Spec>,
+    /// The name of the handler trait fn of a generated server, e.g. `get_monsters_id`.
+    pub(crate) name: String,
+    pub(crate) method: Method,
+    /// The route, e.g. `/monsters/{id}`.
+    pub(crate) template: String,
+    /// Matches the path of a request relative to the service root.
+    pub(crate) regex: Regex,
+    pub(crate) route_params: Vec<ast::FieldDefPair>,
+    pub(crate) query: Option<ast::TypeIdent>,
+    pub(crate) body: Option<ast::BodyType>,
+    /// `None` for websocket routes.
+    pub(crate) ret: Option<ast::ReturnType>,
+    pub(crate) websocket_messages: Option<(ast::TypeIdent, ast::TypeIdent)>,
+}
+
+/// Groups the services of `spec` by the URL path prefix they are mounted at.
+///
+/// Services are mounted at `root`, unless overridden by their name in `mounts`.
+pub(crate) fn mount_services<'a>(
+    spec: &'a ast::Spec,
+    root: &str,
+    mounts: &HashMap<String, String>,
+) -> anyhow::Result<Vec<(String, Vec<&'a ast::ServiceDef>)>> {
+    let service_defs: Vec<_> = spec.iter().filter_map(|si| si.service_def()).collect();
+    if let Some(name) = mounts
+        .keys()
+        .find(|name| !service_defs.iter().any(|s| &s.name == *name))
+    {
+        anyhow::bail!("cannot mount unknown service `{}`", name);
+    }
+
+    let mut mounted: Vec<(String, Vec<&ast::ServiceDef>)> = Vec::new();
+    for service_def in service_defs {
+        let root = mounts
+            .get(&service_def.name)
+            .map(String::as_str)
+            .unwrap_or(root);
+        if !root.starts_with('/') || root.ends_with('/') {
+            anyhow::bail!(
+                "root {:?} of service `{}` must start and must not end with \"/\"",
+                root,
+                service_def.name
+            );
+        }
+        match mounted.iter_mut().find(|(r, _)| r == root) {
+            Some((_, services)) => services.push(service_def),
+            None => mounted.push((root.to_owned(), vec![service_def])),
+        }
+    }
+    Ok(mounted)
+}
+
+/// Matches the URL path of requests to services mounted at `root`, like a generated server.
+pub(crate) fn root_regex(root: &str) -> Regex {
+    Regex::new(&format!(
+        r"^(?P<root>{})(?P<suffix>/.*)",
+        regex::escape(root)
+    ))
+    .expect("root regex is valid")
+}
+
+impl Endpoint {
+    pub(crate) fn new(spec: &Arc<ast::Spec>, endpoint: &ast::ServiceEndpoint) -> Self {
+        let route = &endpoint.route;
+        let mut regex_str = String::from("^");
+        let mut template = String::new();
+        let mut route_params = Vec::new();
+        for component in route.components() {
+            match component {
+                ast::ServiceRouteComponent::Literal(literal) => {
+                    regex_str.push_str(&format!("/{}", regex::escape(literal)));
+                    template.push_str(&format!("/{}", literal));
+                }
+                ast::ServiceRouteComponent::Variable(pair) => {
+                    regex_str.push_str(&format!("/(?P<{}>[^/]+)", pair.name));
+                    template.push_str(&format!("/{{{}}}", pair.name));
+                    route_params.push(pair.clone());
+                }
+                ast::ServiceRouteComponent::Rest(pair) => {
+                    regex_str.push_str(&format!("/(?P<{}>.*)", pair.name));
+                    template.push_str(&format!("/{{{}..}}", pair.name));
+                    route_params.push(pair.clone());
+                }
+            }
+        }
+        regex_str.push('$');
+
+        let method = match route {
+            ast::ServiceRoute::Get { .. } | ast::ServiceRoute::Ws { .. } => Method::GET,
+            ast::ServiceRoute::Delete { .. } => Method::DELETE,
+            ast::ServiceRoute::Post { .. } => Method::POST,
+            ast::ServiceRoute::Put { .. } => Method::PUT,
+            ast::ServiceRoute::Patch { .. } => Method::PATCH,
+        };
+
+        Self {
+            spec: Arc::clone(spec),
+            name: crate::backend::rust::route_name(route),
+            method,
+            template,
+            regex: Regex::new(&regex_str).expect("route regex is valid"),
+            route_params,
+            query: route.query().clone(),
+            body: route.request_body().cloned(),
+            ret: route.return_type().cloned(),
+            websocket_messages: route
+                .websocket_messages()
+                .map(|(client_msg, server_msg)| (client_msg.clone(), server_msg.clone())),
+        }
+    }
+
+    pub(crate) fn check_route_params(
+        &self,
+        captures: &regex::Captures,
+    ) -> Result<(), ErrorResponse> {
+        for ast::FieldDefPair { name, type_ident } in &self.route_params {
+            let value: String = deser::deser_param(name, &captures[name.as_str()])?;
+            let parse_error = match type_ident {
+                ast::TypeIdent::BuiltIn(atom) => validate::parse_atom(*atom, &value).err(),
+                _ => self.check(type_ident, &Value::String(value), Encoding::Query),
+            };
+            if let Some(parse_error) = parse_error {
+                return Err(RuntimeError::RouteParamInvalid {
+                    param_name: name.clone(),
+                    parse_error,
+                }
+                .to_error_response());
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_query(&self, req: &Request<Body>) -> Result<(), ErrorResponse> {
+        let (query_type, query) = match (&self.query, req.uri().query()) {
+            (Some(query_type), Some(query)) => (query_type, query),
+            _ => return Ok(()),
+        };
+        let error = match query_type {
+            ast::TypeIdent::BuiltIn(atom) => {
+                let value: String = deser::deser_query_primitive(query)?;
+                validate::parse_atom(*atom, &value).err()
+            }
+            ast::TypeIdent::List(inner) => match **inner {
+                ast::TypeIdent::BuiltIn(atom) => deser::deser_query_list::<String>(query)?
+                    .iter()
+                    .find_map(|value| validate::parse_atom(atom, value).err()),
+                _ => None,
+            },
+            _ => {
+                // serde_qs only decodes structs and maps at the top level
+                let fields: serde_json::Map<String, Value> = deser::deser_query_serde_qs(query)?;
+                self.check(query_type, &Value::Object(fields), Encoding::Query)
+            }
+        };
+        match error {
+            Some(error) => Err(RuntimeError::QueryInvalid(error).to_error_response()),
+            None => Ok(()),
+        }
+    }
+
+    /// Reads and validates the request body.
+    pub(crate) async fn check_body(&self, req: &mut Request<Body>) -> Result<(), ErrorResponse> {
+        let error = match &self.body {
+            None => None,
+            Some(ast::BodyType::Blob(media_type)) => {
+                let blob = blob::deser_blob(req, media_type)?;
+                hyper::body::to_bytes(blob.into_body())
+                    .await
+                    .map_err(|e| RuntimeError::PostBodyReadError(e.to_string()))
+                    .map_err(|e| e.to_error_response())?;
+                None
+            }
+            Some(ast::BodyType::Multipart(parts)) => {
+                let mut multipart = Multipart::from_request(req, None).await?;
+                let mut error = None;
+                for part in parts {
+                    match part {
+                        ast::MultipartPart::Field(ast::FieldDefPair { name, type_ident }) => {
+                            let value: Value = multipart.field(name)?;
+                            error = error.or_else(|| {
+                                self.check(type_ident, &value, Encoding::Json)
+                                    .map(|e| format!("invalid part `{}`: {}", name, e))
+                            });
+                        }
+                        ast::MultipartPart::File(name) => {
+                            multipart.buffered_file(name)?;
+                        }
+                    }
+                }
+                error
+            }
+            Some(ast::BodyType::Data(body_type)) => {
+                let value: Value = deser::deser_post_data(req).await?;
+                self.check(body_type, &value, Encoding::Json)
+            }
+        };
+        match error {
+            Some(error) => Err(RuntimeError::PostBodyInvalid(error).to_error_response()),
+            None => Ok(()),
+        }
+    }
+
+    /// Validates `value`, returning a description of all mismatches if it is invalid.
+    pub(crate) fn check(
+        &self,
+        type_ident: &ast::TypeIdent,
+        value: &Value,
+        encoding: Encoding,
+    ) -> Option<String> {
+        let errors = validate::validate(&self.spec, type_ident, value, encoding);
+        describe_errors(&errors)
+    }
+}
+
+fn describe_errors(errors: &[ValueError]) -> Option<String> {
+    if errors.is_empty() {
+        None
+    } else {
+        Some(
+            errors
+                .iter()
+                .map(ValueError::to_string)
+                .collect::<Vec<_>>()
+                .join("; "),
+        )
+    }
+}
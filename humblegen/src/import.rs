@@ -0,0 +1,3 @@
+//! Importers converting other API descriptions into humble specs.
+
+pub mod openapi;
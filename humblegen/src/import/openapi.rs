@@ -0,0 +1,1427 @@
+//! OpenAPI 3 importer.
+//!
+//! Converts an OpenAPI 3 document into a humble spec, which can be printed as humble source.
+//! Documents generated by the OpenAPI backend (`-l openapi`) are converted back to the spec
+//! they describe, except that tuples become lists and query structs are named after their endpoint.
+//!
+//! - component schemas become structs and enums; schemas of other types (e.g. `type: string`) are
+//!   not named in humble, so references to them are replaced by their type,
+//! - inline objects and enums become types named after where they are defined, e.g. `MonsterOwner`,
+//! - string enums and externally tagged `oneOf`s (objects with a single property) become enums,
+//!   `{"Ok": ..}`/`{"Err": ..}` alternatives become `result`s,
+//! - `allOf` becomes a struct embedding the referenced structs,
+//! - operations become endpoints of the service named after their first tag,
+//!   and their query parameters become a struct named after the operation.
+//!
+//! Everything else is approximated and reported as a `Warning`.
+
+use crate::ast;
+
+use inflector::cases::{pascalcase::to_pascal_case, snakecase::to_snake_case};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Something in the OpenAPI document that cannot be expressed in humble, and how it was approximated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// JSON pointer to the offending part of the document, e.g. `/paths/~1monsters/head`.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}: {}", self.pointer, self.message)
+    }
+}
+
+/// Converts an OpenAPI 3 document, returning the spec and everything that could not be expressed.
+pub fn import(document: &Value) -> anyhow::Result<(ast::Spec, Vec<Warning>)> {
+    match document.get("openapi").and_then(Value::as_str) {
+        Some(version) if version.starts_with("3.") => {}
+        Some(version) => anyhow::bail!("unsupported OpenAPI version {}", version),
+        None if document.get("swagger").is_some() => {
+            anyhow::bail!("Swagger 2.0 documents are not supported, convert them to OpenAPI 3")
+        }
+        None => anyhow::bail!("not an OpenAPI document"),
+    }
+
+    let mut importer = Importer {
+        document,
+        component_names: HashMap::new(),
+        type_names: HashSet::new(),
+        resolving: Vec::new(),
+        types: Vec::new(),
+        services: Vec::new(),
+        warnings: Vec::new(),
+    };
+    importer.import_schemas();
+    importer.import_paths();
+
+    let mut warnings: Vec<Warning> = Vec::new();
+    for warning in importer.warnings {
+        // schemas that are not named in humble are converted once per reference
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
+    let mut spec_items = importer.types;
+    spec_items.extend(importer.services.into_iter().map(ast::SpecItem::ServiceDef));
+    Ok((ast::Spec(spec_items), warnings))
+}
+
+/// Validation keywords without an equivalent in humble.
+const IGNORED_KEYWORDS: &[&str] = &[
+    "pattern",
+    "minLength",
+    "maxLength",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "minProperties",
+    "maxProperties",
+    "not",
+    "default",
+];
+
+struct Importer<'a> {
+    document: &'a Value,
+    /// Names of the types defined by component schemas, by reference (e.g. `#/components/schemas/Monster`).
+    component_names: HashMap<String, String>,
+    /// Names of all types and services.
+    type_names: HashSet<String>,
+    /// References to schemas that are currently converted, to detect cycles.
+    resolving: Vec<String>,
+    types: Vec<ast::SpecItem>,
+    services: Vec<ast::ServiceDef>,
+    warnings: Vec<Warning>,
+}
+
+impl<'a> Importer<'a> {
+    fn warn(&mut self, pointer: &str, message: impl Into<String>) {
+        self.warnings.push(Warning {
+            pointer: pointer.to_owned(),
+            message: message.into(),
+        });
+    }
+
+    fn import_schemas(&mut self) {
+        let schemas = match self
+            .document
+            .pointer("/components/schemas")
+            .and_then(Value::as_object)
+        {
+            Some(schemas) => schemas,
+            None => return,
+        };
+
+        // name all types first, so that references can be resolved in any order
+        for (name, schema) in schemas {
+            if defines_type(schema) && !is_error_response(schema) {
+                let type_name = self.unique_type_name(name);
+                self.component_names
+                    .insert(format!("#/components/schemas/{}", escape(name)), type_name);
+            }
+        }
+        for (name, schema) in schemas {
+            let reference = format!("#/components/schemas/{}", escape(name));
+            if let Some(type_name) = self.component_names.get(&reference).cloned() {
+                self.define(&type_name, schema, &reference[1..]);
+            }
+        }
+    }
+
+    /// Defines the struct or enum `name` described by `schema`.
+    fn define(&mut self, name: &str, schema: &Value, pointer: &str) {
+        // types defined while converting this one follow it
+        let index = self.types.len();
+        if is_never(schema) {
+            // the encoding of enums without variants
+            self.types.push(ast::SpecItem::EnumDef(ast::EnumDef {
+                name: name.to_owned(),
+                variants: Vec::new(),
+                doc_comment: description(schema),
+            }));
+            return;
+        }
+        self.check_keywords(schema, pointer);
+        if schema.get("nullable") == Some(&Value::Bool(true)) {
+            self.warn(
+                pointer,
+                "`nullable` is ignored, references must be `option`s instead",
+            );
+        }
+        let doc_comment = description(schema);
+
+        let spec_item = if let Some(values) = string_enum(schema) {
+            let variants = values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| ast::VariantDef {
+                    name: self.variant_name(value, &format!("{}/enum/{}", pointer, i)),
+                    variant_type: ast::VariantType::Simple,
+                    doc_comment: None,
+                })
+                .collect();
+            ast::SpecItem::EnumDef(ast::EnumDef {
+                name: name.to_owned(),
+                variants,
+                doc_comment,
+            })
+        } else if let Some((keyword, alternatives)) = alternatives(schema) {
+            let alternatives_pointer = format!("{}/{}", pointer, keyword);
+            let variants = match self.tagged_variants(alternatives, name, &alternatives_pointer) {
+                Some(variants) => variants,
+                None => match schema.pointer("/discriminator/propertyName") {
+                    Some(property) => {
+                        self.warn(
+                            &format!("{}/discriminator", pointer),
+                            format!(
+                                "humblegen enums are externally tagged: values of `{}` are objects with the variant name as their only property, \
+                                 instead of objects with a `{}` property",
+                                name,
+                                property.as_str().unwrap_or_default()
+                            ),
+                        );
+                        self.discriminated_variants(
+                            alternatives,
+                            schema,
+                            name,
+                            &alternatives_pointer,
+                        )
+                    }
+                    None => {
+                        self.warn(
+                            &alternatives_pointer,
+                            format!(
+                                "`{}` without a discriminator cannot be expressed, `{}` is an empty struct",
+                                keyword, name
+                            ),
+                        );
+                        return self.define_struct(
+                            index,
+                            name,
+                            ast::StructFields(Vec::new()),
+                            doc_comment,
+                        );
+                    }
+                },
+            };
+            ast::SpecItem::EnumDef(ast::EnumDef {
+                name: name.to_owned(),
+                variants,
+                doc_comment,
+            })
+        } else if let Some(members) = schema.get("allOf").and_then(Value::as_array) {
+            let mut fields = Vec::new();
+            for (i, member) in members.iter().enumerate() {
+                let member_pointer = format!("{}/allOf/{}", pointer, i);
+                let embedded = member
+                    .get("$ref")
+                    .and_then(Value::as_str)
+                    .and_then(|reference| self.component_names.get(reference))
+                    .cloned();
+                match embedded {
+                    Some(type_name) => fields.push(ast::FieldNode {
+                        pair: ast::FieldDefPair {
+                            name: type_name.clone(),
+                            type_ident: ast::TypeIdent::UserDefined(type_name),
+                        },
+                        doc_comment: None,
+                    }),
+                    None if is_object(member) => {
+                        let member_fields = self.struct_fields(member, name, &member_pointer);
+                        fields.extend(member_fields.0)
+                    }
+                    None => self.warn(
+                        &member_pointer,
+                        "only structs can be merged by `allOf`, this schema is ignored",
+                    ),
+                }
+            }
+            fields.extend(self.struct_fields(schema, name, pointer).0);
+            ast::SpecItem::StructDef(ast::StructDef {
+                name: name.to_owned(),
+                fields: ast::StructFields(fields),
+                doc_comment,
+            })
+        } else {
+            let fields = self.struct_fields(schema, name, pointer);
+            ast::SpecItem::StructDef(ast::StructDef {
+                name: name.to_owned(),
+                fields,
+                doc_comment,
+            })
+        };
+        self.types.insert(index, spec_item);
+    }
+
+    fn define_struct(
+        &mut self,
+        index: usize,
+        name: &str,
+        fields: ast::StructFields,
+        doc_comment: Option<String>,
+    ) {
+        self.types.insert(
+            index,
+            ast::SpecItem::StructDef(ast::StructDef {
+                name: name.to_owned(),
+                fields,
+                doc_comment,
+            }),
+        );
+    }
+
+    /// Defines a type for an inline schema, named `hint` unless that name is taken.
+    fn define_inline(&mut self, hint: &str, schema: &Value, pointer: &str) -> ast::TypeIdent {
+        let name = self.unique_type_name(hint);
+        self.define(&name, schema, pointer);
+        ast::TypeIdent::UserDefined(name)
+    }
+
+    /// The fields of an object schema. Fields that are not required must be `option`s, or `list`s
+    /// and `map`s, which are empty if missing.
+    fn struct_fields(
+        &mut self,
+        schema: &Value,
+        type_name: &str,
+        pointer: &str,
+    ) -> ast::StructFields {
+        let required = required(schema);
+        let mut fields = Vec::new();
+        if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            for (name, property) in properties {
+                let pointer = format!("{}/properties/{}", pointer, escape(name));
+                let field_name = self.field_name(name, &pointer);
+                let hint = format!("{}{}", type_name, type_name_of(name));
+                let mut type_ident = self.type_of(property, &pointer, &hint);
+                if !required.contains(name.as_str()) {
+                    type_ident = optional(type_ident);
+                }
+                fields.push(ast::FieldNode {
+                    pair: ast::FieldDefPair {
+                        name: field_name,
+                        type_ident,
+                    },
+                    doc_comment: description(property),
+                });
+            }
+            if schema
+                .get("additionalProperties")
+                .and_then(Value::as_object)
+                .is_some()
+            {
+                self.warn(
+                    &format!("{}/additionalProperties", pointer),
+                    "additional properties of structs are ignored",
+                );
+            }
+        }
+        ast::StructFields(fields)
+    }
+
+    /// The variants of an externally tagged enum: each alternative is either a string enum
+    /// of simple variants, or an object with the variant name as its only property.
+    fn tagged_variants(
+        &mut self,
+        alternatives: &[Value],
+        enum_name: &str,
+        pointer: &str,
+    ) -> Option<Vec<ast::VariantDef>> {
+        if !alternatives.iter().all(is_tagged_alternative) {
+            return None;
+        }
+        let mut variants = Vec::new();
+        for (i, alternative) in alternatives.iter().enumerate() {
+            let pointer = format!("{}/{}", pointer, i);
+            if let Some(values) = string_enum(alternative) {
+                for (j, value) in values.iter().enumerate() {
+                    variants.push(ast::VariantDef {
+                        name: self.variant_name(value, &format!("{}/enum/{}", pointer, j)),
+                        variant_type: ast::VariantType::Simple,
+                        doc_comment: None,
+                    });
+                }
+                continue;
+            }
+            let (tag, content) = alternative["properties"]
+                .as_object()
+                .and_then(|properties| properties.iter().next())
+                .expect("tagged alternatives have a single property");
+            let content_pointer = format!("{}/properties/{}", pointer, escape(tag));
+            let name = self.variant_name(tag, &content_pointer);
+            let hint = format!("{}{}", enum_name, name);
+            let variant_type = if content.get("properties").is_some() && !defines_enum(content) {
+                self.check_keywords(content, &content_pointer);
+                ast::VariantType::Struct(self.struct_fields(content, &hint, &content_pointer))
+            } else {
+                ast::VariantType::Newtype(self.type_of(content, &content_pointer, &hint))
+            };
+            variants.push(ast::VariantDef {
+                name,
+                variant_type,
+                doc_comment: description(alternative),
+            });
+        }
+        Some(variants)
+    }
+
+    /// The variants of an internally tagged enum, one per referenced schema.
+    fn discriminated_variants(
+        &mut self,
+        alternatives: &[Value],
+        schema: &Value,
+        enum_name: &str,
+        pointer: &str,
+    ) -> Vec<ast::VariantDef> {
+        let mapping = schema
+            .pointer("/discriminator/mapping")
+            .and_then(Value::as_object);
+        let mut variants = Vec::new();
+        for (i, alternative) in alternatives.iter().enumerate() {
+            let pointer = format!("{}/{}", pointer, i);
+            let reference = alternative.get("$ref").and_then(Value::as_str);
+            let tag = reference.map(|reference| {
+                mapping
+                    .and_then(|mapping| {
+                        mapping
+                            .iter()
+                            .find(|(_, target)| target.as_str() == Some(reference))
+                    })
+                    .map(|(tag, _)| tag.as_str())
+                    .unwrap_or_else(|| reference.rsplit('/').next().unwrap_or(reference))
+            });
+            let name = match tag {
+                Some(tag) => self.variant_name(tag, &pointer),
+                None => format!("Variant{}", i + 1),
+            };
+            let hint = format!("{}{}", enum_name, name);
+            variants.push(ast::VariantDef {
+                variant_type: ast::VariantType::Newtype(self.type_of(alternative, &pointer, &hint)),
+                name,
+                doc_comment: description(alternative),
+            });
+        }
+        variants
+    }
+
+    /// The type of values described by `schema`. Inline objects and enums are defined as types named `hint`.
+    fn type_of(&mut self, schema: &Value, pointer: &str, hint: &str) -> ast::TypeIdent {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            return self.resolve(reference, pointer, hint);
+        }
+        if is_null(schema) {
+            return ast::TypeIdent::BuiltIn(ast::AtomType::Empty);
+        }
+        if let Some(inner) = non_null(schema) {
+            return optional(self.type_of(&inner, pointer, hint));
+        }
+
+        if let Some((keyword, alternatives)) = alternatives(schema) {
+            let pointer = format!("{}/{}", pointer, keyword);
+            let non_null_alternatives: Vec<_> = alternatives
+                .iter()
+                .enumerate()
+                .filter(|(_, alternative)| !is_null(alternative))
+                .collect();
+            if let [(i, alternative)] = non_null_alternatives.as_slice() {
+                let pointer = format!("{}/{}", pointer, i);
+                let type_ident = self.type_of(alternative, &pointer, hint);
+                return if alternatives.len() > 1 {
+                    optional(type_ident)
+                } else {
+                    type_ident
+                };
+            }
+            if let Some((ok, err)) = result_alternatives(alternatives) {
+                let ok = self.type_of(
+                    ok,
+                    &format!("{}/0/properties/Ok", pointer),
+                    &format!("{}Ok", hint),
+                );
+                let err = self.type_of(
+                    err,
+                    &format!("{}/1/properties/Err", pointer),
+                    &format!("{}Err", hint),
+                );
+                return ast::TypeIdent::Result(Box::new(ok), Box::new(err));
+            }
+        }
+        if let Some([member]) = schema
+            .get("allOf")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+        {
+            if schema.get("properties").is_none() {
+                return self.type_of(member, &format!("{}/allOf/0", pointer), hint);
+            }
+        }
+        if defines_type(schema) {
+            return self.define_inline(hint, schema, pointer);
+        }
+        if schema.get("enum").is_some() {
+            self.warn(
+                &format!("{}/enum", pointer),
+                "only enums of strings are supported, the enum is ignored",
+            );
+        }
+
+        self.check_keywords(schema, pointer);
+        match schema.get("type").and_then(Value::as_str) {
+            Some("string") => {
+                let atom = match schema.get("format").and_then(Value::as_str) {
+                    Some("date-time") => ast::AtomType::DateTime,
+                    Some("date") => ast::AtomType::Date,
+                    Some("uuid") => ast::AtomType::Uuid,
+                    Some("byte") => ast::AtomType::Bytes,
+                    Some("binary") => {
+                        self.warn(
+                            &format!("{}/format", pointer),
+                            "binary strings in JSON are base64 encoded `bytes`",
+                        );
+                        ast::AtomType::Bytes
+                    }
+                    _ => ast::AtomType::Str,
+                };
+                ast::TypeIdent::BuiltIn(atom)
+            }
+            Some("integer") => ast::TypeIdent::BuiltIn(self.integer(schema, pointer)),
+            Some("number") => {
+                self.check_bounds(schema, pointer);
+                ast::TypeIdent::BuiltIn(ast::AtomType::F64)
+            }
+            Some("boolean") => ast::TypeIdent::BuiltIn(ast::AtomType::Bool),
+            Some("array") => match schema.get("items") {
+                Some(items) => {
+                    let item_hint = format!("{}Item", hint);
+                    let item = self.type_of(items, &format!("{}/items", pointer), &item_hint);
+                    ast::TypeIdent::List(Box::new(item))
+                }
+                None => {
+                    self.warn(
+                        pointer,
+                        "arrays without `items` cannot be expressed, using `list[str]`",
+                    );
+                    ast::TypeIdent::List(Box::new(ast::TypeIdent::BuiltIn(ast::AtomType::Str)))
+                }
+            },
+            Some("object") | None
+                if schema
+                    .get("additionalProperties")
+                    .and_then(Value::as_object)
+                    .is_some() =>
+            {
+                let key = match schema.get("x-humblegen-keys") {
+                    Some(keys) => {
+                        self.type_of(keys, &format!("{}/x-humblegen-keys", pointer), hint)
+                    }
+                    None => ast::TypeIdent::BuiltIn(ast::AtomType::Str),
+                };
+                let value = self.type_of(
+                    &schema["additionalProperties"],
+                    &format!("{}/additionalProperties", pointer),
+                    &format!("{}Value", hint),
+                );
+                ast::TypeIdent::Map(Box::new(key), Box::new(value))
+            }
+            _ => {
+                self.warn(
+                    pointer,
+                    format!(
+                        "schemas without a type cannot be expressed, `{}` is an empty struct",
+                        hint
+                    ),
+                );
+                self.define_inline(hint, &json!({}), pointer)
+            }
+        }
+    }
+
+    /// The type of the schema referenced by `reference`.
+    fn resolve(&mut self, reference: &str, pointer: &str, hint: &str) -> ast::TypeIdent {
+        if let Some(type_name) = self.component_names.get(reference) {
+            return ast::TypeIdent::UserDefined(type_name.clone());
+        }
+        let name = type_name_of(reference.rsplit('/').next().unwrap_or(hint));
+        let target = reference
+            .strip_prefix('#')
+            .and_then(|target_pointer| self.document.pointer(target_pointer));
+        match target {
+            Some(target) if !self.resolving.iter().any(|r| r == reference) => {
+                // a schema that is not named in humble
+                self.resolving.push(reference.to_owned());
+                let type_ident = self.type_of(target, &reference[1..], &name);
+                self.resolving.pop();
+                type_ident
+            }
+            Some(_) => {
+                self.warn(
+                    pointer,
+                    format!(
+                        "cyclic reference `{}` cannot be expressed, `{}` is an empty struct",
+                        reference, name
+                    ),
+                );
+                self.define_inline(&name, &json!({}), pointer)
+            }
+            None => {
+                self.warn(
+                    pointer,
+                    format!(
+                        "reference `{}` cannot be resolved, `{}` is an empty struct",
+                        reference, name
+                    ),
+                );
+                self.define_inline(&name, &json!({}), pointer)
+            }
+        }
+    }
+
+    /// The smallest integer type covering the bounds of the schema.
+    fn integer(&mut self, schema: &Value, pointer: &str) -> ast::AtomType {
+        let minimum = schema.get("minimum").and_then(Value::as_f64);
+        let maximum = schema.get("maximum").and_then(Value::as_f64);
+        let unsigned = matches!(minimum, Some(minimum) if minimum >= 0.0);
+        let int64 = schema.get("format").and_then(Value::as_str) == Some("int64");
+        if unsigned && matches!(maximum, Some(maximum) if maximum <= f64::from(u8::MAX)) {
+            ast::AtomType::U8
+        } else if unsigned
+            && (!int64 || matches!(maximum, Some(maximum) if maximum <= f64::from(u32::MAX)))
+        {
+            ast::AtomType::U32
+        } else {
+            if int64 {
+                self.warn(
+                    &format!("{}/format", pointer),
+                    "64-bit integers are not supported, using `i32`",
+                );
+            }
+            ast::AtomType::I32
+        }
+    }
+
+    fn check_bounds(&mut self, schema: &Value, pointer: &str) {
+        for keyword in &["minimum", "maximum"] {
+            if schema.get(*keyword).is_some() {
+                self.warn(pointer, format!("`{}` is ignored", keyword));
+            }
+        }
+    }
+
+    fn check_keywords(&mut self, schema: &Value, pointer: &str) {
+        for keyword in IGNORED_KEYWORDS {
+            if schema.get(*keyword).is_some() {
+                self.warn(pointer, format!("`{}` is ignored", keyword));
+            }
+        }
+    }
+
+    fn import_paths(&mut self) {
+        let paths = match self.document.get("paths").and_then(Value::as_object) {
+            Some(paths) => paths,
+            None => return,
+        };
+        for (path, path_item) in paths {
+            let pointer = format!("/paths/{}", escape(path));
+            let segments = match self.route_segments(path, &pointer) {
+                Some(segments) => segments,
+                None => continue,
+            };
+            let path_item = self.deref(path_item);
+            let operations = match path_item.as_object() {
+                Some(operations) => operations,
+                None => continue,
+            };
+            for (method, operation) in operations {
+                let operation_pointer = format!("{}/{}", pointer, method);
+                match method.as_str() {
+                    "get" | "put" | "post" | "delete" | "patch" => self.import_operation(
+                        path,
+                        path_item,
+                        method,
+                        operation,
+                        &segments,
+                        &operation_pointer,
+                    ),
+                    "head" | "options" | "trace" => self.warn(
+                        &operation_pointer,
+                        format!("{} requests are not supported", method.to_uppercase()),
+                    ),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// The segments of a path, which are literals or names of path parameters.
+    fn route_segments(&mut self, path: &str, pointer: &str) -> Option<Vec<Segment>> {
+        let mut segments = Vec::new();
+        // the path of a route that consists of a rest parameter only
+        if path == "/" {
+            return Some(segments);
+        }
+        for segment in path.split('/').skip(1) {
+            if let Some(name) = segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                segments.push(Segment::Param(name.to_owned()));
+            } else if is_kebab_case_ident(segment) {
+                segments.push(Segment::Literal(segment.to_owned()));
+            } else {
+                self.warn(
+                    pointer,
+                    format!(
+                        "path segment `{}` cannot be expressed, only lowercase letters, digits and `-` are allowed; the path is ignored",
+                        segment
+                    ),
+                );
+                return None;
+            }
+        }
+        Some(segments)
+    }
+
+    fn import_operation(
+        &mut self,
+        path: &str,
+        path_item: &Value,
+        method: &str,
+        operation: &Value,
+        segments: &[Segment],
+        pointer: &str,
+    ) {
+        let operation_name = match operation.get("operationId").and_then(Value::as_str) {
+            Some(operation_id) => type_name_of(operation_id),
+            None => type_name_of(&format!("{} {}", method, path)),
+        };
+
+        // parameters of the operation override parameters of the path
+        let mut parameters: Vec<(String, &Value)> = Vec::new();
+        for (parameters_pointer, item) in &[
+            (format!("/paths/{}/parameters", escape(path)), path_item),
+            (format!("{}/parameters", pointer), operation),
+        ] {
+            if let Some(item_parameters) = item.get("parameters").and_then(Value::as_array) {
+                for (i, parameter) in item_parameters.iter().enumerate() {
+                    let parameter = self.deref(parameter);
+                    parameters.retain(|(_, p)| {
+                        p["name"] != parameter["name"] || p["in"] != parameter["in"]
+                    });
+                    parameters.push((format!("{}/{}", parameters_pointer, i), parameter));
+                }
+            }
+        }
+
+        let mut components = Vec::new();
+        for segment in segments {
+            match segment {
+                Segment::Literal(literal) => {
+                    components.push(ast::ServiceRouteComponent::Literal(literal.clone()))
+                }
+                Segment::Param(name) => {
+                    let parameter = parameters
+                        .iter()
+                        .find(|(_, p)| p["in"] == "path" && p["name"] == name.as_str());
+                    let type_ident = match parameter {
+                        Some((parameter_pointer, parameter)) => self.parameter_type(
+                            parameter,
+                            parameter_pointer,
+                            &format!("{}{}", operation_name, type_name_of(name)),
+                        ),
+                        None => ast::TypeIdent::BuiltIn(ast::AtomType::Str),
+                    };
+                    components.push(ast::ServiceRouteComponent::Variable(ast::FieldDefPair {
+                        // the names of path parameters are not part of requests
+                        name: snake_case_ident(name),
+                        type_ident,
+                    }));
+                }
+            }
+        }
+        if let Some(rest) = operation.get("x-humblegen-rest") {
+            let rest_pointer = format!("{}/x-humblegen-rest", pointer);
+            let name = rest["name"].as_str().unwrap_or("rest");
+            let type_ident = self.parameter_type(
+                rest,
+                &rest_pointer,
+                &format!("{}{}", operation_name, type_name_of(name)),
+            );
+            components.push(ast::ServiceRouteComponent::Rest(ast::FieldDefPair {
+                name: snake_case_ident(name),
+                type_ident,
+            }));
+        }
+        if components.is_empty() {
+            self.warn(
+                pointer,
+                "routes must not be empty, the operation is ignored",
+            );
+            return;
+        }
+
+        let mut query_fields = Vec::new();
+        for (parameter_pointer, parameter) in &parameters {
+            let name = parameter["name"].as_str().unwrap_or_default();
+            match parameter["in"].as_str() {
+                Some("query") => {
+                    let field_name = self.field_name(name, parameter_pointer);
+                    let hint = format!("{}{}", operation_name, type_name_of(name));
+                    let mut type_ident = self.parameter_type(parameter, parameter_pointer, &hint);
+                    if parameter.get("required") != Some(&Value::Bool(true)) {
+                        type_ident = optional(type_ident);
+                    }
+                    query_fields.push(ast::FieldNode {
+                        pair: ast::FieldDefPair {
+                            name: field_name,
+                            type_ident,
+                        },
+                        doc_comment: description(parameter),
+                    });
+                }
+                Some("header")
+                    if name.eq_ignore_ascii_case("If-Match")
+                        && (method == "put" || method == "patch") => {}
+                Some("path") => {}
+                Some(location) => self.warn(
+                    parameter_pointer,
+                    format!(
+                        "{} parameters are not supported, `{}` is ignored",
+                        location, name
+                    ),
+                ),
+                None => {}
+            }
+        }
+        let query = match operation.get("x-humblegen-query") {
+            Some(query) => Some(self.type_of(
+                query,
+                &format!("{}/x-humblegen-query", pointer),
+                &format!("{}Query", operation_name),
+            )),
+            None if !query_fields.is_empty() => {
+                let name = self.unique_type_name(&format!("{}Query", operation_name));
+                self.types.push(ast::SpecItem::StructDef(ast::StructDef {
+                    name: name.clone(),
+                    fields: ast::StructFields(query_fields),
+                    doc_comment: None,
+                }));
+                Some(ast::TypeIdent::UserDefined(name))
+            }
+            None => None,
+        };
+
+        let body = match operation.get("requestBody") {
+            Some(request_body) => {
+                let request_body = self.deref(request_body);
+                let content_pointer = format!("{}/requestBody/content", pointer);
+                if method == "get" || method == "delete" {
+                    self.warn(
+                        &format!("{}/requestBody", pointer),
+                        format!(
+                            "{} requests cannot have a body, it is ignored",
+                            method.to_uppercase()
+                        ),
+                    );
+                    None
+                } else {
+                    Some(self.request_body(
+                        &request_body["content"],
+                        &content_pointer,
+                        &format!("{}Body", operation_name),
+                    ))
+                }
+            }
+            None => None,
+        };
+        let body = body.unwrap_or(ast::BodyType::Data(ast::TypeIdent::BuiltIn(
+            ast::AtomType::Empty,
+        )));
+
+        let route = match operation.get("x-humblegen-websocket") {
+            Some(messages) if method == "get" => {
+                let client_msg = self.type_of(
+                    &messages["client"],
+                    &format!("{}/x-humblegen-websocket/client", pointer),
+                    &format!("{}ClientMsg", operation_name),
+                );
+                let server_msg = self.type_of(
+                    &messages["server"],
+                    &format!("{}/x-humblegen-websocket/server", pointer),
+                    &format!("{}ServerMsg", operation_name),
+                );
+                ast::ServiceRoute::Ws {
+                    components,
+                    query,
+                    client_msg,
+                    server_msg,
+                }
+            }
+            _ => {
+                let ret = self.responses(operation, pointer, &operation_name);
+                match method {
+                    "get" => ast::ServiceRoute::Get {
+                        components,
+                        query,
+                        ret,
+                    },
+                    "delete" => ast::ServiceRoute::Delete {
+                        components,
+                        query,
+                        ret,
+                    },
+                    "post" => ast::ServiceRoute::Post {
+                        components,
+                        query,
+                        body,
+                        ret,
+                    },
+                    "put" => ast::ServiceRoute::Put {
+                        components,
+                        query,
+                        body,
+                        ret,
+                    },
+                    _ => ast::ServiceRoute::Patch {
+                        components,
+                        query,
+                        body,
+                        ret,
+                    },
+                }
+            }
+        };
+
+        let doc_comment = match (
+            operation.get("summary").and_then(Value::as_str),
+            description(operation),
+        ) {
+            (Some(summary), Some(description)) => {
+                Some(format!("{}\n\n{}", summary.trim(), description))
+            }
+            (Some(summary), None) => Some(summary.trim().to_owned()),
+            (None, description) => description,
+        };
+        let tag = operation
+            .pointer("/tags/0")
+            .and_then(Value::as_str)
+            .map(str::to_owned);
+        self.service(tag)
+            .endpoints
+            .push(ast::ServiceEndpoint { doc_comment, route });
+    }
+
+    fn parameter_type(&mut self, parameter: &Value, pointer: &str, hint: &str) -> ast::TypeIdent {
+        match parameter.get("schema") {
+            Some(schema) => self.type_of(schema, &format!("{}/schema", pointer), hint),
+            None => {
+                self.warn(
+                    pointer,
+                    "parameters without a schema cannot be expressed, using `str`",
+                );
+                ast::TypeIdent::BuiltIn(ast::AtomType::Str)
+            }
+        }
+    }
+
+    fn request_body(&mut self, content: &Value, pointer: &str, hint: &str) -> ast::BodyType {
+        let media_type = match self.media_type(content, pointer, &[]) {
+            Some(media_type) => media_type,
+            None => return ast::BodyType::Data(ast::TypeIdent::BuiltIn(ast::AtomType::Empty)),
+        };
+        let media_type_pointer = format!("{}/{}", pointer, escape(&media_type));
+        let schema = &content[media_type.as_str()]["schema"];
+        let schema_pointer = format!("{}/schema", media_type_pointer);
+        if is_json(&media_type) {
+            ast::BodyType::Data(self.type_of(schema, &schema_pointer, hint))
+        } else if media_type == "multipart/form-data" {
+            let schema = self.deref(schema);
+            let required = required(schema);
+            let mut parts = Vec::new();
+            if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+                for (name, property) in properties {
+                    let pointer = format!("{}/properties/{}", schema_pointer, escape(name));
+                    let part_name = self.field_name(name, &pointer);
+                    let property = self.deref(property);
+                    if property.get("format").and_then(Value::as_str) == Some("binary") {
+                        parts.push(ast::MultipartPart::File(part_name));
+                    } else {
+                        let mut type_ident = self.type_of(
+                            property,
+                            &pointer,
+                            &format!("{}{}", hint, type_name_of(name)),
+                        );
+                        if !required.contains(name.as_str()) {
+                            type_ident = optional(type_ident);
+                        }
+                        parts.push(ast::MultipartPart::Field(ast::FieldDefPair {
+                            name: part_name,
+                            type_ident,
+                        }));
+                    }
+                }
+            }
+            if parts.is_empty() {
+                self.warn(
+                    &schema_pointer,
+                    "multipart bodies without parts cannot be expressed, using `()`",
+                );
+                return ast::BodyType::Data(ast::TypeIdent::BuiltIn(ast::AtomType::Empty));
+            }
+            ast::BodyType::Multipart(parts)
+        } else {
+            if media_type == "application/x-www-form-urlencoded" {
+                self.warn(
+                    &media_type_pointer,
+                    "form bodies are not supported, the body is a raw `blob`",
+                );
+            }
+            ast::BodyType::Blob(media_type)
+        }
+    }
+
+    /// The return type of an operation, described by its successful response.
+    fn responses(
+        &mut self,
+        operation: &Value,
+        pointer: &str,
+        operation_name: &str,
+    ) -> ast::ReturnType {
+        let responses_pointer = format!("{}/responses", pointer);
+        let responses = match operation.get("responses").and_then(Value::as_object) {
+            Some(responses) => responses,
+            None => return ast::ReturnType::Data(ast::TypeIdent::BuiltIn(ast::AtomType::Empty)),
+        };
+        let mut successes: Vec<_> = responses
+            .iter()
+            .filter(|(code, _)| code.starts_with('2'))
+            .collect();
+        successes.sort_by_key(|(code, _)| code.as_str());
+
+        for (code, response) in responses {
+            let response = self.deref(response);
+            let is_error = code == "default" || code.starts_with('4') || code.starts_with('5');
+            let schema = response.pointer("/content/application~1json/schema");
+            let schema = schema.map(|schema| self.deref(schema));
+            if is_error && matches!(schema, Some(schema) if !is_error_response(schema)) {
+                self.warn(
+                    &format!("{}/{}", responses_pointer, escape(code)),
+                    "error responses are always `ErrorResponse`s, use a `result` return type for domain errors",
+                );
+            }
+        }
+
+        let (code, response) = match successes.as_slice() {
+            [] => {
+                self.warn(&responses_pointer, "no successful response, returning `()`");
+                return ast::ReturnType::Data(ast::TypeIdent::BuiltIn(ast::AtomType::Empty));
+            }
+            [(code, response), others @ ..] => {
+                for (other, _) in others {
+                    self.warn(
+                        &format!("{}/{}", responses_pointer, escape(other)),
+                        format!("only the `{}` response is used", code),
+                    );
+                }
+                (code.as_str(), self.deref(response))
+            }
+        };
+        let response_pointer = format!("{}/{}", responses_pointer, escape(code));
+        if code != "200" {
+            self.warn(
+                &response_pointer,
+                format!("responds with status 200 instead of {}", code),
+            );
+        }
+
+        let content_pointer = format!("{}/content", response_pointer);
+        let content = &response["content"];
+        // streams are sent as NDJSON or Server-Sent Events, whichever the client accepts
+        let media_type = match self.media_type(content, &content_pointer, &[NDJSON, SSE]) {
+            Some(media_type) => media_type,
+            None => return ast::ReturnType::Data(ast::TypeIdent::BuiltIn(ast::AtomType::Empty)),
+        };
+        let schema = &content[media_type.as_str()]["schema"];
+        let schema_pointer = format!("{}/{}/schema", content_pointer, escape(&media_type));
+        let hint = format!("{}Response", operation_name);
+        if is_json(&media_type) {
+            ast::ReturnType::Data(self.type_of(schema, &schema_pointer, &hint))
+        } else if media_type == NDJSON || media_type == SSE {
+            ast::ReturnType::Stream(self.type_of(schema, &schema_pointer, &hint))
+        } else {
+            ast::ReturnType::Blob(media_type)
+        }
+    }
+
+    /// The media type of a request or response body: JSON if possible, otherwise the first.
+    /// Alternatives are not supported, except for the `equivalent` media types.
+    fn media_type(
+        &mut self,
+        content: &Value,
+        pointer: &str,
+        equivalent: &[&str],
+    ) -> Option<String> {
+        let media_types: Vec<&String> = content.as_object()?.keys().collect();
+        let media_type: &String = media_types
+            .iter()
+            .find(|media_type| is_json(media_type))
+            .or_else(|| media_types.first())?;
+        for other in &media_types {
+            let alternative = *other != media_type
+                && !(equivalent.contains(&other.as_str())
+                    && equivalent.contains(&media_type.as_str()));
+            if alternative {
+                self.warn(
+                    &format!("{}/{}", pointer, escape(other)),
+                    format!(
+                        "alternative media types are not supported, using `{}`",
+                        media_type
+                    ),
+                );
+            }
+        }
+        // parameters such as `charset` cannot be expressed
+        let essence = media_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase();
+        if !is_media_type(&essence) {
+            self.warn(
+                &format!("{}/{}", pointer, escape(media_type)),
+                "invalid media type, using `application/octet-stream`",
+            );
+            return Some("application/octet-stream".to_owned());
+        }
+        Some(essence)
+    }
+
+    /// The service of operations tagged `tag`, or of untagged operations.
+    fn service(&mut self, tag: Option<String>) -> &mut ast::ServiceDef {
+        let tag = tag.unwrap_or_else(|| {
+            self.document
+                .pointer("/info/title")
+                .and_then(Value::as_str)
+                .unwrap_or("Api")
+                .to_owned()
+        });
+        let name = type_name_of(&tag);
+        let index = match self.services.iter().position(|s| s.name == name) {
+            Some(index) => index,
+            None => {
+                let doc_comment = self
+                    .document
+                    .get("tags")
+                    .and_then(Value::as_array)
+                    .and_then(|tags| tags.iter().find(|t| t["name"] == tag.as_str()))
+                    .and_then(description);
+                let name = self.unique_type_name(&name);
+                self.services.push(ast::ServiceDef {
+                    name,
+                    doc_comment,
+                    endpoints: Vec::new(),
+                });
+                self.services.len() - 1
+            }
+        };
+        &mut self.services[index]
+    }
+
+    /// Follows a local reference, e.g. to a parameter defined in `components`.
+    fn deref(&self, value: &'a Value) -> &'a Value {
+        let mut value = value;
+        // bounded to ignore cycles
+        for _ in 0..16 {
+            match value
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|reference| reference.strip_prefix('#'))
+                .and_then(|pointer| self.document.pointer(pointer))
+            {
+                Some(target) => value = target,
+                None => break,
+            }
+        }
+        value
+    }
+
+    fn unique_type_name(&mut self, name: &str) -> String {
+        let base = type_name_of(name);
+        let mut unique = base.clone();
+        for i in 2.. {
+            if self.type_names.insert(unique.clone()) {
+                break;
+            }
+            unique = format!("{}{}", base, i);
+        }
+        unique
+    }
+
+    /// The name of a field, which is also its JSON name, query parameter name or part name.
+    fn field_name(&mut self, name: &str, pointer: &str) -> String {
+        let field_name = snake_case_ident(name);
+        if field_name != name {
+            self.warn(
+                pointer,
+                format!(
+                    "`{}` is renamed to `{}`, which changes its encoding",
+                    name, field_name
+                ),
+            );
+        }
+        field_name
+    }
+
+    /// The name of a variant, which is also its JSON name.
+    fn variant_name(&mut self, name: &str, pointer: &str) -> String {
+        let variant_name = type_name_of(name);
+        if variant_name != name {
+            self.warn(
+                pointer,
+                format!(
+                    "`{}` is renamed to `{}`, which changes its encoding",
+                    name, variant_name
+                ),
+            );
+        }
+        variant_name
+    }
+}
+
+enum Segment {
+    Literal(String),
+    Param(String),
+}
+
+/// Rust keywords in snake case, which cannot be field names of generated structs.
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in",
+    "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+const NDJSON: &str = "application/x-ndjson";
+const SSE: &str = "text/event-stream";
+
+/// Whether a schema at the top level of `components/schemas` is a struct or enum,
+/// rather than a type that is not named in humble.
+fn defines_type(schema: &Value) -> bool {
+    if schema.get("$ref").is_some() || is_null(schema) || non_null(schema).is_some() {
+        false
+    } else if schema.get("enum").is_some() {
+        string_enum(schema).is_some()
+    } else if is_never(schema) {
+        true
+    } else if let Some((_, alternatives)) = alternatives(schema) {
+        alternatives.iter().filter(|a| !is_null(a)).count() > 1
+            && result_alternatives(alternatives).is_none()
+    } else if let Some(members) = schema.get("allOf").and_then(Value::as_array) {
+        members.len() > 1 || schema.get("properties").is_some()
+    } else {
+        is_object(schema)
+            && schema
+                .get("additionalProperties")
+                .and_then(Value::as_object)
+                .is_none()
+    }
+}
+
+fn defines_enum(schema: &Value) -> bool {
+    string_enum(schema).is_some() || alternatives(schema).is_some()
+}
+
+fn is_object(schema: &Value) -> bool {
+    schema.get("properties").is_some()
+        || schema.get("type").and_then(Value::as_str) == Some("object")
+}
+
+/// The values of an enum of strings.
+fn string_enum(schema: &Value) -> Option<Vec<&str>> {
+    schema
+        .get("enum")?
+        .as_array()?
+        .iter()
+        .filter(|value| !value.is_null())
+        .map(Value::as_str)
+        .collect()
+}
+
+/// The alternatives of a `oneOf` or `anyOf`, and which of the two it is.
+fn alternatives(schema: &Value) -> Option<(&'static str, &Vec<Value>)> {
+    match schema.get("oneOf").and_then(Value::as_array) {
+        Some(alternatives) => Some(("oneOf", alternatives)),
+        None => schema
+            .get("anyOf")
+            .and_then(Value::as_array)
+            .map(|alternatives| ("anyOf", alternatives)),
+    }
+}
+
+/// Whether the alternative is an enum of strings or an object with a single required property.
+fn is_tagged_alternative(alternative: &Value) -> bool {
+    if string_enum(alternative).is_some() {
+        return true;
+    }
+    let properties = match alternative.get("properties").and_then(Value::as_object) {
+        Some(properties) if properties.len() == 1 => properties,
+        _ => return false,
+    };
+    let required = required(alternative);
+    properties.keys().all(|key| required.contains(key.as_str()))
+}
+
+/// The schemas of `T` and `E` if the alternatives are those of a `result[T][E]`.
+fn result_alternatives(alternatives: &[Value]) -> Option<(&Value, &Value)> {
+    match alternatives {
+        [ok, err] if is_tagged_alternative(ok) && is_tagged_alternative(err) => Some((
+            ok.pointer("/properties/Ok")?,
+            err.pointer("/properties/Err")?,
+        )),
+        _ => None,
+    }
+}
+
+/// Whether the schema allows no values at all.
+fn is_never(schema: &Value) -> bool {
+    schema.get("not") == Some(&json!({})) && schema.get("type").is_none()
+}
+
+/// Whether the schema only allows `null`.
+fn is_null(schema: &Value) -> bool {
+    schema.get("type").and_then(Value::as_str) == Some("null")
+        || schema.get("enum") == Some(&json!([null]))
+}
+
+/// The schema without `null`, if it allows `null` in addition to other values.
+fn non_null(schema: &Value) -> Option<Value> {
+    let mut schema = schema.as_object()?.clone();
+    match schema.get("type") {
+        // OpenAPI 3.1, e.g. `type: [string, "null"]`
+        Some(Value::Array(types)) if types.iter().any(|t| t == "null") => {
+            let types: Vec<_> = types.iter().filter(|t| *t != "null").cloned().collect();
+            match types.as_slice() {
+                [single] => schema.insert("type".to_owned(), single.clone()),
+                _ => schema.insert("type".to_owned(), Value::Array(types)),
+            };
+            Some(Value::Object(schema))
+        }
+        _ if schema.get("nullable") == Some(&Value::Bool(true)) => {
+            schema.remove("nullable");
+            Some(Value::Object(schema))
+        }
+        _ => None,
+    }
+}
+
+fn required(schema: &Value) -> HashSet<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// Whether the schema is that of `humblegen_rt::service_protocol::ErrorResponse`.
+fn is_error_response(schema: &Value) -> bool {
+    schema.pointer("/properties/code").is_some() && schema.pointer("/properties/kind").is_some()
+}
+
+fn is_json(media_type: &str) -> bool {
+    media_type == "application/json" || media_type.ends_with("+json")
+}
+
+fn description(schema: &Value) -> Option<String> {
+    schema
+        .get("description")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|description| !description.is_empty())
+        .map(str::to_owned)
+}
+
+/// `option[T]`, unless `T` is already optional or empty if missing.
+fn optional(type_ident: ast::TypeIdent) -> ast::TypeIdent {
+    match type_ident {
+        ast::TypeIdent::Option(_) | ast::TypeIdent::List(_) | ast::TypeIdent::Map(_, _) => {
+            type_ident
+        }
+        _ => ast::TypeIdent::Option(Box::new(type_ident)),
+    }
+}
+
+/// A name that is a valid humble type name, e.g. `MonsterData` for `monster_data`.
+fn type_name_of(name: &str) -> String {
+    if is_camel_case_ident(name) {
+        return name.to_owned();
+    }
+    let pascal: String = to_pascal_case(name)
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect();
+    if pascal.starts_with(|c: char| c.is_ascii_uppercase()) {
+        pascal
+    } else {
+        format!("T{}", pascal)
+    }
+}
+
+/// A name that is a valid humble field name, e.g. `max_age` for `maxAge`
+/// and `type_` for `type`, which is a keyword in the generated Rust code.
+fn snake_case_ident(name: &str) -> String {
+    let snake = if is_snake_case_ident(name) {
+        name.to_owned()
+    } else {
+        let snake: String = to_snake_case(name)
+            .chars()
+            .filter(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || *c == '_')
+            .collect();
+        if snake.starts_with(|c: char| c.is_ascii_lowercase()) {
+            snake
+        } else {
+            format!("field_{}", snake)
+        }
+    };
+    if RUST_KEYWORDS.contains(&snake.as_str()) {
+        format!("{}_", snake)
+    } else {
+        snake
+    }
+}
+
+fn is_camel_case_ident(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_uppercase()) && s.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn is_snake_case_ident(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_lowercase())
+        && s.chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn is_kebab_case_ident(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_lowercase())
+        && s.chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+fn is_media_type(s: &str) -> bool {
+    let is_token = |t: &str| {
+        !t.is_empty()
+            && t.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "*.+-".contains(c))
+    };
+    match s.find('/') {
+        Some(i) => is_token(&s[..i]) && is_token(&s[i + 1..]),
+        None => false,
+    }
+}
+
+/// Escapes a JSON pointer token.
+fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
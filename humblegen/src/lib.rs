@@ -6,7 +6,18 @@ pub use ast::Spec;
 
 pub mod ast;
 pub mod backend;
+#[cfg(any(feature = "mock", feature = "proxy"))]
+mod endpoint;
+#[cfg(feature = "openapi")]
+pub mod import;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod parser;
+mod printer;
+#[cfg(feature = "proxy")]
+pub mod proxy;
+#[cfg(feature = "validate")]
+pub mod validate;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -24,6 +35,14 @@ pub enum LibError {
     IoError(#[from] io::Error),
     #[error(transparent)]
     ParseError(#[from] pest::error::Error<parser::Rule>),
+    #[error(
+        "backend '{backend}' does not support the query type of endpoint '{endpoint}': {reason}"
+    )]
+    UnsupportedQuery {
+        backend: &'static str,
+        endpoint: String,
+        reason: &'static str,
+    },
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
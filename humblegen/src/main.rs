@@ -3,21 +3,39 @@
 mod cli;
 
 use anyhow::{Context, Result};
+use std::path::Path;
 use structopt::StructOpt;
 
 fn main() -> Result<()> {
     let args = cli::CliArgs::from_args();
 
-    let spec_file = std::fs::File::open(&args.input).context(format!(
-        "unable to open specification file {:?}",
-        &args.input
-    ))?;
-    let spec = humblegen::parse(spec_file).context(format!(
-        "failed to parse specification file {:?}",
-        &args.input
-    ))?;
-
-    args.code_generator()?.generate(&spec, &args.output)?;
+    match args.command {
+        #[cfg(feature = "mock")]
+        Some(cli::Command::Mock(mock_args)) => {
+            let spec = parse_spec_file(&mock_args.input)?;
+            mock_args.run(spec)
+        }
+        #[cfg(feature = "proxy")]
+        Some(cli::Command::Proxy(proxy_args)) => {
+            let spec = parse_spec_file(&proxy_args.input)?;
+            proxy_args.run(spec)
+        }
+        #[cfg(feature = "openapi")]
+        Some(cli::Command::ImportOpenapi(import_args)) => import_args.run(),
+        None => {
+            let input = args.input.as_ref().expect("required without subcommand");
+            let output = args.output.as_ref().expect("required without subcommand");
+            let spec = parse_spec_file(input)?;
+            args.code_generator()?.generate(&spec, output)?;
+            Ok(())
+        }
+    }
+}
 
-    Ok(())
+fn parse_spec_file(input: &Path) -> Result<humblegen::Spec> {
+    let spec_file = std::fs::File::open(input)
+        .context(format!("unable to open specification file {:?}", input))?;
+    let spec = humblegen::parse(spec_file)
+        .context(format!("failed to parse specification file {:?}", input))?;
+    Ok(spec)
 }
@@ -0,0 +1,339 @@
+//! A mock server for the services of a humblespec that needs no generated code (`humblegen mock`).
+//!
+//! The server is assembled at runtime from the `humblegen_rt::server` building blocks that generated
+//! code uses, i.e., it speaks the same protocol as a generated server:
+//!
+//! - Route params, URL queries and request bodies are validated against the spec (see `validate`).
+//!   Invalid requests are rejected with the `ErrorResponse` a generated server would send.
+//! - Responses are random, but valid, values of the endpoints' return types (see `humblegen_rt::mock`).
+//!   They are deterministic: for a given seed, a request is always answered with the same response.
+//! - WebSocket endpoints answer every valid client message with a random server message.
+
+use crate::ast;
+use crate::endpoint::{self, Endpoint};
+use crate::validate::Encoding;
+
+use anyhow::Context;
+use futures::{SinkExt, StreamExt};
+use humblegen_rt::blob::{self, Blob};
+use humblegen_rt::handler::{HandlerResponse, ServiceError};
+use humblegen_rt::hyper::{Body, Request, Response};
+use humblegen_rt::mock::{Fake, FakeRng};
+use humblegen_rt::regexset_map::RegexSetMap;
+use humblegen_rt::server::{self, Route, Server, Service, ServiceConfig};
+use humblegen_rt::service_protocol::ErrorResponse;
+use humblegen_rt::stream::{self, ResponseStream};
+use humblegen_rt::websocket::{self, MessageSink, MessageStream};
+use humblegen_rt::{tracing, wire_format};
+use serde_json::Value;
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// Configuration of a mock server.
+#[derive(Debug)]
+pub struct MockConfig {
+    /// The seed of the fake data.
+    pub seed: u64,
+    /// The URL path prefix at which services are mounted, unless overridden in `mounts`.
+    pub root: String,
+    /// URL path prefixes of individual services, keyed by service name.
+    pub mounts: HashMap<String, String>,
+    /// Server-wide configuration, e.g. CORS.
+    pub server: server::Config,
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            root: "/api".to_owned(),
+            mounts: HashMap::new(),
+            server: server::Config::default(),
+        }
+    }
+}
+
+/// Builds a server that answers requests to the services of `spec` with fake data.
+///
+/// Services mounted at the same URL path prefix are served together.
+pub fn server(spec: ast::Spec, config: MockConfig) -> anyhow::Result<Server> {
+    let spec = Arc::new(spec);
+    let services = endpoint::mount_services(&spec, &config.root, &config.mounts)?
+        .into_iter()
+        .map(|(root, service_defs)| {
+            let routes = service_defs
+                .iter()
+                .flat_map(|service_def| &service_def.endpoints)
+                .map(|endpoint| mock_route(Endpoint::new(&spec, endpoint), config.seed));
+            Ok(Service {
+                name: service_defs
+                    .iter()
+                    .map(|service_def| service_def.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                regex: endpoint::root_regex(&root),
+                root,
+                routes: RegexSetMap::new(routes.collect())?,
+                config: ServiceConfig::default(),
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(Server {
+        services: RegexSetMap::new(services).context("invalid service configuration")?,
+        config: config.server,
+    })
+}
+
+/// Serves the services of `spec` with fake data at address `addr`.
+pub async fn listen_and_run_forever(
+    spec: ast::Spec,
+    config: MockConfig,
+    addr: &SocketAddr,
+) -> anyhow::Result<()> {
+    server::listen_and_run_forever(server(spec, config)?, addr).await
+}
+
+/// A random, but valid, value of `type_ident`, in its JSON representation.
+///
+/// Results are always `Ok`. Streams, multipart and blob bodies are not JSON values and faked as `null`.
+pub fn fake_value(spec: &ast::Spec, type_ident: &ast::TypeIdent, rng: &mut FakeRng) -> Value {
+    match type_ident {
+        ast::TypeIdent::BuiltIn(atom) => fake_atom(*atom, rng),
+        ast::TypeIdent::List(inner) => {
+            let len = rng.collection_len();
+            Value::Array(
+                (0..len)
+                    .map(|_| rng.nested(|rng| fake_value(spec, inner, rng)))
+                    .collect(),
+            )
+        }
+        ast::TypeIdent::Option(inner) => {
+            if rng.option_is_some() {
+                rng.nested(|rng| fake_value(spec, inner, rng))
+            } else {
+                Value::Null
+            }
+        }
+        ast::TypeIdent::Result(ok, _) => tagged("Ok", fake_value(spec, ok, rng)),
+        ast::TypeIdent::Map(key, inner) => {
+            let len = rng.collection_len();
+            Value::Object(
+                (0..len)
+                    .map(|_| {
+                        let key = match rng.nested(|rng| fake_value(spec, key, rng)) {
+                            Value::String(key) => key,
+                            key => key.to_string(),
+                        };
+                        (key, rng.nested(|rng| fake_value(spec, inner, rng)))
+                    })
+                    .collect(),
+            )
+        }
+        ast::TypeIdent::Tuple(tdef) => fake_tuple(spec, tdef.elements(), rng),
+        ast::TypeIdent::UserDefined(name) => fake_user_defined(spec, name, rng),
+    }
+}
+
+fn fake_atom(atom: ast::AtomType, rng: &mut FakeRng) -> Value {
+    fn json<T: Fake + serde::Serialize>(rng: &mut FakeRng) -> Value {
+        serde_json::to_value(T::fake(rng)).expect("built-in values are valid JSON")
+    }
+    match atom {
+        ast::AtomType::Empty => Value::Null,
+        ast::AtomType::Str => json::<String>(rng),
+        ast::AtomType::I32 => json::<i32>(rng),
+        ast::AtomType::U32 => json::<u32>(rng),
+        ast::AtomType::U8 => json::<u8>(rng),
+        ast::AtomType::F64 => json::<f64>(rng),
+        ast::AtomType::Bool => json::<bool>(rng),
+        ast::AtomType::DateTime => {
+            json::<humblegen_rt::chrono::DateTime<humblegen_rt::chrono::Utc>>(rng)
+        }
+        ast::AtomType::Date => json::<humblegen_rt::chrono::NaiveDate>(rng),
+        ast::AtomType::Uuid => json::<humblegen_rt::uuid::Uuid>(rng),
+        ast::AtomType::Bytes => Value::String(base64::encode(Vec::<u8>::fake(rng))),
+    }
+}
+
+fn fake_tuple(spec: &ast::Spec, elements: &[ast::TypeIdent], rng: &mut FakeRng) -> Value {
+    Value::Array(elements.iter().map(|e| fake_value(spec, e, rng)).collect())
+}
+
+fn fake_fields(spec: &ast::Spec, fields: &ast::StructFields, rng: &mut FakeRng) -> Value {
+    Value::Object(
+        fields
+            .iter()
+            .map(|f| {
+                (
+                    f.pair.name.clone(),
+                    fake_value(spec, &f.pair.type_ident, rng),
+                )
+            })
+            .collect(),
+    )
+}
+
+fn fake_user_defined(spec: &ast::Spec, name: &str, rng: &mut FakeRng) -> Value {
+    for item in spec.iter() {
+        match item {
+            ast::SpecItem::StructDef(sdef) if sdef.name == name => {
+                return fake_fields(spec, &sdef.fields, rng);
+            }
+            ast::SpecItem::EnumDef(edef) if edef.name == name => {
+                if edef.variants.is_empty() {
+                    return Value::Null;
+                }
+                let variant = &edef.variants[rng.variant(edef.variants.len())];
+                let content = match &variant.variant_type {
+                    ast::VariantType::Simple => return Value::String(variant.name.clone()),
+                    ast::VariantType::Newtype(type_ident) => fake_value(spec, type_ident, rng),
+                    // a tuple variant of one element is a newtype variant
+                    ast::VariantType::Tuple(tdef) if tdef.elements().len() == 1 => {
+                        fake_value(spec, &tdef.elements()[0], rng)
+                    }
+                    ast::VariantType::Tuple(tdef) => fake_tuple(spec, tdef.elements(), rng),
+                    ast::VariantType::Struct(fields) => fake_fields(spec, fields, rng),
+                };
+                return tagged(&variant.name, content);
+            }
+            _ => (),
+        }
+    }
+    Value::Null
+}
+
+fn tagged(tag: &str, content: Value) -> Value {
+    let mut object = serde_json::Map::new();
+    object.insert(tag.to_owned(), content);
+    Value::Object(object)
+}
+
+/// An endpoint answered with fake data.
+struct MockEndpoint {
+    endpoint: Endpoint,
+    seed: u64,
+}
+
+fn mock_route(endpoint: Endpoint, seed: u64) -> Route {
+    let name = endpoint.name.clone();
+    let method = endpoint.method.clone();
+    let template = endpoint.template.clone();
+    let regex = endpoint.regex.clone();
+    let mock = Arc::new(MockEndpoint { endpoint, seed });
+    Route {
+        name,
+        method,
+        template,
+        regex,
+        dispatcher: Box::new(move |req, captures| {
+            let mock = Arc::clone(&mock);
+            // the captures cannot be moved into the future
+            let route_params = mock.endpoint.check_route_params(&captures);
+            Box::pin(async move {
+                route_params?;
+                mock.respond(req).await
+            })
+        }),
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Folds `bytes` into the FNV-1a hash `hash`.
+///
+/// Unlike `DefaultHasher`, FNV-1a is specified, so the fake data of a request
+/// stays the same across Rust versions and platforms.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+impl MockEndpoint {
+    /// The seed of the fake data of the response to `req`.
+    fn request_seed(&self, req: &Request<Body>) -> u64 {
+        let path_and_query = req.uri().path_and_query().map_or("", |pq| pq.as_str());
+        let hash = fnv1a(FNV_OFFSET_BASIS, &self.seed.to_le_bytes());
+        let hash = fnv1a(hash, req.method().as_str().as_bytes());
+        // the path starts with `/`, which separates it from the method
+        fnv1a(hash, path_and_query.as_bytes())
+    }
+
+    async fn respond(
+        self: Arc<Self>,
+        mut req: Request<Body>,
+    ) -> Result<Response<Body>, ErrorResponse> {
+        self.endpoint.check_query(&req)?;
+        self.endpoint.check_body(&mut req).await?;
+
+        let spec = &self.endpoint.spec;
+        let mut rng = FakeRng::seeded(self.request_seed(&req));
+        if self.endpoint.websocket_messages.is_some() {
+            let response = websocket::upgrade_response(&req)?;
+            websocket::spawn_handler(req, move |incoming, outgoing| {
+                self.serve_websocket(incoming, outgoing, rng)
+            });
+            return Ok(response);
+        }
+
+        let response = match &self.endpoint.ret {
+            Some(ast::ReturnType::Stream(item)) => {
+                let len = rng.collection_len();
+                let items: Vec<_> = (0..len).map(|_| fake_value(spec, item, &mut rng)).collect();
+                stream::handler_response_to_hyper_response(
+                    stream::response_format(&req),
+                    Ok(ResponseStream::new(futures::stream::iter(items))),
+                )
+            }
+            Some(ast::ReturnType::Blob(media_type)) => {
+                blob::handler_response_to_hyper_response(media_type, Ok(Blob::fake(&mut rng)))
+            }
+            Some(ast::ReturnType::Data(ast::TypeIdent::Result(ok, _))) => {
+                server::result_handler_response_to_hyper_response_with_format(
+                    wire_format::response_format(&req),
+                    Ok(Ok::<_, Value>(fake_value(spec, ok, &mut rng))),
+                )
+            }
+            Some(ast::ReturnType::Data(ret)) => {
+                server::handler_response_to_hyper_response_with_format(
+                    wire_format::response_format(&req),
+                    Ok(fake_value(spec, ret, &mut rng)),
+                )
+            }
+            None => unreachable!("websocket routes are served above"),
+        };
+        Ok(response)
+    }
+
+    /// Answers every valid client message with a fake server message.
+    async fn serve_websocket(
+        self: Arc<Self>,
+        mut incoming: MessageStream<Value>,
+        mut outgoing: MessageSink<Value>,
+        mut rng: FakeRng,
+    ) -> HandlerResponse<()> {
+        let (client_msg, server_msg) = self
+            .endpoint
+            .websocket_messages
+            .as_ref()
+            .expect("only invoked for WebSocket endpoints");
+        while let Some(message) = incoming.next().await {
+            let error = match message {
+                Ok(message) => self.endpoint.check(client_msg, &message, Encoding::Json),
+                Err(e) => Some(e.to_string()),
+            };
+            if let Some(error) = error {
+                tracing::warn!(%error, "invalid client message");
+                continue;
+            }
+            let reply = fake_value(&self.endpoint.spec, server_msg, &mut rng);
+            outgoing
+                .send(reply)
+                .await
+                .map_err(|e| ServiceError::Internal(Box::new(e)))?;
+        }
+        Ok(())
+    }
+}
@@ -205,6 +205,7 @@ fn parse_service_rule_def(pair: pest::iterators::Pair<Rule>) -> ServiceRoute {
         Rule::http_post => parse_service_rule_post,
         Rule::http_put => parse_service_rule_put,
         Rule::http_patch => parse_service_rule_patch,
+        Rule::http_ws => parse_service_rule_ws,
         x => panic!("unexpected token {:?}", x),
     };
     nodes.next().unwrap(); // consume what we peeked
@@ -217,7 +218,7 @@ fn parse_service_rule_get(pair: &mut pest::iterators::Pairs<Rule>) -> ServiceRou
     ServiceRoute::Get {
         components: parse_http_route(pair.next().unwrap()),
         query: parse_http_query(pair),
-        ret: parse_type_ident(pair.next().unwrap()),
+        ret: parse_return_type_ident(pair.next().unwrap()),
     }
 }
 
@@ -225,7 +226,7 @@ fn parse_service_rule_delete(pair: &mut pest::iterators::Pairs<Rule>) -> Service
     ServiceRoute::Delete {
         components: parse_http_route(pair.next().unwrap()),
         query: parse_http_query(pair),
-        ret: parse_type_ident(pair.next().unwrap()),
+        ret: parse_return_type_ident(pair.next().unwrap()),
     }
 }
 
@@ -233,8 +234,8 @@ fn parse_service_rule_post(pair: &mut pest::iterators::Pairs<Rule>) -> ServiceRo
     ServiceRoute::Post {
         components: parse_http_route(pair.next().unwrap()),
         query: parse_http_query(pair),
-        body: parse_type_ident(pair.next().unwrap()),
-        ret: parse_type_ident(pair.next().unwrap()),
+        body: parse_request_body_ident(pair.next().unwrap()),
+        ret: parse_return_type_ident(pair.next().unwrap()),
     }
 }
 
@@ -242,8 +243,8 @@ fn parse_service_rule_put(pair: &mut pest::iterators::Pairs<Rule>) -> ServiceRou
     ServiceRoute::Put {
         components: parse_http_route(pair.next().unwrap()),
         query: parse_http_query(pair),
-        body: parse_type_ident(pair.next().unwrap()),
-        ret: parse_type_ident(pair.next().unwrap()),
+        body: parse_request_body_ident(pair.next().unwrap()),
+        ret: parse_return_type_ident(pair.next().unwrap()),
     }
 }
 
@@ -251,8 +252,17 @@ fn parse_service_rule_patch(pair: &mut pest::iterators::Pairs<Rule>) -> ServiceR
     ServiceRoute::Patch {
         components: parse_http_route(pair.next().unwrap()),
         query: parse_http_query(pair),
-        body: parse_type_ident(pair.next().unwrap()),
-        ret: parse_type_ident(pair.next().unwrap()),
+        body: parse_request_body_ident(pair.next().unwrap()),
+        ret: parse_return_type_ident(pair.next().unwrap()),
+    }
+}
+
+fn parse_service_rule_ws(pair: &mut pest::iterators::Pairs<Rule>) -> ServiceRoute {
+    ServiceRoute::Ws {
+        components: parse_http_route(pair.next().unwrap()),
+        query: parse_http_query(pair),
+        client_msg: parse_type_ident(pair.next().unwrap()),
+        server_msg: parse_type_ident(pair.next().unwrap()),
     }
 }
 
@@ -274,6 +284,15 @@ fn parse_http_route_segment(pair: pest::iterators::Pair<Rule>) -> ServiceRouteCo
             assert_eq!(nodes.next(), None);
             ret
         }
+        Rule::http_route_rest_arg => {
+            let mut nodes = comp.into_inner();
+            let ret = ServiceRouteComponent::Rest(FieldDefPair {
+                name: nodes.next().unwrap().as_str().to_string(),
+                type_ident: TypeIdent::BuiltIn(AtomType::Str),
+            });
+            assert_eq!(nodes.next(), None);
+            ret
+        }
         x => panic!("unexpected token {:?}", x),
     }
 }
@@ -290,6 +309,55 @@ fn parse_http_query(pairs: &mut pest::iterators::Pairs<Rule>) -> Option<TypeIden
     ret
 }
 
+/// Parse the return type of an endpoint, which may be a stream or a blob.
+fn parse_return_type_ident(pair: pest::iterators::Pair<Rule>) -> ReturnType {
+    let inner = pair.into_inner().next().unwrap();
+    match inner.as_rule() {
+        Rule::stream_type => {
+            ReturnType::Stream(parse_type_ident(inner.into_inner().next().unwrap()))
+        }
+        Rule::blob_type => ReturnType::Blob(parse_blob_type(inner)),
+        Rule::type_ident => ReturnType::Data(parse_type_ident(inner)),
+        _ => unreachable!("{:?}", inner),
+    }
+}
+
+/// Parse the request body of an endpoint, which may be a multipart form or a blob.
+fn parse_request_body_ident(pair: pest::iterators::Pair<Rule>) -> BodyType {
+    let inner = pair.into_inner().next().unwrap();
+    match inner.as_rule() {
+        Rule::multipart_body => {
+            BodyType::Multipart(inner.into_inner().map(parse_multipart_part).collect())
+        }
+        Rule::blob_type => BodyType::Blob(parse_blob_type(inner)),
+        Rule::type_ident => BodyType::Data(parse_type_ident(inner)),
+        _ => unreachable!("{:?}", inner),
+    }
+}
+
+/// Parse a `blob["media/type"]` into its media type.
+fn parse_blob_type(pair: pest::iterators::Pair<Rule>) -> String {
+    let literal = pair.into_inner().next().unwrap();
+    let media_type = literal.into_inner().next().unwrap();
+    media_type.as_str().to_string()
+}
+
+/// Parse a single part of a multipart body.
+fn parse_multipart_part(pair: pest::iterators::Pair<Rule>) -> MultipartPart {
+    let mut nodes = pair.into_inner();
+    let name = nodes.next().unwrap().as_str().to_string();
+    let ty = nodes.next().unwrap();
+    assert_eq!(nodes.next(), None);
+    match ty.as_rule() {
+        Rule::multipart_file => MultipartPart::File(name),
+        Rule::type_ident => MultipartPart::Field(FieldDefPair {
+            name,
+            type_ident: parse_type_ident(ty),
+        }),
+        _ => unreachable!("{:?}", ty),
+    }
+}
+
 /// Parse type identifier.
 fn parse_type_ident(pair: pest::iterators::Pair<Rule>) -> TypeIdent {
     let inner = pair.into_inner().next().unwrap();
@@ -0,0 +1,208 @@
+//! Pretty-printing of humble specs: `Spec`, `ServiceRoute` and `TypeIdent` display as humble source.
+
+use crate::ast::*;
+
+use std::fmt;
+
+const INDENT: &str = "    ";
+
+impl fmt::Display for Spec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, spec_item) in self.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            match spec_item {
+                SpecItem::StructDef(sdef) => {
+                    write_doc_comment(f, &sdef.doc_comment, "")?;
+                    write!(f, "struct {} ", sdef.name)?;
+                    write_struct_fields(f, &sdef.fields, "")?;
+                    writeln!(f)?;
+                }
+                SpecItem::EnumDef(edef) => {
+                    write_doc_comment(f, &edef.doc_comment, "")?;
+                    writeln!(f, "enum {} {{", edef.name)?;
+                    for variant in &edef.variants {
+                        write_doc_comment(f, &variant.doc_comment, INDENT)?;
+                        write!(f, "{}{}", INDENT, variant.name)?;
+                        match &variant.variant_type {
+                            VariantType::Simple => {}
+                            VariantType::Tuple(tdef) => write!(f, "{}", tdef)?,
+                            VariantType::Struct(fields) => {
+                                write!(f, " ")?;
+                                write_struct_fields(f, fields, INDENT)?;
+                            }
+                            VariantType::Newtype(type_ident) => write!(f, "({})", type_ident)?,
+                        }
+                        writeln!(f, ",")?;
+                    }
+                    writeln!(f, "}}")?;
+                }
+                SpecItem::ServiceDef(service) => {
+                    write_doc_comment(f, &service.doc_comment, "")?;
+                    writeln!(f, "service {} {{", service.name)?;
+                    for endpoint in &service.endpoints {
+                        write_doc_comment(f, &endpoint.doc_comment, INDENT)?;
+                        writeln!(f, "{}{},", INDENT, endpoint.route)?;
+                    }
+                    writeln!(f, "}}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_doc_comment(
+    f: &mut fmt::Formatter<'_>,
+    doc_comment: &Option<String>,
+    indent: &str,
+) -> fmt::Result {
+    if let Some(doc_comment) = doc_comment {
+        for line in doc_comment.lines() {
+            if line.is_empty() {
+                writeln!(f, "{}///", indent)?;
+            } else {
+                writeln!(f, "{}/// {}", indent, line)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_struct_fields(
+    f: &mut fmt::Formatter<'_>,
+    fields: &StructFields,
+    indent: &str,
+) -> fmt::Result {
+    if fields.0.is_empty() {
+        return write!(f, "{{}}");
+    }
+    writeln!(f, "{{")?;
+    let field_indent = format!("{}{}", indent, INDENT);
+    for field in fields.iter() {
+        write_doc_comment(f, &field.doc_comment, &field_indent)?;
+        if field.pair.is_embed() {
+            writeln!(f, "{}..{},", field_indent, field.pair.type_ident)?;
+        } else {
+            writeln!(f, "{}{},", field_indent, field.pair)?;
+        }
+    }
+    write!(f, "{}}}", indent)
+}
+
+impl fmt::Display for ServiceRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.http_method_as_str())?;
+        for component in self.components() {
+            match component {
+                ServiceRouteComponent::Literal(literal) => write!(f, "/{}", literal)?,
+                ServiceRouteComponent::Variable(pair) => write!(f, "/{{{}}}", pair)?,
+                ServiceRouteComponent::Rest(pair) => write!(f, "/{{{}..}}", pair)?,
+            }
+        }
+        if let Some(query) = self.query() {
+            write!(f, "?{{{}}}", query)?;
+        }
+        match self {
+            ServiceRoute::Get { ret, .. } | ServiceRoute::Delete { ret, .. } => {
+                write!(f, " -> {}", ret)
+            }
+            ServiceRoute::Post { body, ret, .. }
+            | ServiceRoute::Put { body, ret, .. }
+            | ServiceRoute::Patch { body, ret, .. } => write!(f, " -> {} -> {}", body, ret),
+            ServiceRoute::Ws {
+                client_msg,
+                server_msg,
+                ..
+            } => write!(f, " -> {} -> {}", client_msg, server_msg),
+        }
+    }
+}
+
+impl fmt::Display for FieldDefPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.type_ident)
+    }
+}
+
+impl fmt::Display for TypeIdent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeIdent::BuiltIn(atom) => write!(f, "{}", atom),
+            TypeIdent::List(inner) => write!(f, "list[{}]", inner),
+            TypeIdent::Option(inner) => write!(f, "option[{}]", inner),
+            TypeIdent::Result(ok, err) => write!(f, "result[{}][{}]", ok, err),
+            TypeIdent::Map(key, value) => write!(f, "map[{}][{}]", key, value),
+            TypeIdent::Tuple(tdef) => write!(f, "{}", tdef),
+            TypeIdent::UserDefined(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl fmt::Display for ReturnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReturnType::Data(type_ident) => write!(f, "{}", type_ident),
+            ReturnType::Stream(item) => write!(f, "stream[{}]", item),
+            ReturnType::Blob(media_type) => write!(f, "blob[\"{}\"]", media_type),
+        }
+    }
+}
+
+impl fmt::Display for BodyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BodyType::Data(type_ident) => write!(f, "{}", type_ident),
+            BodyType::Multipart(parts) => {
+                write!(f, "multipart {{ ")?;
+                for (i, part) in parts.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match part {
+                        MultipartPart::Field(pair) => write!(f, "{}", pair)?,
+                        MultipartPart::File(name) => write!(f, "{}: file", name)?,
+                    }
+                }
+                write!(f, " }}")
+            }
+            BodyType::Blob(media_type) => write!(f, "blob[\"{}\"]", media_type),
+        }
+    }
+}
+
+impl fmt::Display for TupleDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(")?;
+        for (i, element) in self.elements().iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", element)?;
+        }
+        // tuples need a comma to be distinguished from newtypes
+        if self.elements().len() == 1 {
+            write!(f, ",")?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl fmt::Display for AtomType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AtomType::Empty => "()",
+            AtomType::Str => "str",
+            AtomType::I32 => "i32",
+            AtomType::U32 => "u32",
+            AtomType::U8 => "u8",
+            AtomType::F64 => "f64",
+            AtomType::Bool => "bool",
+            AtomType::DateTime => "datetime",
+            AtomType::Date => "date",
+            AtomType::Uuid => "uuid",
+            AtomType::Bytes => "bytes",
+        })
+    }
+}
@@ -0,0 +1,608 @@
+//! A reverse proxy that checks the traffic to a backend against a humblespec (`humblegen proxy`).
+//!
+//! The backend does not need to be implemented with humblegen. The proxy routes each request to an
+//! endpoint of the spec like a generated server would, and validates
+//!
+//! - the route params, query and body of requests (see `Endpoint::check_body`), and
+//! - the bodies of responses: error responses must be `ErrorResponse`s,
+//!   all other responses values of the endpoint's return type (streams are validated line by line).
+//!
+//! Mismatches are logged, and, with `Mismatches::Reject`, answered with the error response a generated
+//! server would send (requests) or with status code 502 (`BadGateway`, responses).
+//!
+//! Bodies are buffered for validation within `ProxyConfig::body_limits`: larger request bodies are
+//! answered with status code 413 (`PayloadTooLarge`), larger response bodies with 502.
+//!
+//! Limitations: the proxy asks the backend for uncompressed responses, request bodies with a
+//! `Content-Encoding` are not validated, and WebSocket messages are forwarded without validation.
+
+use crate::ast;
+use crate::endpoint::{self, Endpoint};
+use crate::validate::Encoding;
+
+use futures::StreamExt;
+use humblegen_rt::hyper::client::HttpConnector;
+use humblegen_rt::hyper::header::{self, HeaderMap, HeaderValue};
+use humblegen_rt::hyper::server::conn::AddrStream;
+use humblegen_rt::hyper::service::{make_service_fn, service_fn};
+use humblegen_rt::hyper::{self, Body, Client, Request, Response, StatusCode, Uri};
+use humblegen_rt::regex::Regex;
+use humblegen_rt::serialization_helpers::read_body;
+use humblegen_rt::server::BodyReadLimits;
+use humblegen_rt::service_protocol::{ErrorResponse, RuntimeError, ToErrorResponse};
+use humblegen_rt::{stream, tracing};
+use serde_json::Value;
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// What the proxy does with requests and responses that do not match the spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mismatches {
+    /// Log mismatches and forward requests and responses anyway.
+    Log,
+    /// Log mismatches and answer them with an error response.
+    Reject,
+}
+
+/// Configuration of a validating proxy.
+#[derive(Debug)]
+pub struct ProxyConfig {
+    /// The backend, e.g. `http://127.0.0.1:3000`.
+    pub upstream: Uri,
+    /// The URL path prefix at which the backend serves services, unless overridden in `mounts`.
+    pub root: String,
+    /// URL path prefixes of individual services, keyed by service name.
+    pub mounts: HashMap<String, String>,
+    pub mismatches: Mismatches,
+    /// The limits of buffering request and response bodies for validation.
+    pub body_limits: BodyReadLimits,
+}
+
+impl ProxyConfig {
+    pub fn new(upstream: Uri) -> Self {
+        Self {
+            upstream,
+            root: "/api".to_owned(),
+            mounts: HashMap::new(),
+            mismatches: Mismatches::Log,
+            body_limits: BodyReadLimits::default(),
+        }
+    }
+}
+
+/// A reverse proxy validating requests and responses against a spec.
+pub struct Proxy {
+    services: Vec<ProxiedService>,
+    upstream: Uri,
+    mismatches: Mismatches,
+    body_limits: BodyReadLimits,
+    client: Client<HttpConnector>,
+}
+
+/// Why the body of a request is not forwarded as is.
+enum BodyError {
+    /// The body could not be read (completely), so it cannot be forwarded at all.
+    Unreadable(ErrorResponse),
+    /// The body does not match the spec.
+    Invalid(ErrorResponse),
+}
+
+struct ProxiedService {
+    root: String,
+    regex: Regex,
+    endpoints: Vec<Arc<Endpoint>>,
+}
+
+/// Headers that apply to a single connection, and are not forwarded.
+const HOP_BY_HOP_HEADERS: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+impl Proxy {
+    pub fn new(spec: ast::Spec, config: ProxyConfig) -> anyhow::Result<Self> {
+        if config.upstream.scheme_str() != Some("http") || config.upstream.authority().is_none() {
+            anyhow::bail!(
+                "upstream must be an http:// URL, e.g. http://127.0.0.1:3000, got {}",
+                config.upstream
+            );
+        }
+        let spec = Arc::new(spec);
+        let services = endpoint::mount_services(&spec, &config.root, &config.mounts)?
+            .into_iter()
+            .map(|(root, service_defs)| ProxiedService {
+                regex: endpoint::root_regex(&root),
+                root,
+                endpoints: service_defs
+                    .iter()
+                    .flat_map(|service_def| &service_def.endpoints)
+                    .map(|endpoint| Arc::new(Endpoint::new(&spec, endpoint)))
+                    .collect(),
+            })
+            .collect();
+        Ok(Self {
+            services,
+            upstream: config.upstream,
+            mismatches: config.mismatches,
+            body_limits: config.body_limits,
+            client: Client::new(),
+        })
+    }
+
+    /// Forwards a request from `remote_addr` to the upstream, validating request and response.
+    pub async fn handle(&self, mut req: Request<Body>, remote_addr: SocketAddr) -> Response<Body> {
+        let mut endpoint = match self.check_request(&req) {
+            Ok(endpoint) => endpoint,
+            Err(error_response) => match self.request_mismatch(&req, error_response) {
+                Some(response) => return response,
+                None => None,
+            },
+        };
+        let body_checked = match &endpoint {
+            Some(endpoint) => self.check_request_body(endpoint, &mut req).await,
+            None => Ok(()),
+        };
+        match body_checked {
+            Ok(()) => {}
+            Err(BodyError::Unreadable(error_response)) => {
+                return error_response.to_hyper_response()
+            }
+            Err(BodyError::Invalid(error_response)) => {
+                if let Some(response) = self.request_mismatch(&req, error_response) {
+                    return response;
+                }
+                endpoint = None;
+            }
+        }
+
+        let upgrade = matches!(&endpoint, Some(e) if e.websocket_messages.is_some())
+            && req.headers().contains_key(header::UPGRADE);
+        let method = req.method().clone();
+        let uri = req.uri().clone();
+        let (upstream_req, client_body) = self.upstream_request(req, remote_addr, upgrade);
+        let response = match self.client.request(upstream_req).await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::error!(%method, %uri, error = %e, "upstream unavailable");
+                return RuntimeError::BadGateway(format!("upstream unavailable: {}", e))
+                    .to_error_response()
+                    .to_hyper_response();
+            }
+        };
+
+        if upgrade && response.status() == StatusCode::SWITCHING_PROTOCOLS {
+            return splice_upgraded(client_body, response);
+        }
+        let endpoint = match endpoint {
+            Some(endpoint) => endpoint,
+            None => return without_hop_by_hop_headers(response, false),
+        };
+        let (mut parts, body) = response.into_parts();
+        remove_hop_by_hop_headers(&mut parts.headers, false);
+
+        if let Some(ast::ReturnType::Stream(_)) = endpoint.ret {
+            if parts.status.is_success() {
+                let body = self.check_stream(endpoint, body, &parts.headers, method, uri);
+                return Response::from_parts(parts, body);
+            }
+        }
+
+        // responses to HEAD requests have no body
+        if method == hyper::Method::HEAD {
+            return Response::from_parts(parts, body);
+        }
+        let body = match read_body(&parts.headers, body, self.body_limits).await {
+            Ok(body) => body,
+            Err(e) => {
+                let reason = match e {
+                    RuntimeError::PayloadTooLarge { max_body_size } => {
+                        format!("upstream response exceeds {} bytes", max_body_size)
+                    }
+                    RuntimeError::PostBodyReadTimeout => {
+                        "reading upstream response timed out".to_owned()
+                    }
+                    RuntimeError::PostBodyReadError(e) => {
+                        format!("reading upstream response: {}", e)
+                    }
+                    e => format!("reading upstream response: {:?}", e),
+                };
+                tracing::error!(%method, %uri, %reason, "upstream response not forwarded");
+                return RuntimeError::BadGateway(reason)
+                    .to_error_response()
+                    .to_hyper_response();
+            }
+        };
+        let error = if parts.headers.contains_key(header::CONTENT_ENCODING) {
+            None
+        } else {
+            endpoint.check_response(parts.status, &parts.headers, &body)
+        };
+        if let Some(error) = error {
+            tracing::warn!(
+                %method,
+                %uri,
+                endpoint = %endpoint.name,
+                route = %endpoint.template,
+                status = parts.status.as_u16(),
+                %error,
+                "response does not match the spec"
+            );
+            if self.mismatches == Mismatches::Reject {
+                return RuntimeError::BadGateway(format!("invalid upstream response: {}", error))
+                    .to_error_response()
+                    .to_hyper_response();
+            }
+        }
+        Response::from_parts(parts, Body::from(body))
+    }
+
+    /// Routes a request and validates its route params and query.
+    ///
+    /// Returns `None` for requests that are not validated, e.g. CORS preflight requests.
+    fn check_request(&self, req: &Request<Body>) -> Result<Option<Arc<Endpoint>>, ErrorResponse> {
+        if req.method() == hyper::Method::OPTIONS {
+            return Ok(None);
+        }
+        let path = req.uri().path();
+        let (service, suffix) = self
+            .services
+            .iter()
+            .find_map(|service| {
+                let captures = service.regex.captures(path)?;
+                Some((service, captures["suffix"].to_owned()))
+            })
+            .ok_or_else(|| RuntimeError::NoServiceMounted.to_error_response())?;
+        let matching: Vec<_> = service
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.regex.is_match(&suffix))
+            .collect();
+        if matching.is_empty() {
+            return Err(RuntimeError::NoRouteMountedInService {
+                service: service.root.clone(),
+            }
+            .to_error_response());
+        }
+        // like a generated server, HEAD requests are routed to GET endpoints
+        let method = match req.method() {
+            &hyper::Method::HEAD => &hyper::Method::GET,
+            method => method,
+        };
+        let endpoint = match matching.iter().find(|e| e.method == method) {
+            Some(endpoint) => Arc::clone(endpoint),
+            None => {
+                return Err(RuntimeError::MethodNotAllowed {
+                    service: service.root.clone(),
+                    allowed: matching.iter().map(|e| e.method.to_string()).collect(),
+                }
+                .to_error_response())
+            }
+        };
+
+        let captures = endpoint
+            .regex
+            .captures(&suffix)
+            .expect("route matched before");
+        endpoint.check_route_params(&captures)?;
+        endpoint.check_query(req)?;
+        Ok(Some(endpoint))
+    }
+
+    /// Buffers and validates the body of a request to `endpoint`.
+    async fn check_request_body(
+        &self,
+        endpoint: &Endpoint,
+        req: &mut Request<Body>,
+    ) -> Result<(), BodyError> {
+        if endpoint.body.is_none() || req.headers().contains_key(header::CONTENT_ENCODING) {
+            return Ok(());
+        }
+        let body = std::mem::take(req.body_mut());
+        let body = read_body(req.headers(), body, self.body_limits)
+            .await
+            .map_err(|e| BodyError::Unreadable(e.to_error_response()))?;
+        // validation consumes the body, forward a copy of it
+        let mut copy = Request::new(Body::from(body.clone()));
+        *copy.headers_mut() = req.headers().clone();
+        copy.extensions_mut().insert(self.body_limits);
+        *req.body_mut() = Body::from(body);
+        endpoint
+            .check_body(&mut copy)
+            .await
+            .map_err(BodyError::Invalid)
+    }
+
+    /// Logs a request that does not match the spec, and answers it with `Mismatches::Reject`.
+    fn request_mismatch(
+        &self,
+        req: &Request<Body>,
+        error_response: ErrorResponse,
+    ) -> Option<Response<Body>> {
+        tracing::warn!(
+            method = %req.method(),
+            uri = %req.uri(),
+            error = ?error_response.kind,
+            "request does not match the spec"
+        );
+        match self.mismatches {
+            Mismatches::Reject => Some(error_response.to_hyper_response()),
+            Mismatches::Log => None,
+        }
+    }
+
+    /// The request to forward to the upstream, and the body of `req` if it is to be upgraded.
+    fn upstream_request(
+        &self,
+        req: Request<Body>,
+        remote_addr: SocketAddr,
+        upgrade: bool,
+    ) -> (Request<Body>, Option<Body>) {
+        let (mut parts, body) = req.into_parts();
+        let mut uri = hyper::http::uri::Parts::from(self.upstream.clone());
+        uri.path_and_query = parts.uri.path_and_query().cloned();
+        parts.uri = Uri::from_parts(uri).expect("upstream and path are valid");
+
+        remove_hop_by_hop_headers(&mut parts.headers, upgrade);
+        parts.headers.remove(header::HOST);
+        // compressed response bodies cannot be validated
+        parts.headers.remove(header::ACCEPT_ENCODING);
+        let forwarded_for = match parts
+            .headers
+            .get("x-forwarded-for")
+            .and_then(|v| v.to_str().ok())
+        {
+            Some(forwarded_for) => format!("{}, {}", forwarded_for, remote_addr.ip()),
+            None => remote_addr.ip().to_string(),
+        };
+        if let Ok(forwarded_for) = HeaderValue::from_str(&forwarded_for) {
+            parts.headers.insert("x-forwarded-for", forwarded_for);
+        }
+
+        if upgrade {
+            (Request::from_parts(parts, Body::empty()), Some(body))
+        } else {
+            (Request::from_parts(parts, body), None)
+        }
+    }
+
+    /// Validates the items of a streamed response as they pass through.
+    fn check_stream(
+        &self,
+        endpoint: Arc<Endpoint>,
+        body: Body,
+        headers: &HeaderMap,
+        method: hyper::Method,
+        uri: Uri,
+    ) -> Body {
+        let checker = StreamChecker {
+            sse: content_type_is(headers, stream::SSE_MEDIA_TYPE),
+            reject: self.mismatches == Mismatches::Reject,
+            endpoint,
+            method,
+            uri,
+            line: Vec::new(),
+        };
+        let checked = futures::stream::unfold(Some((body, checker)), |state| async move {
+            let (mut body, mut checker) = state?;
+            match body.next().await {
+                Some(Ok(chunk)) => match checker.feed(&chunk) {
+                    Ok(()) => Some((Ok(chunk), Some((body, checker)))),
+                    Err(e) => Some((Err(e), None)),
+                },
+                Some(Err(e)) => Some((Err(e.into()), None)),
+                None => checker.finish().err().map(|e| (Err(e), None)),
+            }
+        });
+        Body::wrap_stream(checked)
+    }
+}
+
+type StreamError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Validates a streamed response line by line.
+struct StreamChecker {
+    endpoint: Arc<Endpoint>,
+    sse: bool,
+    reject: bool,
+    method: hyper::Method,
+    uri: Uri,
+    /// The incomplete line at the end of the data received so far.
+    line: Vec<u8>,
+}
+
+impl StreamChecker {
+    /// Validates the lines completed by `chunk`.
+    /// Fails with the first invalid line with `Mismatches::Reject`, which aborts the response,
+    /// as its status has been sent already.
+    fn feed(&mut self, chunk: &[u8]) -> Result<(), StreamError> {
+        for byte in chunk {
+            if *byte == b'\n' {
+                self.check_line()?;
+            } else {
+                self.line.push(*byte);
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates the last line, which need not end with a newline.
+    fn finish(&mut self) -> Result<(), StreamError> {
+        if self.line.is_empty() {
+            Ok(())
+        } else {
+            self.check_line()
+        }
+    }
+
+    fn check_line(&mut self) -> Result<(), StreamError> {
+        let item = match &self.endpoint.ret {
+            Some(ast::ReturnType::Stream(item)) => item,
+            _ => unreachable!("only invoked for streams"),
+        };
+        let error = match std::str::from_utf8(&self.line) {
+            Ok(line) => self.endpoint.check_stream_line(item, line, self.sse),
+            Err(e) => Some(e.to_string()),
+        };
+        self.line.clear();
+        match error {
+            Some(error) => {
+                tracing::warn!(
+                    method = %self.method,
+                    uri = %self.uri,
+                    endpoint = %self.endpoint.name,
+                    route = %self.endpoint.template,
+                    %error,
+                    "streamed item does not match the spec"
+                );
+                if self.reject {
+                    Err(error.into())
+                } else {
+                    Ok(())
+                }
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl Endpoint {
+    /// Validates a complete response body, returning a description of all mismatches if it is invalid.
+    ///
+    /// Error responses must be `ErrorResponse`s; successful responses must be values of the return type.
+    /// Bodies in formats other than JSON, blobs and WebSocket upgrades are not validated.
+    fn check_response(
+        &self,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Option<String> {
+        if self.websocket_messages.is_some()
+            || status == StatusCode::NOT_MODIFIED
+            || status == StatusCode::NO_CONTENT
+        {
+            return None;
+        }
+        if !status.is_success() {
+            return serde_json::from_slice::<ErrorResponse>(body)
+                .err()
+                .map(|e| format!("invalid error response: {}", e));
+        }
+        match &self.ret {
+            None | Some(ast::ReturnType::Blob(_)) => None,
+            Some(ast::ReturnType::Stream(item)) => {
+                let sse = content_type_is(headers, stream::SSE_MEDIA_TYPE);
+                std::str::from_utf8(body)
+                    .map_err(|e| e.to_string())
+                    .map(|body| {
+                        body.lines()
+                            .filter_map(|line| self.check_stream_line(item, line, sse))
+                            .next()
+                    })
+                    .unwrap_or_else(Some)
+            }
+            Some(ast::ReturnType::Data(ret)) if content_type_is(headers, "application/json") => {
+                match serde_json::from_slice::<Value>(body) {
+                    Ok(value) => self.check(ret, &value, Encoding::Json),
+                    Err(e) => Some(format!("invalid JSON: {}", e)),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Validates a line of a streamed response (NDJSON or Server-Sent Events).
+    fn check_stream_line(&self, item: &ast::TypeIdent, line: &str, sse: bool) -> Option<String> {
+        let json = if sse {
+            // comments, event types and ids are not items
+            line.strip_prefix("data:")?
+        } else {
+            line
+        };
+        if json.trim().is_empty() {
+            return None;
+        }
+        match serde_json::from_str::<Value>(json) {
+            Ok(value) => self.check(item, &value, Encoding::Json),
+            Err(e) => Some(format!("invalid JSON: {}", e)),
+        }
+    }
+}
+
+/// Whether the `Content-Type` header is `media_type` (ignoring parameters).
+fn content_type_is(headers: &HeaderMap, media_type: &str) -> bool {
+    match headers.get(header::CONTENT_TYPE).map(|v| v.to_str()) {
+        Some(Ok(content_type)) => content_type
+            .split(';')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .eq_ignore_ascii_case(media_type),
+        _ => false,
+    }
+}
+
+/// Serves `proxy` at address `addr`.
+pub async fn listen_and_run_forever(proxy: Proxy, addr: &SocketAddr) -> anyhow::Result<()> {
+    let proxy = Arc::new(proxy);
+    let make_service = make_service_fn(move |conn: &AddrStream| {
+        let proxy = Arc::clone(&proxy);
+        let remote_addr = conn.remote_addr();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let proxy = Arc::clone(&proxy);
+                async move { Ok::<_, Infallible>(proxy.handle(req, remote_addr).await) }
+            }))
+        }
+    });
+    hyper::Server::try_bind(addr)?.serve(make_service).await?;
+    Ok(())
+}
+
+fn remove_hop_by_hop_headers(headers: &mut HeaderMap, upgrade: bool) {
+    for name in HOP_BY_HOP_HEADERS {
+        if upgrade && (*name == "connection" || *name == "upgrade") {
+            continue;
+        }
+        headers.remove(*name);
+    }
+}
+
+fn without_hop_by_hop_headers(mut response: Response<Body>, upgrade: bool) -> Response<Body> {
+    remove_hop_by_hop_headers(response.headers_mut(), upgrade);
+    response
+}
+
+/// Answers the client's upgrade request with the upstream's `101 Switching Protocols` response,
+/// and copies data between both upgraded connections until either side closes.
+fn splice_upgraded(client_body: Option<Body>, response: Response<Body>) -> Response<Body> {
+    let (parts, upstream_body) = response.into_parts();
+    let client_body = client_body.expect("upgrade requests keep the client body");
+    humblegen_rt::tokio::spawn(async move {
+        let upgraded =
+            futures::future::try_join(client_body.on_upgrade(), upstream_body.on_upgrade());
+        let (client, upstream) = match upgraded.await {
+            Ok(upgraded) => upgraded,
+            Err(e) => {
+                tracing::warn!(error = %e, "upgrading the connection failed");
+                return;
+            }
+        };
+        let (mut client_read, mut client_write) = tokio::io::split(client);
+        let (mut upstream_read, mut upstream_write) = tokio::io::split(upstream);
+        let _ = futures::future::select(
+            Box::pin(tokio::io::copy(&mut client_read, &mut upstream_write)),
+            Box::pin(tokio::io::copy(&mut upstream_read, &mut client_write)),
+        )
+        .await;
+    });
+    Response::from_parts(parts, Body::empty())
+}
@@ -0,0 +1,364 @@
+//! Validation of JSON values against the types of a humblespec (see `Spec::validate_value`).
+//!
+//! A value is valid if the generated Rust type deserializes it, i.e., values are checked against
+//! the serde representation of the generated types:
+//!
+//! - structs are objects, whose `option`, `list` and `map` fields may be missing, and unknown fields are ignored,
+//! - enum variants are externally tagged (`"Simple"`, `{"Newtype": ..}`, `{"Tuple": [..]}`, `{"Struct": {..}}`),
+//! - results are `{"Ok": ..}` or `{"Err": ..}`,
+//! - `datetime`, `date`, `uuid` and `bytes` are strings in RFC 3339, ISO 8601, hyphenated and base64 format.
+//!
+//! URL queries decoded from bracket notation carry all built-in values as strings,
+//! which are parsed like route params, and lists of built-in values whose key occurs only once
+//! as a single string (see `Encoding::Query`).
+
+use crate::ast;
+
+use humblegen_rt::serialization_helpers::RouteParam;
+use serde_json::Value;
+
+use std::convert::TryFrom;
+use std::fmt;
+
+/// A mismatch between a value and its humblespec type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueError {
+    /// JSON pointer (RFC 6901) to the mismatching value, empty for the value itself.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.pointer, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ValueError {}
+
+impl ast::Spec {
+    /// Validates a JSON value against a type of this spec, returning all mismatches.
+    ///
+    /// The value is valid, i.e., the result is empty, if code generated from this spec accepts it.
+    /// For example, `TypeIdent::UserDefined("Monster".to_owned())` validates a `Monster`.
+    pub fn validate_value(&self, type_ident: &ast::TypeIdent, value: &Value) -> Vec<ValueError> {
+        validate(self, type_ident, value, Encoding::Json)
+    }
+}
+
+/// The representation of built-in values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    /// JSON, e.g. request bodies.
+    Json,
+    /// Strings, e.g. URL queries decoded from bracket notation.
+    #[cfg_attr(not(any(feature = "mock", feature = "proxy")), allow(dead_code))]
+    Query,
+}
+
+/// Validates `value` against `type_ident`, returning all mismatches.
+pub(crate) fn validate(
+    spec: &ast::Spec,
+    type_ident: &ast::TypeIdent,
+    value: &Value,
+    encoding: Encoding,
+) -> Vec<ValueError> {
+    let mut validator = Validator {
+        spec,
+        encoding,
+        errors: Vec::new(),
+    };
+    validator.check(type_ident, value, "");
+    validator.errors
+}
+
+/// Parses a built-in value from its string representation in route params and URL queries.
+pub(crate) fn parse_atom(atom: ast::AtomType, value: &str) -> Result<(), String> {
+    fn parse<T: RouteParam>(value: &str) -> Result<(), String> {
+        T::parse_route_param(value).map(drop)
+    }
+    match atom {
+        ast::AtomType::Empty if value.is_empty() => Ok(()),
+        ast::AtomType::Empty => Err(format!("expected an empty value, got {:?}", value)),
+        ast::AtomType::Str => Ok(()),
+        ast::AtomType::I32 => parse::<i32>(value),
+        ast::AtomType::U32 => parse::<u32>(value),
+        ast::AtomType::U8 => parse::<u8>(value),
+        ast::AtomType::F64 => parse::<f64>(value),
+        ast::AtomType::Bool => parse::<bool>(value),
+        ast::AtomType::DateTime => {
+            parse::<humblegen_rt::chrono::DateTime<humblegen_rt::chrono::Utc>>(value)
+        }
+        ast::AtomType::Date => parse::<humblegen_rt::chrono::NaiveDate>(value),
+        ast::AtomType::Uuid => parse::<humblegen_rt::uuid::Uuid>(value),
+        ast::AtomType::Bytes => base64::decode(value)
+            .map(drop)
+            .map_err(|e| format!("expected base64-encoded bytes, got {:?}: {}", value, e)),
+    }
+}
+
+struct Validator<'a> {
+    spec: &'a ast::Spec,
+    encoding: Encoding,
+    errors: Vec<ValueError>,
+}
+
+impl<'a> Validator<'a> {
+    fn error(&mut self, pointer: &str, message: String) {
+        self.errors.push(ValueError {
+            pointer: pointer.to_owned(),
+            message,
+        });
+    }
+
+    fn mismatch(&mut self, pointer: &str, expected: &str, value: &Value) {
+        self.error(
+            pointer,
+            format!("expected {}, got {}", expected, describe(value)),
+        );
+    }
+
+    fn check(&mut self, type_ident: &ast::TypeIdent, value: &Value, pointer: &str) {
+        match type_ident {
+            ast::TypeIdent::BuiltIn(atom) => self.check_atom(*atom, value, pointer),
+            ast::TypeIdent::List(inner) => match value {
+                Value::Array(items) => {
+                    for (idx, item) in items.iter().enumerate() {
+                        self.check(inner, item, &child(pointer, &idx.to_string()));
+                    }
+                }
+                // a list of built-in values whose key occurs only once in a URL query
+                Value::String(_)
+                    if self.encoding == Encoding::Query
+                        && matches!(
+                            **inner,
+                            ast::TypeIdent::BuiltIn(atom) if !matches!(atom, ast::AtomType::Empty)
+                        ) =>
+                {
+                    self.check(inner, value, &child(pointer, "0"))
+                }
+                _ => self.mismatch(pointer, "a list", value),
+            },
+            ast::TypeIdent::Option(inner) => {
+                if !value.is_null() {
+                    self.check(inner, value, pointer);
+                }
+            }
+            ast::TypeIdent::Result(ok, err) => match tagged(value) {
+                Some(("Ok", inner)) => self.check(ok, inner, &child(pointer, "Ok")),
+                Some(("Err", inner)) => self.check(err, inner, &child(pointer, "Err")),
+                _ => self.mismatch(pointer, "{\"Ok\": ..} or {\"Err\": ..}", value),
+            },
+            ast::TypeIdent::Map(key, inner) => match value {
+                Value::Object(entries) => {
+                    for (k, v) in entries {
+                        let entry_pointer = child(pointer, k);
+                        self.check_key(key, k, &entry_pointer);
+                        self.check(inner, v, &entry_pointer);
+                    }
+                }
+                _ => self.mismatch(pointer, "a map", value),
+            },
+            ast::TypeIdent::Tuple(tdef) => self.check_tuple(tdef.elements(), value, pointer),
+            ast::TypeIdent::UserDefined(name) => self.check_user_defined(name, value, pointer),
+        }
+    }
+
+    fn check_atom(&mut self, atom: ast::AtomType, value: &Value, pointer: &str) {
+        let valid = match (self.encoding, atom, value) {
+            (Encoding::Query, _, Value::String(s))
+            | (_, ast::AtomType::DateTime, Value::String(s))
+            | (_, ast::AtomType::Date, Value::String(s))
+            | (_, ast::AtomType::Uuid, Value::String(s))
+            | (_, ast::AtomType::Bytes, Value::String(s)) => {
+                if let Err(e) = parse_atom(atom, s) {
+                    self.error(pointer, e);
+                }
+                return;
+            }
+            (_, ast::AtomType::Empty, Value::Null) => true,
+            (_, ast::AtomType::Str, Value::String(_)) => true,
+            (_, ast::AtomType::I32, Value::Number(n)) => {
+                n.as_i64().and_then(|n| i32::try_from(n).ok()).is_some()
+            }
+            (_, ast::AtomType::U32, Value::Number(n)) => {
+                n.as_u64().and_then(|n| u32::try_from(n).ok()).is_some()
+            }
+            (_, ast::AtomType::U8, Value::Number(n)) => {
+                n.as_u64().and_then(|n| u8::try_from(n).ok()).is_some()
+            }
+            (_, ast::AtomType::F64, Value::Number(_)) => true,
+            (_, ast::AtomType::Bool, Value::Bool(_)) => true,
+            _ => false,
+        };
+        if !valid {
+            self.mismatch(pointer, atom_description(atom), value);
+        }
+    }
+
+    /// Map keys are strings, which are parsed into built-in key types.
+    fn check_key(&mut self, key_type: &ast::TypeIdent, key: &str, pointer: &str) {
+        if let ast::TypeIdent::BuiltIn(atom) = key_type {
+            if let Err(e) = parse_atom(*atom, key) {
+                self.error(pointer, format!("invalid key: {}", e));
+            }
+        }
+    }
+
+    fn check_tuple(&mut self, elements: &[ast::TypeIdent], value: &Value, pointer: &str) {
+        match value {
+            Value::Array(items) if items.len() == elements.len() => {
+                for (idx, (element, item)) in elements.iter().zip(items).enumerate() {
+                    self.check(element, item, &child(pointer, &idx.to_string()));
+                }
+            }
+            _ => self.mismatch(
+                pointer,
+                &format!("a list of {} elements", elements.len()),
+                value,
+            ),
+        }
+    }
+
+    fn check_user_defined(&mut self, name: &str, value: &Value, pointer: &str) {
+        let spec = self.spec;
+        let item = spec.iter().find(|item| match item {
+            ast::SpecItem::StructDef(sdef) => sdef.name == name,
+            ast::SpecItem::EnumDef(edef) => edef.name == name,
+            ast::SpecItem::ServiceDef(_) => false,
+        });
+        match item {
+            Some(ast::SpecItem::StructDef(sdef)) => {
+                self.check_fields(&sdef.fields, value, pointer, &format!("a `{}`", name))
+            }
+            Some(ast::SpecItem::EnumDef(edef)) => self.check_enum(edef, value, pointer),
+            _ => self.error(pointer, format!("unknown type `{}`", name)),
+        }
+    }
+
+    fn check_fields(
+        &mut self,
+        fields: &ast::StructFields,
+        value: &Value,
+        pointer: &str,
+        expected: &str,
+    ) {
+        let object = match value {
+            Value::Object(object) => object,
+            _ => return self.mismatch(pointer, expected, value),
+        };
+        for field in fields.iter() {
+            let ast::FieldDefPair { name, type_ident } = &field.pair;
+            match object.get(name) {
+                Some(v) => self.check(type_ident, v, &child(pointer, name)),
+                None if is_defaulted(type_ident) => (),
+                None => self.error(pointer, format!("missing field `{}`", name)),
+            }
+        }
+    }
+
+    fn check_enum(&mut self, edef: &ast::EnumDef, value: &Value, pointer: &str) {
+        let (name, inner) = match (value, tagged(value)) {
+            (Value::String(name), _) => (name.as_str(), None),
+            (_, Some((name, inner))) => (name, Some(inner)),
+            _ => return self.mismatch(pointer, &format!("a variant of `{}`", edef.name), value),
+        };
+        let variant = match edef.variants.iter().find(|v| v.name == name) {
+            Some(variant) => variant,
+            None => {
+                return self.error(
+                    pointer,
+                    format!("unknown variant `{}` of `{}`", name, edef.name),
+                )
+            }
+        };
+        let inner_pointer = child(pointer, name);
+        match (&variant.variant_type, inner) {
+            (ast::VariantType::Simple, None) => (),
+            (ast::VariantType::Newtype(type_ident), Some(inner)) => {
+                self.check(type_ident, inner, &inner_pointer)
+            }
+            // a tuple variant of one element is a newtype variant
+            (ast::VariantType::Tuple(tdef), Some(inner)) if tdef.elements().len() == 1 => {
+                self.check(&tdef.elements()[0], inner, &inner_pointer)
+            }
+            (ast::VariantType::Tuple(tdef), Some(inner)) => {
+                self.check_tuple(tdef.elements(), inner, &inner_pointer)
+            }
+            (ast::VariantType::Struct(fields), Some(inner)) => self.check_fields(
+                fields,
+                inner,
+                &inner_pointer,
+                &format!("the fields of `{}::{}`", edef.name, name),
+            ),
+            (ast::VariantType::Simple, Some(_)) => self.error(
+                pointer,
+                format!("expected the string {:?}, got {}", name, describe(value)),
+            ),
+            (_, None) => self.error(
+                pointer,
+                format!("expected {{{:?}: ..}}, got {}", name, describe(value)),
+            ),
+        }
+    }
+}
+
+/// Fields that serde fills in when they are missing.
+fn is_defaulted(type_ident: &ast::TypeIdent) -> bool {
+    matches!(
+        type_ident,
+        ast::TypeIdent::Option(_) | ast::TypeIdent::List(_) | ast::TypeIdent::Map(_, _)
+    )
+}
+
+/// The tag and content of an externally tagged value, i.e., an object with a single entry.
+fn tagged(value: &Value) -> Option<(&str, &Value)> {
+    match value {
+        Value::Object(object) if object.len() == 1 => {
+            object.iter().next().map(|(k, v)| (k.as_str(), v))
+        }
+        _ => None,
+    }
+}
+
+/// The JSON pointer to `token` within the value at `pointer`.
+fn child(pointer: &str, token: &str) -> String {
+    format!(
+        "{}/{}",
+        pointer,
+        token.replace('~', "~0").replace('/', "~1")
+    )
+}
+
+fn atom_description(atom: ast::AtomType) -> &'static str {
+    match atom {
+        ast::AtomType::Empty => "null",
+        ast::AtomType::Str => "a string",
+        ast::AtomType::I32 => "a signed 32-bit integer",
+        ast::AtomType::U32 => "an unsigned 32-bit integer",
+        ast::AtomType::U8 => "an unsigned 8-bit integer",
+        ast::AtomType::F64 => "a number",
+        ast::AtomType::Bool => "a boolean",
+        ast::AtomType::DateTime => "an RFC 3339 date and time",
+        ast::AtomType::Date => "an ISO 8601 date",
+        ast::AtomType::Uuid => "a UUID",
+        ast::AtomType::Bytes => "base64-encoded bytes",
+    }
+}
+
+/// A short description of `value` for error messages.
+fn describe(value: &Value) -> String {
+    const MAX_LEN: usize = 40;
+    match value {
+        Value::Array(_) => "a list".to_owned(),
+        Value::Object(_) => "an object".to_owned(),
+        Value::String(s) if s.chars().count() > MAX_LEN => {
+            format!("{:?}..", s.chars().take(MAX_LEN).collect::<String>())
+        }
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,73 @@
+use humblegen::CodeGenerator;
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Reads all files below `dir`, keyed by their path relative to `dir`.
+fn read_tree(dir: &Path) -> BTreeMap<PathBuf, String> {
+    fn visit(root: &Path, dir: &Path, files: &mut BTreeMap<PathBuf, String>) {
+        for entry in std::fs::read_dir(dir).expect("read dir") {
+            let path = entry.expect("read dir entry").path();
+            if path.is_dir() {
+                visit(root, &path, files);
+            } else {
+                let contents = std::fs::read_to_string(&path).expect("read file");
+                files.insert(path.strip_prefix(root).unwrap().to_owned(), contents);
+            }
+        }
+    }
+    let mut files = BTreeMap::new();
+    visit(dir, dir, &mut files);
+    files
+}
+
+/// Generates the Elm client of `spec.humble` in each directory of ./tests/elm and compares it
+/// to the reference output in its `out` directory.
+///
+/// Set `HUMBLEGEN_BLESS=1` to overwrite the reference output instead.
+#[test]
+fn elm() {
+    for dir in std::fs::read_dir("./tests/elm/").expect("read test dir") {
+        let dir = dir.expect("read test dir entry").path();
+        println!("running test {:?}", dir);
+
+        let spec_file =
+            std::fs::File::open(dir.join("spec.humble")).expect("open humble spec file");
+        let spec = humblegen::parse(spec_file).expect("parse humble spec file");
+        let outdir = tempfile::tempdir().expect("create output dir");
+        humblegen::backend::elm::Generator::new(
+            humblegen::Artifact::ClientEndpoints,
+            "Protocol".to_owned(),
+        )
+        .expect("failed to init humblegen elm backend")
+        .generate(&spec, outdir.path())
+        .expect("humblegen elm backend failed");
+        let generated = read_tree(outdir.path());
+
+        let reference_dir = dir.join("out");
+        if std::env::var_os("HUMBLEGEN_BLESS").is_some() {
+            let _ = std::fs::remove_dir_all(&reference_dir);
+            for (path, contents) in &generated {
+                let path = reference_dir.join(path);
+                std::fs::create_dir_all(path.parent().unwrap()).expect("create reference dir");
+                std::fs::write(path, contents).expect("write reference output");
+            }
+            continue;
+        }
+
+        let reference = read_tree(&reference_dir);
+        assert_eq!(
+            generated.keys().collect::<Vec<_>>(),
+            reference.keys().collect::<Vec<_>>(),
+            "generated files differ from {:?}",
+            reference_dir
+        );
+        for (path, contents) in &generated {
+            assert!(
+                contents == &reference[path],
+                "{:?} differs from the reference output, rerun with HUMBLEGEN_BLESS=1 to update it",
+                path
+            );
+        }
+    }
+}
@@ -0,0 +1,63 @@
+module Protocol.Data exposing (..)
+
+
+
+import Date -- justinmimbs/date
+import Dict exposing (Dict)
+import Iso8601  -- rtfeldman/elm-iso8601-date-strings
+import Time  -- elm/time
+import Protocol.BuiltIn.Bytes as BuiltinBytes
+import Protocol.BuiltIn.Uuid as BuiltinUuid
+
+
+
+
+
+type alias Monster =
+    { id: Int
+    , name: String
+    , hp: Int
+    , portrait: Maybe BuiltinBytes.Bytes
+    , born: Time.Posix
+    }
+
+
+
+
+type alias MonsterData =
+    { name: String
+    , hp: Int
+    }
+
+
+
+
+type alias MonsterPatch =
+    { name: Maybe String
+    , hp: Maybe Int
+    }
+
+
+
+
+type alias MonsterError__TooStrong__Internal__ =
+    { maxStrength: Int
+    }
+
+
+
+
+type MonsterError
+    = TooWeak
+    | TooStrong MonsterError__TooStrong__Internal__
+    | Named String
+
+
+
+
+type alias MonsterQuery =
+    { name: Maybe String
+    , tags: List String
+    }
+
+
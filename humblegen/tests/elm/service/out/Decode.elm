@@ -0,0 +1,110 @@
+module Protocol.Decode exposing (..)
+
+
+
+import Protocol.Data exposing (..)
+import Date -- justinmimbs/date
+import Dict exposing (Dict)
+import Iso8601  -- rtfeldman/elm-iso8601-date-strings
+import Json.Decode as D
+import Time  -- elm/time
+import Protocol.BuiltIn.Bytes as BuiltinBytes
+import Protocol.BuiltIn.Uuid as BuiltinUuid
+
+-- TODO: move into its own module to avoid name collision
+
+custom : D.Decoder a -> D.Decoder (a -> b) -> D.Decoder b
+custom =
+    D.map2 (|>)
+
+required : String -> D.Decoder a -> D.Decoder (a -> b) -> D.Decoder b
+required key valDecoder decoder =
+    custom (D.field key valDecoder) decoder
+
+-- A helper function for a required index in a JSON list.
+requiredIdx : Int -> D.Decoder a -> D.Decoder (a -> b) -> D.Decoder b
+requiredIdx idx itemDecoder decoder =
+    custom (D.index idx itemDecoder) decoder
+
+-- Maybe-unwrapping decoder: Turns a `Maybe t` decoder into an a `t` decoder by outputting an error on `Nothing`.
+unwrapDecoder : D.Decoder (Maybe t) -> D.Decoder t
+unwrapDecoder =
+    D.andThen
+        (\x ->
+            case x of
+                Just v ->
+                    D.succeed v
+
+                Nothing ->
+                    D.fail "invalid enum string value"
+        )
+
+
+builtinDecodeDate : D.Decoder Date.Date
+builtinDecodeDate =
+    D.map Date.fromIsoString D.string
+    |> D.andThen
+        (\result ->
+            case result of
+                Ok v ->
+                    D.succeed v
+
+                Err errMsg ->
+                    D.fail <| "not a valid date: " ++ errMsg
+        )
+
+builtinDecodeIso8601 : D.Decoder Time.Posix
+builtinDecodeIso8601 =
+    Iso8601.decoder
+
+
+builtinDecodeResult : D.Decoder error -> D.Decoder value -> D.Decoder (Result error value)
+builtinDecodeResult error value =
+    D.oneOf 
+        [ D.field "Ok" value |> D.map Ok
+        , D.field "Err" error |> D.map Err
+        ]
+
+builtinDecodeOption : D.Decoder value -> D.Decoder (Maybe value)
+builtinDecodeOption =
+    D.nullable
+
+
+decodeMonster : D.Decoder Monster 
+decodeMonster =
+   D.succeed Monster
+        |> required "id" D.int
+        |> required "name" D.string
+        |> required "hp" D.int
+        |> required "portrait" (builtinDecodeOption BuiltinBytes.decode)
+        |> required "born" builtinDecodeIso8601
+
+
+decodeMonsterData : D.Decoder MonsterData 
+decodeMonsterData =
+   D.succeed MonsterData
+        |> required "name" D.string
+        |> required "hp" D.int
+
+
+decodeMonsterPatch : D.Decoder MonsterPatch 
+decodeMonsterPatch =
+   D.succeed MonsterPatch
+        |> required "name" (builtinDecodeOption D.string)
+        |> required "hp" (builtinDecodeOption D.int)
+
+
+decodeMonsterError : D.Decoder MonsterError
+decodeMonsterError =
+    D.oneOf
+        [D.string |> D.andThen (\s -> if s == "TooWeak" then D.succeed TooWeak else D.fail "")
+        ,D.field "TooStrong" (D.succeed MonsterError__TooStrong__Internal__ |> required "max_strength" D.int |> D.map TooStrong)
+        ,D.field "Named" (D.map Named D.string)
+        ]
+
+
+decodeMonsterQuery : D.Decoder MonsterQuery 
+decodeMonsterQuery =
+   D.succeed MonsterQuery
+        |> required "name" (builtinDecodeOption D.string)
+        |> required "tags" (D.list D.string)
\ No newline at end of file
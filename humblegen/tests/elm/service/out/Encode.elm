@@ -0,0 +1,206 @@
+module Protocol.Encode exposing (..)
+
+
+
+import Protocol.Data exposing (..)
+import Date -- justinmimbs/date
+import Dict exposing (Dict)
+import Iso8601  -- rtfeldman/elm-iso8601-date-strings
+import Json.Encode as E
+import Time  -- elm/time
+import Url.Builder
+import Protocol.BuiltIn.Bytes as BuiltinBytes
+import Protocol.BuiltIn.Uuid as BuiltinUuid
+
+
+
+
+builtinEncodeDate : Date.Date -> E.Value
+builtinEncodeDate =
+    Date.toIsoString >> E.string
+
+builtinEncodeIso8601 : Time.Posix -> E.Value
+builtinEncodeIso8601 =
+    Iso8601.encode
+
+builtinEncodeDateUrlcomponent : Date.Date -> String
+builtinEncodeDateUrlcomponent =
+    Date.toIsoString
+
+builtinEncodeIso8601Urlcomponent : Time.Posix -> String
+builtinEncodeIso8601Urlcomponent =
+    Iso8601.fromTime
+
+builtinEncodeBoolUrlcomponent : Bool -> String
+builtinEncodeBoolUrlcomponent b =
+    if b then "true" else "false"
+
+
+builtinEncodeMaybe : (t -> E.Value) -> Maybe t -> E.Value
+builtinEncodeMaybe encoder =
+    Maybe.map encoder >> Maybe.withDefault E.null
+
+
+builtinEncodeResult : (err -> E.Value) -> (ok -> E.Value) -> Result err ok -> E.Value
+builtinEncodeResult errEncoder okEncoder res =
+    case res of
+        Err err -> E.object [("Err", errEncoder err)] 
+        Ok ok -> E.object [("Ok", okEncoder ok)]
+
+
+builtinQueryKey : String -> String -> String
+builtinQueryKey prefix name =
+    if String.isEmpty prefix then name else prefix ++ "[" ++ name ++ "]"
+
+
+builtinQueryAtom : (t -> String) -> String -> t -> List Url.Builder.QueryParameter
+builtinQueryAtom toString key v =
+    [ Url.Builder.string key (toString v) ]
+
+
+builtinQueryMaybe : (String -> t -> List Url.Builder.QueryParameter) -> String -> Maybe t -> List Url.Builder.QueryParameter
+builtinQueryMaybe encoder key =
+    Maybe.map (encoder key) >> Maybe.withDefault []
+
+
+builtinQueryList : (String -> t -> List Url.Builder.QueryParameter) -> String -> List t -> List Url.Builder.QueryParameter
+builtinQueryList encoder key =
+    List.concatMap (encoder key)
+
+
+builtinQueryIndexedList : (String -> t -> List Url.Builder.QueryParameter) -> String -> List t -> List Url.Builder.QueryParameter
+builtinQueryIndexedList encoder key =
+    List.indexedMap (\idx -> encoder (builtinQueryKey key (String.fromInt idx))) >> List.concat
+
+
+builtinQueryDict : (String -> t -> List Url.Builder.QueryParameter) -> String -> Dict String t -> List Url.Builder.QueryParameter
+builtinQueryDict encoder key =
+    Dict.toList >> List.concatMap (\( k, v ) -> encoder (builtinQueryKey key k) v)
+
+
+builtinQueryResult : (String -> err -> List Url.Builder.QueryParameter) -> (String -> ok -> List Url.Builder.QueryParameter) -> String -> Result err ok -> List Url.Builder.QueryParameter
+builtinQueryResult errEncoder okEncoder key res =
+    case res of
+        Err err -> errEncoder (builtinQueryKey key "Err") err
+        Ok ok -> okEncoder (builtinQueryKey key "Ok") ok
+
+
+builtinQueryTuple1 : (String -> a -> List Url.Builder.QueryParameter) -> String -> a -> List Url.Builder.QueryParameter
+builtinQueryTuple1 encoder0 key x0 =
+    encoder0 (builtinQueryKey key "0") x0
+
+
+builtinQueryTuple2 : (String -> a -> List Url.Builder.QueryParameter) -> (String -> b -> List Url.Builder.QueryParameter) -> String -> ( a, b ) -> List Url.Builder.QueryParameter
+builtinQueryTuple2 encoder0 encoder1 key ( x0, x1 ) =
+    encoder0 (builtinQueryKey key "0") x0 ++ encoder1 (builtinQueryKey key "1") x1
+
+
+builtinQueryTuple3 : (String -> a -> List Url.Builder.QueryParameter) -> (String -> b -> List Url.Builder.QueryParameter) -> (String -> c -> List Url.Builder.QueryParameter) -> String -> ( a, b, c ) -> List Url.Builder.QueryParameter
+builtinQueryTuple3 encoder0 encoder1 encoder2 key ( x0, x1, x2 ) =
+    encoder0 (builtinQueryKey key "0") x0 ++ encoder1 (builtinQueryKey key "1") x1 ++ encoder2 (builtinQueryKey key "2") x2
+
+
+encodeMonster : Monster -> E.Value
+encodeMonster obj =
+    E.object
+        [ ("id", E.int obj.id)
+        , ("name", E.string obj.name)
+        , ("hp", E.int obj.hp)
+        , ("portrait", builtinEncodeMaybe BuiltinBytes.encode obj.portrait)
+        , ("born", builtinEncodeIso8601 obj.born)
+        ]
+
+
+buildQueryMonster : Monster -> List Url.Builder.QueryParameter
+buildQueryMonster =
+    queryEncodeMonster ""
+
+
+queryEncodeMonster : String -> Monster -> List Url.Builder.QueryParameter
+queryEncodeMonster prefix obj =
+    List.concat
+        [ builtinQueryAtom String.fromInt (builtinQueryKey prefix "id") obj.id
+        , builtinQueryAtom identity (builtinQueryKey prefix "name") obj.name
+        , builtinQueryAtom String.fromInt (builtinQueryKey prefix "hp") obj.hp
+        , builtinQueryMaybe (builtinQueryAtom BuiltinBytes.encodeQuery) (builtinQueryKey prefix "portrait") obj.portrait
+        , builtinQueryAtom builtinEncodeIso8601Urlcomponent (builtinQueryKey prefix "born") obj.born
+        ]
+
+
+encodeMonsterData : MonsterData -> E.Value
+encodeMonsterData obj =
+    E.object
+        [ ("name", E.string obj.name)
+        , ("hp", E.int obj.hp)
+        ]
+
+
+buildQueryMonsterData : MonsterData -> List Url.Builder.QueryParameter
+buildQueryMonsterData =
+    queryEncodeMonsterData ""
+
+
+queryEncodeMonsterData : String -> MonsterData -> List Url.Builder.QueryParameter
+queryEncodeMonsterData prefix obj =
+    List.concat
+        [ builtinQueryAtom identity (builtinQueryKey prefix "name") obj.name
+        , builtinQueryAtom String.fromInt (builtinQueryKey prefix "hp") obj.hp
+        ]
+
+
+encodeMonsterPatch : MonsterPatch -> E.Value
+encodeMonsterPatch obj =
+    E.object
+        [ ("name", builtinEncodeMaybe E.string obj.name)
+        , ("hp", builtinEncodeMaybe E.int obj.hp)
+        ]
+
+
+buildQueryMonsterPatch : MonsterPatch -> List Url.Builder.QueryParameter
+buildQueryMonsterPatch =
+    queryEncodeMonsterPatch ""
+
+
+queryEncodeMonsterPatch : String -> MonsterPatch -> List Url.Builder.QueryParameter
+queryEncodeMonsterPatch prefix obj =
+    List.concat
+        [ builtinQueryMaybe (builtinQueryAtom identity) (builtinQueryKey prefix "name") obj.name
+        , builtinQueryMaybe (builtinQueryAtom String.fromInt) (builtinQueryKey prefix "hp") obj.hp
+        ]
+
+
+encodeMonsterError : MonsterError -> E.Value
+encodeMonsterError v =
+    case v of
+        TooWeak -> E.string "TooWeak"
+        TooStrong obj -> E.object [ ("TooStrong", E.object [("max_strength", E.int obj.maxStrength)]) ]
+        Named obj -> E.object [ ("Named", E.string obj) ]
+
+
+queryEncodeMonsterError : String -> MonsterError -> List Url.Builder.QueryParameter
+queryEncodeMonsterError prefix v =
+    case v of
+        TooWeak -> [ Url.Builder.string prefix "TooWeak" ]
+        TooStrong obj -> List.concat [ builtinQueryAtom String.fromInt (builtinQueryKey (builtinQueryKey prefix "TooStrong") "max_strength") obj.maxStrength ]
+        Named obj -> builtinQueryAtom identity (builtinQueryKey prefix "Named") obj
+
+
+encodeMonsterQuery : MonsterQuery -> E.Value
+encodeMonsterQuery obj =
+    E.object
+        [ ("name", builtinEncodeMaybe E.string obj.name)
+        , ("tags", E.list E.string obj.tags)
+        ]
+
+
+buildQueryMonsterQuery : MonsterQuery -> List Url.Builder.QueryParameter
+buildQueryMonsterQuery =
+    queryEncodeMonsterQuery ""
+
+
+queryEncodeMonsterQuery : String -> MonsterQuery -> List Url.Builder.QueryParameter
+queryEncodeMonsterQuery prefix obj =
+    List.concat
+        [ builtinQueryMaybe (builtinQueryAtom identity) (builtinQueryKey prefix "name") obj.name
+        , builtinQueryList (builtinQueryAtom identity) (builtinQueryKey prefix "tags") obj.tags
+        ]
\ No newline at end of file
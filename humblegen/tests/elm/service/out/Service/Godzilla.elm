@@ -0,0 +1,153 @@
+module Protocol.Service.Godzilla exposing (..)
+
+
+
+import Protocol.Data as Ty
+import Json.Decode as D
+import Json.Encode as E
+import Protocol.Encode as AE
+import Protocol.Decode as AD
+import Protocol.ServiceBuiltIn exposing (..)
+import Url.Builder
+import Http
+import File
+import Protocol.BuiltIn.Bytes as BuiltinBytes
+import Protocol.BuiltIn.Uuid as BuiltinUuid
+import Protocol.ServiceBuiltIn
+type alias Error = Protocol.ServiceBuiltIn.Error
+type alias Request q t  = Protocol.ServiceBuiltIn.Request q t
+withHeader = Protocol.ServiceBuiltIn.withHeader
+withQuery = Protocol.ServiceBuiltIn.withQuery
+withBase = Protocol.ServiceBuiltIn.withBase
+toTask = Protocol.ServiceBuiltIn.toTask
+
+
+
+
+
+
+
+
+getFoo : Request NoQuery Int
+getFoo =
+    makeRequest
+        "GET"
+            [ "foo"
+            ]
+            noQueryEncoder
+            (jsonResolver (D.int))
+
+getByIdOfMonsters : Int -> Request NoQuery (Result Ty.MonsterError Ty.Monster)
+getByIdOfMonsters component1_id =
+    makeRequest
+        "GET"
+            [ "monsters"
+            , component1_id |> String.fromInt
+            ]
+            noQueryEncoder
+            (jsonResolver ((AD.builtinDecodeResult AD.decodeMonsterError AD.decodeMonster)))
+
+getMonsters : Request Ty.MonsterQuery (List Ty.Monster)
+getMonsters =
+    makeRequest
+        "GET"
+            [ "monsters"
+            ]
+            AE.buildQueryMonsterQuery
+            (jsonResolver ((D.list AD.decodeMonster)))
+
+createMonsters : Ty.MonsterData -> Request NoQuery (Result Ty.MonsterError Ty.Monster)
+createMonsters body =
+    makeRequest
+        "POST"
+            [ "monsters"
+            ]
+            noQueryEncoder
+            (jsonResolver ((AD.builtinDecodeResult AD.decodeMonsterError AD.decodeMonster)))
+            |> withJsonBody AE.encodeMonsterData body
+
+modifyByIdOfMonsters : String -> Ty.MonsterPatch -> Request NoQuery (Result Ty.MonsterError ())
+modifyByIdOfMonsters component1_id body =
+    makeRequest
+        "PATCH"
+            [ "monsters"
+            , component1_id |> identity
+            ]
+            noQueryEncoder
+            (jsonResolver ((AD.builtinDecodeResult AD.decodeMonsterError (D.null ()))))
+            |> withJsonBody AE.encodeMonsterPatch body
+
+deleteByIdOfMonster : String -> Request NoQuery (Result Ty.MonsterError ())
+deleteByIdOfMonster component1_id =
+    makeRequest
+        "DELETE"
+            [ "monster"
+            , component1_id |> identity
+            ]
+            noQueryEncoder
+            (jsonResolver ((AD.builtinDecodeResult AD.decodeMonsterError (D.null ()))))
+
+getByPathOfFiles : String -> Request NoQuery BuiltinBytes.Bytes
+getByPathOfFiles component1_path =
+    makeRequest
+        "GET"
+            [ "files"
+            , component1_path |> identity
+            ]
+            noQueryEncoder
+            (jsonResolver (BuiltinBytes.decode))
+
+getByVerifiedOfByDayOfSightings : Date.Date -> Bool -> Request NoQuery (List Ty.Monster)
+getByVerifiedOfByDayOfSightings component1_day component2_verified =
+    makeRequest
+        "GET"
+            [ "sightings"
+            , component1_day |> AE.builtinEncodeDateUrlcomponent
+            , component2_verified |> AE.builtinEncodeBoolUrlcomponent
+            ]
+            noQueryEncoder
+            (jsonResolver ((D.list AD.decodeMonster)))
+
+getMonsterExport : Request Ty.MonsterQuery (List Ty.Monster)
+getMonsterExport =
+    makeRequest
+        "GET"
+            [ "monster-export"
+            ]
+            AE.buildQueryMonsterQuery
+            (ndjsonResolver (AD.decodeMonster))
+
+createPortraitByIdOfMonsters : Int -> ({ caption : Maybe String, thumbnail : File.File, portrait : File.File }) -> Request NoQuery (Result Ty.MonsterError ())
+createPortraitByIdOfMonsters component1_id body =
+    makeRequest
+        "POST"
+            [ "monsters"
+            , component1_id |> String.fromInt
+            , "portrait"
+            ]
+            noQueryEncoder
+            (jsonResolver ((AD.builtinDecodeResult AD.decodeMonsterError (D.null ()))))
+            |> withBody (Http.multipartBody [ Http.stringPart "caption" (E.encode 0 (AE.builtinEncodeMaybe E.string body.caption)), Http.filePart "thumbnail" body.thumbnail, Http.filePart "portrait" body.portrait ])
+
+getPortraitByIdOfMonsters : Int -> Request NoQuery Blob
+getPortraitByIdOfMonsters component1_id =
+    makeRequest
+        "GET"
+            [ "monsters"
+            , component1_id |> String.fromInt
+            , "portrait"
+            ]
+            noQueryEncoder
+            blobResolver
+
+createMonstersImport : Blob -> Request NoQuery (Result Ty.MonsterError Int)
+createMonstersImport body =
+    makeRequest
+        "POST"
+            [ "monsters-import"
+            ]
+            noQueryEncoder
+            (jsonResolver ((AD.builtinDecodeResult AD.decodeMonsterError D.int)))
+            |> withBlobBody body
+
+
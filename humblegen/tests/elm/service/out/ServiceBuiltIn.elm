@@ -0,0 +1,213 @@
+module Protocol.ServiceBuiltIn exposing (..)
+
+
+import Bytes exposing (Bytes)
+import Bytes.Decode
+import Dict
+import Http
+import Json.Decode as D
+import Json.Encode as E
+import Url
+import Url.Builder
+import Task exposing (Task)
+
+type alias QueryEncoder q = (q -> List Url.Builder.QueryParameter)
+
+type alias Request q t =
+    { method : String
+    , headers : List Http.Header
+    , urlComponents : List String
+    , query: Maybe q
+    , queryEncoder: QueryEncoder q
+    , body : Http.Body
+    , resolver : Http.Resolver Error t
+    , timeout : Maybe Float
+    , base : String
+    }
+
+
+type ResponseBody
+    = StringResponse String
+
+
+{-| A raw binary body (`blob["media/type"]`) along with its content type.
+-}
+type alias Blob =
+    { contentType : String
+    , body : Bytes
+    }
+
+
+type Error
+    = Bug String
+    | HttpBug Http.Metadata ResponseBody
+    | InvalidResponse Http.Metadata ResponseBody D.Error
+    | TransportError String
+    | AuthorizationError -- humble service protocol level authorization error (e.g. the server-side request handler indicates that the client is unauthorized to access the resource. The client's access token is valid, though.
+    | AuthenticationError -- humble service protocol level authentication error (e.g. the server-side request handler indicates that the client did not provide a valid access token)
+    | PreconditionFailed -- humble service protocol level precondition failure (e.g. the `If-Match` header of a PUT or PATCH request does not match the current entity tag of the resource)
+    | ServerError
+
+
+makeRequest : String -> List String -> QueryEncoder q -> Http.Resolver Error t -> Request q t
+makeRequest method urlComponents queryEncoder resolver =
+    { method = method
+    , headers = []
+    , base = ""
+    , query = Nothing
+    , queryEncoder = queryEncoder
+    , urlComponents = urlComponents
+    , body = Http.emptyBody
+    , resolver = resolver
+    , timeout = Nothing
+    }
+
+type alias NoQuery = Never
+
+noQueryEncoder : QueryEncoder Never
+noQueryEncoder _ = []
+
+jsonResolver : D.Decoder t -> Http.Resolver Error t
+jsonResolver decoder =
+    bodyResolver (D.decodeString decoder)
+
+
+{-| Resolves the NDJSON body of a `stream[T]` endpoint once it is complete, decoding one item per line.
+-}
+ndjsonResolver : D.Decoder t -> Http.Resolver Error (List t)
+ndjsonResolver decoder =
+    let
+        decodeLines body =
+            body
+                |> String.lines
+                |> List.filter (not << String.isEmpty << String.trim)
+                |> List.foldr (\line acc -> Result.map2 (::) (D.decodeString decoder line) acc) (Ok [])
+    in
+    bodyResolver decodeLines
+
+
+bodyResolver : (String -> Result D.Error t) -> Http.Resolver Error t
+bodyResolver =
+    let
+        resolve decode response =
+            case response of
+                Http.GoodStatus_ metadata body ->
+                    decode body
+                        |> Result.mapError (InvalidResponse metadata (StringResponse body))
+
+                _ ->
+                    Err <| responseError identity response
+    in
+    Http.stringResolver << resolve
+
+
+{-| Resolves the body of a `blob` endpoint, taking its content type from the response headers.
+-}
+blobResolver : Http.Resolver Error Blob
+blobResolver =
+    let
+        -- error responses are JSON
+        bytesToString bytes =
+            Bytes.Decode.decode (Bytes.Decode.string (Bytes.width bytes)) bytes
+                |> Maybe.withDefault ""
+
+        resolve response =
+            case response of
+                Http.GoodStatus_ metadata body ->
+                    Ok
+                        { contentType = Dict.get "content-type" metadata.headers |> Maybe.withDefault "application/octet-stream"
+                        , body = body
+                        }
+
+                _ ->
+                    Err <| responseError bytesToString response
+    in
+    Http.bytesResolver resolve
+
+
+responseError : (body -> String) -> Http.Response body -> Error
+responseError bodyToString response =
+    case response of
+        Http.BadUrl_ badUrl ->
+            Bug <| "bad url: " ++ badUrl
+
+        Http.Timeout_ ->
+            TransportError "Http.Timeout_"
+
+        Http.NetworkError_ ->
+            TransportError "Http.NetworkError_"
+
+        Http.BadStatus_ metadata body ->
+            case metadata.statusCode of
+                401 ->
+                    AuthorizationError
+
+                403 ->
+                    AuthenticationError
+
+                412 ->
+                    PreconditionFailed
+
+                500 ->
+                    ServerError
+
+                _ ->
+                    HttpBug metadata (StringResponse (bodyToString body))
+
+        Http.GoodStatus_ metadata body ->
+            Bug "unexpected good status"
+
+
+withBase : String -> Request q t -> Request q t
+withBase base req =
+    { req | base = base }
+
+withQuery : q -> Request q t -> Request q t
+withQuery query req =
+    { req | query = Just query }
+
+    
+
+withBody : Http.Body -> Request q t -> Request q t
+withBody body req =
+    { req | body = body }
+
+
+withTimeout : Float -> Request q t -> Request q t
+withTimeout timeout req =
+    { req | timeout = Just timeout }
+
+
+withHeader : String -> String -> Request q t -> Request q t
+withHeader name value req =
+    { req | headers = Http.header name value :: req.headers }
+
+
+withJsonBody : (body -> E.Value) -> body -> Request q t -> Request q t
+withJsonBody encoder value req =
+    { req | body = Http.stringBody "application/json" <| E.encode 2 (encoder value) }
+
+
+withBlobBody : Blob -> Request q t -> Request q t
+withBlobBody blob req =
+    { req | body = Http.bytesBody blob.contentType blob.body }
+
+
+makeUrl : Request q t -> String
+makeUrl req =
+    Url.Builder.crossOrigin
+         req.base
+            (List.map Url.percentEncode req.urlComponents)
+            (Maybe.withDefault [] <| Maybe.map req.queryEncoder req.query)
+
+
+toTask : Request q t -> Task Error t
+toTask req =
+    Http.task
+        { method = req.method
+        , headers = req.headers
+        , url = makeUrl req
+        , body = req.body
+        , resolver = req.resolver
+        , timeout = req.timeout
+        }
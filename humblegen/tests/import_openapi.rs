@@ -0,0 +1,170 @@
+use humblegen::{backend::openapi::Generator, import::openapi::import};
+use serde_json::json;
+
+const SPEC: &str = r#"/// A monster.
+struct Monster {
+    id: i32,
+    /// Picture, if any.
+    picture: option[bytes],
+    tags: list[str],
+    born: date,
+}
+
+enum MonsterError {
+    TooWeak,
+    Named(str),
+    Hp {
+        max: u32,
+    },
+}
+
+/// Monster related services.
+service Monsters {
+    /// Find monsters.
+    GET /monsters?{list[i32]} -> list[Monster],
+    POST /monsters -> Monster -> result[Monster][MonsterError],
+    PUT /monsters/{id: i32} -> Monster -> result[()][MonsterError],
+    GET /monsters/{id: i32}/picture -> blob["image/*"],
+    POST /monsters/{id: i32}/picture -> multipart { caption: option[str], picture: file } -> (),
+    GET /monster-export -> stream[Monster],
+    GET /monsters/{id: i32}/files/{path: str..} -> bytes,
+    WS /watch -> str -> Monster,
+}
+"#;
+
+#[test]
+fn printed_spec_parses() {
+    let spec = humblegen::parse(SPEC.as_bytes()).expect("parse spec");
+    let printed = spec.to_string();
+    assert_eq!(printed, SPEC);
+    let reparsed = humblegen::parse(printed.as_bytes()).expect("parse printed spec");
+    assert_eq!(reparsed.to_string(), printed);
+}
+
+#[test]
+fn round_trip() {
+    let spec = humblegen::parse(SPEC.as_bytes()).expect("parse spec");
+    let document = Generator::default().document(&spec);
+    let (imported, warnings) = import(&document).expect("import");
+    assert_eq!(warnings, vec![]);
+    assert_eq!(imported.to_string(), SPEC);
+}
+
+#[test]
+fn unsupported_features_are_warnings() {
+    let document = json!({
+        "openapi": "3.0.3",
+        "info": { "title": "Pets", "version": "1" },
+        "paths": {
+            "/pets": {
+                "get": {
+                    "operationId": "listPets",
+                    "parameters": [
+                        { "name": "X-Request-Id", "in": "header", "schema": { "type": "string" } },
+                        { "name": "limit", "in": "query", "required": true, "schema": { "type": "integer", "minimum": 0 } },
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "pets",
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Pet" } } },
+                        },
+                    },
+                },
+                "head": { "responses": { "200": { "description": "pets" } } },
+            },
+        },
+        "components": {
+            "schemas": {
+                "Pet": { "oneOf": [{ "type": "string" }, { "type": "integer" }] },
+            },
+        },
+    });
+    let (spec, warnings) = import(&document).expect("import");
+    let warnings: Vec<_> = warnings.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        warnings,
+        vec![
+            "#/components/schemas/Pet/oneOf: `oneOf` without a discriminator cannot be expressed, `Pet` is an empty struct",
+            "#/paths/~1pets/get/parameters/0: header parameters are not supported, `X-Request-Id` is ignored",
+            "#/paths/~1pets/head: HEAD requests are not supported",
+        ]
+    );
+    assert_eq!(
+        spec.to_string(),
+        "struct Pet {}
+
+struct ListPetsQuery {
+    limit: u32,
+}
+
+service Pets {
+    GET /pets?{ListPetsQuery} -> Pet,
+}
+"
+    );
+}
+
+#[test]
+fn discriminators_and_keyword_fields_are_warnings() {
+    let document = json!({
+        "openapi": "3.0.3",
+        "info": { "title": "Pets", "version": "1" },
+        "paths": {},
+        "components": {
+            "schemas": {
+                "Pet": {
+                    "oneOf": [
+                        { "$ref": "#/components/schemas/Cat" },
+                        { "$ref": "#/components/schemas/Dog" },
+                    ],
+                    "discriminator": { "propertyName": "type" },
+                },
+                "Cat": {
+                    "type": "object",
+                    "required": ["type", "name"],
+                    "properties": { "type": { "type": "string" }, "name": { "type": "string" } },
+                },
+                "Dog": {
+                    "type": "object",
+                    "required": ["type"],
+                    "properties": { "type": { "type": "string" }, "barks": { "type": "boolean" } },
+                },
+            },
+        },
+    });
+    let (spec, warnings) = import(&document).expect("import");
+    let warnings: Vec<_> = warnings.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        warnings,
+        vec![
+            "#/components/schemas/Pet/discriminator: humblegen enums are externally tagged: values of `Pet` are objects with the variant name as their only property, \
+             instead of objects with a `type` property",
+            "#/components/schemas/Cat/properties/type: `type` is renamed to `type_`, which changes its encoding",
+            "#/components/schemas/Dog/properties/type: `type` is renamed to `type_`, which changes its encoding",
+        ]
+    );
+    assert_eq!(
+        spec.to_string(),
+        "enum Pet {
+    Cat(Cat),
+    Dog(Dog),
+}
+
+struct Cat {
+    type_: str,
+    name: str,
+}
+
+struct Dog {
+    type_: str,
+    barks: option[bool],
+}
+"
+    );
+}
+
+#[test]
+fn rejects_other_documents() {
+    assert!(import(&json!({ "swagger": "2.0" })).is_err());
+    assert!(import(&json!({ "openapi": "2.0" })).is_err());
+}
@@ -0,0 +1,321 @@
+use humblegen::backend::openapi::Generator;
+use humblegen_rt::service_protocol::{ErrorResponse, ErrorResponseKind, RuntimeError};
+use serde_json::{json, Value};
+
+const SPEC: &str = r#"
+/// A monster.
+struct Monster {
+    id: i32,
+    /// Picture, if any.
+    picture: option[bytes],
+    tags: list[str],
+    pair: (u8, str),
+}
+
+enum MonsterError {
+    TooWeak,
+    Named(str),
+    Hp { max: u32 },
+}
+
+struct MonsterQuery {
+    name: option[str],
+    filter: map[str][i32],
+}
+
+service Monsters {
+    /// Find monsters.
+    GET /monsters?{MonsterQuery} -> list[Monster],
+    POST /monsters -> Monster -> result[Monster][MonsterError],
+    PUT /monsters/{id: i32} -> Monster -> result[()][MonsterError],
+    GET /monsters/{id: i32}/picture -> blob["image/*"],
+    GET /monsters/{id: i32}/files/{path: str..} -> bytes,
+    GET /numbers?{list[i32]} -> stream[i32],
+    WS /watch -> str -> Monster,
+}
+"#;
+
+fn document() -> Value {
+    let spec = humblegen::parse(SPEC.as_bytes()).expect("parse spec");
+    Generator::default()
+        .with_title("Monsters")
+        .with_version("1.0.0")
+        .document(&spec)
+}
+
+/// An externally tagged value.
+fn tagged(tag: &str, content: Value) -> Value {
+    json!({
+        "type": "object",
+        "required": [tag],
+        "properties": { tag: content },
+        "additionalProperties": false,
+    })
+}
+
+#[test]
+fn info_and_paths() {
+    let doc = document();
+    assert_eq!(doc["openapi"], "3.0.3");
+    assert_eq!(
+        doc["info"],
+        json!({ "title": "Monsters", "version": "1.0.0" })
+    );
+    assert_eq!(doc["servers"], json!([{ "url": "/api" }]));
+
+    let get = &doc["paths"]["/monsters"]["get"];
+    assert_eq!(get["operationId"], "get_monsters");
+    assert_eq!(get["tags"], json!(["Monsters"]));
+    assert_eq!(get["description"], "Find monsters.");
+    assert_eq!(
+        get["parameters"],
+        json!([
+            { "name": "name", "in": "query", "required": false, "schema": { "type": "string", "nullable": true } },
+            {
+                "name": "filter",
+                "in": "query",
+                "required": false,
+                "schema": { "type": "object", "additionalProperties": { "type": "integer", "format": "int32" } },
+                "style": "deepObject",
+                "explode": true,
+            },
+        ])
+    );
+    assert_eq!(
+        get["responses"]["default"]["content"]["application/json"]["schema"],
+        json!({ "$ref": "#/components/schemas/ErrorResponse" })
+    );
+
+    let put = &doc["paths"]["/monsters/{id}"]["put"];
+    assert_eq!(put["parameters"][0]["in"], "path");
+    assert_eq!(put["parameters"][1]["name"], "If-Match");
+    assert_eq!(
+        put["requestBody"]["content"]["application/json"]["schema"],
+        json!({ "$ref": "#/components/schemas/Monster" })
+    );
+
+    let picture = &doc["paths"]["/monsters/{id}/picture"]["get"]["responses"]["200"];
+    assert_eq!(
+        picture["content"]["image/*"]["schema"],
+        json!({ "type": "string", "format": "binary" })
+    );
+
+    let files = &doc["paths"]["/monsters/{id}/files"]["get"];
+    assert_eq!(files["parameters"].as_array().map(Vec::len), Some(1));
+    assert_eq!(
+        files["x-humblegen-rest"],
+        json!({
+            "name": "path",
+            "description": "The rest of the path, which may contain `/`.",
+            "schema": { "type": "string" },
+        })
+    );
+
+    let numbers = &doc["paths"]["/numbers"]["get"];
+    assert_eq!(
+        numbers["x-humblegen-query"],
+        json!({ "type": "array", "items": { "type": "integer", "format": "int32" } })
+    );
+    assert!(numbers["responses"]["200"]["content"]["application/x-ndjson"].is_object());
+
+    let watch = &doc["paths"]["/watch"]["get"];
+    assert!(watch["responses"]["101"].is_object());
+    assert_eq!(
+        watch["x-humblegen-websocket"]["client"],
+        json!({ "type": "string" })
+    );
+}
+
+#[test]
+fn json_encodings() {
+    let doc = document();
+    let schemas = &doc["components"]["schemas"];
+
+    assert_eq!(
+        schemas["Monster"],
+        json!({
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "format": "int32" },
+                "picture": { "type": "string", "format": "byte", "nullable": true, "description": "Picture, if any." },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "pair": {
+                    "type": "array",
+                    "items": { "anyOf": [{ "type": "integer", "minimum": 0, "maximum": 255 }, { "type": "string" }] },
+                    "minItems": 2,
+                    "maxItems": 2,
+                },
+            },
+            "required": ["id", "pair"],
+            "description": "A monster.",
+        })
+    );
+
+    assert_eq!(
+        schemas["MonsterError"],
+        json!({
+            "oneOf": [
+                { "type": "string", "enum": ["TooWeak"] },
+                tagged("Named", json!({ "type": "string" })),
+                tagged("Hp", json!({
+                    "type": "object",
+                    "properties": { "max": { "type": "integer", "format": "int64", "minimum": 0, "maximum": 4_294_967_295u32 } },
+                    "required": ["max"],
+                })),
+            ],
+        })
+    );
+
+    let post = &doc["paths"]["/monsters"]["post"]["responses"]["200"];
+    assert_eq!(
+        post["content"]["application/json"]["schema"],
+        json!({
+            "oneOf": [
+                tagged("Ok", json!({ "$ref": "#/components/schemas/Monster" })),
+                tagged("Err", json!({ "$ref": "#/components/schemas/MonsterError" })),
+            ],
+        })
+    );
+
+    let error_response = &schemas["ErrorResponse"];
+    assert_eq!(error_response["required"], json!(["code", "kind"]));
+    assert_eq!(
+        error_response["properties"]["kind"]["oneOf"][0]["required"],
+        json!(["Service"])
+    );
+}
+
+/// One value of every `RuntimeError` variant.
+fn runtime_errors() -> Vec<RuntimeError> {
+    let errors = vec![
+        RuntimeError::NoServiceMounted,
+        RuntimeError::ServiceMountsAmbiguous,
+        RuntimeError::NoRouteMountedInService {
+            service: "monsters".to_owned(),
+        },
+        RuntimeError::RouteMountsAmbiguous {
+            service: "monsters".to_owned(),
+        },
+        RuntimeError::MethodNotAllowed {
+            service: "monsters".to_owned(),
+            allowed: vec!["GET".to_owned()],
+        },
+        RuntimeError::RouteParamInvalid {
+            param_name: "id".to_owned(),
+            parse_error: "invalid digit".to_owned(),
+        },
+        RuntimeError::QueryInvalid("query".to_owned()),
+        RuntimeError::PostBodyReadError("read".to_owned()),
+        RuntimeError::PostBodyReadTimeout,
+        RuntimeError::PostBodyInvalid("body".to_owned()),
+        RuntimeError::PayloadTooLarge {
+            max_body_size: 1024,
+        },
+        RuntimeError::Timeout,
+        RuntimeError::RateLimited { retry_after: 1 },
+        RuntimeError::CorsRequestRejected("origin".to_owned()),
+        RuntimeError::UnsupportedContentEncoding("br".to_owned()),
+        RuntimeError::UnsupportedMediaType("text/plain".to_owned()),
+        RuntimeError::NotReady("starting".to_owned()),
+        RuntimeError::WebSocketUpgradeRequired("upgrade".to_owned()),
+        RuntimeError::SerializeHandlerResponse("handler".to_owned()),
+        RuntimeError::SerializeErrorResponse("error".to_owned()),
+        RuntimeError::BadGateway("upstream".to_owned()),
+    ];
+    // fails to compile when a variant is added, as a reminder to add it to the list above
+    for error in &errors {
+        match error {
+            RuntimeError::NoServiceMounted
+            | RuntimeError::ServiceMountsAmbiguous
+            | RuntimeError::NoRouteMountedInService { .. }
+            | RuntimeError::RouteMountsAmbiguous { .. }
+            | RuntimeError::MethodNotAllowed { .. }
+            | RuntimeError::RouteParamInvalid { .. }
+            | RuntimeError::QueryInvalid(_)
+            | RuntimeError::PostBodyReadError(_)
+            | RuntimeError::PostBodyReadTimeout
+            | RuntimeError::PostBodyInvalid(_)
+            | RuntimeError::PayloadTooLarge { .. }
+            | RuntimeError::Timeout
+            | RuntimeError::RateLimited { .. }
+            | RuntimeError::CorsRequestRejected(_)
+            | RuntimeError::UnsupportedContentEncoding(_)
+            | RuntimeError::UnsupportedMediaType(_)
+            | RuntimeError::NotReady(_)
+            | RuntimeError::WebSocketUpgradeRequired(_)
+            | RuntimeError::SerializeHandlerResponse(_)
+            | RuntimeError::SerializeErrorResponse(_)
+            | RuntimeError::BadGateway(_) => {}
+        }
+    }
+    errors
+}
+
+/// Whether `value` matches `schema`, for the subset of JSON Schema used by `ErrorResponse`.
+fn matches_schema(schema: &Value, value: &Value) -> bool {
+    if let Some(alternatives) = schema["oneOf"].as_array() {
+        let matching = alternatives
+            .iter()
+            .filter(|alternative| matches_schema(alternative, value))
+            .count();
+        if matching != 1 {
+            return false;
+        }
+    }
+    let type_matches = match (schema["type"].as_str(), value) {
+        (None, _) => true,
+        (Some("string"), Value::String(_)) => true,
+        (Some("integer"), Value::Number(number)) => number.is_i64() || number.is_u64(),
+        (Some("array"), Value::Array(items)) => items
+            .iter()
+            .all(|item| matches_schema(&schema["items"], item)),
+        (Some("object"), Value::Object(object)) => {
+            let properties = schema["properties"]
+                .as_object()
+                .cloned()
+                .unwrap_or_default();
+            let required = schema["required"].as_array().cloned().unwrap_or_default();
+            required
+                .iter()
+                .all(|name| object.contains_key(name.as_str().unwrap()))
+                && object
+                    .iter()
+                    .all(|(name, field)| match properties.get(name) {
+                        Some(property) => matches_schema(property, field),
+                        None => schema["additionalProperties"] != false,
+                    })
+        }
+        (Some("string"), _) | (Some("integer"), _) | (Some("array"), _) | (Some("object"), _) => {
+            false
+        }
+        (Some(other), _) => panic!("unsupported schema type {}", other),
+    };
+    let enum_matches = match schema["enum"].as_array() {
+        Some(values) => values.contains(value),
+        None => true,
+    };
+    let minimum_matches = match (schema["minimum"].as_i64(), value.as_i64()) {
+        (Some(minimum), Some(value)) => value >= minimum,
+        _ => true,
+    };
+    type_matches && enum_matches && minimum_matches
+}
+
+#[test]
+fn error_response_schema_matches_every_runtime_error() {
+    let doc = document();
+    let schema = &doc["components"]["schemas"]["ErrorResponse"];
+    for error in runtime_errors() {
+        let response = ErrorResponse {
+            code: 400,
+            kind: ErrorResponseKind::Runtime(error),
+        };
+        let value = serde_json::to_value(&response).unwrap();
+        assert!(matches_schema(schema, &value), "{} does not match", value);
+    }
+
+    let service_error = json!({ "code": 500, "kind": { "Service": { "Internal": "db" } } });
+    assert!(matches_schema(schema, &service_error));
+    let unknown = json!({ "code": 400, "kind": { "Runtime": "NoSuchError" } });
+    assert!(!matches_schema(schema, &unknown));
+}
@@ -10,15 +10,20 @@ struct RustTestCase {
     humble_spec: PathBuf,
     humble_rust_out: PathBuf,
     main: PathBuf,
+    /// Whether mock handlers are generated, only for test case dirs named `*-mocks`.
+    mocks: bool,
 }
 
 impl RustTestCase {
     fn run(&self) {
         let spec_file = std::fs::File::open(&self.humble_spec).expect("open humble spec file");
         let spec = humblegen::parse(spec_file).expect("parse humble spec file");
-        let codegen =
+        let mut codegen =
             humblegen::backend::rust::Generator::new(humblegen::Artifact::ServerEndpoints)
                 .expect("failed to init humblegen rust backend");
+        if self.mocks {
+            codegen = codegen.with_mocks();
+        }
         codegen
             .generate(&spec, &self.humble_rust_out)
             .expect("humblegen rust backend failed");
@@ -84,6 +89,7 @@ impl RustTestCase {
             humble_spec: humble_spec.must_exist()?,
             humble_rust_out: humble_rust_out.must_exist()?,
             main: main.must_exist()?,
+            mocks: name.ends_with("-mocks"),
         })
     }
 }
@@ -0,0 +1,44 @@
+include!("spec.rs");
+
+use serde_json::json;
+
+fn main() {
+    // bytes are base64-encoded as fields and nested in options, lists and maps
+    let attachments = Attachments {
+        data: b"hi".to_vec(),
+        preview: Some(b"hi".to_vec()),
+        chunks: vec![b"hi".to_vec(), b"ho".to_vec()],
+        named: vec![("a".to_owned(), b"hi".to_vec())].into_iter().collect(),
+        versions: Some(vec![b"hi".to_vec()]),
+    };
+    let serialized = serde_json::to_value(&attachments).unwrap();
+    assert_eq!(
+        serialized,
+        json!({
+            "data": "aGk=",
+            "preview": "aGk=",
+            "chunks": ["aGk=", "aG8="],
+            "named": {"a": "aGk="},
+            "versions": ["aGk="],
+        })
+    );
+    let deserialized: Attachments = serde_json::from_value(serialized).unwrap();
+    assert_eq!(deserialized.chunks, attachments.chunks);
+    assert_eq!(deserialized.named, attachments.named);
+    assert_eq!(deserialized.versions, attachments.versions);
+
+    let payload = Payload::Raw {
+        data: b"hi".to_vec(),
+        parts: vec![b"ho".to_vec()],
+    };
+    assert_eq!(
+        serde_json::to_value(&payload).unwrap(),
+        json!({"Raw": {"data": "aGk=", "parts": ["aG8="]}})
+    );
+
+    // lists of bytes in URL queries are repeated keys of base64-encoded values
+    let checksums: Checksums = deser_query_serde_qs("hashes=aGk%3D&hashes=aG8%3D").unwrap();
+    assert_eq!(checksums.hashes, vec![b"hi".to_vec(), b"ho".to_vec()]);
+    let checksums: Checksums = deser_query_serde_qs("hashes=aGk%3D").unwrap();
+    assert_eq!(checksums.hashes, vec![b"hi".to_vec()]);
+}
@@ -59,9 +59,32 @@ impl BlogApi for S {
 
 #[tokio::main]
 async fn main() {
-    // let listen_addr: std::net::SocketAddr = "127.0.0.1:3000".parse().unwrap();
-    Builder::new().add("/api", Handler::BlogApi(Arc::new(S)));
-    // .listen_and_run_forever(&listen_addr)
-    // .await
-    // .unwrap();
+    let client = Builder::new()
+        .add("/api", Handler::BlogApi(Arc::new(S)))
+        .into_test_client()
+        .unwrap();
+    let post = Post {
+        content: "hello".to_owned(),
+    };
+
+    // without the token, the interceptor rejects the request
+    let res = client.post("/api/alice/posts").json(&post).send().await;
+    assert_eq!(res.status, 403);
+    assert!(res.error_response().is_some());
+
+    let authorized = client
+        .clone()
+        .with_header(hyper::header::AUTHORIZATION, "Custom AUTHZ_TOKEN");
+    let blog = BlogApiTestClient::new(&authorized, "/api");
+    let posted = blog
+        .post_user_posts(post, "alice".to_owned())
+        .await
+        .unwrap();
+    assert_eq!(posted.content, "hello");
+
+    // alice may not post for bob
+    match blog.post_user_posts(posted, "bob".to_owned()).await {
+        Err(humblegen_rt::test_client::TestError::ErrorResponse(e)) => assert_eq!(e.code, 403),
+        other => panic!("unexpected response {:?}", other),
+    }
 }
@@ -0,0 +1,96 @@
+include!("spec.rs");
+
+/// Serves `mock` and returns a client of it.
+fn serve(mock: MockGodzilla) -> ::humblegen_rt::test_client::TestClient {
+    Builder::new()
+        .add("/api", Handler::Godzilla(Arc::new(mock)))
+        .into_test_client()
+        .unwrap()
+}
+
+#[tokio::main]
+async fn main() {
+    let client = serve(
+        MockGodzilla::default()
+            .on_get_foo(|| Ok(42))
+            .on_get_monsters_id(|| Ok(Err(MonsterError::TooWeak))),
+    );
+    let godzilla = GodzillaTestClient::new(&client, "/api");
+
+    // canned responses
+    assert_eq!(godzilla.get_foo().await.unwrap(), 42);
+    match godzilla.get_monsters_id(1).await.unwrap() {
+        Err(MonsterError::TooWeak) => (),
+        other => panic!("unexpected response {:?}", other),
+    }
+
+    // fake data
+    godzilla.get_monsters(None).await.unwrap();
+    godzilla.get_monsters_5(None).await.unwrap();
+    godzilla
+        .post_monsters_import(humblegen_rt::blob::Blob::new("a,b"))
+        .await
+        .unwrap()
+        .unwrap();
+    godzilla.get_monster_export(None).await.unwrap();
+    godzilla.get_monsters_id_portrait(7).await.unwrap();
+
+    // query lists are repeated keys, a single key is a list of one element
+    for query in &[
+        "tags=a&tags=b",
+        "tags=a",
+        "tags[]=a&tags[]=b",
+        "tags=a&ids[x]=1",
+    ] {
+        let response = client
+            .get(&format!("/api/monsters5?{}", query))
+            .send()
+            .await;
+        assert!(response.status.is_success(), "{}: {:?}", query, response);
+    }
+    let response = client.get("/api/monsters5?ids[x]=1&ids[x]=2").send().await;
+    assert_eq!(response.status, 400);
+
+    // multipart parts are collected by name, only the streamed last file part must come last
+    let multipart = |parts: &[(&str, &str)]| {
+        let body: String = parts
+            .iter()
+            .map(|(name, value)| {
+                format!(
+                    "--X\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                    name, value
+                )
+            })
+            .collect();
+        client
+            .post("/api/monsters/7/portrait")
+            .body(body + "--X--\r\n", "multipart/form-data; boundary=X")
+            .send()
+    };
+    let response = multipart(&[("thumbnail", "t"), ("caption", "\"c\""), ("portrait", "p")]).await;
+    assert!(response.status.is_success(), "{:?}", response);
+    let response = multipart(&[("caption", "\"c\""), ("portrait", "p")]).await;
+    assert_eq!(response.status, 400);
+    let error = serde_json::to_string(&response.error_response().unwrap()).unwrap();
+    assert!(error.contains("missing file part `thumbnail`"), "{}", error);
+
+    // compressed multipart bodies are rejected
+    let response = client
+        .post("/api/monsters/7/portrait")
+        .header(humblegen_rt::hyper::header::CONTENT_ENCODING, "gzip")
+        .body("--X--\r\n", "multipart/form-data; boundary=X")
+        .send()
+        .await;
+    assert_eq!(response.status, 415);
+
+    // mocks with the same seed answer with the same fake data
+    let monsters = |seed| async move {
+        let client = serve(MockGodzilla::with_seed(seed));
+        let monsters = GodzillaTestClient::new(&client, "/api")
+            .get_monsters_4()
+            .await
+            .unwrap();
+        serde_json::to_value(monsters).unwrap()
+    };
+    assert_eq!(monsters(7).await, monsters(7).await);
+}
@@ -0,0 +1,1105 @@
+#[derive(Debug, Clone, serde :: Deserialize, serde :: Serialize)]
+#[doc = "A wandering monster"]
+pub struct Monster {
+    #[doc = "Monster ID."]
+    pub id: i32,
+    #[doc = "The monster's name"]
+    pub name: String,
+    #[doc = "Max hitpoints."]
+    pub hp: i32,
+}
+#[derive(Debug, Clone, serde :: Deserialize, serde :: Serialize)]
+#[doc = ""]
+pub struct MonsterData {
+    #[doc = "The monster's name"]
+    pub name: String,
+    #[doc = "Max hitpoints."]
+    pub hp: i32,
+}
+#[derive(Debug, Clone, serde :: Deserialize, serde :: Serialize)]
+#[doc = "Errors returned by the monster service."]
+pub enum MonsterError {
+    #[doc = ""]
+    TooWeak,
+    #[doc = ""]
+    TooStrong {
+        #[doc = ""]
+        max_strength: i32,
+    },
+}
+#[derive(Debug, Clone, serde :: Deserialize, serde :: Serialize)]
+#[doc = ""]
+pub struct MonsterQuery {
+    #[doc = ""]
+    pub name: Option<String>,
+    #[doc = ""]
+    pub max_age: Option<i32>,
+}
+#[derive(Debug, Clone, serde :: Deserialize, serde :: Serialize)]
+#[doc = "A query with nested values, encoded using bracket notation."]
+pub struct MonsterFilter {
+    #[doc = ""]
+    #[serde(deserialize_with = "::humblegen_rt::serialization_helpers::deser_query_list_field")]
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[doc = ""]
+    pub error: Option<MonsterError>,
+    #[doc = ""]
+    pub data: Option<MonsterData>,
+    #[doc = ""]
+    #[serde(default)]
+    pub ids: ::std::collections::HashMap<String, i32>,
+}
+#[allow(unused_imports)]
+use ::humblegen_rt::deser_helpers::{
+    deser_param, deser_post_data, deser_query_list, deser_query_primitive, deser_query_serde_qs,
+};
+#[allow(unused_imports)]
+pub use ::humblegen_rt::handler::{self, HandlerResponse as Response, ServiceError};
+#[allow(unused_imports)]
+use ::humblegen_rt::regexset_map::RegexSetMap;
+#[allow(unused_imports)]
+use ::humblegen_rt::server::{self, Route, Service};
+#[allow(unused_imports)]
+use ::humblegen_rt::service_protocol::ErrorResponse;
+use ::humblegen_rt::tracing_futures::Instrument;
+#[allow(unused_imports)]
+use ::humblegen_rt::{hyper, tracing};
+#[allow(unused_imports)]
+use ::std::sync::Arc;
+use std::net::SocketAddr;
+#[doc = r" Builds an HTTP server that exposes services implemented by handler trait objects."]
+#[derive(Debug)]
+pub struct Builder {
+    services: Vec<Service>,
+    config: server::Config,
+}
+impl Builder {
+    pub fn new() -> Self {
+        Self {
+            services: vec![],
+            config: server::Config::default(),
+        }
+    }
+    #[doc = r" Sets the maximum size of a request body in bytes."]
+    #[doc = r" Requests with larger bodies are rejected with HTTP status 413."]
+    #[doc = r" Can be overridden per service using `add_with_config`."]
+    pub fn max_body_size(mut self, max_body_size: u64) -> Self {
+        self.config.max_body_size = max_body_size;
+        self
+    }
+    #[doc = r" Sets the maximum time spent reading a request body."]
+    #[doc = r" Requests whose body takes longer are rejected with HTTP status 408."]
+    #[doc = r" Can be overridden per service using `add_with_config`."]
+    pub fn body_read_timeout(mut self, timeout: ::std::time::Duration) -> Self {
+        self.config.body_read_timeout = Some(timeout);
+        self
+    }
+    #[doc = r" Sets the maximum time spent handling a request."]
+    #[doc = r" When it elapses, the handler is cancelled and the request is answered with HTTP status 504."]
+    #[doc = r" Can be overridden per service using `add_with_config`."]
+    pub fn request_timeout(mut self, timeout: ::std::time::Duration) -> Self {
+        self.config.request_timeout = Some(timeout);
+        self
+    }
+    #[doc = r" Enables Cross-Origin Resource Sharing."]
+    #[doc = r" CORS preflight requests are answered for every mounted route,"]
+    #[doc = r" and CORS headers are added to every response to an allowed origin."]
+    pub fn cors(mut self, cors: ::humblegen_rt::cors::CorsConfig) -> Self {
+        self.config.cors = Some(cors);
+        self
+    }
+    #[doc = r" Enables response compression for response bodies of at least `threshold` bytes."]
+    #[doc = r" The content coding (`br`, `gzip` or `deflate`) is negotiated using the request's"]
+    #[doc = r" `Accept-Encoding` header."]
+    pub fn compression(mut self, threshold: usize) -> Self {
+        self.config.compression_threshold = Some(threshold);
+        self
+    }
+    #[doc = r" Enables weak `ETag`s on successful JSON responses to `GET` requests."]
+    #[doc = r" Requests whose `If-None-Match` header matches the `ETag` are answered with HTTP status 304."]
+    pub fn etags(mut self) -> Self {
+        self.config.etags = true;
+        self
+    }
+    #[doc = r" Sets the generator of request IDs for requests without a valid `Request-ID` header"]
+    #[doc = r" (default: 30 random alphanumeric characters)."]
+    pub fn request_id_generator(
+        mut self,
+        generator: impl Fn() -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.config.request_id_generator = ::std::sync::Arc::new(generator);
+        self
+    }
+    #[doc = r" Enables Prometheus metrics (requires the `metrics` feature of `humblegen-rt`)."]
+    #[doc = r" Requests are recorded per route and outcome; set `MetricsConfig::endpoint`"]
+    #[doc = r" to expose the metrics."]
+    pub fn metrics(mut self, metrics: ::humblegen_rt::metrics::MetricsConfig) -> Self {
+        self.config.metrics = Some(metrics);
+        self
+    }
+    #[doc = r" Mounts a liveness route at URL path `path` (e.g. `/health`)"]
+    #[doc = r" that answers `GET` requests with HTTP status 200."]
+    pub fn liveness_endpoint(mut self, path: &str) -> Self {
+        self.config.builtin_routes.liveness = Some(path.to_owned());
+        self
+    }
+    #[doc = r" Mounts a readiness route at URL path `path` (e.g. `/ready`)."]
+    #[doc = r" It answers `GET` requests with HTTP status 200 if `check` returns `Ok`,"]
+    #[doc = r" and with HTTP status 503 otherwise."]
+    pub fn readiness_endpoint<F, Fut>(mut self, path: &str, check: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: ::std::future::Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.config.builtin_routes.readiness = Some((
+            path.to_owned(),
+            ::humblegen_rt::builtin_routes::readiness_check(check),
+        ));
+        self
+    }
+    #[doc = r" Mounts an introspection route at URL path `path` (e.g. `/introspection`)"]
+    #[doc = r" that answers `GET` requests with the mounted services and their routes."]
+    pub fn introspection_endpoint(mut self, path: &str) -> Self {
+        self.config.builtin_routes.introspection = Some(path.to_owned());
+        self
+    }
+    #[doc = r" Trusts the `X-Forwarded-For` header of requests from the given proxies"]
+    #[doc = r" to name the client (see `humblegen_rt::connection::ClientIp`)."]
+    pub fn trusted_proxies(
+        mut self,
+        proxies: impl IntoIterator<Item = ::std::net::IpAddr>,
+    ) -> Self {
+        self.config.trusted_proxies = proxies.into_iter().collect();
+        self
+    }
+    #[doc = r" Mounts `handler` at URL path prefix `root`."]
+    #[doc = r" This means that a `handler` implementing humble service"]
+    #[doc = r" ```"]
+    #[doc = r" service S {"]
+    #[doc = r"     GET /bar -> i32,"]
+    #[doc = r"     GET /baz -> str,"]
+    #[doc = r" }"]
+    #[doc = r" ```"]
+    #[doc = r#" and `root="/api"` will expose"#]
+    #[doc = r" * handler method `fn bar() -> i32` at `/api/bar` and"]
+    #[doc = r" * handler method `fn baz() -> String` at `/api/baz`"]
+    pub fn add<Context: Default + Sized + Send + Sync>(
+        self,
+        root: &str,
+        handler: Handler<Context>,
+    ) -> Self {
+        self.add_with_config(root, handler, server::ServiceConfig::default())
+    }
+    #[doc = r" Like `add`, but overrides the server-wide configuration for this service"]
+    #[doc = r" with the values set in `config`."]
+    pub fn add_with_config<Context: Default + Sized + Send + Sync>(
+        mut self,
+        root: &str,
+        handler: Handler<Context>,
+        config: server::ServiceConfig,
+    ) -> Self {
+        if !root.starts_with('/') {
+            panic!("root must start with \"/\"")
+        } else if root.ends_with('/') {
+            panic!("root must not end with \"/\"")
+        }
+        let name = handler.service_name().to_owned();
+        let routes: Vec<Route> = handler.into_routes();
+        let routes = RegexSetMap::new(routes).unwrap();
+        self.services.push(Service {
+            name,
+            root: root.to_owned(),
+            regex: humblegen_rt::regex::Regex::new(&format!(r"^(?P<root>{})(?P<suffix>/.*)", root))
+                .unwrap(),
+            routes,
+            config,
+        });
+        self
+    }
+    #[doc = r" Starts an HTTP server bound to address `addr` and serves incoming requests using"]
+    #[doc = r" the previously `add`ed handlers."]
+    pub async fn listen_and_run_forever(
+        self,
+        addr: &SocketAddr,
+    ) -> humblegen_rt::anyhow::Result<()> {
+        server::listen_and_run_forever(self.into_server()?, addr).await
+    }
+    #[doc = r" Builds the server without starting it, e.g. to serve connections accepted by a custom"]
+    #[doc = r" accept loop (that terminates TLS) using `humblegen_rt::server::handle_request`."]
+    pub fn into_server(self) -> humblegen_rt::anyhow::Result<server::Server> {
+        use humblegen_rt::anyhow::Context;
+        for service in &self.services {
+            service.check_config()?;
+        }
+        let services = RegexSetMap::new(self.services).context("invalid service configuration")?;
+        Ok(server::Server {
+            services,
+            config: self.config,
+        })
+    }
+    #[doc = r" Builds an in-process client of the server for testing, which passes requests"]
+    #[doc = r" to the handlers without binding a socket."]
+    #[doc = r" Use `$ServiceNameTestClient` for typed requests to a mounted service."]
+    pub fn into_test_client(
+        self,
+    ) -> humblegen_rt::anyhow::Result<::humblegen_rt::test_client::TestClient> {
+        Ok(::humblegen_rt::test_client::TestClient::new(
+            self.into_server()?,
+        ))
+    }
+}
+#[doc = r" Wrapper enum with one variant for each service defined in the humble spec."]
+#[doc = r" Used to pass instantiated handler trait objects to `Builder::add`."]
+#[allow(dead_code)]
+pub enum Handler<Context: Default + Sized + Send + Sync + 'static> {
+    Godzilla(Arc<dyn Godzilla<Context = Context> + Send + Sync>),
+}
+impl<Context: Default + Sized + Send + Sync + 'static> Handler<Context> {
+    fn into_routes(self) -> Vec<Route> {
+        match self {
+            Handler::Godzilla(h) => routes_Godzilla(h),
+        }
+    }
+    fn service_name(&self) -> &'static str {
+        match self {
+            Handler::Godzilla(_) => "Godzilla",
+        }
+    }
+}
+impl<Context: Default + Sized + Send + Sync + 'static> std::fmt::Debug for Handler<Context> {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Handler::Godzilla(_) => write!(formatter, "{}", "Godzilla")?,
+        }
+        Ok(())
+    }
+}
+#[derive(Debug)]
+#[doc = "Multipart body of `Godzilla::post_monsters_id_portrait`."]
+pub struct GodzillaPostMonstersIdPortraitMultipart {
+    pub caption: Option<String>,
+    pub thumbnail: ::humblegen_rt::multipart::FilePart,
+    pub portrait: ::humblegen_rt::multipart::FilePart,
+}
+#[doc = "service Godzilla provides services related to monsters."]
+#[doc = "```\n#[humblegen_rt::async_trait(Sync)]\npub trait Godzilla {\n    type Context: Default + Sized + Send + Sync;\n    async fn intercept_handler_pre(\n        &self,\n        _req: &hyper::Request<hyper::Body>,\n    ) -> Result<Self::Context, ServiceError> {\n        Ok(Self::Context::default())\n    }\n    fn rate_limit_key(&self, _ctx: &Self::Context) -> Option<String> {\n        None\n    }\n    async fn get_foo(&self, ctx: Self::Context) -> Response<u32>;\n    async fn get_monsters_id(\n        &self,\n        ctx: Self::Context,\n        id: i32,\n    ) -> Response<Result<Monster, MonsterError>>;\n    async fn get_monsters(\n        &self,\n        ctx: Self::Context,\n        query: Option<MonsterQuery>,\n    ) -> Response<Vec<Monster>>;\n    async fn get_monsters_4(&self, ctx: Self::Context) -> Response<Vec<Monster>>;\n    async fn get_monsters_5(\n        &self,\n        ctx: Self::Context,\n        query: Option<MonsterFilter>,\n    ) -> Response<Vec<Monster>>;\n    async fn get_monster_export(\n        &self,\n        ctx: Self::Context,\n        query: Option<MonsterQuery>,\n    ) -> Response<::humblegen_rt::stream::ResponseStream<Monster>>;\n    async fn post_monsters_id_portrait(\n        &self,\n        ctx: Self::Context,\n        post_body: GodzillaPostMonstersIdPortraitMultipart,\n        id: i32,\n    ) -> Response<Result<(), MonsterError>>;\n    async fn get_monsters_id_portrait(\n        &self,\n        ctx: Self::Context,\n        id: i32,\n    ) -> Response<::humblegen_rt::blob::Blob>;\n    async fn post_monsters_import(\n        &self,\n        ctx: Self::Context,\n        post_body: ::humblegen_rt::blob::Blob,\n    ) -> Response<Result<u32, MonsterError>>;\n}\n\n```"]
+#[humblegen_rt::async_trait(Sync)]
+pub trait Godzilla {
+    type Context: Default + Sized + Send + Sync;
+    async fn intercept_handler_pre(
+        &self,
+        _req: &hyper::Request<hyper::Body>,
+    ) -> Result<Self::Context, ServiceError> {
+        Ok(Self::Context::default())
+    }
+    fn rate_limit_key(&self, _ctx: &Self::Context) -> Option<String> {
+        None
+    }
+    #[doc = "```\nasync fn get_foo(&self, ctx: Self::Context) -> Response<u32> {}\n\n```"]
+    #[doc = "Get foo."]
+    async fn get_foo(&self, ctx: Self::Context) -> Response<u32>;
+    #[doc = "```\nasync fn get_monsters_id(\n    &self,\n    ctx: Self::Context,\n    id: i32,\n) -> Response<Result<Monster, MonsterError>> {\n}\n\n```"]
+    #[doc = "Get monster by id"]
+    async fn get_monsters_id(
+        &self,
+        ctx: Self::Context,
+        id: i32,
+    ) -> Response<Result<Monster, MonsterError>>;
+    #[doc = "```\nasync fn get_monsters(\n    &self,\n    ctx: Self::Context,\n    query: Option<MonsterQuery>,\n) -> Response<Vec<Monster>> {\n}\n\n```"]
+    #[doc = ""]
+    async fn get_monsters(
+        &self,
+        ctx: Self::Context,
+        query: Option<MonsterQuery>,
+    ) -> Response<Vec<Monster>>;
+    #[doc = "```\nasync fn get_monsters_4(&self, ctx: Self::Context) -> Response<Vec<Monster>> {}\n\n```"]
+    #[doc = ""]
+    async fn get_monsters_4(&self, ctx: Self::Context) -> Response<Vec<Monster>>;
+    #[doc = "```\nasync fn get_monsters_5(\n    &self,\n    ctx: Self::Context,\n    query: Option<MonsterFilter>,\n) -> Response<Vec<Monster>> {\n}\n\n```"]
+    #[doc = ""]
+    async fn get_monsters_5(
+        &self,
+        ctx: Self::Context,
+        query: Option<MonsterFilter>,
+    ) -> Response<Vec<Monster>>;
+    #[doc = "```\nasync fn get_monster_export(\n    &self,\n    ctx: Self::Context,\n    query: Option<MonsterQuery>,\n) -> Response<::humblegen_rt::stream::ResponseStream<Monster>> {\n}\n\n```"]
+    #[doc = "Export all monsters, one at a time."]
+    async fn get_monster_export(
+        &self,
+        ctx: Self::Context,
+        query: Option<MonsterQuery>,
+    ) -> Response<::humblegen_rt::stream::ResponseStream<Monster>>;
+    #[doc = "```\nasync fn post_monsters_id_portrait(\n    &self,\n    ctx: Self::Context,\n    post_body: GodzillaPostMonstersIdPortraitMultipart,\n    id: i32,\n) -> Response<Result<(), MonsterError>> {\n}\n\n```"]
+    #[doc = "Upload a portrait of a monster, along with a thumbnail."]
+    async fn post_monsters_id_portrait(
+        &self,
+        ctx: Self::Context,
+        post_body: GodzillaPostMonstersIdPortraitMultipart,
+        id: i32,
+    ) -> Response<Result<(), MonsterError>>;
+    #[doc = "```\nasync fn get_monsters_id_portrait(\n    &self,\n    ctx: Self::Context,\n    id: i32,\n) -> Response<::humblegen_rt::blob::Blob> {\n}\n\n```"]
+    #[doc = "Get the portrait of a monster, in whatever image format it was uploaded."]
+    async fn get_monsters_id_portrait(
+        &self,
+        ctx: Self::Context,
+        id: i32,
+    ) -> Response<::humblegen_rt::blob::Blob>;
+    #[doc = "```\nasync fn post_monsters_import(\n    &self,\n    ctx: Self::Context,\n    post_body: ::humblegen_rt::blob::Blob,\n) -> Response<Result<u32, MonsterError>> {\n}\n\n```"]
+    #[doc = "Import monsters from a CSV file, returning the number of imported monsters."]
+    async fn post_monsters_import(
+        &self,
+        ctx: Self::Context,
+        post_body: ::humblegen_rt::blob::Blob,
+    ) -> Response<Result<u32, MonsterError>>;
+}
+#[allow(unused_variables)]
+#[allow(unused_mut)]
+#[allow(non_snake_case)]
+#[allow(clippy::trivial_regex)]
+#[allow(clippy::single_char_pattern)]
+fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
+    handler: Arc<dyn Godzilla<Context = Context> + Send + Sync>,
+) -> Vec<Route> {
+    vec![
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "get_foo".to_owned(),
+                method: ::humblegen_rt::hyper::Method::GET,
+                template: "/foo".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/foo$").unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
+                                    handler.get_foo(ctx).instrument(span).await,
+                                ))
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "get_monsters_id".to_owned(),
+                method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monsters/{id}".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monsters/(?P<id>[^/]+)$").unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        let id: Result<i32, ErrorResponse> = deser_param("id", &captures["id"]);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let id = id?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(
+                                    server::result_handler_response_to_hyper_response_with_format(
+                                        response_format,
+                                        handler.get_monsters_id(ctx, id).instrument(span).await,
+                                    ),
+                                )
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "get_monsters".to_owned(),
+                method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monsters".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monsters$").unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let query: Option<MonsterQuery> = match req.uri().query() {
+                                None => None,
+                                Some(q) => Some(deser_query_serde_qs(q)?),
+                            };
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
+                                    handler.get_monsters(ctx, query).instrument(span).await,
+                                ))
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "get_monsters_4".to_owned(),
+                method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monsters4".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monsters4$").unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
+                                    handler.get_monsters_4(ctx).instrument(span).await,
+                                ))
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "get_monsters_5".to_owned(),
+                method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monsters5".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monsters5$").unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let query: Option<MonsterFilter> = match req.uri().query() {
+                                None => None,
+                                Some(q) => Some(deser_query_serde_qs(q)?),
+                            };
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
+                                    handler.get_monsters_5(ctx, query).instrument(span).await,
+                                ))
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "get_monster_export".to_owned(),
+                method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monster-export".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monster-export$").unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let query: Option<MonsterQuery> = match req.uri().query() {
+                                None => None,
+                                Some(q) => Some(deser_query_serde_qs(q)?),
+                            };
+                            let response_format = ::humblegen_rt::stream::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(::humblegen_rt::stream::handler_response_to_hyper_response(
+                                    response_format,
+                                    handler
+                                        .get_monster_export(ctx, query)
+                                        .instrument(span)
+                                        .await,
+                                ))
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "post_monsters_id_portrait".to_owned(),
+                method: ::humblegen_rt::hyper::Method::POST,
+                template: "/monsters/{id}/portrait".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monsters/(?P<id>[^/]+)/portrait$")
+                    .unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        let id: Result<i32, ErrorResponse> = deser_param("id", &captures["id"]);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let id = id?;
+                            let mut multipart = ::humblegen_rt::multipart::Multipart::from_request(
+                                &mut req,
+                                Some("portrait"),
+                            )
+                            .await?;
+                            let post_body = GodzillaPostMonstersIdPortraitMultipart {
+                                caption: multipart.field::<Option<String>>("caption")?,
+                                thumbnail: multipart.buffered_file("thumbnail")?,
+                                portrait: multipart.streaming_file("portrait")?,
+                            };
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(
+                                    server::result_handler_response_to_hyper_response_with_format(
+                                        response_format,
+                                        handler
+                                            .post_monsters_id_portrait(ctx, post_body, id)
+                                            .instrument(span)
+                                            .await,
+                                    ),
+                                )
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "get_monsters_id_portrait".to_owned(),
+                method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monsters/{id}/portrait".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monsters/(?P<id>[^/]+)/portrait$")
+                    .unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        let id: Result<i32, ErrorResponse> = deser_param("id", &captures["id"]);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let id = id?;
+                            let response_format = "image/*";
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(::humblegen_rt::blob::handler_response_to_hyper_response(
+                                    response_format,
+                                    handler
+                                        .get_monsters_id_portrait(ctx, id)
+                                        .instrument(span)
+                                        .await,
+                                ))
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "post_monsters_import".to_owned(),
+                method: ::humblegen_rt::hyper::Method::POST,
+                template: "/monsters-import".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monsters-import$").unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let post_body = ::humblegen_rt::blob::deser_blob(&mut req, "text/csv")?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(
+                                    server::result_handler_response_to_hyper_response_with_format(
+                                        response_format,
+                                        handler
+                                            .post_monsters_import(ctx, post_body)
+                                            .instrument(span)
+                                            .await,
+                                    ),
+                                )
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+    ]
+}
+#[doc = "Typed in-process client of a mounted `Godzilla` service, for testing (see `Builder::into_test_client`)."]
+#[derive(Debug, Clone)]
+pub struct GodzillaTestClient<'a> {
+    client: &'a ::humblegen_rt::test_client::TestClient,
+    root: String,
+}
+impl<'a> GodzillaTestClient<'a> {
+    #[doc = r" A client of the service mounted at URL path prefix `root`."]
+    pub fn new(client: &'a ::humblegen_rt::test_client::TestClient, root: &str) -> Self {
+        Self {
+            client,
+            root: root.to_owned(),
+        }
+    }
+    #[doc = "Invokes `Godzilla::get_foo`."]
+    pub async fn get_foo(&self) -> Result<u32, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/foo", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_monsters_id`."]
+    pub async fn get_monsters_id(
+        &self,
+        id: i32,
+    ) -> Result<Result<Monster, MonsterError>, ::humblegen_rt::test_client::TestError> {
+        let path = format!(
+            "{}/monsters/{}",
+            self.root,
+            ::humblegen_rt::test_client::route_param(&id)
+        );
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_monsters`."]
+    pub async fn get_monsters(
+        &self,
+        query: Option<MonsterQuery>,
+    ) -> Result<Vec<Monster>, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/monsters", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        let request = match &query {
+            Some(query) => request.query(query),
+            None => request,
+        };
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_monsters_4`."]
+    pub async fn get_monsters_4(
+        &self,
+    ) -> Result<Vec<Monster>, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/monsters4", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_monsters_5`."]
+    pub async fn get_monsters_5(
+        &self,
+        query: Option<MonsterFilter>,
+    ) -> Result<Vec<Monster>, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/monsters5", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        let request = match &query {
+            Some(query) => request.query(query),
+            None => request,
+        };
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_monster_export`."]
+    pub async fn get_monster_export(
+        &self,
+        query: Option<MonsterQuery>,
+    ) -> Result<Vec<Monster>, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/monster-export", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        let request = match &query {
+            Some(query) => request.query(query),
+            None => request,
+        };
+        request.send().await.decode_ndjson()
+    }
+    #[doc = "Invokes `Godzilla::get_monsters_id_portrait`."]
+    pub async fn get_monsters_id_portrait(
+        &self,
+        id: i32,
+    ) -> Result<::humblegen_rt::blob::Blob, ::humblegen_rt::test_client::TestError> {
+        let path = format!(
+            "{}/monsters/{}/portrait",
+            self.root,
+            ::humblegen_rt::test_client::route_param(&id)
+        );
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        request.send().await.decode_blob()
+    }
+    #[doc = "Invokes `Godzilla::post_monsters_import`."]
+    pub async fn post_monsters_import(
+        &self,
+        post_body: ::humblegen_rt::blob::Blob,
+    ) -> Result<Result<u32, MonsterError>, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/monsters-import", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::POST, &path);
+        let request = request.blob(post_body, "text/csv");
+        request.send().await.decode()
+    }
+}
+impl ::humblegen_rt::mock::Fake for Monster {
+    fn fake(rng: &mut ::humblegen_rt::mock::FakeRng) -> Self {
+        Monster {
+            id: ::humblegen_rt::mock::Fake::fake(rng),
+            name: ::humblegen_rt::mock::Fake::fake(rng),
+            hp: ::humblegen_rt::mock::Fake::fake(rng),
+        }
+    }
+}
+impl ::humblegen_rt::mock::Fake for MonsterData {
+    fn fake(rng: &mut ::humblegen_rt::mock::FakeRng) -> Self {
+        MonsterData {
+            name: ::humblegen_rt::mock::Fake::fake(rng),
+            hp: ::humblegen_rt::mock::Fake::fake(rng),
+        }
+    }
+}
+impl ::humblegen_rt::mock::Fake for MonsterError {
+    fn fake(rng: &mut ::humblegen_rt::mock::FakeRng) -> Self {
+        match rng.variant(2) {
+            0 => MonsterError::TooWeak,
+            _ => MonsterError::TooStrong {
+                max_strength: ::humblegen_rt::mock::Fake::fake(rng),
+            },
+        }
+    }
+}
+impl ::humblegen_rt::mock::Fake for MonsterQuery {
+    fn fake(rng: &mut ::humblegen_rt::mock::FakeRng) -> Self {
+        MonsterQuery {
+            name: ::humblegen_rt::mock::Fake::fake(rng),
+            max_age: ::humblegen_rt::mock::Fake::fake(rng),
+        }
+    }
+}
+impl ::humblegen_rt::mock::Fake for MonsterFilter {
+    fn fake(rng: &mut ::humblegen_rt::mock::FakeRng) -> Self {
+        MonsterFilter {
+            tags: ::humblegen_rt::mock::Fake::fake(rng),
+            error: ::humblegen_rt::mock::Fake::fake(rng),
+            data: ::humblegen_rt::mock::Fake::fake(rng),
+            ids: ::humblegen_rt::mock::Fake::fake(rng),
+        }
+    }
+}
+#[doc = "Mock implementation of `Godzilla` that answers requests with fake data (see `humblegen_rt::mock`), unless a canned response is set for the endpoint. WebSocket connections are closed right away."]
+pub struct MockGodzilla {
+    rng: ::std::sync::Mutex<::humblegen_rt::mock::FakeRng>,
+    get_foo: Option<Box<dyn Fn() -> Response<u32> + Send + Sync>>,
+    get_monsters_id: Option<Box<dyn Fn() -> Response<Result<Monster, MonsterError>> + Send + Sync>>,
+    get_monsters: Option<Box<dyn Fn() -> Response<Vec<Monster>> + Send + Sync>>,
+    get_monsters_4: Option<Box<dyn Fn() -> Response<Vec<Monster>> + Send + Sync>>,
+    get_monsters_5: Option<Box<dyn Fn() -> Response<Vec<Monster>> + Send + Sync>>,
+    get_monster_export: Option<
+        Box<dyn Fn() -> Response<::humblegen_rt::stream::ResponseStream<Monster>> + Send + Sync>,
+    >,
+    post_monsters_id_portrait:
+        Option<Box<dyn Fn() -> Response<Result<(), MonsterError>> + Send + Sync>>,
+    get_monsters_id_portrait:
+        Option<Box<dyn Fn() -> Response<::humblegen_rt::blob::Blob> + Send + Sync>>,
+    post_monsters_import:
+        Option<Box<dyn Fn() -> Response<Result<u32, MonsterError>> + Send + Sync>>,
+}
+impl MockGodzilla {
+    #[doc = r" A mock whose fake data is generated from `seed`."]
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: ::std::sync::Mutex::new(::humblegen_rt::mock::FakeRng::seeded(seed)),
+            get_foo: None,
+            get_monsters_id: None,
+            get_monsters: None,
+            get_monsters_4: None,
+            get_monsters_5: None,
+            get_monster_export: None,
+            post_monsters_id_portrait: None,
+            get_monsters_id_portrait: None,
+            post_monsters_import: None,
+        }
+    }
+    #[doc = "Answers `Godzilla::get_foo` with the result of `response` instead of fake data."]
+    pub fn on_get_foo(
+        mut self,
+        response: impl Fn() -> Response<u32> + Send + Sync + 'static,
+    ) -> Self {
+        self.get_foo = Some(Box::new(response));
+        self
+    }
+    #[doc = "Answers `Godzilla::get_monsters_id` with the result of `response` instead of fake data."]
+    pub fn on_get_monsters_id(
+        mut self,
+        response: impl Fn() -> Response<Result<Monster, MonsterError>> + Send + Sync + 'static,
+    ) -> Self {
+        self.get_monsters_id = Some(Box::new(response));
+        self
+    }
+    #[doc = "Answers `Godzilla::get_monsters` with the result of `response` instead of fake data."]
+    pub fn on_get_monsters(
+        mut self,
+        response: impl Fn() -> Response<Vec<Monster>> + Send + Sync + 'static,
+    ) -> Self {
+        self.get_monsters = Some(Box::new(response));
+        self
+    }
+    #[doc = "Answers `Godzilla::get_monsters_4` with the result of `response` instead of fake data."]
+    pub fn on_get_monsters_4(
+        mut self,
+        response: impl Fn() -> Response<Vec<Monster>> + Send + Sync + 'static,
+    ) -> Self {
+        self.get_monsters_4 = Some(Box::new(response));
+        self
+    }
+    #[doc = "Answers `Godzilla::get_monsters_5` with the result of `response` instead of fake data."]
+    pub fn on_get_monsters_5(
+        mut self,
+        response: impl Fn() -> Response<Vec<Monster>> + Send + Sync + 'static,
+    ) -> Self {
+        self.get_monsters_5 = Some(Box::new(response));
+        self
+    }
+    #[doc = "Answers `Godzilla::get_monster_export` with the result of `response` instead of fake data."]
+    pub fn on_get_monster_export(
+        mut self,
+        response: impl Fn() -> Response<::humblegen_rt::stream::ResponseStream<Monster>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.get_monster_export = Some(Box::new(response));
+        self
+    }
+    #[doc = "Answers `Godzilla::post_monsters_id_portrait` with the result of `response` instead of fake data."]
+    pub fn on_post_monsters_id_portrait(
+        mut self,
+        response: impl Fn() -> Response<Result<(), MonsterError>> + Send + Sync + 'static,
+    ) -> Self {
+        self.post_monsters_id_portrait = Some(Box::new(response));
+        self
+    }
+    #[doc = "Answers `Godzilla::get_monsters_id_portrait` with the result of `response` instead of fake data."]
+    pub fn on_get_monsters_id_portrait(
+        mut self,
+        response: impl Fn() -> Response<::humblegen_rt::blob::Blob> + Send + Sync + 'static,
+    ) -> Self {
+        self.get_monsters_id_portrait = Some(Box::new(response));
+        self
+    }
+    #[doc = "Answers `Godzilla::post_monsters_import` with the result of `response` instead of fake data."]
+    pub fn on_post_monsters_import(
+        mut self,
+        response: impl Fn() -> Response<Result<u32, MonsterError>> + Send + Sync + 'static,
+    ) -> Self {
+        self.post_monsters_import = Some(Box::new(response));
+        self
+    }
+    #[allow(dead_code)]
+    fn fake<T: ::humblegen_rt::mock::Fake>(&self) -> T {
+        T::fake(&mut self.rng.lock().unwrap())
+    }
+}
+impl Default for MockGodzilla {
+    fn default() -> Self {
+        Self::with_seed(0)
+    }
+}
+impl std::fmt::Debug for MockGodzilla {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockGodzilla").finish()
+    }
+}
+#[allow(unused_variables)]
+#[humblegen_rt::async_trait(Sync)]
+impl Godzilla for MockGodzilla {
+    type Context = ();
+    async fn get_foo(&self, ctx: Self::Context) -> Response<u32> {
+        match &self.get_foo {
+            Some(response) => response(),
+            None => Ok(self.fake()),
+        }
+    }
+    async fn get_monsters_id(
+        &self,
+        ctx: Self::Context,
+        id: i32,
+    ) -> Response<Result<Monster, MonsterError>> {
+        match &self.get_monsters_id {
+            Some(response) => response(),
+            None => Ok(self.fake()),
+        }
+    }
+    async fn get_monsters(
+        &self,
+        ctx: Self::Context,
+        query: Option<MonsterQuery>,
+    ) -> Response<Vec<Monster>> {
+        match &self.get_monsters {
+            Some(response) => response(),
+            None => Ok(self.fake()),
+        }
+    }
+    async fn get_monsters_4(&self, ctx: Self::Context) -> Response<Vec<Monster>> {
+        match &self.get_monsters_4 {
+            Some(response) => response(),
+            None => Ok(self.fake()),
+        }
+    }
+    async fn get_monsters_5(
+        &self,
+        ctx: Self::Context,
+        query: Option<MonsterFilter>,
+    ) -> Response<Vec<Monster>> {
+        match &self.get_monsters_5 {
+            Some(response) => response(),
+            None => Ok(self.fake()),
+        }
+    }
+    async fn get_monster_export(
+        &self,
+        ctx: Self::Context,
+        query: Option<MonsterQuery>,
+    ) -> Response<::humblegen_rt::stream::ResponseStream<Monster>> {
+        match &self.get_monster_export {
+            Some(response) => response(),
+            None => Ok(self.fake()),
+        }
+    }
+    async fn post_monsters_id_portrait(
+        &self,
+        ctx: Self::Context,
+        post_body: GodzillaPostMonstersIdPortraitMultipart,
+        id: i32,
+    ) -> Response<Result<(), MonsterError>> {
+        match &self.post_monsters_id_portrait {
+            Some(response) => response(),
+            None => Ok(self.fake()),
+        }
+    }
+    async fn get_monsters_id_portrait(
+        &self,
+        ctx: Self::Context,
+        id: i32,
+    ) -> Response<::humblegen_rt::blob::Blob> {
+        match &self.get_monsters_id_portrait {
+            Some(response) => response(),
+            None => Ok(self.fake()),
+        }
+    }
+    async fn post_monsters_import(
+        &self,
+        ctx: Self::Context,
+        post_body: ::humblegen_rt::blob::Blob,
+    ) -> Response<Result<u32, MonsterError>> {
+        match &self.post_monsters_import {
+            Some(response) => response(),
+            None => Ok(self.fake()),
+        }
+    }
+}
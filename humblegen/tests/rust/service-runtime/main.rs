@@ -0,0 +1,462 @@
+include!("spec.rs");
+
+use humblegen_rt::conditional::{ETag, IfMatch};
+use humblegen_rt::connection::{ClientIp, ConnectionInfo};
+use humblegen_rt::cors::CorsConfig;
+use humblegen_rt::rate_limit::{RateLimit, RateLimitKey};
+use humblegen_rt::test_client::{TestClient, TestResponse};
+use hyper::header;
+use std::net::IpAddr;
+use std::time::Duration;
+
+struct Zoo;
+
+#[humblegen_rt::async_trait(Sync)]
+impl Monsters for Zoo {
+    /// The client IP address of the request.
+    type Context = Option<IpAddr>;
+
+    async fn intercept_handler_pre(
+        &self,
+        req: &hyper::Request<hyper::Body>,
+    ) -> Result<Self::Context, ServiceError> {
+        Ok(ClientIp::of(req))
+    }
+
+    async fn post_monsters(&self, _ctx: Self::Context, post_body: Monster) -> Response<Monster> {
+        Ok(post_body)
+    }
+
+    async fn get_monsters_id(&self, _ctx: Self::Context, id: i32) -> Response<Monster> {
+        Ok(stored_monster(id))
+    }
+
+    async fn put_monsters_id(
+        &self,
+        _ctx: Self::Context,
+        post_body: Monster,
+        id: i32,
+        if_match: Option<IfMatch>,
+    ) -> Response<Monster> {
+        if let Some(if_match) = if_match {
+            if_match.check(Some(&stored_monster(id)))?;
+        }
+        Ok(post_body)
+    }
+
+    async fn get_client_ip(&self, ctx: Self::Context) -> Response<String> {
+        Ok(ctx.map(|ip| ip.to_string()).unwrap_or_default())
+    }
+}
+
+/// The monster that `Zoo` pretends to have stored with ID `id`.
+fn stored_monster(id: i32) -> Monster {
+    Monster { id, ..godzilla() }
+}
+
+/// Serves `Zoo` at `/api` of a server configured by `builder`.
+fn serve(builder: Builder) -> TestClient {
+    builder
+        .add("/api", Handler::Monsters(Arc::new(Zoo)))
+        .into_test_client()
+        .unwrap()
+}
+
+fn godzilla() -> Monster {
+    Monster {
+        id: 1,
+        name: "Godzilla".to_owned(),
+    }
+}
+
+/// Request bodies beyond the size limit are rejected with 413, slow ones with 408.
+async fn body_limits() {
+    let client = serve(
+        Builder::new()
+            .max_body_size(32)
+            .body_read_timeout(Duration::from_millis(50)),
+    );
+    let res = client.post("/api/monsters").json(&godzilla()).send().await;
+    assert_eq!(res.status, 200, "{:?}", res);
+    assert_eq!(res.json::<Monster>().unwrap().name, "Godzilla");
+
+    // announced by the Content-Length header
+    let large = serde_json::to_vec(&Monster {
+        id: 1,
+        name: "G".repeat(64),
+    })
+    .unwrap();
+    let res = client
+        .post("/api/monsters")
+        .header(header::CONTENT_LENGTH, &large.len().to_string())
+        .body(large, "application/json")
+        .send()
+        .await;
+    assert_eq!(res.status, 413);
+
+    // noticed while reading a body of unknown length
+    let (mut sender, body) = hyper::Body::channel();
+    tokio::spawn(async move {
+        for _ in 0..8 {
+            if sender.send_data(vec![b' '; 8].into()).await.is_err() {
+                break;
+            }
+        }
+    });
+    let res = client
+        .post("/api/monsters")
+        .body(body, "application/json")
+        .send()
+        .await;
+    assert_eq!(res.status, 413);
+
+    // the body is never sent
+    let (_sender, body) = hyper::Body::channel();
+    let res = client
+        .post("/api/monsters")
+        .body(body, "application/json")
+        .send()
+        .await;
+    assert_eq!(res.status, 408);
+}
+
+fn varies_by(res: &TestResponse, header: &str) -> bool {
+    res.headers
+        .get_all(header::VARY)
+        .iter()
+        .any(|v| v.to_str().unwrap().eq_ignore_ascii_case(header))
+}
+
+/// CORS preflight requests are answered for every route,
+/// responses to allowed origins carry the CORS headers.
+async fn cors() {
+    // credentials can only be allowed for explicitly allowed origins
+    assert!(CorsConfig::builder()
+        .allow_credentials(true)
+        .build()
+        .is_err());
+
+    let client = serve(
+        Builder::new().cors(
+            CorsConfig::builder()
+                .allowed_origins(vec!["https://zoo.example"])
+                .allow_credentials(true)
+                .build()
+                .unwrap(),
+        ),
+    );
+    let preflight = |origin: &str| {
+        client
+            .request(hyper::Method::OPTIONS, "/api/monsters")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .send()
+    };
+    let res = preflight("https://zoo.example").await;
+    assert_eq!(res.status, 204, "{:?}", res);
+    assert_eq!(
+        res.headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://zoo.example"
+    );
+    assert_eq!(
+        res.headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS],
+        "true"
+    );
+    assert_eq!(
+        res.headers[header::ACCESS_CONTROL_ALLOW_METHODS],
+        "POST, OPTIONS"
+    );
+    assert!(varies_by(&res, "Origin"));
+    let res = preflight("https://evil.example").await;
+    assert_eq!(res.status, 403);
+
+    let post = |origin: &str| {
+        client
+            .post("/api/monsters")
+            .header(header::ORIGIN, origin)
+            .json(&godzilla())
+            .send()
+    };
+    let res = post("https://zoo.example").await;
+    assert_eq!(res.status, 200);
+    assert_eq!(
+        res.headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://zoo.example"
+    );
+    assert!(varies_by(&res, "Origin"));
+    assert!(res.headers[header::ACCESS_CONTROL_EXPOSE_HEADERS]
+        .to_str()
+        .unwrap()
+        .eq_ignore_ascii_case("Request-ID"));
+    let res = post("https://evil.example").await;
+    assert_eq!(res.status, 200);
+    assert!(!res
+        .headers
+        .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+
+    // any origin, without credentials
+    let client = serve(Builder::new().cors(CorsConfig::default()));
+    let res = client
+        .post("/api/monsters")
+        .header(header::ORIGIN, "https://zoo.example")
+        .json(&godzilla())
+        .send()
+        .await;
+    assert_eq!(res.headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    assert!(!varies_by(&res, "Origin"));
+}
+
+/// Responses of at least the threshold size are compressed with the best coding the client accepts.
+async fn compression() {
+    let client = serve(Builder::new().compression(64));
+    let large = Monster {
+        id: 1,
+        name: "G".repeat(256),
+    };
+    let post = |monster: &Monster, accept_encoding: &str| {
+        client
+            .post("/api/monsters")
+            .header(header::ACCEPT_ENCODING, accept_encoding)
+            .json(monster)
+            .send()
+    };
+
+    let res = post(&large, "gzip;q=0.5, br").await;
+    assert_eq!(res.headers[header::CONTENT_ENCODING], "br");
+    let res = post(&large, "gzip, br;q=0").await;
+    assert_eq!(res.status, 200);
+    assert_eq!(res.headers[header::CONTENT_ENCODING], "gzip");
+    assert!(varies_by(&res, "Accept-Encoding"));
+    assert!(res.body.len() < large.name.len());
+    let body = humblegen_rt::compression::decode_request_body(
+        &res.headers[header::CONTENT_ENCODING],
+        res.body.to_vec(),
+        1024,
+    )
+    .unwrap();
+    assert_eq!(
+        serde_json::from_slice::<Monster>(&body).unwrap().name,
+        large.name
+    );
+
+    // no supported coding accepted
+    let res = post(&large, "identity").await;
+    assert!(!res.headers.contains_key(header::CONTENT_ENCODING));
+    assert!(varies_by(&res, "Accept-Encoding"));
+
+    // below the threshold
+    let res = post(&godzilla(), "gzip").await;
+    assert!(!res.headers.contains_key(header::CONTENT_ENCODING));
+}
+
+/// Valid request IDs are echoed, others are replaced by a generated one.
+/// The trace of an incoming `traceparent` header is continued.
+async fn correlation() {
+    let client = serve(Builder::new().request_id_generator(|| "generated".to_owned()));
+    let request_id = header::HeaderName::from_static("request-id");
+    let post = |id: Option<&str>| {
+        let mut req = client.post("/api/monsters").json(&godzilla());
+        if let Some(id) = id {
+            req = req.header(request_id.clone(), id);
+        }
+        req.send()
+    };
+    let res = post(Some("abc-123")).await;
+    assert_eq!(res.headers[&request_id], "abc-123");
+    let res = post(Some("not valid")).await;
+    assert_eq!(res.headers[&request_id], "generated");
+    let res = post(None).await;
+    assert_eq!(res.headers[&request_id], "generated");
+
+    let res = client
+        .post("/api/monsters")
+        .header(
+            header::HeaderName::from_static("traceparent"),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        )
+        .header(header::HeaderName::from_static("tracestate"), "zoo=1")
+        .json(&godzilla())
+        .send()
+        .await;
+    let traceparent = res.headers["traceparent"].to_str().unwrap();
+    assert!(traceparent.starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"));
+    assert!(traceparent.ends_with("-01"));
+    assert!(!traceparent.contains("00f067aa0ba902b7"));
+    assert_eq!(res.headers["tracestate"], "zoo=1");
+}
+
+/// `HEAD` requests are dispatched to `GET` routes. Requests with a method that no route is mounted for
+/// are rejected with 405, or answered with 204 for `OPTIONS`, along with an `Allow` header.
+async fn methods() {
+    let client = serve(Builder::new());
+    let res = client
+        .request(hyper::Method::HEAD, "/api/monsters/7")
+        .send()
+        .await;
+    assert_eq!(res.status, 200);
+    assert_eq!(res.headers[header::CONTENT_TYPE], "application/json");
+
+    let res = client.delete("/api/monsters/7").send().await;
+    assert_eq!(res.status, 405);
+    assert_eq!(res.headers[header::ALLOW], "GET, PUT, HEAD, OPTIONS");
+    assert!(res.error_response().is_some());
+
+    let res = client
+        .request(hyper::Method::OPTIONS, "/api/monsters")
+        .send()
+        .await;
+    assert_eq!(res.status, 204);
+    assert_eq!(res.headers[header::ALLOW], "POST, OPTIONS");
+    assert!(res.body.is_empty());
+}
+
+/// Successful `GET` responses carry a weak `ETag`, with which conditional requests are answered with 304.
+/// `PUT` handlers evaluate `If-Match` preconditions against it.
+async fn conditional_requests() {
+    let client = serve(Builder::new().etags());
+    let res = client.get("/api/monsters/7").send().await;
+    let etag = res.headers[header::ETAG].to_str().unwrap().to_owned();
+    assert_eq!(etag, ETag::of(&stored_monster(7)).unwrap().0);
+
+    let get = |path: &str| {
+        client
+            .get(path)
+            .header(header::IF_NONE_MATCH, &format!("\"other\", {}", etag))
+            .send()
+    };
+    let res = get("/api/monsters/7").await;
+    assert_eq!(res.status, 304);
+    assert_eq!(res.headers[header::ETAG], etag.as_str());
+    assert!(res.body.is_empty());
+    let res = get("/api/monsters/8").await;
+    assert_eq!(res.status, 200);
+
+    let put = |if_match: &str| {
+        client
+            .put("/api/monsters/7")
+            .header(header::IF_MATCH, if_match)
+            .json(&stored_monster(7))
+            .send()
+    };
+    assert_eq!(put(&etag).await.status, 200);
+    assert_eq!(put("*").await.status, 200);
+    assert_eq!(put("W/\"stale\"").await.status, 412);
+}
+
+/// Requests beyond the rate limit of a client are rejected with 429 and a `Retry-After` header.
+async fn rate_limits() {
+    let api_key = header::HeaderName::from_static("api-key");
+    let limit =
+        |requests| RateLimit::per_minute(requests).key(RateLimitKey::Header(api_key.clone()));
+    let serve_limited = |endpoint: &str| {
+        let mut config = server::ServiceConfig {
+            rate_limit: Some(limit(2)),
+            ..server::ServiceConfig::default()
+        };
+        config
+            .endpoint_rate_limits
+            .insert(endpoint.to_owned(), limit(1));
+        Builder::new()
+            .add_with_config("/api", Handler::Monsters(Arc::new(Zoo)), config)
+            .into_test_client()
+    };
+    assert!(serve_limited("no_such_endpoint").is_err());
+    let client = serve_limited("get_monsters_id").unwrap();
+
+    let post = |key: Option<&str>| {
+        let mut req = client.post("/api/monsters").json(&godzilla());
+        if let Some(key) = key {
+            req = req.header(api_key.clone(), key);
+        }
+        req.send()
+    };
+    assert_eq!(post(Some("a")).await.status, 200);
+    assert_eq!(post(Some("a")).await.status, 200);
+    let res = post(Some("a")).await;
+    assert_eq!(res.status, 429);
+    assert_eq!(res.headers[header::RETRY_AFTER], "30");
+    // every client has a bucket of its own, requests without a key are not limited
+    assert_eq!(post(Some("b")).await.status, 200);
+    for _ in 0..3 {
+        assert_eq!(post(None).await.status, 200);
+    }
+
+    // the limit of an endpoint overrides the limit of its service
+    let get = || {
+        client
+            .get("/api/monsters/7")
+            .header(api_key.clone(), "a")
+            .send()
+    };
+    assert_eq!(get().await.status, 200);
+    let res = get().await;
+    assert_eq!(res.status, 429);
+    assert_eq!(res.headers[header::RETRY_AFTER], "60");
+}
+
+/// The client IP address of a request is the peer's, unless the peer is a trusted proxy:
+/// then it is the last address in `X-Forwarded-For` that is not a trusted proxy.
+async fn client_ip() {
+    let proxy: IpAddr = "10.0.0.1".parse().unwrap();
+    let limited = server::ServiceConfig {
+        rate_limit: Some(RateLimit::per_minute(1).key(RateLimitKey::ClientIp)),
+        ..server::ServiceConfig::default()
+    };
+    let client = Builder::new()
+        .trusted_proxies(vec![proxy])
+        .add_with_config("/api", Handler::Monsters(Arc::new(Zoo)), limited)
+        .into_test_client()
+        .unwrap();
+    let client_ip = |peer: &str, forwarded_for: &str| {
+        let client = client.clone().with_connection_info(ConnectionInfo {
+            remote_addr: SocketAddr::new(peer.parse().unwrap(), 4711),
+            local_addr: "127.0.0.1:8080".parse().unwrap(),
+            tls_peer_certificates: None,
+        });
+        let forwarded_for = forwarded_for.to_owned();
+        async move {
+            let res = client
+                .get("/api/client-ip")
+                .header(
+                    header::HeaderName::from_static("x-forwarded-for"),
+                    &forwarded_for,
+                )
+                .send()
+                .await;
+            match res.status.as_u16() {
+                200 => res.json::<String>().unwrap(),
+                status => status.to_string(),
+            }
+        }
+    };
+
+    assert_eq!(client_ip("10.0.0.1", "203.0.113.1").await, "203.0.113.1");
+    assert_eq!(
+        client_ip("10.0.0.1", "203.0.113.2, 10.0.0.1").await,
+        "203.0.113.2"
+    );
+    // the header of an untrusted peer is ignored
+    assert_eq!(
+        client_ip("198.51.100.1", "203.0.113.3").await,
+        "198.51.100.1"
+    );
+    // clients behind a proxy are rate limited separately
+    assert_eq!(client_ip("10.0.0.1", "203.0.113.1").await, "429");
+    assert_eq!(client_ip("10.0.0.1", "203.0.113.4").await, "203.0.113.4");
+
+    // requests without connection info have no client IP
+    let res = client.get("/api/client-ip").send().await;
+    assert_eq!(res.json::<String>().unwrap(), "");
+}
+
+#[tokio::main]
+async fn main() {
+    body_limits().await;
+    cors().await;
+    compression().await;
+    correlation().await;
+    methods().await;
+    conditional_requests().await;
+    rate_limits().await;
+    client_ip().await;
+}
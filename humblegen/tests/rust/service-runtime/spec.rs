@@ -0,0 +1,494 @@
+#[derive(Debug, Clone, serde :: Deserialize, serde :: Serialize)]
+#[doc = ""]
+pub struct Monster {
+    #[doc = ""]
+    pub id: i32,
+    #[doc = ""]
+    pub name: String,
+}
+#[allow(unused_imports)]
+use ::humblegen_rt::deser_helpers::{
+    deser_param, deser_post_data, deser_query_list, deser_query_primitive, deser_query_serde_qs,
+};
+#[allow(unused_imports)]
+pub use ::humblegen_rt::handler::{self, HandlerResponse as Response, ServiceError};
+#[allow(unused_imports)]
+use ::humblegen_rt::regexset_map::RegexSetMap;
+#[allow(unused_imports)]
+use ::humblegen_rt::server::{self, Route, Service};
+#[allow(unused_imports)]
+use ::humblegen_rt::service_protocol::ErrorResponse;
+use ::humblegen_rt::tracing_futures::Instrument;
+#[allow(unused_imports)]
+use ::humblegen_rt::{hyper, tracing};
+#[allow(unused_imports)]
+use ::std::sync::Arc;
+use std::net::SocketAddr;
+#[doc = r" Builds an HTTP server that exposes services implemented by handler trait objects."]
+#[derive(Debug)]
+pub struct Builder {
+    services: Vec<Service>,
+    config: server::Config,
+}
+impl Builder {
+    pub fn new() -> Self {
+        Self {
+            services: vec![],
+            config: server::Config::default(),
+        }
+    }
+    #[doc = r" Sets the maximum size of a request body in bytes."]
+    #[doc = r" Requests with larger bodies are rejected with HTTP status 413."]
+    #[doc = r" Can be overridden per service using `add_with_config`."]
+    pub fn max_body_size(mut self, max_body_size: u64) -> Self {
+        self.config.max_body_size = max_body_size;
+        self
+    }
+    #[doc = r" Sets the maximum time spent reading a request body."]
+    #[doc = r" Requests whose body takes longer are rejected with HTTP status 408."]
+    #[doc = r" Can be overridden per service using `add_with_config`."]
+    pub fn body_read_timeout(mut self, timeout: ::std::time::Duration) -> Self {
+        self.config.body_read_timeout = Some(timeout);
+        self
+    }
+    #[doc = r" Sets the maximum time spent handling a request."]
+    #[doc = r" When it elapses, the handler is cancelled and the request is answered with HTTP status 504."]
+    #[doc = r" Can be overridden per service using `add_with_config`."]
+    pub fn request_timeout(mut self, timeout: ::std::time::Duration) -> Self {
+        self.config.request_timeout = Some(timeout);
+        self
+    }
+    #[doc = r" Enables Cross-Origin Resource Sharing."]
+    #[doc = r" CORS preflight requests are answered for every mounted route,"]
+    #[doc = r" and CORS headers are added to every response to an allowed origin."]
+    pub fn cors(mut self, cors: ::humblegen_rt::cors::CorsConfig) -> Self {
+        self.config.cors = Some(cors);
+        self
+    }
+    #[doc = r" Enables response compression for response bodies of at least `threshold` bytes."]
+    #[doc = r" The content coding (`br`, `gzip` or `deflate`) is negotiated using the request's"]
+    #[doc = r" `Accept-Encoding` header."]
+    pub fn compression(mut self, threshold: usize) -> Self {
+        self.config.compression_threshold = Some(threshold);
+        self
+    }
+    #[doc = r" Enables weak `ETag`s on successful JSON responses to `GET` requests."]
+    #[doc = r" Requests whose `If-None-Match` header matches the `ETag` are answered with HTTP status 304."]
+    pub fn etags(mut self) -> Self {
+        self.config.etags = true;
+        self
+    }
+    #[doc = r" Sets the generator of request IDs for requests without a valid `Request-ID` header"]
+    #[doc = r" (default: 30 random alphanumeric characters)."]
+    pub fn request_id_generator(
+        mut self,
+        generator: impl Fn() -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.config.request_id_generator = ::std::sync::Arc::new(generator);
+        self
+    }
+    #[doc = r" Enables Prometheus metrics (requires the `metrics` feature of `humblegen-rt`)."]
+    #[doc = r" Requests are recorded per route and outcome; set `MetricsConfig::endpoint`"]
+    #[doc = r" to expose the metrics."]
+    pub fn metrics(mut self, metrics: ::humblegen_rt::metrics::MetricsConfig) -> Self {
+        self.config.metrics = Some(metrics);
+        self
+    }
+    #[doc = r" Mounts a liveness route at URL path `path` (e.g. `/health`)"]
+    #[doc = r" that answers `GET` requests with HTTP status 200."]
+    pub fn liveness_endpoint(mut self, path: &str) -> Self {
+        self.config.builtin_routes.liveness = Some(path.to_owned());
+        self
+    }
+    #[doc = r" Mounts a readiness route at URL path `path` (e.g. `/ready`)."]
+    #[doc = r" It answers `GET` requests with HTTP status 200 if `check` returns `Ok`,"]
+    #[doc = r" and with HTTP status 503 otherwise."]
+    pub fn readiness_endpoint<F, Fut>(mut self, path: &str, check: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: ::std::future::Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.config.builtin_routes.readiness = Some((
+            path.to_owned(),
+            ::humblegen_rt::builtin_routes::readiness_check(check),
+        ));
+        self
+    }
+    #[doc = r" Mounts an introspection route at URL path `path` (e.g. `/introspection`)"]
+    #[doc = r" that answers `GET` requests with the mounted services and their routes."]
+    pub fn introspection_endpoint(mut self, path: &str) -> Self {
+        self.config.builtin_routes.introspection = Some(path.to_owned());
+        self
+    }
+    #[doc = r" Trusts the `X-Forwarded-For` header of requests from the given proxies"]
+    #[doc = r" to name the client (see `humblegen_rt::connection::ClientIp`)."]
+    pub fn trusted_proxies(
+        mut self,
+        proxies: impl IntoIterator<Item = ::std::net::IpAddr>,
+    ) -> Self {
+        self.config.trusted_proxies = proxies.into_iter().collect();
+        self
+    }
+    #[doc = r" Mounts `handler` at URL path prefix `root`."]
+    #[doc = r" This means that a `handler` implementing humble service"]
+    #[doc = r" ```"]
+    #[doc = r" service S {"]
+    #[doc = r"     GET /bar -> i32,"]
+    #[doc = r"     GET /baz -> str,"]
+    #[doc = r" }"]
+    #[doc = r" ```"]
+    #[doc = r#" and `root="/api"` will expose"#]
+    #[doc = r" * handler method `fn bar() -> i32` at `/api/bar` and"]
+    #[doc = r" * handler method `fn baz() -> String` at `/api/baz`"]
+    pub fn add<Context: Default + Sized + Send + Sync>(
+        self,
+        root: &str,
+        handler: Handler<Context>,
+    ) -> Self {
+        self.add_with_config(root, handler, server::ServiceConfig::default())
+    }
+    #[doc = r" Like `add`, but overrides the server-wide configuration for this service"]
+    #[doc = r" with the values set in `config`."]
+    pub fn add_with_config<Context: Default + Sized + Send + Sync>(
+        mut self,
+        root: &str,
+        handler: Handler<Context>,
+        config: server::ServiceConfig,
+    ) -> Self {
+        if !root.starts_with('/') {
+            panic!("root must start with \"/\"")
+        } else if root.ends_with('/') {
+            panic!("root must not end with \"/\"")
+        }
+        let name = handler.service_name().to_owned();
+        let routes: Vec<Route> = handler.into_routes();
+        let routes = RegexSetMap::new(routes).unwrap();
+        self.services.push(Service {
+            name,
+            root: root.to_owned(),
+            regex: humblegen_rt::regex::Regex::new(&format!(r"^(?P<root>{})(?P<suffix>/.*)", root))
+                .unwrap(),
+            routes,
+            config,
+        });
+        self
+    }
+    #[doc = r" Starts an HTTP server bound to address `addr` and serves incoming requests using"]
+    #[doc = r" the previously `add`ed handlers."]
+    pub async fn listen_and_run_forever(
+        self,
+        addr: &SocketAddr,
+    ) -> humblegen_rt::anyhow::Result<()> {
+        server::listen_and_run_forever(self.into_server()?, addr).await
+    }
+    #[doc = r" Builds the server without starting it, e.g. to serve connections accepted by a custom"]
+    #[doc = r" accept loop (that terminates TLS) using `humblegen_rt::server::handle_request`."]
+    pub fn into_server(self) -> humblegen_rt::anyhow::Result<server::Server> {
+        use humblegen_rt::anyhow::Context;
+        for service in &self.services {
+            service.check_config()?;
+        }
+        let services = RegexSetMap::new(self.services).context("invalid service configuration")?;
+        Ok(server::Server {
+            services,
+            config: self.config,
+        })
+    }
+    #[doc = r" Builds an in-process client of the server for testing, which passes requests"]
+    #[doc = r" to the handlers without binding a socket."]
+    #[doc = r" Use `$ServiceNameTestClient` for typed requests to a mounted service."]
+    pub fn into_test_client(
+        self,
+    ) -> humblegen_rt::anyhow::Result<::humblegen_rt::test_client::TestClient> {
+        Ok(::humblegen_rt::test_client::TestClient::new(
+            self.into_server()?,
+        ))
+    }
+}
+#[doc = r" Wrapper enum with one variant for each service defined in the humble spec."]
+#[doc = r" Used to pass instantiated handler trait objects to `Builder::add`."]
+#[allow(dead_code)]
+pub enum Handler<Context: Default + Sized + Send + Sync + 'static> {
+    Monsters(Arc<dyn Monsters<Context = Context> + Send + Sync>),
+}
+impl<Context: Default + Sized + Send + Sync + 'static> Handler<Context> {
+    fn into_routes(self) -> Vec<Route> {
+        match self {
+            Handler::Monsters(h) => routes_Monsters(h),
+        }
+    }
+    fn service_name(&self) -> &'static str {
+        match self {
+            Handler::Monsters(_) => "Monsters",
+        }
+    }
+}
+impl<Context: Default + Sized + Send + Sync + 'static> std::fmt::Debug for Handler<Context> {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Handler::Monsters(_) => write!(formatter, "{}", "Monsters")?,
+        }
+        Ok(())
+    }
+}
+#[doc = ""]
+#[doc = "```\n#[humblegen_rt::async_trait(Sync)]\npub trait Monsters {\n    type Context: Default + Sized + Send + Sync;\n    async fn intercept_handler_pre(\n        &self,\n        _req: &hyper::Request<hyper::Body>,\n    ) -> Result<Self::Context, ServiceError> {\n        Ok(Self::Context::default())\n    }\n    fn rate_limit_key(&self, _ctx: &Self::Context) -> Option<String> {\n        None\n    }\n    async fn post_monsters(&self, ctx: Self::Context, post_body: Monster) -> Response<Monster>;\n    async fn get_monsters_id(&self, ctx: Self::Context, id: i32) -> Response<Monster>;\n    async fn put_monsters_id(\n        &self,\n        ctx: Self::Context,\n        post_body: Monster,\n        id: i32,\n        if_match: Option<::humblegen_rt::conditional::IfMatch>,\n    ) -> Response<Monster>;\n    async fn get_client_ip(&self, ctx: Self::Context) -> Response<String>;\n}\n\n```"]
+#[humblegen_rt::async_trait(Sync)]
+pub trait Monsters {
+    type Context: Default + Sized + Send + Sync;
+    async fn intercept_handler_pre(
+        &self,
+        _req: &hyper::Request<hyper::Body>,
+    ) -> Result<Self::Context, ServiceError> {
+        Ok(Self::Context::default())
+    }
+    fn rate_limit_key(&self, _ctx: &Self::Context) -> Option<String> {
+        None
+    }
+    #[doc = "```\nasync fn post_monsters(&self, ctx: Self::Context, post_body: Monster) -> Response<Monster> {}\n\n```"]
+    #[doc = "Create a monster, answering with the created monster."]
+    async fn post_monsters(&self, ctx: Self::Context, post_body: Monster) -> Response<Monster>;
+    #[doc = "```\nasync fn get_monsters_id(&self, ctx: Self::Context, id: i32) -> Response<Monster> {}\n\n```"]
+    #[doc = "Get a monster by id."]
+    async fn get_monsters_id(&self, ctx: Self::Context, id: i32) -> Response<Monster>;
+    #[doc = "```\nasync fn put_monsters_id(\n    &self,\n    ctx: Self::Context,\n    post_body: Monster,\n    id: i32,\n    if_match: Option<::humblegen_rt::conditional::IfMatch>,\n) -> Response<Monster> {\n}\n\n```"]
+    #[doc = "Replace a monster, if it is unchanged since the client read it."]
+    async fn put_monsters_id(
+        &self,
+        ctx: Self::Context,
+        post_body: Monster,
+        id: i32,
+        if_match: Option<::humblegen_rt::conditional::IfMatch>,
+    ) -> Response<Monster>;
+    #[doc = "```\nasync fn get_client_ip(&self, ctx: Self::Context) -> Response<String> {}\n\n```"]
+    #[doc = "The IP address of the client, as seen by the interceptor."]
+    async fn get_client_ip(&self, ctx: Self::Context) -> Response<String>;
+}
+#[allow(unused_variables)]
+#[allow(unused_mut)]
+#[allow(non_snake_case)]
+#[allow(clippy::trivial_regex)]
+#[allow(clippy::single_char_pattern)]
+fn routes_Monsters<Context: Default + Sized + Send + Sync + 'static>(
+    handler: Arc<dyn Monsters<Context = Context> + Send + Sync>,
+) -> Vec<Route> {
+    vec![
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "post_monsters".to_owned(),
+                method: ::humblegen_rt::hyper::Method::POST,
+                template: "/monsters".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monsters$").unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let post_body: Monster = deser_post_data(&mut req).await?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
+                                    handler.post_monsters(ctx, post_body).instrument(span).await,
+                                ))
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "get_monsters_id".to_owned(),
+                method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monsters/{id}".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monsters/(?P<id>[^/]+)$").unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        let id: Result<i32, ErrorResponse> = deser_param("id", &captures["id"]);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let id = id?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
+                                    handler.get_monsters_id(ctx, id).instrument(span).await,
+                                ))
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "put_monsters_id".to_owned(),
+                method: ::humblegen_rt::hyper::Method::PUT,
+                template: "/monsters/{id}".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monsters/(?P<id>[^/]+)$").unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        let id: Result<i32, ErrorResponse> = deser_param("id", &captures["id"]);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let id = id?;
+                            let if_match = ::humblegen_rt::conditional::IfMatch::from_request(&req);
+                            let post_body: Monster = deser_post_data(&mut req).await?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
+                                    handler
+                                        .put_monsters_id(ctx, post_body, id, if_match)
+                                        .instrument(span)
+                                        .await,
+                                ))
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "get_client_ip".to_owned(),
+                method: ::humblegen_rt::hyper::Method::GET,
+                template: "/client-ip".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/client-ip$").unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
+                                    handler.get_client_ip(ctx).instrument(span).await,
+                                ))
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+    ]
+}
+#[doc = "Typed in-process client of a mounted `Monsters` service, for testing (see `Builder::into_test_client`)."]
+#[derive(Debug, Clone)]
+pub struct MonstersTestClient<'a> {
+    client: &'a ::humblegen_rt::test_client::TestClient,
+    root: String,
+}
+impl<'a> MonstersTestClient<'a> {
+    #[doc = r" A client of the service mounted at URL path prefix `root`."]
+    pub fn new(client: &'a ::humblegen_rt::test_client::TestClient, root: &str) -> Self {
+        Self {
+            client,
+            root: root.to_owned(),
+        }
+    }
+    #[doc = "Invokes `Monsters::post_monsters`."]
+    pub async fn post_monsters(
+        &self,
+        post_body: Monster,
+    ) -> Result<Monster, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/monsters", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::POST, &path);
+        let request = request.json(&post_body);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Monsters::get_monsters_id`."]
+    pub async fn get_monsters_id(
+        &self,
+        id: i32,
+    ) -> Result<Monster, ::humblegen_rt::test_client::TestError> {
+        let path = format!(
+            "{}/monsters/{}",
+            self.root,
+            ::humblegen_rt::test_client::route_param(&id)
+        );
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Monsters::put_monsters_id`."]
+    pub async fn put_monsters_id(
+        &self,
+        post_body: Monster,
+        id: i32,
+    ) -> Result<Monster, ::humblegen_rt::test_client::TestError> {
+        let path = format!(
+            "{}/monsters/{}",
+            self.root,
+            ::humblegen_rt::test_client::route_param(&id)
+        );
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::PUT, &path);
+        let request = request.json(&post_body);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Monsters::get_client_ip`."]
+    pub async fn get_client_ip(&self) -> Result<String, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/client-ip", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        request.send().await.decode()
+    }
+}
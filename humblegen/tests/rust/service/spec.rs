@@ -69,16 +69,31 @@ pub struct MonsterQuery {
     #[doc = ""]
     pub max_age: Option<i32>,
 }
+#[derive(Debug, Clone, serde :: Deserialize, serde :: Serialize)]
+#[doc = "A query with nested values, encoded using bracket notation."]
+pub struct MonsterFilter {
+    #[doc = ""]
+    #[serde(deserialize_with = "::humblegen_rt::serialization_helpers::deser_query_list_field")]
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[doc = ""]
+    pub error: Option<MonsterError>,
+    #[doc = ""]
+    pub data: Option<MonsterData>,
+    #[doc = ""]
+    #[serde(default)]
+    pub ids: ::std::collections::HashMap<String, i32>,
+}
 #[allow(unused_imports)]
 use ::humblegen_rt::deser_helpers::{
-    deser_param, deser_post_data, deser_query_primitive, deser_query_serde_urlencoded,
+    deser_param, deser_post_data, deser_query_list, deser_query_primitive, deser_query_serde_qs,
 };
 #[allow(unused_imports)]
 pub use ::humblegen_rt::handler::{self, HandlerResponse as Response, ServiceError};
 #[allow(unused_imports)]
 use ::humblegen_rt::regexset_map::RegexSetMap;
 #[allow(unused_imports)]
-use ::humblegen_rt::server::{self, handler_response_to_hyper_response, Route, Service};
+use ::humblegen_rt::server::{self, Route, Service};
 #[allow(unused_imports)]
 use ::humblegen_rt::service_protocol::ErrorResponse;
 use ::humblegen_rt::tracing_futures::Instrument;
@@ -91,10 +106,106 @@ use std::net::SocketAddr;
 #[derive(Debug)]
 pub struct Builder {
     services: Vec<Service>,
+    config: server::Config,
 }
 impl Builder {
     pub fn new() -> Self {
-        Self { services: vec![] }
+        Self {
+            services: vec![],
+            config: server::Config::default(),
+        }
+    }
+    #[doc = r" Sets the maximum size of a request body in bytes."]
+    #[doc = r" Requests with larger bodies are rejected with HTTP status 413."]
+    #[doc = r" Can be overridden per service using `add_with_config`."]
+    pub fn max_body_size(mut self, max_body_size: u64) -> Self {
+        self.config.max_body_size = max_body_size;
+        self
+    }
+    #[doc = r" Sets the maximum time spent reading a request body."]
+    #[doc = r" Requests whose body takes longer are rejected with HTTP status 408."]
+    #[doc = r" Can be overridden per service using `add_with_config`."]
+    pub fn body_read_timeout(mut self, timeout: ::std::time::Duration) -> Self {
+        self.config.body_read_timeout = Some(timeout);
+        self
+    }
+    #[doc = r" Sets the maximum time spent handling a request."]
+    #[doc = r" When it elapses, the handler is cancelled and the request is answered with HTTP status 504."]
+    #[doc = r" Can be overridden per service using `add_with_config`."]
+    pub fn request_timeout(mut self, timeout: ::std::time::Duration) -> Self {
+        self.config.request_timeout = Some(timeout);
+        self
+    }
+    #[doc = r" Enables Cross-Origin Resource Sharing."]
+    #[doc = r" CORS preflight requests are answered for every mounted route,"]
+    #[doc = r" and CORS headers are added to every response to an allowed origin."]
+    pub fn cors(mut self, cors: ::humblegen_rt::cors::CorsConfig) -> Self {
+        self.config.cors = Some(cors);
+        self
+    }
+    #[doc = r" Enables response compression for response bodies of at least `threshold` bytes."]
+    #[doc = r" The content coding (`br`, `gzip` or `deflate`) is negotiated using the request's"]
+    #[doc = r" `Accept-Encoding` header."]
+    pub fn compression(mut self, threshold: usize) -> Self {
+        self.config.compression_threshold = Some(threshold);
+        self
+    }
+    #[doc = r" Enables weak `ETag`s on successful JSON responses to `GET` requests."]
+    #[doc = r" Requests whose `If-None-Match` header matches the `ETag` are answered with HTTP status 304."]
+    pub fn etags(mut self) -> Self {
+        self.config.etags = true;
+        self
+    }
+    #[doc = r" Sets the generator of request IDs for requests without a valid `Request-ID` header"]
+    #[doc = r" (default: 30 random alphanumeric characters)."]
+    pub fn request_id_generator(
+        mut self,
+        generator: impl Fn() -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.config.request_id_generator = ::std::sync::Arc::new(generator);
+        self
+    }
+    #[doc = r" Enables Prometheus metrics (requires the `metrics` feature of `humblegen-rt`)."]
+    #[doc = r" Requests are recorded per route and outcome; set `MetricsConfig::endpoint`"]
+    #[doc = r" to expose the metrics."]
+    pub fn metrics(mut self, metrics: ::humblegen_rt::metrics::MetricsConfig) -> Self {
+        self.config.metrics = Some(metrics);
+        self
+    }
+    #[doc = r" Mounts a liveness route at URL path `path` (e.g. `/health`)"]
+    #[doc = r" that answers `GET` requests with HTTP status 200."]
+    pub fn liveness_endpoint(mut self, path: &str) -> Self {
+        self.config.builtin_routes.liveness = Some(path.to_owned());
+        self
+    }
+    #[doc = r" Mounts a readiness route at URL path `path` (e.g. `/ready`)."]
+    #[doc = r" It answers `GET` requests with HTTP status 200 if `check` returns `Ok`,"]
+    #[doc = r" and with HTTP status 503 otherwise."]
+    pub fn readiness_endpoint<F, Fut>(mut self, path: &str, check: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: ::std::future::Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.config.builtin_routes.readiness = Some((
+            path.to_owned(),
+            ::humblegen_rt::builtin_routes::readiness_check(check),
+        ));
+        self
+    }
+    #[doc = r" Mounts an introspection route at URL path `path` (e.g. `/introspection`)"]
+    #[doc = r" that answers `GET` requests with the mounted services and their routes."]
+    pub fn introspection_endpoint(mut self, path: &str) -> Self {
+        self.config.builtin_routes.introspection = Some(path.to_owned());
+        self
+    }
+    #[doc = r" Trusts the `X-Forwarded-For` header of requests from the given proxies"]
+    #[doc = r" to name the client (see `humblegen_rt::connection::ClientIp`)."]
+    pub fn trusted_proxies(
+        mut self,
+        proxies: impl IntoIterator<Item = ::std::net::IpAddr>,
+    ) -> Self {
+        self.config.trusted_proxies = proxies.into_iter().collect();
+        self
     }
     #[doc = r" Mounts `handler` at URL path prefix `root`."]
     #[doc = r" This means that a `handler` implementing humble service"]
@@ -108,22 +219,36 @@ impl Builder {
     #[doc = r" * handler method `fn bar() -> i32` at `/api/bar` and"]
     #[doc = r" * handler method `fn baz() -> String` at `/api/baz`"]
     pub fn add<Context: Default + Sized + Send + Sync>(
+        self,
+        root: &str,
+        handler: Handler<Context>,
+    ) -> Self {
+        self.add_with_config(root, handler, server::ServiceConfig::default())
+    }
+    #[doc = r" Like `add`, but overrides the server-wide configuration for this service"]
+    #[doc = r" with the values set in `config`."]
+    pub fn add_with_config<Context: Default + Sized + Send + Sync>(
         mut self,
         root: &str,
         handler: Handler<Context>,
+        config: server::ServiceConfig,
     ) -> Self {
         if !root.starts_with('/') {
             panic!("root must start with \"/\"")
         } else if root.ends_with('/') {
             panic!("root must not end with \"/\"")
         }
+        let name = handler.service_name().to_owned();
         let routes: Vec<Route> = handler.into_routes();
         let routes = RegexSetMap::new(routes).unwrap();
-        self.services.push(Service((
-            humblegen_rt::regex::Regex::new(&format!(r"^(?P<root>{})(?P<suffix>/.*)", root))
+        self.services.push(Service {
+            name,
+            root: root.to_owned(),
+            regex: humblegen_rt::regex::Regex::new(&format!(r"^(?P<root>{})(?P<suffix>/.*)", root))
                 .unwrap(),
             routes,
-        )));
+            config,
+        });
         self
     }
     #[doc = r" Starts an HTTP server bound to address `addr` and serves incoming requests using"]
@@ -132,9 +257,30 @@ impl Builder {
         self,
         addr: &SocketAddr,
     ) -> humblegen_rt::anyhow::Result<()> {
+        server::listen_and_run_forever(self.into_server()?, addr).await
+    }
+    #[doc = r" Builds the server without starting it, e.g. to serve connections accepted by a custom"]
+    #[doc = r" accept loop (that terminates TLS) using `humblegen_rt::server::handle_request`."]
+    pub fn into_server(self) -> humblegen_rt::anyhow::Result<server::Server> {
         use humblegen_rt::anyhow::Context;
+        for service in &self.services {
+            service.check_config()?;
+        }
         let services = RegexSetMap::new(self.services).context("invalid service configuration")?;
-        server::listen_and_run_forever(services, addr).await
+        Ok(server::Server {
+            services,
+            config: self.config,
+        })
+    }
+    #[doc = r" Builds an in-process client of the server for testing, which passes requests"]
+    #[doc = r" to the handlers without binding a socket."]
+    #[doc = r" Use `$ServiceNameTestClient` for typed requests to a mounted service."]
+    pub fn into_test_client(
+        self,
+    ) -> humblegen_rt::anyhow::Result<::humblegen_rt::test_client::TestClient> {
+        Ok(::humblegen_rt::test_client::TestClient::new(
+            self.into_server()?,
+        ))
     }
 }
 #[doc = r" Wrapper enum with one variant for each service defined in the humble spec."]
@@ -151,6 +297,12 @@ impl<Context: Default + Sized + Send + Sync + 'static> Handler<Context> {
             Handler::Movies(h) => routes_Movies(h),
         }
     }
+    fn service_name(&self) -> &'static str {
+        match self {
+            Handler::Godzilla(_) => "Godzilla",
+            Handler::Movies(_) => "Movies",
+        }
+    }
 }
 impl<Context: Default + Sized + Send + Sync + 'static> std::fmt::Debug for Handler<Context> {
     fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -161,8 +313,15 @@ impl<Context: Default + Sized + Send + Sync + 'static> std::fmt::Debug for Handl
         Ok(())
     }
 }
+#[derive(Debug)]
+#[doc = "Multipart body of `Godzilla::post_monsters_id_portrait`."]
+pub struct GodzillaPostMonstersIdPortraitMultipart {
+    pub caption: Option<String>,
+    pub thumbnail: ::humblegen_rt::multipart::FilePart,
+    pub portrait: ::humblegen_rt::multipart::FilePart,
+}
 #[doc = "service Godzilla provides services related to monsters."]
-#[doc = "```\n#[humblegen_rt::async_trait(Sync)]\npub trait Godzilla {\n    type Context: Default + Sized + Send + Sync;\n    async fn intercept_handler_pre(\n        &self,\n        _req: &hyper::Request<hyper::Body>,\n    ) -> Result<Self::Context, ServiceError> {\n        Ok(Self::Context::default())\n    }\n    async fn get_foo(&self, ctx: Self::Context) -> Response<u32>;\n    async fn get_monsters_id(\n        &self,\n        ctx: Self::Context,\n        id: i32,\n    ) -> Response<Result<Monster, MonsterError>>;\n    async fn get_monsters(\n        &self,\n        ctx: Self::Context,\n        query: Option<MonsterQuery>,\n    ) -> Response<Vec<Monster>>;\n    async fn get_monsters_2(\n        &self,\n        ctx: Self::Context,\n        query: Option<String>,\n    ) -> Response<Vec<Monster>>;\n    async fn get_monsters_3(\n        &self,\n        ctx: Self::Context,\n        query: Option<i32>,\n    ) -> Response<Vec<Monster>>;\n    async fn get_monsters_4(&self, ctx: Self::Context) -> Response<Vec<Monster>>;\n    async fn post_monsters(\n        &self,\n        ctx: Self::Context,\n        post_body: MonsterData,\n    ) -> Response<Result<Monster, MonsterError>>;\n    async fn put_monsters_id(\n        &self,\n        ctx: Self::Context,\n        post_body: Monster,\n        id: String,\n    ) -> Response<Result<(), MonsterError>>;\n    async fn patch_monsters_id(\n        &self,\n        ctx: Self::Context,\n        post_body: MonsterPatch,\n        id: String,\n    ) -> Response<Result<(), MonsterError>>;\n    async fn delete_monster_id(\n        &self,\n        ctx: Self::Context,\n        id: String,\n    ) -> Response<Result<(), MonsterError>>;\n    async fn get_version(&self, ctx: Self::Context) -> Response<String>;\n    async fn get_tokio_police_locations(\n        &self,\n        ctx: Self::Context,\n    ) -> Response<Result<Vec<PoliceCar>, PoliceError>>;\n}\n\n```"]
+#[doc = "```\n#[humblegen_rt::async_trait(Sync)]\npub trait Godzilla {\n    type Context: Default + Sized + Send + Sync;\n    async fn intercept_handler_pre(\n        &self,\n        _req: &hyper::Request<hyper::Body>,\n    ) -> Result<Self::Context, ServiceError> {\n        Ok(Self::Context::default())\n    }\n    fn rate_limit_key(&self, _ctx: &Self::Context) -> Option<String> {\n        None\n    }\n    async fn get_foo(&self, ctx: Self::Context) -> Response<u32>;\n    async fn get_monsters_id(\n        &self,\n        ctx: Self::Context,\n        id: i32,\n    ) -> Response<Result<Monster, MonsterError>>;\n    async fn get_monsters(\n        &self,\n        ctx: Self::Context,\n        query: Option<MonsterQuery>,\n    ) -> Response<Vec<Monster>>;\n    async fn get_monsters_2(\n        &self,\n        ctx: Self::Context,\n        query: Option<String>,\n    ) -> Response<Vec<Monster>>;\n    async fn get_monsters_3(\n        &self,\n        ctx: Self::Context,\n        query: Option<i32>,\n    ) -> Response<Vec<Monster>>;\n    async fn get_monsters_4(&self, ctx: Self::Context) -> Response<Vec<Monster>>;\n    async fn get_monsters_5(\n        &self,\n        ctx: Self::Context,\n        query: Option<MonsterFilter>,\n    ) -> Response<Vec<Monster>>;\n    async fn get_monsters_6(\n        &self,\n        ctx: Self::Context,\n        query: Option<Vec<i32>>,\n    ) -> Response<Vec<Monster>>;\n    async fn post_monsters(\n        &self,\n        ctx: Self::Context,\n        post_body: MonsterData,\n    ) -> Response<Result<Monster, MonsterError>>;\n    async fn put_monsters_id(\n        &self,\n        ctx: Self::Context,\n        post_body: Monster,\n        id: String,\n        if_match: Option<::humblegen_rt::conditional::IfMatch>,\n    ) -> Response<Result<(), MonsterError>>;\n    async fn patch_monsters_id(\n        &self,\n        ctx: Self::Context,\n        post_body: MonsterPatch,\n        id: String,\n        if_match: Option<::humblegen_rt::conditional::IfMatch>,\n    ) -> Response<Result<(), MonsterError>>;\n    async fn delete_monster_id(\n        &self,\n        ctx: Self::Context,\n        id: String,\n    ) -> Response<Result<(), MonsterError>>;\n    async fn get_version(&self, ctx: Self::Context) -> Response<String>;\n    async fn get_tokio_police_locations(\n        &self,\n        ctx: Self::Context,\n    ) -> Response<Result<Vec<PoliceCar>, PoliceError>>;\n    async fn get_files_path(&self, ctx: Self::Context, path: String) -> Response<Vec<u8>>;\n    async fn get_sightings_day_verified(\n        &self,\n        ctx: Self::Context,\n        day: ::humblegen_rt::chrono::NaiveDate,\n        verified: bool,\n    ) -> Response<Vec<Monster>>;\n    async fn get_monster_export(\n        &self,\n        ctx: Self::Context,\n        query: Option<MonsterQuery>,\n    ) -> Response<::humblegen_rt::stream::ResponseStream<Monster>>;\n    async fn post_monsters_id_portrait(\n        &self,\n        ctx: Self::Context,\n        post_body: GodzillaPostMonstersIdPortraitMultipart,\n        id: i32,\n    ) -> Response<Result<(), MonsterError>>;\n    async fn get_monsters_id_portrait(\n        &self,\n        ctx: Self::Context,\n        id: i32,\n    ) -> Response<::humblegen_rt::blob::Blob>;\n    async fn post_monsters_import(\n        &self,\n        ctx: Self::Context,\n        post_body: ::humblegen_rt::blob::Blob,\n    ) -> Response<Result<u32, MonsterError>>;\n    async fn ws_monster_watch_area(\n        &self,\n        ctx: Self::Context,\n        area: String,\n        incoming: ::humblegen_rt::websocket::MessageStream<MonsterPatch>,\n        outgoing: ::humblegen_rt::websocket::MessageSink<Monster>,\n    ) -> Response<()>;\n}\n\n```"]
 #[humblegen_rt::async_trait(Sync)]
 pub trait Godzilla {
     type Context: Default + Sized + Send + Sync;
@@ -172,6 +331,9 @@ pub trait Godzilla {
     ) -> Result<Self::Context, ServiceError> {
         Ok(Self::Context::default())
     }
+    fn rate_limit_key(&self, _ctx: &Self::Context) -> Option<String> {
+        None
+    }
     #[doc = "```\nasync fn get_foo(&self, ctx: Self::Context) -> Response<u32> {}\n\n```"]
     #[doc = "Get foo."]
     async fn get_foo(&self, ctx: Self::Context) -> Response<u32>;
@@ -206,6 +368,20 @@ pub trait Godzilla {
     #[doc = "```\nasync fn get_monsters_4(&self, ctx: Self::Context) -> Response<Vec<Monster>> {}\n\n```"]
     #[doc = ""]
     async fn get_monsters_4(&self, ctx: Self::Context) -> Response<Vec<Monster>>;
+    #[doc = "```\nasync fn get_monsters_5(\n    &self,\n    ctx: Self::Context,\n    query: Option<MonsterFilter>,\n) -> Response<Vec<Monster>> {\n}\n\n```"]
+    #[doc = ""]
+    async fn get_monsters_5(
+        &self,
+        ctx: Self::Context,
+        query: Option<MonsterFilter>,
+    ) -> Response<Vec<Monster>>;
+    #[doc = "```\nasync fn get_monsters_6(\n    &self,\n    ctx: Self::Context,\n    query: Option<Vec<i32>>,\n) -> Response<Vec<Monster>> {\n}\n\n```"]
+    #[doc = ""]
+    async fn get_monsters_6(
+        &self,
+        ctx: Self::Context,
+        query: Option<Vec<i32>>,
+    ) -> Response<Vec<Monster>>;
     #[doc = "```\nasync fn post_monsters(\n    &self,\n    ctx: Self::Context,\n    post_body: MonsterData,\n) -> Response<Result<Monster, MonsterError>> {\n}\n\n```"]
     #[doc = "Create a new monster."]
     async fn post_monsters(
@@ -213,21 +389,23 @@ pub trait Godzilla {
         ctx: Self::Context,
         post_body: MonsterData,
     ) -> Response<Result<Monster, MonsterError>>;
-    #[doc = "```\nasync fn put_monsters_id(\n    &self,\n    ctx: Self::Context,\n    post_body: Monster,\n    id: String,\n) -> Response<Result<(), MonsterError>> {\n}\n\n```"]
+    #[doc = "```\nasync fn put_monsters_id(\n    &self,\n    ctx: Self::Context,\n    post_body: Monster,\n    id: String,\n    if_match: Option<::humblegen_rt::conditional::IfMatch>,\n) -> Response<Result<(), MonsterError>> {\n}\n\n```"]
     #[doc = "Overwrite a monster."]
     async fn put_monsters_id(
         &self,
         ctx: Self::Context,
         post_body: Monster,
         id: String,
+        if_match: Option<::humblegen_rt::conditional::IfMatch>,
     ) -> Response<Result<(), MonsterError>>;
-    #[doc = "```\nasync fn patch_monsters_id(\n    &self,\n    ctx: Self::Context,\n    post_body: MonsterPatch,\n    id: String,\n) -> Response<Result<(), MonsterError>> {\n}\n\n```"]
+    #[doc = "```\nasync fn patch_monsters_id(\n    &self,\n    ctx: Self::Context,\n    post_body: MonsterPatch,\n    id: String,\n    if_match: Option<::humblegen_rt::conditional::IfMatch>,\n) -> Response<Result<(), MonsterError>> {\n}\n\n```"]
     #[doc = "Patch a monster."]
     async fn patch_monsters_id(
         &self,
         ctx: Self::Context,
         post_body: MonsterPatch,
         id: String,
+        if_match: Option<::humblegen_rt::conditional::IfMatch>,
     ) -> Response<Result<(), MonsterError>>;
     #[doc = "```\nasync fn delete_monster_id(\n    &self,\n    ctx: Self::Context,\n    id: String,\n) -> Response<Result<(), MonsterError>> {\n}\n\n```"]
     #[doc = "Delete a monster"]
@@ -245,6 +423,55 @@ pub trait Godzilla {
         &self,
         ctx: Self::Context,
     ) -> Response<Result<Vec<PoliceCar>, PoliceError>>;
+    #[doc = "```\nasync fn get_files_path(&self, ctx: Self::Context, path: String) -> Response<Vec<u8>> {}\n\n```"]
+    #[doc = "Get a file by its path, which may contain slashes."]
+    async fn get_files_path(&self, ctx: Self::Context, path: String) -> Response<Vec<u8>>;
+    #[doc = "```\nasync fn get_sightings_day_verified(\n    &self,\n    ctx: Self::Context,\n    day: ::humblegen_rt::chrono::NaiveDate,\n    verified: bool,\n) -> Response<Vec<Monster>> {\n}\n\n```"]
+    #[doc = "Get the monster sightings of a day."]
+    async fn get_sightings_day_verified(
+        &self,
+        ctx: Self::Context,
+        day: ::humblegen_rt::chrono::NaiveDate,
+        verified: bool,
+    ) -> Response<Vec<Monster>>;
+    #[doc = "```\nasync fn get_monster_export(\n    &self,\n    ctx: Self::Context,\n    query: Option<MonsterQuery>,\n) -> Response<::humblegen_rt::stream::ResponseStream<Monster>> {\n}\n\n```"]
+    #[doc = "Export all monsters, one at a time."]
+    async fn get_monster_export(
+        &self,
+        ctx: Self::Context,
+        query: Option<MonsterQuery>,
+    ) -> Response<::humblegen_rt::stream::ResponseStream<Monster>>;
+    #[doc = "```\nasync fn post_monsters_id_portrait(\n    &self,\n    ctx: Self::Context,\n    post_body: GodzillaPostMonstersIdPortraitMultipart,\n    id: i32,\n) -> Response<Result<(), MonsterError>> {\n}\n\n```"]
+    #[doc = "Upload a portrait of a monster, along with a thumbnail."]
+    async fn post_monsters_id_portrait(
+        &self,
+        ctx: Self::Context,
+        post_body: GodzillaPostMonstersIdPortraitMultipart,
+        id: i32,
+    ) -> Response<Result<(), MonsterError>>;
+    #[doc = "```\nasync fn get_monsters_id_portrait(\n    &self,\n    ctx: Self::Context,\n    id: i32,\n) -> Response<::humblegen_rt::blob::Blob> {\n}\n\n```"]
+    #[doc = "Get the portrait of a monster, in whatever image format it was uploaded."]
+    async fn get_monsters_id_portrait(
+        &self,
+        ctx: Self::Context,
+        id: i32,
+    ) -> Response<::humblegen_rt::blob::Blob>;
+    #[doc = "```\nasync fn post_monsters_import(\n    &self,\n    ctx: Self::Context,\n    post_body: ::humblegen_rt::blob::Blob,\n) -> Response<Result<u32, MonsterError>> {\n}\n\n```"]
+    #[doc = "Import monsters from a CSV file, returning the number of imported monsters."]
+    async fn post_monsters_import(
+        &self,
+        ctx: Self::Context,
+        post_body: ::humblegen_rt::blob::Blob,
+    ) -> Response<Result<u32, MonsterError>>;
+    #[doc = "```\nasync fn ws_monster_watch_area(\n    &self,\n    ctx: Self::Context,\n    area: String,\n    incoming: ::humblegen_rt::websocket::MessageStream<MonsterPatch>,\n    outgoing: ::humblegen_rt::websocket::MessageSink<Monster>,\n) -> Response<()> {\n}\n\n```"]
+    #[doc = "Watch monsters as they move, renaming them on the way."]
+    async fn ws_monster_watch_area(
+        &self,
+        ctx: Self::Context,
+        area: String,
+        incoming: ::humblegen_rt::websocket::MessageStream<MonsterPatch>,
+        outgoing: ::humblegen_rt::websocket::MessageSink<Monster>,
+    ) -> Response<()>;
 }
 #[allow(unused_variables)]
 #[allow(unused_mut)]
@@ -258,7 +485,9 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
         {
             let handler = Arc::clone(&handler);
             Route {
+                name: "get_foo".to_owned(),
                 method: ::humblegen_rt::hyper::Method::GET,
+                template: "/foo".to_owned(),
                 regex: ::humblegen_rt::regex::Regex::new("^/foo$").unwrap(),
                 dispatcher: Box::new(
                     move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
@@ -268,12 +497,18 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
                             use ::humblegen_rt::service_protocol::ToErrorResponse;
                             let ctx = {
                                 let span = tracing::error_span!("interceptor");
-                                handler . intercept_handler_pre ( & req ) . instrument ( span ) . await . map_err ( :: humblegen_rt :: service_protocol :: ServiceError :: from ) . map_err ( | e | { tracing :: debug ! ( service_error = ? format ! ( "{:?}" , e ) , "interceptor rejected request" ) ; e } ) . map_err ( | e | e . to_error_response ( ) ) ?
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
                             };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
                             drop(req);
                             {
                                 let span = tracing::error_span!("handler");
-                                Ok(handler_response_to_hyper_response(
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
                                     handler.get_foo(ctx).instrument(span).await,
                                 ))
                             }
@@ -285,7 +520,9 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
         {
             let handler = Arc::clone(&handler);
             Route {
+                name: "get_monsters_id".to_owned(),
                 method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monsters/{id}".to_owned(),
                 regex: ::humblegen_rt::regex::Regex::new("^/monsters/(?P<id>[^/]+)$").unwrap(),
                 dispatcher: Box::new(
                     move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
@@ -296,15 +533,23 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
                             use ::humblegen_rt::service_protocol::ToErrorResponse;
                             let ctx = {
                                 let span = tracing::error_span!("interceptor");
-                                handler . intercept_handler_pre ( & req ) . instrument ( span ) . await . map_err ( :: humblegen_rt :: service_protocol :: ServiceError :: from ) . map_err ( | e | { tracing :: debug ! ( service_error = ? format ! ( "{:?}" , e ) , "interceptor rejected request" ) ; e } ) . map_err ( | e | e . to_error_response ( ) ) ?
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
                             };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
                             let id = id?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
                             drop(req);
                             {
                                 let span = tracing::error_span!("handler");
-                                Ok(handler_response_to_hyper_response(
-                                    handler.get_monsters_id(ctx, id).instrument(span).await,
-                                ))
+                                Ok(
+                                    server::result_handler_response_to_hyper_response_with_format(
+                                        response_format,
+                                        handler.get_monsters_id(ctx, id).instrument(span).await,
+                                    ),
+                                )
                             }
                         })
                     },
@@ -314,7 +559,9 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
         {
             let handler = Arc::clone(&handler);
             Route {
+                name: "get_monsters".to_owned(),
                 method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monsters".to_owned(),
                 regex: ::humblegen_rt::regex::Regex::new("^/monsters$").unwrap(),
                 dispatcher: Box::new(
                     move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
@@ -324,16 +571,22 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
                             use ::humblegen_rt::service_protocol::ToErrorResponse;
                             let ctx = {
                                 let span = tracing::error_span!("interceptor");
-                                handler . intercept_handler_pre ( & req ) . instrument ( span ) . await . map_err ( :: humblegen_rt :: service_protocol :: ServiceError :: from ) . map_err ( | e | { tracing :: debug ! ( service_error = ? format ! ( "{:?}" , e ) , "interceptor rejected request" ) ; e } ) . map_err ( | e | e . to_error_response ( ) ) ?
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
                             };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
                             let query: Option<MonsterQuery> = match req.uri().query() {
                                 None => None,
-                                Some(q) => Some(deser_query_serde_urlencoded(q)?),
+                                Some(q) => Some(deser_query_serde_qs(q)?),
                             };
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
                             drop(req);
                             {
                                 let span = tracing::error_span!("handler");
-                                Ok(handler_response_to_hyper_response(
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
                                     handler.get_monsters(ctx, query).instrument(span).await,
                                 ))
                             }
@@ -345,7 +598,9 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
         {
             let handler = Arc::clone(&handler);
             Route {
+                name: "get_monsters_2".to_owned(),
                 method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monsters2".to_owned(),
                 regex: ::humblegen_rt::regex::Regex::new("^/monsters2$").unwrap(),
                 dispatcher: Box::new(
                     move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
@@ -355,16 +610,22 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
                             use ::humblegen_rt::service_protocol::ToErrorResponse;
                             let ctx = {
                                 let span = tracing::error_span!("interceptor");
-                                handler . intercept_handler_pre ( & req ) . instrument ( span ) . await . map_err ( :: humblegen_rt :: service_protocol :: ServiceError :: from ) . map_err ( | e | { tracing :: debug ! ( service_error = ? format ! ( "{:?}" , e ) , "interceptor rejected request" ) ; e } ) . map_err ( | e | e . to_error_response ( ) ) ?
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
                             };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
                             let query: Option<String> = match req.uri().query() {
                                 None => None,
                                 Some(q) => Some(deser_query_primitive(q)?),
                             };
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
                             drop(req);
                             {
                                 let span = tracing::error_span!("handler");
-                                Ok(handler_response_to_hyper_response(
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
                                     handler.get_monsters_2(ctx, query).instrument(span).await,
                                 ))
                             }
@@ -376,7 +637,9 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
         {
             let handler = Arc::clone(&handler);
             Route {
+                name: "get_monsters_3".to_owned(),
                 method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monsters3".to_owned(),
                 regex: ::humblegen_rt::regex::Regex::new("^/monsters3$").unwrap(),
                 dispatcher: Box::new(
                     move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
@@ -386,16 +649,22 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
                             use ::humblegen_rt::service_protocol::ToErrorResponse;
                             let ctx = {
                                 let span = tracing::error_span!("interceptor");
-                                handler . intercept_handler_pre ( & req ) . instrument ( span ) . await . map_err ( :: humblegen_rt :: service_protocol :: ServiceError :: from ) . map_err ( | e | { tracing :: debug ! ( service_error = ? format ! ( "{:?}" , e ) , "interceptor rejected request" ) ; e } ) . map_err ( | e | e . to_error_response ( ) ) ?
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
                             };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
                             let query: Option<i32> = match req.uri().query() {
                                 None => None,
                                 Some(q) => Some(deser_query_primitive(q)?),
                             };
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
                             drop(req);
                             {
                                 let span = tracing::error_span!("handler");
-                                Ok(handler_response_to_hyper_response(
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
                                     handler.get_monsters_3(ctx, query).instrument(span).await,
                                 ))
                             }
@@ -407,7 +676,9 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
         {
             let handler = Arc::clone(&handler);
             Route {
+                name: "get_monsters_4".to_owned(),
                 method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monsters4".to_owned(),
                 regex: ::humblegen_rt::regex::Regex::new("^/monsters4$").unwrap(),
                 dispatcher: Box::new(
                     move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
@@ -417,12 +688,18 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
                             use ::humblegen_rt::service_protocol::ToErrorResponse;
                             let ctx = {
                                 let span = tracing::error_span!("interceptor");
-                                handler . intercept_handler_pre ( & req ) . instrument ( span ) . await . map_err ( :: humblegen_rt :: service_protocol :: ServiceError :: from ) . map_err ( | e | { tracing :: debug ! ( service_error = ? format ! ( "{:?}" , e ) , "interceptor rejected request" ) ; e } ) . map_err ( | e | e . to_error_response ( ) ) ?
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
                             };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
                             drop(req);
                             {
                                 let span = tracing::error_span!("handler");
-                                Ok(handler_response_to_hyper_response(
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
                                     handler.get_monsters_4(ctx).instrument(span).await,
                                 ))
                             }
@@ -434,7 +711,87 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
         {
             let handler = Arc::clone(&handler);
             Route {
+                name: "get_monsters_5".to_owned(),
+                method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monsters5".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monsters5$").unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let query: Option<MonsterFilter> = match req.uri().query() {
+                                None => None,
+                                Some(q) => Some(deser_query_serde_qs(q)?),
+                            };
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
+                                    handler.get_monsters_5(ctx, query).instrument(span).await,
+                                ))
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "get_monsters_6".to_owned(),
+                method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monsters6".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monsters6$").unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let query: Option<Vec<i32>> = match req.uri().query() {
+                                None => None,
+                                Some(q) => Some(deser_query_list(q)?),
+                            };
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
+                                    handler.get_monsters_6(ctx, query).instrument(span).await,
+                                ))
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "post_monsters".to_owned(),
                 method: ::humblegen_rt::hyper::Method::POST,
+                template: "/monsters".to_owned(),
                 regex: ::humblegen_rt::regex::Regex::new("^/monsters$").unwrap(),
                 dispatcher: Box::new(
                     move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
@@ -444,15 +801,26 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
                             use ::humblegen_rt::service_protocol::ToErrorResponse;
                             let ctx = {
                                 let span = tracing::error_span!("interceptor");
-                                handler . intercept_handler_pre ( & req ) . instrument ( span ) . await . map_err ( :: humblegen_rt :: service_protocol :: ServiceError :: from ) . map_err ( | e | { tracing :: debug ! ( service_error = ? format ! ( "{:?}" , e ) , "interceptor rejected request" ) ; e } ) . map_err ( | e | e . to_error_response ( ) ) ?
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
                             };
-                            let post_body: MonsterData = deser_post_data(req.body_mut()).await?;
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let post_body: MonsterData = deser_post_data(&mut req).await?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
                             drop(req);
                             {
                                 let span = tracing::error_span!("handler");
-                                Ok(handler_response_to_hyper_response(
-                                    handler.post_monsters(ctx, post_body).instrument(span).await,
-                                ))
+                                Ok(
+                                    server::result_handler_response_to_hyper_response_with_format(
+                                        response_format,
+                                        handler
+                                            .post_monsters(ctx, post_body)
+                                            .instrument(span)
+                                            .await,
+                                    ),
+                                )
                             }
                         })
                     },
@@ -462,7 +830,9 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
         {
             let handler = Arc::clone(&handler);
             Route {
+                name: "put_monsters_id".to_owned(),
                 method: ::humblegen_rt::hyper::Method::PUT,
+                template: "/monsters/{id}".to_owned(),
                 regex: ::humblegen_rt::regex::Regex::new("^/monsters/(?P<id>[^/]+)$").unwrap(),
                 dispatcher: Box::new(
                     move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
@@ -473,19 +843,28 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
                             use ::humblegen_rt::service_protocol::ToErrorResponse;
                             let ctx = {
                                 let span = tracing::error_span!("interceptor");
-                                handler . intercept_handler_pre ( & req ) . instrument ( span ) . await . map_err ( :: humblegen_rt :: service_protocol :: ServiceError :: from ) . map_err ( | e | { tracing :: debug ! ( service_error = ? format ! ( "{:?}" , e ) , "interceptor rejected request" ) ; e } ) . map_err ( | e | e . to_error_response ( ) ) ?
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
                             };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
                             let id = id?;
-                            let post_body: Monster = deser_post_data(req.body_mut()).await?;
+                            let if_match = ::humblegen_rt::conditional::IfMatch::from_request(&req);
+                            let post_body: Monster = deser_post_data(&mut req).await?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
                             drop(req);
                             {
                                 let span = tracing::error_span!("handler");
-                                Ok(handler_response_to_hyper_response(
-                                    handler
-                                        .put_monsters_id(ctx, post_body, id)
-                                        .instrument(span)
-                                        .await,
-                                ))
+                                Ok(
+                                    server::result_handler_response_to_hyper_response_with_format(
+                                        response_format,
+                                        handler
+                                            .put_monsters_id(ctx, post_body, id, if_match)
+                                            .instrument(span)
+                                            .await,
+                                    ),
+                                )
                             }
                         })
                     },
@@ -495,7 +874,9 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
         {
             let handler = Arc::clone(&handler);
             Route {
+                name: "patch_monsters_id".to_owned(),
                 method: ::humblegen_rt::hyper::Method::PATCH,
+                template: "/monsters/{id}".to_owned(),
                 regex: ::humblegen_rt::regex::Regex::new("^/monsters/(?P<id>[^/]+)$").unwrap(),
                 dispatcher: Box::new(
                     move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
@@ -506,19 +887,28 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
                             use ::humblegen_rt::service_protocol::ToErrorResponse;
                             let ctx = {
                                 let span = tracing::error_span!("interceptor");
-                                handler . intercept_handler_pre ( & req ) . instrument ( span ) . await . map_err ( :: humblegen_rt :: service_protocol :: ServiceError :: from ) . map_err ( | e | { tracing :: debug ! ( service_error = ? format ! ( "{:?}" , e ) , "interceptor rejected request" ) ; e } ) . map_err ( | e | e . to_error_response ( ) ) ?
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
                             };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
                             let id = id?;
-                            let post_body: MonsterPatch = deser_post_data(req.body_mut()).await?;
+                            let if_match = ::humblegen_rt::conditional::IfMatch::from_request(&req);
+                            let post_body: MonsterPatch = deser_post_data(&mut req).await?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
                             drop(req);
                             {
                                 let span = tracing::error_span!("handler");
-                                Ok(handler_response_to_hyper_response(
-                                    handler
-                                        .patch_monsters_id(ctx, post_body, id)
-                                        .instrument(span)
-                                        .await,
-                                ))
+                                Ok(
+                                    server::result_handler_response_to_hyper_response_with_format(
+                                        response_format,
+                                        handler
+                                            .patch_monsters_id(ctx, post_body, id, if_match)
+                                            .instrument(span)
+                                            .await,
+                                    ),
+                                )
                             }
                         })
                     },
@@ -528,7 +918,9 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
         {
             let handler = Arc::clone(&handler);
             Route {
+                name: "delete_monster_id".to_owned(),
                 method: ::humblegen_rt::hyper::Method::DELETE,
+                template: "/monster/{id}".to_owned(),
                 regex: ::humblegen_rt::regex::Regex::new("^/monster/(?P<id>[^/]+)$").unwrap(),
                 dispatcher: Box::new(
                     move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
@@ -539,15 +931,23 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
                             use ::humblegen_rt::service_protocol::ToErrorResponse;
                             let ctx = {
                                 let span = tracing::error_span!("interceptor");
-                                handler . intercept_handler_pre ( & req ) . instrument ( span ) . await . map_err ( :: humblegen_rt :: service_protocol :: ServiceError :: from ) . map_err ( | e | { tracing :: debug ! ( service_error = ? format ! ( "{:?}" , e ) , "interceptor rejected request" ) ; e } ) . map_err ( | e | e . to_error_response ( ) ) ?
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
                             };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
                             let id = id?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
                             drop(req);
                             {
                                 let span = tracing::error_span!("handler");
-                                Ok(handler_response_to_hyper_response(
-                                    handler.delete_monster_id(ctx, id).instrument(span).await,
-                                ))
+                                Ok(
+                                    server::result_handler_response_to_hyper_response_with_format(
+                                        response_format,
+                                        handler.delete_monster_id(ctx, id).instrument(span).await,
+                                    ),
+                                )
                             }
                         })
                     },
@@ -557,7 +957,9 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
         {
             let handler = Arc::clone(&handler);
             Route {
+                name: "get_version".to_owned(),
                 method: ::humblegen_rt::hyper::Method::GET,
+                template: "/version".to_owned(),
                 regex: ::humblegen_rt::regex::Regex::new("^/version$").unwrap(),
                 dispatcher: Box::new(
                     move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
@@ -567,12 +969,18 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
                             use ::humblegen_rt::service_protocol::ToErrorResponse;
                             let ctx = {
                                 let span = tracing::error_span!("interceptor");
-                                handler . intercept_handler_pre ( & req ) . instrument ( span ) . await . map_err ( :: humblegen_rt :: service_protocol :: ServiceError :: from ) . map_err ( | e | { tracing :: debug ! ( service_error = ? format ! ( "{:?}" , e ) , "interceptor rejected request" ) ; e } ) . map_err ( | e | e . to_error_response ( ) ) ?
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
                             };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
                             drop(req);
                             {
                                 let span = tracing::error_span!("handler");
-                                Ok(handler_response_to_hyper_response(
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
                                     handler.get_version(ctx).instrument(span).await,
                                 ))
                             }
@@ -584,7 +992,9 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
         {
             let handler = Arc::clone(&handler);
             Route {
+                name: "get_tokio_police_locations".to_owned(),
                 method: ::humblegen_rt::hyper::Method::GET,
+                template: "/tokio-police-locations".to_owned(),
                 regex: ::humblegen_rt::regex::Regex::new("^/tokio-police-locations$").unwrap(),
                 dispatcher: Box::new(
                     move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
@@ -594,14 +1004,107 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
                             use ::humblegen_rt::service_protocol::ToErrorResponse;
                             let ctx = {
                                 let span = tracing::error_span!("interceptor");
-                                handler . intercept_handler_pre ( & req ) . instrument ( span ) . await . map_err ( :: humblegen_rt :: service_protocol :: ServiceError :: from ) . map_err ( | e | { tracing :: debug ! ( service_error = ? format ! ( "{:?}" , e ) , "interceptor rejected request" ) ; e } ) . map_err ( | e | e . to_error_response ( ) ) ?
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(
+                                    server::result_handler_response_to_hyper_response_with_format(
+                                        response_format,
+                                        handler
+                                            .get_tokio_police_locations(ctx)
+                                            .instrument(span)
+                                            .await,
+                                    ),
+                                )
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "get_files_path".to_owned(),
+                method: ::humblegen_rt::hyper::Method::GET,
+                template: "/files/{path..}".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/files/(?P<path>.*)$").unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        let path: Result<String, ErrorResponse> =
+                            deser_param("path", &captures["path"]);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let path = path?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
+                                    handler.get_files_path(ctx, path).instrument(span).await,
+                                ))
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "get_sightings_day_verified".to_owned(),
+                method: ::humblegen_rt::hyper::Method::GET,
+                template: "/sightings/{day}/{verified}".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new(
+                    "^/sightings/(?P<day>[^/]+)/(?P<verified>[^/]+)$",
+                )
+                .unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        let day: Result<::humblegen_rt::chrono::NaiveDate, ErrorResponse> =
+                            deser_param("day", &captures["day"]);
+                        let verified: Result<bool, ErrorResponse> =
+                            deser_param("verified", &captures["verified"]);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
                             };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let day = day?;
+                            let verified = verified?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
                             drop(req);
                             {
                                 let span = tracing::error_span!("handler");
-                                Ok(handler_response_to_hyper_response(
+                                Ok(server::handler_response_to_hyper_response_with_format(
+                                    response_format,
                                     handler
-                                        .get_tokio_police_locations(ctx)
+                                        .get_sightings_day_verified(ctx, day, verified)
                                         .instrument(span)
                                         .await,
                                 ))
@@ -611,10 +1114,502 @@ fn routes_Godzilla<Context: Default + Sized + Send + Sync + 'static>(
                 ),
             }
         },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "get_monster_export".to_owned(),
+                method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monster-export".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monster-export$").unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let query: Option<MonsterQuery> = match req.uri().query() {
+                                None => None,
+                                Some(q) => Some(deser_query_serde_qs(q)?),
+                            };
+                            let response_format = ::humblegen_rt::stream::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(::humblegen_rt::stream::handler_response_to_hyper_response(
+                                    response_format,
+                                    handler
+                                        .get_monster_export(ctx, query)
+                                        .instrument(span)
+                                        .await,
+                                ))
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "post_monsters_id_portrait".to_owned(),
+                method: ::humblegen_rt::hyper::Method::POST,
+                template: "/monsters/{id}/portrait".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monsters/(?P<id>[^/]+)/portrait$")
+                    .unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        let id: Result<i32, ErrorResponse> = deser_param("id", &captures["id"]);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let id = id?;
+                            let mut multipart = ::humblegen_rt::multipart::Multipart::from_request(
+                                &mut req,
+                                Some("portrait"),
+                            )
+                            .await?;
+                            let post_body = GodzillaPostMonstersIdPortraitMultipart {
+                                caption: multipart.field::<Option<String>>("caption")?,
+                                thumbnail: multipart.buffered_file("thumbnail")?,
+                                portrait: multipart.streaming_file("portrait")?,
+                            };
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(
+                                    server::result_handler_response_to_hyper_response_with_format(
+                                        response_format,
+                                        handler
+                                            .post_monsters_id_portrait(ctx, post_body, id)
+                                            .instrument(span)
+                                            .await,
+                                    ),
+                                )
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "get_monsters_id_portrait".to_owned(),
+                method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monsters/{id}/portrait".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monsters/(?P<id>[^/]+)/portrait$")
+                    .unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        let id: Result<i32, ErrorResponse> = deser_param("id", &captures["id"]);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let id = id?;
+                            let response_format = "image/*";
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(::humblegen_rt::blob::handler_response_to_hyper_response(
+                                    response_format,
+                                    handler
+                                        .get_monsters_id_portrait(ctx, id)
+                                        .instrument(span)
+                                        .await,
+                                ))
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "post_monsters_import".to_owned(),
+                method: ::humblegen_rt::hyper::Method::POST,
+                template: "/monsters-import".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monsters-import$").unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let post_body = ::humblegen_rt::blob::deser_blob(&mut req, "text/csv")?;
+                            let response_format =
+                                ::humblegen_rt::wire_format::response_format(&req);
+                            drop(req);
+                            {
+                                let span = tracing::error_span!("handler");
+                                Ok(
+                                    server::result_handler_response_to_hyper_response_with_format(
+                                        response_format,
+                                        handler
+                                            .post_monsters_import(ctx, post_body)
+                                            .instrument(span)
+                                            .await,
+                                    ),
+                                )
+                            }
+                        })
+                    },
+                ),
+            }
+        },
+        {
+            let handler = Arc::clone(&handler);
+            Route {
+                name: "ws_monster_watch_area".to_owned(),
+                method: ::humblegen_rt::hyper::Method::GET,
+                template: "/monster-watch/{area}".to_owned(),
+                regex: ::humblegen_rt::regex::Regex::new("^/monster-watch/(?P<area>[^/]+)$")
+                    .unwrap(),
+                dispatcher: Box::new(
+                    move |mut req: ::humblegen_rt::hyper::Request<::humblegen_rt::hyper::Body>,
+                          captures| {
+                        let handler = Arc::clone(&handler);
+                        let area: Result<String, ErrorResponse> =
+                            deser_param("area", &captures["area"]);
+                        Box::pin(async move {
+                            use ::humblegen_rt::service_protocol::ToErrorResponse;
+                            let ctx = {
+                                let span = tracing::error_span!("interceptor");
+                                handler . intercept_handler_pre (& req) . instrument (span) . await . map_err (:: humblegen_rt :: service_protocol :: ServiceError :: from) . map_err (| e | { tracing :: debug ! (service_error = ? format ! ("{:?}" , e) , "interceptor rejected request") ; e }) . map_err (| e | e . to_error_response ()) ?
+                            };
+                            ::humblegen_rt::rate_limit::admit_context(&req, || {
+                                handler.rate_limit_key(&ctx)
+                            })?;
+                            let area = area?;
+                            let response = ::humblegen_rt::websocket::upgrade_response(&req)?;
+                            ::humblegen_rt::websocket::spawn_handler(
+                                req,
+                                move |incoming, outgoing| async move {
+                                    let span = tracing::error_span!("handler");
+                                    handler
+                                        .ws_monster_watch_area(ctx, area, incoming, outgoing)
+                                        .instrument(span)
+                                        .await
+                                },
+                            );
+                            Ok(response)
+                        })
+                    },
+                ),
+            }
+        },
     ]
 }
+#[doc = "Typed in-process client of a mounted `Godzilla` service, for testing (see `Builder::into_test_client`)."]
+#[derive(Debug, Clone)]
+pub struct GodzillaTestClient<'a> {
+    client: &'a ::humblegen_rt::test_client::TestClient,
+    root: String,
+}
+impl<'a> GodzillaTestClient<'a> {
+    #[doc = r" A client of the service mounted at URL path prefix `root`."]
+    pub fn new(client: &'a ::humblegen_rt::test_client::TestClient, root: &str) -> Self {
+        Self {
+            client,
+            root: root.to_owned(),
+        }
+    }
+    #[doc = "Invokes `Godzilla::get_foo`."]
+    pub async fn get_foo(&self) -> Result<u32, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/foo", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_monsters_id`."]
+    pub async fn get_monsters_id(
+        &self,
+        id: i32,
+    ) -> Result<Result<Monster, MonsterError>, ::humblegen_rt::test_client::TestError> {
+        let path = format!(
+            "{}/monsters/{}",
+            self.root,
+            ::humblegen_rt::test_client::route_param(&id)
+        );
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_monsters`."]
+    pub async fn get_monsters(
+        &self,
+        query: Option<MonsterQuery>,
+    ) -> Result<Vec<Monster>, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/monsters", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        let request = match &query {
+            Some(query) => request.query(query),
+            None => request,
+        };
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_monsters_2`."]
+    pub async fn get_monsters_2(
+        &self,
+        query: Option<String>,
+    ) -> Result<Vec<Monster>, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/monsters2", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        let request = match &query {
+            Some(query) => request.query_primitive(query),
+            None => request,
+        };
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_monsters_3`."]
+    pub async fn get_monsters_3(
+        &self,
+        query: Option<i32>,
+    ) -> Result<Vec<Monster>, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/monsters3", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        let request = match &query {
+            Some(query) => request.query_primitive(query),
+            None => request,
+        };
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_monsters_4`."]
+    pub async fn get_monsters_4(
+        &self,
+    ) -> Result<Vec<Monster>, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/monsters4", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_monsters_5`."]
+    pub async fn get_monsters_5(
+        &self,
+        query: Option<MonsterFilter>,
+    ) -> Result<Vec<Monster>, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/monsters5", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        let request = match &query {
+            Some(query) => request.query(query),
+            None => request,
+        };
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_monsters_6`."]
+    pub async fn get_monsters_6(
+        &self,
+        query: Option<Vec<i32>>,
+    ) -> Result<Vec<Monster>, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/monsters6", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        let request = match &query {
+            Some(query) => request.query_list(query),
+            None => request,
+        };
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::post_monsters`."]
+    pub async fn post_monsters(
+        &self,
+        post_body: MonsterData,
+    ) -> Result<Result<Monster, MonsterError>, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/monsters", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::POST, &path);
+        let request = request.json(&post_body);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::put_monsters_id`."]
+    pub async fn put_monsters_id(
+        &self,
+        post_body: Monster,
+        id: String,
+    ) -> Result<Result<(), MonsterError>, ::humblegen_rt::test_client::TestError> {
+        let path = format!(
+            "{}/monsters/{}",
+            self.root,
+            ::humblegen_rt::test_client::route_param(&id)
+        );
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::PUT, &path);
+        let request = request.json(&post_body);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::patch_monsters_id`."]
+    pub async fn patch_monsters_id(
+        &self,
+        post_body: MonsterPatch,
+        id: String,
+    ) -> Result<Result<(), MonsterError>, ::humblegen_rt::test_client::TestError> {
+        let path = format!(
+            "{}/monsters/{}",
+            self.root,
+            ::humblegen_rt::test_client::route_param(&id)
+        );
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::PATCH, &path);
+        let request = request.json(&post_body);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::delete_monster_id`."]
+    pub async fn delete_monster_id(
+        &self,
+        id: String,
+    ) -> Result<Result<(), MonsterError>, ::humblegen_rt::test_client::TestError> {
+        let path = format!(
+            "{}/monster/{}",
+            self.root,
+            ::humblegen_rt::test_client::route_param(&id)
+        );
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::DELETE, &path);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_version`."]
+    pub async fn get_version(&self) -> Result<String, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/version", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_tokio_police_locations`."]
+    pub async fn get_tokio_police_locations(
+        &self,
+    ) -> Result<Result<Vec<PoliceCar>, PoliceError>, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/tokio-police-locations", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_files_path`."]
+    pub async fn get_files_path(
+        &self,
+        path: String,
+    ) -> Result<Vec<u8>, ::humblegen_rt::test_client::TestError> {
+        let path = format!(
+            "{}/files/{}",
+            self.root,
+            ::humblegen_rt::test_client::rest_param(&path)
+        );
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_sightings_day_verified`."]
+    pub async fn get_sightings_day_verified(
+        &self,
+        day: ::humblegen_rt::chrono::NaiveDate,
+        verified: bool,
+    ) -> Result<Vec<Monster>, ::humblegen_rt::test_client::TestError> {
+        let path = format!(
+            "{}/sightings/{}/{}",
+            self.root,
+            ::humblegen_rt::test_client::route_param(&day),
+            ::humblegen_rt::test_client::route_param(&verified)
+        );
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        request.send().await.decode()
+    }
+    #[doc = "Invokes `Godzilla::get_monster_export`."]
+    pub async fn get_monster_export(
+        &self,
+        query: Option<MonsterQuery>,
+    ) -> Result<Vec<Monster>, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/monster-export", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        let request = match &query {
+            Some(query) => request.query(query),
+            None => request,
+        };
+        request.send().await.decode_ndjson()
+    }
+    #[doc = "Invokes `Godzilla::get_monsters_id_portrait`."]
+    pub async fn get_monsters_id_portrait(
+        &self,
+        id: i32,
+    ) -> Result<::humblegen_rt::blob::Blob, ::humblegen_rt::test_client::TestError> {
+        let path = format!(
+            "{}/monsters/{}/portrait",
+            self.root,
+            ::humblegen_rt::test_client::route_param(&id)
+        );
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::GET, &path);
+        request.send().await.decode_blob()
+    }
+    #[doc = "Invokes `Godzilla::post_monsters_import`."]
+    pub async fn post_monsters_import(
+        &self,
+        post_body: ::humblegen_rt::blob::Blob,
+    ) -> Result<Result<u32, MonsterError>, ::humblegen_rt::test_client::TestError> {
+        let path = format!("{}/monsters-import", self.root);
+        let request = self
+            .client
+            .request(::humblegen_rt::hyper::Method::POST, &path);
+        let request = request.blob(post_body, "text/csv");
+        request.send().await.decode()
+    }
+}
 #[doc = ""]
-#[doc = "```\n#[humblegen_rt::async_trait(Sync)]\npub trait Movies {\n    type Context: Default + Sized + Send + Sync;\n    async fn intercept_handler_pre(\n        &self,\n        _req: &hyper::Request<hyper::Body>,\n    ) -> Result<Self::Context, ServiceError> {\n        Ok(Self::Context::default())\n    }\n}\n\n```"]
+#[doc = "```\n#[humblegen_rt::async_trait(Sync)]\npub trait Movies {\n    type Context: Default + Sized + Send + Sync;\n    async fn intercept_handler_pre(\n        &self,\n        _req: &hyper::Request<hyper::Body>,\n    ) -> Result<Self::Context, ServiceError> {\n        Ok(Self::Context::default())\n    }\n    fn rate_limit_key(&self, _ctx: &Self::Context) -> Option<String> {\n        None\n    }\n}\n\n```"]
 #[humblegen_rt::async_trait(Sync)]
 pub trait Movies {
     type Context: Default + Sized + Send + Sync;
@@ -624,6 +1619,9 @@ pub trait Movies {
     ) -> Result<Self::Context, ServiceError> {
         Ok(Self::Context::default())
     }
+    fn rate_limit_key(&self, _ctx: &Self::Context) -> Option<String> {
+        None
+    }
 }
 #[allow(unused_variables)]
 #[allow(unused_mut)]
@@ -635,3 +1633,18 @@ fn routes_Movies<Context: Default + Sized + Send + Sync + 'static>(
 ) -> Vec<Route> {
     vec![]
 }
+#[doc = "Typed in-process client of a mounted `Movies` service, for testing (see `Builder::into_test_client`)."]
+#[derive(Debug, Clone)]
+pub struct MoviesTestClient<'a> {
+    client: &'a ::humblegen_rt::test_client::TestClient,
+    root: String,
+}
+impl<'a> MoviesTestClient<'a> {
+    #[doc = r" A client of the service mounted at URL path prefix `root`."]
+    pub fn new(client: &'a ::humblegen_rt::test_client::TestClient, root: &str) -> Self {
+        Self {
+            client,
+            root: root.to_owned(),
+        }
+    }
+}
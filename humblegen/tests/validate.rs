@@ -0,0 +1,199 @@
+use humblegen::ast::{AtomType, TypeIdent};
+use humblegen::Spec;
+use serde_json::{json, Value};
+
+const SPEC: &str = r#"
+struct Everything {
+    int: i32,
+    unsigned: u32,
+    byte: u8,
+    float: f64,
+    flag: bool,
+    text: str,
+    at: datetime,
+    day: date,
+    id: uuid,
+    data: bytes,
+    nothing: (),
+    maybe: option[i32],
+    outcome: result[str][Color],
+    counts: map[str][u32],
+    by_day: map[date][bool],
+    pair: (i32, str),
+    colors: list[Color],
+}
+
+enum Color {
+    Red,
+    Named(str),
+    Rgb(u8, u8, u8),
+    Hsv { h: u8, s: u8, v: u8 },
+}
+"#;
+
+fn spec() -> Spec {
+    humblegen::parse(SPEC.as_bytes()).expect("parse spec")
+}
+
+fn everything() -> Value {
+    json!({
+        "int": -3,
+        "unsigned": 3,
+        "byte": 255,
+        "float": 1.5,
+        "flag": true,
+        "text": "hello",
+        "at": "2020-05-01T12:00:00Z",
+        "day": "2020-05-01",
+        "id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+        "data": "aGVsbG8=",
+        "nothing": null,
+        "maybe": null,
+        "outcome": { "Ok": "fine" },
+        "counts": { "a": 1 },
+        "by_day": { "2020-05-01": true },
+        "pair": [1, "one"],
+        "colors": [
+            "Red",
+            { "Named": "teal" },
+            { "Rgb": [1, 2, 3] },
+            { "Hsv": { "h": 1, "s": 2, "v": 3 } }
+        ]
+    })
+}
+
+/// The pointers of all mismatches of `Everything` with `field` set to `value`.
+fn pointers_with(field: &str, value: Value) -> Vec<String> {
+    let mut everything = everything();
+    everything[field] = value;
+    spec()
+        .validate_value(
+            &TypeIdent::UserDefined("Everything".to_owned()),
+            &everything,
+        )
+        .into_iter()
+        .map(|e| e.pointer)
+        .collect()
+}
+
+#[test]
+fn valid_values_have_no_errors() {
+    let spec = spec();
+    let everything_type = TypeIdent::UserDefined("Everything".to_owned());
+    assert_eq!(spec.validate_value(&everything_type, &everything()), vec![]);
+
+    // option, list and map fields may be missing
+    let mut everything = everything();
+    for field in &["maybe", "counts", "colors"] {
+        everything.as_object_mut().unwrap().remove(*field);
+    }
+    assert_eq!(spec.validate_value(&everything_type, &everything), vec![]);
+}
+
+#[test]
+fn atoms() {
+    for (field, value) in vec![
+        ("int", json!(2_147_483_648i64)),
+        ("int", json!(1.5)),
+        ("unsigned", json!(-1)),
+        ("byte", json!(256)),
+        ("float", json!("1.5")),
+        ("flag", json!(1)),
+        ("text", json!(1)),
+        ("at", json!("2020-05-01")),
+        ("day", json!("2020-02-30")),
+        ("id", json!("67e55044")),
+        ("data", json!("not base64!")),
+        ("data", json!([104, 105])),
+        ("nothing", json!(0)),
+    ] {
+        assert_eq!(
+            pointers_with(field, value.clone()),
+            vec![format!("/{}", field)],
+            "{} = {}",
+            field,
+            value
+        );
+    }
+
+    let errors = spec().validate_value(&TypeIdent::BuiltIn(AtomType::U8), &json!(-1));
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].pointer, "");
+    assert_eq!(
+        errors[0].message,
+        "expected an unsigned 8-bit integer, got -1"
+    );
+}
+
+#[test]
+fn options_results_maps_and_tuples() {
+    assert_eq!(pointers_with("maybe", json!(1)), Vec::<String>::new());
+    assert_eq!(pointers_with("maybe", json!("1")), vec!["/maybe"]);
+
+    assert_eq!(
+        pointers_with("outcome", json!({ "Err": "Red" })),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        pointers_with("outcome", json!({ "Ok": 1 })),
+        vec!["/outcome/Ok"]
+    );
+    assert_eq!(
+        pointers_with("outcome", json!({ "Err": "Purple" })),
+        vec!["/outcome/Err"]
+    );
+    assert_eq!(pointers_with("outcome", json!("fine")), vec!["/outcome"]);
+
+    // keys are escaped in pointers
+    assert_eq!(
+        pointers_with("counts", json!({ "a/b~c": -1 })),
+        vec!["/counts/a~1b~0c"]
+    );
+    assert_eq!(
+        pointers_with("by_day", json!({ "tomorrow": true })),
+        vec!["/by_day/tomorrow"]
+    );
+
+    assert_eq!(pointers_with("pair", json!(["1", "one"])), vec!["/pair/0"]);
+    assert_eq!(pointers_with("pair", json!([1])), vec!["/pair"]);
+}
+
+#[test]
+fn enum_encodings() {
+    for (color, pointer) in vec![
+        (json!("Purple"), "/colors/0"),
+        (json!({ "Red": null }), "/colors/0"),
+        (json!("Named"), "/colors/0"),
+        (json!({ "Named": 1 }), "/colors/0/Named"),
+        (json!({ "Rgb": [1, 2] }), "/colors/0/Rgb"),
+        (json!({ "Rgb": [1, 2, 256] }), "/colors/0/Rgb/2"),
+        (json!({ "Hsv": [1, 2, 3] }), "/colors/0/Hsv"),
+        (json!({ "Hsv": { "h": 1, "s": 2 } }), "/colors/0/Hsv"),
+        (
+            json!({ "Hsv": { "h": 1, "s": 2, "v": -3 } }),
+            "/colors/0/Hsv/v",
+        ),
+        (json!({ "Red": null, "Named": "teal" }), "/colors/0"),
+    ] {
+        assert_eq!(
+            pointers_with("colors", json!([color])),
+            vec![pointer],
+            "{}",
+            color
+        );
+    }
+}
+
+#[test]
+fn all_errors_are_reported() {
+    let errors = spec().validate_value(
+        &TypeIdent::UserDefined("Everything".to_owned()),
+        &json!({ "int": "1" }),
+    );
+    assert_eq!(errors[0].pointer, "/int");
+    assert!(errors[1..].iter().all(|e| e.pointer.is_empty()));
+    assert!(errors
+        .iter()
+        .any(|e| e.message == "missing field `unsigned`"));
+    assert_eq!(errors.len(), 13);
+}